@@ -0,0 +1,12 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use little_exif::filetype::FileExtension;
+
+fuzz_target!(|data: &[u8]| {
+	little_exif_fuzz::read(data, FileExtension::JPEG);
+});
@@ -0,0 +1,32 @@
+#!/bin/sh
+# Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+# See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+# Seeds the corpus of every fuzz target with the sample files from `tests/`
+# that match the file type of the target. Run from within the `fuzz` folder.
+
+set -e
+
+seed()
+{
+    file_type=$1
+    shift
+    for target in read round_trip
+    do
+        mkdir -p "corpus/${target}_${file_type}"
+        for sample in "$@"
+        do
+            if [ -f "$sample" ]
+            then
+                cp "$sample" "corpus/${target}_${file_type}/"
+            fi
+        done
+    done
+}
+
+seed heif ../tests/*.heic
+seed jpg  ../tests/*.jpg ../tests/*.jpeg
+seed jxl  ../tests/*.jxl
+seed png  ../tests/*.png
+seed tiff ../tests/*.tif
+seed webp ../tests/*.webp
@@ -0,0 +1,99 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Shared logic of the fuzz targets in `fuzz_targets/`. Each target only
+//! selects the file type the input gets interpreted as.
+
+use little_exif::exif_tag::ExifTag;
+use little_exif::filetype::FileExtension;
+use little_exif::metadata::DecodeOptions;
+use little_exif::metadata::Metadata;
+
+/// Decodes the input in strict and in lenient mode, both from memory and via
+/// a reader. Errors are fine, panics (and hangs, huge allocations, ...) are
+/// not.
+pub fn
+read
+(
+	data:      &[u8],
+	file_type: FileExtension
+)
+{
+	let _ = Metadata::new_from_vec(data, file_type);
+	let _ = Metadata::new_from_vec_with_options(
+		data,
+		file_type,
+		&DecodeOptions { strict: false }
+	);
+	let _ = Metadata::new_from_reader(&mut std::io::Cursor::new(data), file_type);
+}
+
+/// Reads the metadata of the input, writes it back, writes new metadata (to
+/// a vec and a stream) and clears it. Whenever writing succeeds, the result
+/// has to be readable again.
+pub fn
+round_trip
+(
+	data:      &[u8],
+	file_type: FileExtension
+)
+{
+	let file_buffer = data.to_vec();
+
+	// Write back what has been read
+	if let Ok(metadata) = Metadata::new_from_vec(&file_buffer, file_type)
+	{
+		let mut written_buffer = file_buffer.clone();
+		if metadata.write_to_vec(&mut written_buffer, file_type).is_ok()
+		{
+			if let Err(error) = Metadata::new_from_vec(&written_buffer, file_type)
+			{
+				panic!("Could not read back written metadata: {}", error);
+			}
+		}
+	}
+
+	// Write new metadata, replacing whatever was there before
+	let mut new_metadata = Metadata::new();
+	new_metadata.set_tag(ExifTag::ImageDescription("little_exif".to_string()));
+
+	let mut written_buffer = file_buffer.clone();
+	if new_metadata.write_to_vec(&mut written_buffer, file_type).is_ok()
+	{
+		match Metadata::new_from_vec(&written_buffer, file_type)
+		{
+			Ok(reread_metadata) => assert!(
+				reread_metadata
+					.get_tag(&ExifTag::ImageDescription(String::new()))
+					.next()
+					.is_some(),
+				"Written ImageDescription is missing"
+			),
+			Err(error) => panic!("Could not read back new metadata: {}", error),
+		}
+	}
+
+	// Same, but without writing the image to memory first
+	let mut streamed_buffer = Vec::new();
+	if new_metadata.write_to_stream(&mut std::io::Cursor::new(&file_buffer), &mut streamed_buffer, file_type).is_ok()
+	{
+		match Metadata::new_from_vec(&streamed_buffer, file_type)
+		{
+			Ok(reread_metadata) => assert!(
+				reread_metadata
+					.get_tag(&ExifTag::ImageDescription(String::new()))
+					.next()
+					.is_some(),
+				"Streamed ImageDescription is missing"
+			),
+			Err(error) => panic!("Could not read back streamed metadata: {}", error),
+		}
+	}
+
+	// Clear the metadata
+	let mut cleared_buffer = file_buffer;
+	if Metadata::clear_metadata(&mut cleared_buffer, file_type).is_ok()
+	{
+		let _ = Metadata::new_from_vec(&cleared_buffer, file_type);
+	}
+}
@@ -0,0 +1,491 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Detection and extraction of C2PA manifests ("Content Credentials"). The
+//! manifest store is a JUMBF superbox (ISO/IEC 19566-5) labeled "c2pa" and
+//! stored as follows:
+//! - HEIF: top level `uuid` box with the C2PA user type
+//! - JPEG: APP11 segments, split into packets if necessary
+//! - JXL: top level `jumb` box
+//! - PNG: `caBX` chunk
+//! - TIFF: tag 0xcd41 of IFD0
+//! - WebP: `C2PA` chunk
+//!
+//! A manifest binds its signature to a hash of the rest of the file, so any
+//! modification of the image - including writing its metadata - invalidates
+//! the manifest. What happens to the manifest when writing or clearing the
+//! EXIF data, the XMP packet, the IPTC data, the Photoshop image resources
+//! or the ICC profile of an image is decided by the `C2paPolicy` of the
+//! `WriteOptions`.
+//!
+//! # Examples
+//! ```no_run
+//! use little_exif::c2pa::file_read_jumbf;
+//! use little_exif::c2pa::is_signed;
+//!
+//! let jumbf = file_read_jumbf(std::path::Path::new("image.jpg")).unwrap();
+//! println!("Manifest store with {} bytes, signed: {}", jumbf.len(), is_signed(&jumbf));
+//! ```
+
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::path::Path;
+
+use log::warn;
+
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::error::unsupported_format;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file_atomically;
+use crate::general_file_io::open_read_file;
+use crate::u8conversion::*;
+
+use crate::heif;
+use crate::jpg;
+use crate::jxl;
+use crate::png;
+use crate::tiff;
+use crate::webp;
+
+const JUMB_BOX_TYPE:        [u8; 4] = [0x6a, 0x75, 0x6d, 0x62];                 // "jumb"
+const JUMD_BOX_TYPE:        [u8; 4] = [0x6a, 0x75, 0x6d, 0x64];                 // "jumd"
+const MANIFEST_STORE_LABEL: &str    = "c2pa";
+const SIGNATURE_LABEL:      &str    = "c2pa.signature";
+
+/// Decides what happens to a C2PA manifest of an image when writing its
+/// metadata, see `WriteOptions`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum
+C2paPolicy
+{
+	/// Keep the manifest, even though it no longer matches the image. A
+	/// warning gets logged if the manifest is signed.
+	#[default]
+	Preserve,
+
+	/// Remove the manifest from the image before writing
+	Strip,
+
+	/// Don't write anything and return `Error::C2paManifestPresent`
+	Fail,
+}
+
+/// Checks if an image that is stored in memory holds a C2PA manifest
+pub fn
+has_manifest
+<T: AsRef<[u8]> + ?Sized>
+(
+	file_buffer: &T,
+	file_type:   FileExtension
+)
+-> Result<bool, Error>
+{
+	let mut cursor = Cursor::new(file_buffer.as_ref());
+	return Ok(read_jumbf_from_stream(&mut cursor, file_type)?.is_some());
+}
+
+/// Checks if the image at the specified path holds a C2PA manifest. Only the
+/// structure of the file is read, not the entire image data.
+pub fn
+file_has_manifest
+(
+	path: &Path
+)
+-> Result<bool, Error>
+{
+	let file_type  = get_file_type(path)?;
+	let mut reader = BufReader::new(open_read_file(path)?);
+	return Ok(read_jumbf_from_stream(&mut reader, file_type)?.is_some());
+}
+
+/// Reads the raw JUMBF manifest store of an image that is stored in memory,
+/// starting with the header of its `jumb` superbox. For JPEG images, the
+/// packets of the APP11 segments are reassembled. Returns
+/// `Error::NoC2paManifest` if the image has no manifest.
+pub fn
+read_jumbf
+<T: AsRef<[u8]> + ?Sized>
+(
+	file_buffer: &T,
+	file_type:   FileExtension
+)
+-> Result<Vec<u8>, Error>
+{
+	let mut cursor = Cursor::new(file_buffer.as_ref());
+	return read_jumbf_from_stream(&mut cursor, file_type)?.ok_or(Error::NoC2paManifest);
+}
+
+/// Reads the raw JUMBF manifest store of the image at the specified path
+pub fn
+file_read_jumbf
+(
+	path: &Path
+)
+-> Result<Vec<u8>, Error>
+{
+	let file_type  = get_file_type(path)?;
+	let mut reader = BufReader::new(open_read_file(path)?);
+	return read_jumbf_from_stream(&mut reader, file_type)?.ok_or(Error::NoC2paManifest);
+}
+
+/// Checks if the given JUMBF manifest store holds at least one signature,
+/// i.e. a superbox labeled "c2pa.signature"
+pub fn
+is_signed
+(
+	jumbf: &[u8]
+)
+-> bool
+{
+	return contains_label(jumbf, SIGNATURE_LABEL);
+}
+
+/// Removes the C2PA manifest from an image stored as a `Vec<u8>`. Images
+/// without a manifest are left untouched.
+#[allow(unreachable_patterns)]
+pub fn
+clear_manifest
+(
+	file_buffer: &mut Vec<u8>,
+	file_type:   FileExtension
+)
+-> Result<(), Error>
+{
+	return match file_type
+	{
+		FileExtension::HEIF
+			=> heif::clear_c2pa_jumbf(file_buffer),
+		FileExtension::JPEG
+			=>  jpg::clear_c2pa_jumbf(file_buffer),
+		FileExtension::JXL
+			=>  jxl::clear_c2pa_jumbf(file_buffer),
+		FileExtension::PNG { as_zTXt_chunk: _ }
+			=>  png::clear_c2pa_jumbf(file_buffer),
+		FileExtension::TIFF
+			=> tiff::vec::clear_c2pa_jumbf(file_buffer),
+		FileExtension::WEBP
+			=> webp::vec::clear_c2pa_jumbf(file_buffer),
+		_
+			=> unsupported_format!(
+				format!(
+					"Function 'clear_manifest' not yet implemented for {:?}",
+					file_type
+				)
+			),
+	};
+}
+
+/// Removes the C2PA manifest from the image at the specified path, which
+/// gets replaced atomically
+pub fn
+file_clear_manifest
+(
+	path: &Path
+)
+-> Result<(), Error>
+{
+	let file_type = get_file_type(path)?;
+	return modify_file_atomically(
+		path,
+		|file_buffer| clear_manifest(file_buffer, file_type)
+	);
+}
+
+/// Reads the JUMBF manifest store of the image provided by the given reader,
+/// which has to be positioned at the start of the image
+#[allow(unreachable_patterns)]
+pub(crate) fn
+read_jumbf_from_stream
+<R: Read + Seek>
+(
+	input:     &mut R,
+	file_type: FileExtension
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	return match file_type
+	{
+		FileExtension::HEIF
+			=> heif::read_c2pa_jumbf(input),
+		FileExtension::JPEG
+			=>  jpg::read_c2pa_jumbf(input),
+		FileExtension::JXL
+			=>  jxl::read_c2pa_jumbf(input),
+		FileExtension::PNG { as_zTXt_chunk: _ }
+			=>  png::read_c2pa_jumbf(input),
+		FileExtension::TIFF
+			=> {
+				// Decoding IFD0 requires the entire TIFF data anyway
+				let mut file_buffer = Vec::new();
+				input.read_to_end(&mut file_buffer)?;
+				tiff::vec::read_c2pa_jumbf(&file_buffer)
+			},
+		FileExtension::WEBP
+			=> webp::stream::read_c2pa_jumbf(input),
+		_
+			=> unsupported_format!(
+				format!(
+					"Function 'read_jumbf' not yet implemented for {:?}",
+					file_type
+				)
+			),
+	};
+}
+
+/// Checks the C2PA manifest of the image provided by the given reader against
+/// the policy before its metadata gets written. Returns whether the manifest
+/// needs to be stripped from the image.
+pub(crate) fn
+apply_write_policy
+<R: Read + Seek>
+(
+	input:     &mut R,
+	file_type: FileExtension,
+	policy:    C2paPolicy
+)
+-> Result<bool, Error>
+{
+	let jumbf = match read_jumbf_from_stream(input, file_type)
+	{
+		Ok(Some(jumbf)) => jumbf,
+		Ok(None)        => return Ok(false),
+
+		// Whether the image can be written to is up to the write itself, so
+		// only the policies that need to know about the manifest fail here
+		Err(_) if policy == C2paPolicy::Preserve => return Ok(false),
+		Err(error)                               => return Err(error),
+	};
+
+	return match policy
+	{
+		C2paPolicy::Preserve => {
+			if is_signed(&jumbf)
+			{
+				warn!("Writing the metadata invalidates the signed C2PA manifest of the image!");
+			}
+			Ok(false)
+		},
+		C2paPolicy::Strip => Ok(true),
+		C2paPolicy::Fail  => Err(Error::C2paManifestPresent),
+	};
+}
+
+/// Same as `apply_write_policy`, but for an image stored in memory, from
+/// which the manifest gets removed right away if the policy demands it
+pub(crate) fn
+apply_write_policy_to_vec
+(
+	file_buffer: &mut Vec<u8>,
+	file_type:   FileExtension,
+	policy:      C2paPolicy
+)
+-> Result<(), Error>
+{
+	if apply_write_policy(&mut Cursor::new(file_buffer.as_slice()), file_type, policy)?
+	{
+		return clear_manifest(file_buffer, file_type);
+	}
+	return Ok(());
+}
+
+/// Checks if the given JUMBF superbox is a C2PA manifest store. Only the
+/// description box at its start is needed for this, so the data may also be
+/// the first packet of a manifest store that is split into several ones.
+pub(crate) fn
+is_manifest_store
+(
+	jumbf: &[u8]
+)
+-> bool
+{
+	return get_label(jumbf).as_deref() == Some(MANIFEST_STORE_LABEL);
+}
+
+/// Same as `is_manifest_store`, but for the contents of a `jumb` superbox,
+/// i.e. without its header
+pub(crate) fn
+is_manifest_store_contents
+(
+	contents: &[u8]
+)
+-> bool
+{
+	return get_description_label(contents).as_deref() == Some(MANIFEST_STORE_LABEL);
+}
+
+/// Returns the label of the given JUMBF superbox
+fn
+get_label
+(
+	jumbf: &[u8]
+)
+-> Option<String>
+{
+	let (box_type, header_length, _) = read_box_header(jumbf, 0)?;
+	if box_type != JUMB_BOX_TYPE
+	{
+		return None;
+	}
+
+	return get_description_label(&jumbf[header_length..]);
+}
+
+/// Returns the label stored in the description box at the start of the
+/// contents of a superbox. The description holds the 16 bytes of the content
+/// type, a byte of toggles and - if indicated by the toggles - the NUL
+/// terminated label.
+fn
+get_description_label
+(
+	contents: &[u8]
+)
+-> Option<String>
+{
+	let (box_type, header_length, length) = read_box_header(contents, 0)?;
+	if box_type != JUMD_BOX_TYPE
+	{
+		return None;
+	}
+
+	let description = contents.get(header_length..std::cmp::min(length, contents.len()))?;
+
+	let toggles = *description.get(16)?;
+	if toggles & 0x02 == 0
+	{
+		return None;
+	}
+
+	let label     = &description[17..];
+	let label_end = label.iter().position(|byte| *byte == 0x00)?;
+	return String::from_utf8(label[..label_end].to_vec()).ok();
+}
+
+/// Checks if the given JUMBF superbox or one of the superboxes nested in it
+/// has the given label
+fn
+contains_label
+(
+	jumbf: &[u8],
+	label: &str
+)
+-> bool
+{
+	if get_label(jumbf).as_deref() == Some(label)
+	{
+		return true;
+	}
+
+	let (box_type, header_length, length) = match read_box_header(jumbf, 0)
+	{
+		Some(header) => header,
+		None         => return false,
+	};
+	if box_type != JUMB_BOX_TYPE
+	{
+		return false;
+	}
+
+	let contents     = &jumbf[..std::cmp::min(length, jumbf.len())];
+	let mut position = header_length;
+
+	while let Some((child_type, child_header_length, child_length)) = read_box_header(contents, position)
+	{
+		if child_length < child_header_length
+		{
+			return false;
+		}
+
+		let child_end = std::cmp::min(position.saturating_add(child_length), contents.len());
+		if child_type == JUMB_BOX_TYPE && contains_label(&contents[position..child_end], label)
+		{
+			return true;
+		}
+
+		position = child_end;
+	}
+
+	return false;
+}
+
+/// Reads the header of the box starting at the given position and returns
+/// its type, the length of the header and the length of the entire box.
+/// A length field of 0 indicates that the box extends to the end of the
+/// data, a length field of 1 that the length is given by the 8 bytes of the
+/// XLBox field after the type.
+fn
+read_box_header
+(
+	data:     &[u8],
+	position: usize
+)
+-> Option<([u8; 4], usize, usize)>
+{
+	let header   = data.get(position..position.checked_add(8)?)?;
+	let box_type = [header[4], header[5], header[6], header[7]];
+
+	return match from_u8_vec_macro!(u32, &header[0..4], &Endian::Big)
+	{
+		0 => Some((box_type, 8, data.len() - position)),
+		1 => {
+			let xl_box = data.get(position+8..position+16)?;
+			Some((box_type, 16, from_u8_vec_macro!(u64, xl_box, &Endian::Big) as usize))
+		},
+		length => Some((box_type, 8, length as usize)),
+	};
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	/// Encodes a superbox with the given label and child boxes
+	fn
+	superbox
+	(
+		label:    &str,
+		children: &[u8]
+	)
+	-> Vec<u8>
+	{
+		let mut description = vec![0u8; 16];
+		description.push(0x03);
+		description.extend(label.as_bytes());
+		description.push(0x00);
+
+		let mut jumbf = ((8 + 8 + description.len() + children.len()) as u32).to_be_bytes().to_vec();
+		jumbf.extend(JUMB_BOX_TYPE);
+		jumbf.extend(((8 + description.len()) as u32).to_be_bytes());
+		jumbf.extend(JUMD_BOX_TYPE);
+		jumbf.extend(description);
+		jumbf.extend(children);
+		return jumbf;
+	}
+
+	#[test]
+	fn
+	labels_of_manifest_store()
+	{
+		let signature = superbox(SIGNATURE_LABEL, &[]);
+		let manifest  = superbox("urn:uuid:0123", &signature);
+		let store     = superbox(MANIFEST_STORE_LABEL, &manifest);
+
+		assert!(is_manifest_store(&store));
+		assert!(is_signed(&store));
+
+		// Only the start of the store is needed to identify it
+		assert!(is_manifest_store(&store[..40]));
+		assert!(!is_signed(&store[..40]));
+
+		let unsigned_store = superbox(MANIFEST_STORE_LABEL, &superbox("urn:uuid:0123", &[]));
+		assert!(is_manifest_store(&unsigned_store));
+		assert!(!is_signed(&unsigned_store));
+
+		assert!(!is_manifest_store(&manifest));
+		assert!(!is_manifest_store(&[0x00, 0x00, 0x00, 0x08]));
+	}
+}
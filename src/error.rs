@@ -0,0 +1,230 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::fmt;
+
+use crate::exif_tag_format::ExifTagFormat;
+
+/// The error type used throughout little_exif.
+/// Allows to distinguish between e.g. a file that simply has no EXIF data, a
+/// file with corrupt EXIF data and a file type that is not supported without
+/// having to look at the error messages.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum
+Error
+{
+	/// The file (or data) does not contain any EXIF data
+	NoExifData,
+
+	/// The file (or data) does not contain any XMP data
+	NoXmpData,
+
+	/// The file (or data) does not contain any IPTC data
+	NoIptcData,
+
+	/// The file (or data) does not contain an ICC profile
+	NoIccProfile,
+
+	/// The file (or data) does not contain a C2PA manifest
+	NoC2paManifest,
+
+	/// The image holds a C2PA manifest that would be invalidated by writing,
+	/// which is not allowed by the `C2paPolicy` of the `WriteOptions`
+	C2paManifestPresent,
+
+	/// The encoded EXIF data exceeds the maximum size the file format allows 
+	/// for it, even after applying the `ExifSizeReduction` of the 
+	/// `WriteOptions` (sizes in bytes)
+	ExifTooLarge
+	{
+		size:    usize,
+		maximum: usize,
+	},
+
+	/// The file type is unknown or not supported for the requested operation
+	UnsupportedFormat(String),
+
+	/// The EXIF data could not be decoded, starting at the given offset
+	/// (relative to the start of the TIFF header)
+	MalformedIfd
+	{
+		offset: u64,
+		reason: String,
+	},
+
+	/// A known tag was encountered with a format that does not fit it
+	InvalidTagFormat
+	{
+		tag:      u16,
+		expected: ExifTagFormat,
+		got:      ExifTagFormat,
+	},
+
+	/// The XMP packet could not be parsed
+	MalformedXmp(String),
+
+	/// The IPTC-IIM data could not be parsed
+	MalformedIptc(String),
+
+	/// The structure of the image file itself (e.g. its chunks, boxes or
+	/// segments) is invalid or could not be handled
+	ContainerError(String),
+
+	/// Reading or writing the underlying data failed
+	Io(std::io::Error),
+}
+
+impl
+Error
+{
+	/// Used by the parsers that read chunks until the data runs out to
+	/// distinguish "no further chunks" from actual errors
+	pub(crate) fn
+	is_unexpected_eof
+	(
+		&self
+	)
+	-> bool
+	{
+		if let Error::Io(io_error) = self
+		{
+			return io_error.kind() == std::io::ErrorKind::UnexpectedEof;
+		}
+		return false;
+	}
+}
+
+impl
+fmt::Display
+for
+Error
+{
+	fn
+	fmt
+	(
+		&self,
+		f: &mut fmt::Formatter<'_>
+	)
+	-> fmt::Result
+	{
+		match self
+		{
+			Error::NoExifData
+				=> write!(f, "No EXIF data found!"),
+			Error::NoXmpData
+				=> write!(f, "No XMP data found!"),
+			Error::NoIptcData
+				=> write!(f, "No IPTC data found!"),
+			Error::NoIccProfile
+				=> write!(f, "No ICC profile found!"),
+			Error::NoC2paManifest
+				=> write!(f, "No C2PA manifest found!"),
+			Error::C2paManifestPresent
+				=> write!(f, "Writing would invalidate the C2PA manifest of the image!"),
+			Error::ExifTooLarge { size, maximum }
+				=> write!(f, "EXIF data of {} bytes exceeds the maximum of {} bytes!", size, maximum),
+			Error::UnsupportedFormat(message)
+				=> write!(f, "Unsupported format: {}", message),
+			Error::MalformedIfd { offset, reason }
+				=> write!(f, "Malformed IFD at offset {}: {}", offset, reason),
+			Error::InvalidTagFormat { tag, expected, got }
+				=> write!(f, "Illegal format for tag 0x{:04x}! Expected: {:?} Got: {:?}", tag, expected, got),
+			Error::MalformedXmp(message)
+				=> write!(f, "Malformed XMP: {}", message),
+			Error::MalformedIptc(message)
+				=> write!(f, "Malformed IPTC: {}", message),
+			Error::ContainerError(message)
+				=> write!(f, "{}", message),
+			Error::Io(error)
+				=> write!(f, "{}", error),
+		}
+	}
+}
+
+impl
+std::error::Error
+for
+Error
+{
+	fn
+	source
+	(
+		&self
+	)
+	-> Option<&(dyn std::error::Error + 'static)>
+	{
+		match self
+		{
+			Error::Io(error) => Some(error),
+			_                => None,
+		}
+	}
+}
+
+impl
+From<std::io::Error>
+for
+Error
+{
+	fn
+	from
+	(
+		error: std::io::Error
+	)
+	-> Self
+	{
+		Error::Io(error)
+	}
+}
+
+/// Allows callers that still work with `std::io::Error` (e.g. via `?` in a
+/// function returning `Result<_, std::io::Error>`) to keep doing so
+impl
+From<Error>
+for
+std::io::Error
+{
+	fn
+	from
+	(
+		error: Error
+	)
+	-> Self
+	{
+		match error
+		{
+			Error::Io(io_error)
+				=> io_error,
+			Error::NoExifData |
+			Error::NoXmpData  |
+			Error::NoIptcData |
+			Error::NoIccProfile |
+			Error::NoC2paManifest
+				=> std::io::Error::new(std::io::ErrorKind::NotFound, error),
+			Error::UnsupportedFormat(_)
+				=> std::io::Error::new(std::io::ErrorKind::Unsupported, error),
+			_
+				=> std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+		}
+	}
+}
+
+macro_rules! container_error {
+	($message:expr)
+	=>
+	{
+		Err(crate::error::Error::ContainerError($message.into()))
+	};
+}
+
+macro_rules! unsupported_format {
+	($message:expr)
+	=>
+	{
+		Err(crate::error::Error::UnsupportedFormat($message.into()))
+	};
+}
+
+pub(crate) use container_error;
+pub(crate) use unsupported_format;
@@ -1,8 +1,8 @@
 // Copyright © 2024 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
+use crate::error::Error;
 use crate::endian::Endian;
-use crate::general_file_io::io_error;
 use crate::ifd::ExifTagGroup;
 
 use super::ExifTag;
@@ -18,12 +18,12 @@ decode_tag_with_format_exceptions
 (
 	raw_tag:  &ExifTag,
 	format:    ExifTagFormat,
-	raw_data: &Vec<u8>,
+	raw_data: &[u8],
 	endian:   &Endian,
 	hex_tag:   u16,
 	group:    &ExifTagGroup
 )
--> Result<ExifTag, std::io::Error>
+-> Result<ExifTag, Error>
 {
 	if raw_tag.format().as_u16() != format.as_u16()
 	{
@@ -58,58 +58,52 @@ decode_tag_with_format_exceptions
 				return Ok(raw_tag.set_value_to_int16u_vec(int16u_data).unwrap());
 			},
 
-			(ExifTagFormat::INT8U, ExifTagFormat::STRING) => {
-				if 
-					raw_tag.as_u16()    == 0x0005            && // GPSAltitudeRef
-					raw_tag.get_group() == ExifTagGroup::GPS
-				{
-					// The GPSAltitudeRef tag is a strange case. It is the only
-					// GPS -Ref tag that is a INT8U, all others are STRINGs
-					// with a length of two. 
-					// Some images store this as a string nevertheless. 
-					// So, we try to convert the string by taking its first
-					// character. If it is 0x00 or 0x30 ("0") we set it to 0,
-					// if it is 0x01 or 0x31 ("1") we set it to 1, and
-					// otherwise we panic and tell the user to open a ticket.
-
-					let first_char = raw_data[0];
-					let int8u_data = match first_char
-					{
-						0x00 | 0x30 => vec![0u8],
-						0x01 | 0x31 => vec![1u8],
-						_ => panic!("Problem while decoding GPSAltitudeRef. Please open a new issue for little_exif!")
-					};
+			// The GPSAltitudeRef tag is a strange case. It is the only GPS -Ref
+			// tag that is a INT8U, all others are STRINGs with a length of two
+			(ExifTagFormat::INT8U, ExifTagFormat::STRING) if 
+				raw_tag.as_u16()    == 0x0005            && // GPSAltitudeRef
+				raw_tag.get_group() == ExifTagGroup::GPS
+			=> {
+				// Some images store this as a string nevertheless. 
+				// So, we try to convert the string by taking its first
+				// character. If it is 0x00 or 0x30 ("0") we set it to 0,
+				// if it is 0x01 or 0x31 ("1") we set it to 1, and
+				// otherwise we can't interpret the data.
 
-					return Ok(ExifTag::from_u16_with_data(
-						0x0005, 
-						&ExifTagFormat::INT8U, 
-						&int8u_data, 
-						&endian, 
-						group
-					).unwrap());
-				}
-				else
+				let int8u_data = match raw_data.first()
 				{
-					return io_error!(Other, format!("Unknown tag for combination INT8U vs STRING while decoding: {:?}", raw_tag));
-				}
+					Some(0x00 | 0x30) => vec![0u8],
+					Some(0x01 | 0x31) => vec![1u8],
+					_ => return Err(Error::InvalidTagFormat { 
+						tag:      hex_tag,
+						expected: raw_tag.format(),
+						got:      format
+					})
+				};
+
+				return Ok(ExifTag::from_u16_with_data(
+					0x0005, 
+					&ExifTagFormat::INT8U, 
+					&int8u_data, 
+					endian, 
+					group
+				).unwrap());
 			},
 
 			// See issue #63
-			(ExifTagFormat::UNDEF, ExifTagFormat::STRING) => {
-				if 
-					raw_tag.as_u16()    == 0x001b            && // GPSProcessingMethod	
-					raw_tag.get_group() == ExifTagGroup::GPS
-				{
-					return Ok(raw_tag.set_value_to_undef(raw_data.to_vec()).unwrap());
-				}
-				else
-				{
-					return io_error!(Other, format!("Unknown tag for combination UNDEF vs STRING while decoding: {:?}", raw_tag));
-				}
+			(ExifTagFormat::UNDEF, ExifTagFormat::STRING) if
+				raw_tag.as_u16()    == 0x001b            && // GPSProcessingMethod	
+				raw_tag.get_group() == ExifTagGroup::GPS
+			=> {
+				return Ok(raw_tag.set_value_to_undef(raw_data.to_vec()).unwrap());
 			}
 
 			_ => {
-				return io_error!(Other, format!("Illegal format for known tag! Tag: {:?} Expected: {:?} Got: {:?}", raw_tag, raw_tag.format(), format));
+				return Err(Error::InvalidTagFormat {
+					tag:      hex_tag,
+					expected: raw_tag.format(),
+					got:      format
+				});
 			},
 		};
 	}
@@ -48,6 +48,12 @@ macro_rules! build_tag_enum {
 			StripOffsets(       Vec::<u32>, Vec::<Vec::<u8>>),
 			StripByteCounts(    Vec::<u32>,                 ),
 
+			TileOffsets(        Vec::<u32>, Vec::<Vec::<u8>>),
+			TileByteCounts(     Vec::<u32>,                 ),
+
+			FreeOffsets(        Vec::<u32>, Vec::<Vec::<u8>>),
+			FreeByteCounts(     Vec::<u32>,                 ),
+
 			ThumbnailOffset(    Vec::<u32>, Vec::<u8>),
 			ThumbnailLength(    Vec::<u32>           ),
 
@@ -84,6 +90,12 @@ macro_rules! build_tag_enum {
 					ExifTag::StripOffsets(       _, _,       ) => 0x0111,
 					ExifTag::StripByteCounts(    _,          ) => 0x0117,
 
+					ExifTag::TileOffsets(        _, _,       ) => 0x0144,
+					ExifTag::TileByteCounts(     _,          ) => 0x0145,
+
+					ExifTag::FreeOffsets(        _, _,       ) => 0x0120,
+					ExifTag::FreeByteCounts(     _,          ) => 0x0121,
+
 					ExifTag::ThumbnailOffset(    _, _,       ) => 0x0201,
 					ExifTag::ThumbnailLength(    _,          ) => 0x0202,
 
@@ -131,6 +143,12 @@ macro_rules! build_tag_enum {
 					(0x0111, _) => Ok(ExifTag::StripOffsets(   Vec::new(), Vec::new())),
 					(0x0117, _) => Ok(ExifTag::StripByteCounts(Vec::new(),           )),
 
+					(0x0144, _) => Ok(ExifTag::TileOffsets(    Vec::new(), Vec::new())),
+					(0x0145, _) => Ok(ExifTag::TileByteCounts( Vec::new(),           )),
+
+					(0x0120, _) => Ok(ExifTag::FreeOffsets(    Vec::new(), Vec::new())),
+					(0x0121, _) => Ok(ExifTag::FreeByteCounts( Vec::new(),           )),
+
 					(0x0201, _) => Ok(ExifTag::ThumbnailOffset(Vec::new(), Vec::new())),
 					(0x0202, _) => Ok(ExifTag::ThumbnailLength(Vec::new(),           )),
 
@@ -164,7 +182,7 @@ macro_rules! build_tag_enum {
 			(
 				hex_value: u16,
 				format:    &ExifTagFormat,
-				raw_data:  &Vec<u8>,
+				raw_data:  &[u8],
 				endian:    &Endian,
 				group:     &ExifTagGroup,
 			)
@@ -181,27 +199,48 @@ macro_rules! build_tag_enum {
 					(0x0111, _) => Ok(ExifTag::StripOffsets(   <INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian), Vec::new())),
 					(0x0117, _) => Ok(ExifTag::StripByteCounts(<INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian),           )),
 
+					(0x0144, _) => Ok(ExifTag::TileOffsets(    <INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian), Vec::new())),
+					(0x0145, _) => Ok(ExifTag::TileByteCounts( <INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian),           )),
+
+					(0x0120, _) => Ok(ExifTag::FreeOffsets(    <INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian), Vec::new())),
+					(0x0121, _) => Ok(ExifTag::FreeByteCounts( <INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian),           )),
+
 					(0x0201, _) => Ok(ExifTag::ThumbnailOffset(<INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian), Vec::new())),
 					(0x0202, _) => Ok(ExifTag::ThumbnailLength(<INT32U as U8conversion<INT32U>>::from_u8_vec(&raw_data, endian),           )),
 
-					_ => {
-						// In this case, the given hex_value represents a tag that is unknown
-						match *format
-						{
-							ExifTagFormat::INT8U       => Ok(ExifTag::UnknownINT8U(      <INT8U       as U8conversion<INT8U      >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::STRING      => Ok(ExifTag::UnknownSTRING(     <STRING      as U8conversion<STRING     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::INT16U      => Ok(ExifTag::UnknownINT16U(     <INT16U      as U8conversion<INT16U     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::INT32U      => Ok(ExifTag::UnknownINT32U(     <INT32U      as U8conversion<INT32U     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::RATIONAL64U => Ok(ExifTag::UnknownRATIONAL64U(<RATIONAL64U as U8conversion<RATIONAL64U>>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::INT8S       => Ok(ExifTag::UnknownINT8S(      <INT8S       as U8conversion<INT8S      >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::UNDEF       => Ok(ExifTag::UnknownUNDEF(      <UNDEF       as U8conversion<UNDEF      >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::INT16S      => Ok(ExifTag::UnknownINT16S(     <INT16S      as U8conversion<INT16S     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::INT32S      => Ok(ExifTag::UnknownINT32S(     <INT32S      as U8conversion<INT32S     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::RATIONAL64S => Ok(ExifTag::UnknownRATIONAL64S(<RATIONAL64S as U8conversion<RATIONAL64S>>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::FLOAT       => Ok(ExifTag::UnknownFLOAT(      <FLOAT       as U8conversion<FLOAT      >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-							ExifTagFormat::DOUBLE      => Ok(ExifTag::UnknownDOUBLE(     <DOUBLE      as U8conversion<DOUBLE     >>::from_u8_vec(raw_data, endian), hex_value, *group)),
-						}
-					},
+					// In this case, the given hex_value represents a tag that is unknown
+					_ => Ok(Self::unknown_from_u8_vec(hex_value, format, raw_data, endian, group)),
+				}
+			}
+
+			/// Creates an `Unknown...` tag for the given hex value and format,
+			/// even if a known tag with this hex value exists. Needed e.g. for
+			/// maker notes, where hex values are only known for some vendors.
+			pub(crate) fn
+			unknown_from_u8_vec
+			(
+				hex_value: u16,
+				format:    &ExifTagFormat,
+				raw_data:  &[u8],
+				endian:    &Endian,
+				group:     &ExifTagGroup,
+			)
+			-> ExifTag
+			{
+				match *format
+				{
+					ExifTagFormat::INT8U       => ExifTag::UnknownINT8U(      <INT8U       as U8conversion<INT8U      >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::STRING      => ExifTag::UnknownSTRING(     <STRING      as U8conversion<STRING     >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::INT16U      => ExifTag::UnknownINT16U(     <INT16U      as U8conversion<INT16U     >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::INT32U      => ExifTag::UnknownINT32U(     <INT32U      as U8conversion<INT32U     >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::RATIONAL64U => ExifTag::UnknownRATIONAL64U(<RATIONAL64U as U8conversion<RATIONAL64U>>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::INT8S       => ExifTag::UnknownINT8S(      <INT8S       as U8conversion<INT8S      >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::UNDEF       => ExifTag::UnknownUNDEF(      <UNDEF       as U8conversion<UNDEF      >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::INT16S      => ExifTag::UnknownINT16S(     <INT16S      as U8conversion<INT16S     >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::INT32S      => ExifTag::UnknownINT32S(     <INT32S      as U8conversion<INT32S     >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::RATIONAL64S => ExifTag::UnknownRATIONAL64S(<RATIONAL64S as U8conversion<RATIONAL64S>>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::FLOAT       => ExifTag::UnknownFLOAT(      <FLOAT       as U8conversion<FLOAT      >>::from_u8_vec(raw_data, endian), hex_value, *group),
+					ExifTagFormat::DOUBLE      => ExifTag::UnknownDOUBLE(     <DOUBLE      as U8conversion<DOUBLE     >>::from_u8_vec(raw_data, endian), hex_value, *group),
 				}
 			}
 
@@ -303,6 +342,12 @@ macro_rules! build_tag_enum {
 					ExifTag::StripOffsets(       _, _          ) => ExifTagGroup::GENERIC,
 					ExifTag::StripByteCounts(    _,            ) => ExifTagGroup::GENERIC,
 
+					ExifTag::TileOffsets(        _, _          ) => ExifTagGroup::GENERIC,
+					ExifTag::TileByteCounts(     _,            ) => ExifTagGroup::GENERIC,
+
+					ExifTag::FreeOffsets(        _, _          ) => ExifTagGroup::GENERIC,
+					ExifTag::FreeByteCounts(     _,            ) => ExifTagGroup::GENERIC,
+
 					ExifTag::ThumbnailOffset(    _, _          ) => ExifTagGroup::GENERIC,
 					ExifTag::ThumbnailLength(    _,            ) => ExifTagGroup::GENERIC,
 
@@ -338,6 +383,12 @@ macro_rules! build_tag_enum {
 					ExifTag::StripOffsets(       _, _      ) => ExifTagFormat::INT32U,
 					ExifTag::StripByteCounts(    _,        ) => ExifTagFormat::INT32U,
 
+					ExifTag::TileOffsets(        _, _      ) => ExifTagFormat::INT32U,
+					ExifTag::TileByteCounts(     _,        ) => ExifTagFormat::INT32U,
+
+					ExifTag::FreeOffsets(        _, _      ) => ExifTagFormat::INT32U,
+					ExifTag::FreeByteCounts(     _,        ) => ExifTagFormat::INT32U,
+
 					ExifTag::ThumbnailOffset(    _, _      ) => ExifTagFormat::INT32U,
 					ExifTag::ThumbnailLength(    _,        ) => ExifTagFormat::INT32U,
 
@@ -403,6 +454,12 @@ macro_rules! build_tag_enum {
 					ExifTag::StripOffsets(       _, value      ) => value.len() as u32,
 					ExifTag::StripByteCounts(value,            ) => value.len() as u32,
 
+					ExifTag::TileOffsets(        _, value      ) => value.len() as u32,
+					ExifTag::TileByteCounts( value,            ) => value.len() as u32,
+
+					ExifTag::FreeOffsets(        _, value      ) => value.len() as u32,
+					ExifTag::FreeByteCounts( value,            ) => value.len() as u32,
+
 					ExifTag::ThumbnailOffset(    _, _          ) => 1,
 					ExifTag::ThumbnailLength(    _,            ) => 1,
 
@@ -460,6 +517,12 @@ macro_rules! build_tag_enum {
 					ExifTag::StripOffsets(          _,     _   ) => Vec::new(),
 					ExifTag::StripByteCounts( byte_counts,     ) => byte_counts.to_u8_vec(endian),
 
+					ExifTag::TileOffsets(           _,     _   ) => Vec::new(),
+					ExifTag::TileByteCounts(  byte_counts,     ) => byte_counts.to_u8_vec(endian),
+
+					ExifTag::FreeOffsets(           _,     _   ) => Vec::new(),
+					ExifTag::FreeByteCounts(  byte_counts,     ) => byte_counts.to_u8_vec(endian),
+
 					ExifTag::ThumbnailOffset(       _,     _   ) => Vec::new(),
 					ExifTag::ThumbnailLength( length_data      ) => length_data.to_u8_vec(endian),
 
@@ -563,6 +626,9 @@ build_tag_enum![
 	(YResolution,                 0x011b, RATIONAL64U,   Some::<u32>(1),    true,      GENERIC),                        // Not EXIF but TIFF   x       x         x             x 
 	(PlanarConfiguration,         0x011c, INT16U,        Some::<u32>(1),    true,      GENERIC),               
 
+//  (FreeOffsets,                 0x0120, INT32U,        None::<u32>,       false,     GENERIC),                        // Not EXIF but TIFF
+//  (FreeByteCounts,              0x0121, INT32U,        None::<u32>,       false,     GENERIC),                        // Not EXIF but TIFF
+
 	(ResolutionUnit,              0x0128, INT16U,        Some::<u32>(1),    true,      GENERIC),                        // Not EXIF but TIFF   x       x         x             x 
 
 	(TransferFunction,            0x012d, INT16U,        Some::<u32>(3),    true,      GENERIC),
@@ -577,6 +643,11 @@ build_tag_enum![
 
 	(ColorMap,                    0x0140, INT16U,        None::<u32>,       true,      GENERIC),                        // Not EXIF but TIFF                     x               
 
+	(TileWidth,                   0x0142, INT32U,        Some::<u32>(1),    true,      GENERIC),                        // Not EXIF but TIFF
+	(TileLength,                  0x0143, INT32U,        Some::<u32>(1),    true,      GENERIC),                        // Not EXIF but TIFF
+//  (TileOffsets,                 0x0144, INT32U,        None::<u32>,       false,     GENERIC),                        // Not EXIF but TIFF
+//  (TileByteCounts,              0x0145, INT32U,        None::<u32>,       false,     GENERIC),                        // Not EXIF but TIFF
+
 	// End of TIFF only tags (?)
 
 	// (ThumbnailOffset,             0x0201, INT32U,        Some::<u32>(1),    true,      GENERIC),
@@ -689,7 +760,39 @@ build_tag_enum![
 	(CompositeImageCount,         0xa461, INT16U,        Some::<u32>(2),    true,      EXIF),
 	(CompositeImageExposureTimes, 0xa462, UNDEF,         None::<u32>,       true,      EXIF),
 
-	(Gamma,                       0xa500, RATIONAL64U,   Some::<u32>(1),    true,      EXIF)
+	(Gamma,                       0xa500, RATIONAL64U,   Some::<u32>(1),    true,      EXIF),
+
+	(OffsetSchema,                0xea1d, INT32S,        Some::<u32>(1),    true,      EXIF),                           // Microsoft: Difference between the current and the original offset of the MakerNote
+
+	// Decoded from the MakerNote, only for the vendor given by the name
+	(CanonImageType,              0x0006, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(CanonFirmwareVersion,        0x0007, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(CanonSerialNumber,           0x000c, INT32U,        Some::<u32>(1),    false,     MAKERNOTES),
+	(CanonLensModel,              0x0095, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(CanonInternalSerialNumber,   0x0096, STRING,        None::<u32>,       false,     MAKERNOTES),
+
+	(NikonSerialNumber,           0x001d, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(NikonLensType,               0x0083, INT8U,         Some::<u32>(1),    false,     MAKERNOTES),
+	(NikonLens,                   0x0084, RATIONAL64U,   Some::<u32>(4),    false,     MAKERNOTES),                     // Min. & max. focal length, aperture at min. & max. focal length
+	(NikonShutterCount,           0x00a7, INT32U,        Some::<u32>(1),    false,     MAKERNOTES),
+
+	(SonyModelID,                 0xb001, INT16U,        Some::<u32>(1),    false,     MAKERNOTES),
+	(SonyLensType,                0xb027, INT32U,        Some::<u32>(1),    false,     MAKERNOTES),                     // -> Sony LensType Values: https://exiftool.org/TagNames/Sony.html#LensType
+
+	(FujifilmVersion,             0x0000, UNDEF,         Some::<u32>(4),    false,     MAKERNOTES),
+	(FujifilmSerialNumber,        0x0010, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(FujifilmImageCount,          0x1438, INT16U,        Some::<u32>(1),    false,     MAKERNOTES),
+
+	(OlympusCameraType,           0x0207, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(OlympusSerialNumber,         0x0404, STRING,        None::<u32>,       false,     MAKERNOTES),
+
+	(PanasonicSerialNumber,       0x0025, UNDEF,         Some::<u32>(16),   false,     MAKERNOTES),
+	(PanasonicLensType,           0x0051, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(PanasonicLensSerialNumber,   0x0052, STRING,        None::<u32>,       false,     MAKERNOTES),
+
+	(AppleBurstUUID,              0x000b, STRING,        None::<u32>,       false,     MAKERNOTES),
+	(AppleContentIdentifier,      0x0011, STRING,        None::<u32>,       false,     MAKERNOTES),                     // Pairs a Live Photo with its video
+	(AppleImageUniqueID,          0x0015, STRING,        None::<u32>,       false,     MAKERNOTES)
 ];
 
 impl ExifTag
@@ -720,6 +823,12 @@ impl ExifTag
 			ExifTag::StripOffsets(   offset_data, _) => TagType::DATA_OFFSET(offset_data.clone()),
 			ExifTag::StripByteCounts(byte_counts,  ) => TagType::DATA_OFFSET(byte_counts.clone()),
 
+			ExifTag::TileOffsets(    offset_data, _) => TagType::DATA_OFFSET(offset_data.clone()),
+			ExifTag::TileByteCounts( byte_counts,  ) => TagType::DATA_OFFSET(byte_counts.clone()),
+
+			ExifTag::FreeOffsets(    offset_data, _) => TagType::DATA_OFFSET(offset_data.clone()),
+			ExifTag::FreeByteCounts( byte_counts,  ) => TagType::DATA_OFFSET(byte_counts.clone()),
+
 			ExifTag::ThumbnailOffset(offset_data, _) => TagType::DATA_OFFSET(offset_data.clone()),
 			ExifTag::ThumbnailLength(length_data   ) => TagType::DATA_OFFSET(length_data.clone()),
 
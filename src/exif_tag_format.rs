@@ -95,21 +95,20 @@ ExifTagFormat
 	)
 	-> u32
 	{
-		match self.as_u16()
+		match *self
 		{
-			0x0001  => 1,
-			0x0002  => 1,
-			0x0003  => 2,
-			0x0004  => 4,
-			0x0005  => 8,
-			0x0006  => 1,
-			0x0007  => 1,
-			0x0008  => 2,
-			0x0009  => 4,
-			0x000a  => 8,
-			0x000b  => 4,
-			0x000c  => 8,
-			_       => panic!("Invalid format value for ExifTagFormat!"),
+			ExifTagFormat::INT8U        => 1,
+			ExifTagFormat::STRING       => 1,
+			ExifTagFormat::INT16U       => 2,
+			ExifTagFormat::INT32U       => 4,
+			ExifTagFormat::RATIONAL64U  => 8,
+			ExifTagFormat::INT8S        => 1,
+			ExifTagFormat::UNDEF        => 1,
+			ExifTagFormat::INT16S       => 2,
+			ExifTagFormat::INT32S       => 4,
+			ExifTagFormat::RATIONAL64S  => 8,
+			ExifTagFormat::FLOAT        => 4,
+			ExifTagFormat::DOUBLE       => 8,
 		}
 	}
 }
@@ -1,12 +1,26 @@
 // Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
-use std::io;
-use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::path::Path;
 use std::str::FromStr;
 
+use crate::error::Error;
+use crate::error::unsupported_format;
 use crate::general_file_io::*;
+use crate::heif;
+use crate::jpg;
+use crate::jxl;
+use crate::png;
+use crate::tiff;
+use crate::webp;
+
+/// Number of bytes that are looked at when determining the file type based on
+/// the content. Needs to be large enough for the `ftyp` box of HEIF files, 
+/// which may list a couple of compatible brands.
+const DETECTION_BUFFER_SIZE: usize = 64;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[allow(non_snake_case)]
@@ -26,7 +40,7 @@ FromStr
 for 
 FileExtension 
 {
-    type Err = std::io::Error;
+    type Err = Error;
 
     fn 
     from_str
@@ -37,7 +51,7 @@ FileExtension
     {
         match input 
         {
-            "heif" | "hif" | "heic" 
+            "heif" | "hif" | "heic" | "avif"
                 => Ok(FileExtension::HEIF),
             "jpeg" | "jpg" 
                 => Ok(FileExtension::JPEG),
@@ -49,37 +63,170 @@ FileExtension
                 => Ok(FileExtension::TIFF),
             "webp" 
                 => Ok(FileExtension::WEBP),
-            _ => io_error!(Unsupported, format!("Unknown file type: {}", input)),
+            _ => unsupported_format!(format!("Unknown file type: {}", input)),
+        }
+    }
+}
+
+impl
+FileExtension
+{
+    /// Determines the file type based on the signature at the start of the 
+    /// given data. Returns `None` if none of the supported formats matches.
+    /// For PNG files, the returned value defaults to `as_zTXt_chunk: true`,
+    /// analogous to parsing the `png` file extension. 
+    pub fn
+    detect_from_bytes
+    (
+        bytes: &[u8]
+    )
+    -> Option<FileExtension>
+    {
+        if jpg::check_signature(bytes).is_ok()
+        {
+            return Some(FileExtension::JPEG);
+        }
+
+        if png::starts_with_png_signature(bytes)
+        {
+            return Some(FileExtension::PNG { as_zTXt_chunk: true });
+        }
+
+        if jxl::starts_with_jxl_signature(bytes) 
+        || jxl::starts_with_iso_bmff_signature(bytes)
+        {
+            return Some(FileExtension::JXL);
+        }
+
+        if webp::starts_with_webp_signature(bytes)
+        {
+            return Some(FileExtension::WEBP);
+        }
+
+        if heif::starts_with_heif_signature(bytes)
+        {
+            return Some(FileExtension::HEIF);
+        }
+
+        if tiff::starts_with_tiff_signature(bytes)
+        {
+            return Some(FileExtension::TIFF);
         }
+
+        return None;
     }
+
+    /// Determines the file type based on the signature at the current 
+    /// position of the given reader. Afterwards, the reader is set back to
+    /// the position it had before calling this function. 
+    pub fn
+    detect_from_reader
+    <R: Read + Seek>
+    (
+        reader: &mut R
+    )
+    -> Result<Option<FileExtension>, Error>
+    {
+        return Ok(FileExtension::detect_from_bytes(&read_detection_buffer(reader)?));
+    }
+}
+
+/// Reads the bytes needed for determining the file type at the current 
+/// position of the given reader and sets it back to that position afterwards
+fn
+read_detection_buffer
+<R: Read + Seek>
+(
+    reader: &mut R
+)
+-> Result<Vec<u8>, Error>
+{
+    let start_position = reader.stream_position()?;
+
+    let mut buffer = Vec::with_capacity(DETECTION_BUFFER_SIZE);
+    reader
+        .by_ref()
+        .take(DETECTION_BUFFER_SIZE as u64)
+        .read_to_end(&mut buffer)?;
+
+    reader.seek(SeekFrom::Start(start_position))?;
+
+    return Ok(buffer);
 }
 
+/// Determines the file type of the file at the given path. 
+/// The content of the file has precedence - only if its signature does not
+/// match any of the supported formats, the file extension is used. It also
+/// settles what the content alone can't tell:
+/// - For PNG files, the chunk type of existing EXIF data is kept, otherwise
+///   the value of `as_zTXt_chunk` is based on the extension.
+/// - The brands of an ISO BMFF file (e.g. AVIF) might not indicate a HEIF
+///   file, in which case only a HEIF extension leads to it being treated as
+///   one. Any other extension results in an error.
+#[allow(non_snake_case)]
 pub fn 
 get_file_type
 (
     path: &Path
 ) 
--> Result<FileExtension, io::Error> 
+-> Result<FileExtension, Error> 
 {
     if !path.try_exists()? 
     {
-        return io_error!(Other, "File does not exist!");
+        return io_error!(NotFound, "File does not exist!");
     }
 
-    let raw_file_type_str = path.extension()
-        .ok_or(io::Error::new(ErrorKind::Other, "Can't get file extension!"))?;
+    let file_type_from_extension = path.extension()
+        .ok_or(Error::UnsupportedFormat("Can't get file extension!".to_string()))
+        .and_then(|raw_file_type_str| raw_file_type_str.to_str()
+            .ok_or(Error::UnsupportedFormat("Can't convert extension!".to_string()))
+        )
+        .and_then(|file_type_str| FileExtension::from_str(file_type_str.to_lowercase().as_str()));
 
-    let file_type_str = raw_file_type_str.to_str()
-        .ok_or(io::Error::new(ErrorKind::Other, "Can't convert extension!"))?;
+    if !path.is_file()
+    {
+        return file_type_from_extension;
+    }
 
-    FileExtension::from_str(file_type_str.to_lowercase().as_str()).map_err(|e| 
-        {
-            io::Error::new(
-                ErrorKind::Unsupported,
-                format!("Unsupported file type: {file_type_str} - {e}"),
-            )
-        }
-    )
+    let mut file   = open_read_file(path)?;
+    let     buffer = read_detection_buffer(&mut file)?;
+
+    match (FileExtension::detect_from_bytes(&buffer), file_type_from_extension)
+    {
+        (Some(FileExtension::PNG { .. }), file_type_from_extension) => {
+            // A PNG that can't be read any further is treated like one
+            // without EXIF data, as this is not the place to report that
+            let as_zTXt_chunk = match png::get_exif_text_chunk_name(&mut file)
+            {
+                Ok(Some(chunk_name)) => chunk_name == "zTXt",
+                _ => match file_type_from_extension
+                {
+                    Ok(FileExtension::PNG { as_zTXt_chunk }) => as_zTXt_chunk,
+                    _                                        => true,
+                },
+            };
+            return Ok(FileExtension::PNG { as_zTXt_chunk });
+        },
+
+        (Some(file_type), _) => {
+            return Ok(file_type);
+        },
+
+        (None, Ok(FileExtension::HEIF)) => {
+            return Ok(FileExtension::HEIF);
+        },
+
+        (None, Ok(_)) if heif::starts_with_ftyp_box(&buffer) => {
+            return unsupported_format!(format!(
+                "ISO BMFF file with unsupported brands: {}", 
+                path.display()
+            ));
+        },
+
+        (None, file_type_from_extension) => {
+            return file_type_from_extension;
+        },
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +239,7 @@ mod tests
     {
         let table = vec![
             ("png",  FileExtension::PNG { as_zTXt_chunk: true }),
+            ("avif", FileExtension::HEIF),
             ("jpg",  FileExtension::JPEG),
             ("jpeg", FileExtension::JPEG),
             ("jxl",  FileExtension::JXL),
@@ -107,4 +255,109 @@ mod tests
             assert_eq!(result.unwrap(), expected, "Parsed value mismatch for '{}'", input);
         }
     }
+
+    #[test]
+    fn detect_from_bytes() 
+    {
+        let table = vec![
+            ("tests/read_sample.jpg",     Some(FileExtension::JPEG)),
+            ("tests/sample2.png",         Some(FileExtension::PNG { as_zTXt_chunk: true })),
+            ("tests/with_exif.jxl",       Some(FileExtension::JXL)),
+            ("tests/no_exif.jxl",         Some(FileExtension::JXL)),
+            ("tests/read_sample.tif",     Some(FileExtension::TIFF)),
+            ("tests/read_sample.webp",    Some(FileExtension::WEBP)),
+            ("tests/sample0",             None),
+        ];
+
+        for (path, expected) in table 
+        {
+            let file_buffer = std::fs::read(path).unwrap();
+            assert_eq!(FileExtension::detect_from_bytes(&file_buffer), expected, "Detection mismatch for '{}'", path);
+        }
+
+        // HEIF files are identified by the brands in their ftyp box
+        let heic_ftyp = [
+            0x00, 0x00, 0x00, 0x18, b'f', b't', b'y', b'p',
+            b'h', b'e', b'i', b'c', 0x00, 0x00, 0x00, 0x00,
+            b'm', b'i', b'f', b'1', b'h', b'e', b'i', b'c',
+        ];
+        assert_eq!(FileExtension::detect_from_bytes(&heic_ftyp), Some(FileExtension::HEIF));
+
+        let mp4_ftyp = [
+            0x00, 0x00, 0x00, 0x14, b'f', b't', b'y', b'p',
+            b'i', b's', b'o', b'm', 0x00, 0x00, 0x00, 0x00,
+            b'm', b'p', b'4', b'1',
+        ];
+        assert_eq!(FileExtension::detect_from_bytes(&mp4_ftyp), None);
+
+        // Too little data should not lead to a panic
+        assert_eq!(FileExtension::detect_from_bytes(&[0xff]), None);
+        assert_eq!(FileExtension::detect_from_bytes(&[]),     None);
+    }
+
+    /// Writes the given data to a file in the tests directory, determines its
+    /// type and removes the file again
+    fn get_file_type_of(name: &str, data: &[u8]) -> Result<FileExtension, Error>
+    {
+        let path = Path::new("tests").join(name);
+        std::fs::write(&path, data).unwrap();
+        let result = get_file_type(&path);
+        std::fs::remove_file(&path).unwrap();
+        return result;
+    }
+
+    #[test]
+    fn get_file_type_png_chunk_type() 
+    {
+        // Without EXIF data, the extension decides
+        let mut file_buffer = std::fs::read("tests/sample2.png").unwrap();
+        assert_eq!(get_file_type_of("filetype_no_exif.png", &file_buffer).unwrap(), FileExtension::PNG { as_zTXt_chunk: true });
+
+        // EXIF data in an iTXt chunk is kept there
+        let mut chunk = b"iTXt".to_vec();
+        chunk.extend(b"Raw profile type exif\0\0\0\0\0");
+        chunk.extend(b"\nexif\n       0\n");
+        let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&chunk);
+
+        let mut encoded_chunk = ((chunk.len() - 4) as u32).to_be_bytes().to_vec();
+        encoded_chunk.extend(&chunk);
+        encoded_chunk.extend(crc.to_be_bytes());
+
+        // Insert the chunk right after the IHDR chunk
+        file_buffer.splice(33..33, encoded_chunk);
+        assert_eq!(get_file_type_of("filetype_itxt.png", &file_buffer).unwrap(), FileExtension::PNG { as_zTXt_chunk: false });
+    }
+
+    #[test]
+    fn get_file_type_iso_bmff_brands() 
+    {
+        // AVIF without a brand that indicates a HEIF file
+        let avif_ftyp = [
+            0x00, 0x00, 0x00, 0x14, b'f', b't', b'y', b'p',
+            b'a', b'v', b'i', b'f', 0x00, 0x00, 0x00, 0x00,
+            b'a', b'v', b'i', b's',
+        ];
+        assert_eq!(FileExtension::detect_from_bytes(&avif_ftyp), None);
+        assert_eq!(get_file_type_of("filetype_brands.avif", &avif_ftyp).unwrap(), FileExtension::HEIF);
+        assert_eq!(get_file_type_of("filetype_brands.heic", &avif_ftyp).unwrap(), FileExtension::HEIF);
+        assert!(get_file_type_of("filetype_brands.jpg", &avif_ftyp).is_err());
+
+        // With a HEIF brand, the content has precedence
+        let heic_ftyp = [
+            0x00, 0x00, 0x00, 0x18, b'f', b't', b'y', b'p',
+            b'a', b'v', b'i', b'f', 0x00, 0x00, 0x00, 0x00,
+            b'm', b'i', b'f', b'1', b'a', b'v', b'i', b's',
+        ];
+        assert_eq!(get_file_type_of("filetype_heif_brand.jpg", &heic_ftyp).unwrap(), FileExtension::HEIF);
+    }
+
+    #[test]
+    fn detect_from_reader_resets_position() 
+    {
+        let file_buffer = std::fs::read("tests/sample2.jpg").unwrap();
+        let mut cursor  = std::io::Cursor::new(&file_buffer);
+
+        assert_eq!(FileExtension::detect_from_reader(&mut cursor).unwrap(), Some(FileExtension::JPEG));
+        assert_eq!(cursor.position(), 0);
+    }
 }
\ No newline at end of file
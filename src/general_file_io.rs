@@ -17,7 +17,7 @@ macro_rules! perform_file_action {
 		let file_action_result = $action;
 		if file_action_result.is_err()
 		{
-			return Err(file_action_result.err().unwrap());
+			return Err(file_action_result.err().unwrap().into());
 		}
 	};
 }
@@ -29,13 +29,20 @@ macro_rules! io_error {
 		Err(std::io::Error::new(
 			std::io::ErrorKind::$kind,
 			$message
-		))
+		).into())
 	};
 }
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::Error;
 
 pub(crate) fn
 open_read_file
@@ -73,5 +80,215 @@ open_write_file
 		.open(path)
 }
 
+/// Replaces the file at the given path with whatever the given function
+/// writes to the file it gets passed. 
+/// This first writes to a temporary file next to the original one, which gets
+/// synced to the disk and then renamed to replace the original. So, the file
+/// at the given path is either fully replaced or - e.g. if writing fails or 
+/// the process gets killed - not touched at all. The permissions of the
+/// original file are preserved.
+pub(crate) fn
+write_file_atomically
+<F: FnOnce(&mut File) -> Result<(), Error>>
+(
+	path:  &Path,
+	write: F
+)
+-> Result<(), Error>
+{
+	let permissions = std::fs::metadata(path)?.permissions();
+	let temp_path   = get_temp_path(path)?;
+
+	// Don't overwrite anything that might be located at the temp path
+	let mut temp_file = OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(&temp_path)?;
+
+	let write_result = (|| -> Result<(), Error> {
+		write(&mut temp_file)?;
+		temp_file.set_permissions(permissions)?;
+		temp_file.sync_all()?;
+
+		// The file needs to be closed before it can replace the original
+		drop(temp_file);
+		std::fs::rename(&temp_path, path)?;
+
+		return Ok(());
+	})();
+
+	if let Err(error) = write_result
+	{
+		let _ = std::fs::remove_file(&temp_path);
+		return Err(error);
+	}
+
+	// Make sure the rename itself is persisted as well. Not all platforms
+	// and file systems support this, so errors are ignored
+	#[cfg(unix)]
+	if let Some(Ok(directory)) = path.parent().map(|parent| File::open(
+		if parent.as_os_str().is_empty() { Path::new(".") } else { parent }
+	))
+	{
+		let _ = directory.sync_all();
+	}
+
+	return Ok(());
+}
+
+/// Loads the file at the given path into memory, applies the given vec based
+/// function to it and atomically replaces the file with the result
+pub(crate) fn
+modify_file_atomically
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	path:   &Path,
+	modify: F
+)
+-> Result<(), Error>
+{
+	let mut file_buffer = std::fs::read(path)?;
+	modify(&mut file_buffer)?;
+
+	return write_file_atomically(
+		path, 
+		|temp_file| Ok(temp_file.write_all(&file_buffer)?)
+	);
+}
+
+/// Loads the file at the given path into memory, applies the given vec based
+/// function to it and writes the result back to the same file
+pub(crate) fn
+modify_file_in_place
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	path:   &Path,
+	modify: F
+)
+-> Result<(), Error>
+{
+	let mut file        = open_write_file(path)?;
+	let mut file_buffer = Vec::new();
+	file.read_to_end(&mut file_buffer)?;
+
+	modify(&mut file_buffer)?;
+
+	// Adjust the length of the file, possibly truncating it if the new 
+	// contents are shorter
+	file.seek(SeekFrom::Start(0))?;
+	file.write_all(&file_buffer)?;
+	file.set_len(file_buffer.len() as u64)?;
+
+	return Ok(());
+}
+
+/// Applies the given vec based function to the file at the given path, which
+/// either gets replaced atomically or modified in place, see `WriteOptions`
+pub(crate) fn
+modify_file
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	path:   &Path,
+	atomic: bool,
+	modify: F
+)
+-> Result<(), Error>
+{
+	if atomic
+	{
+		return modify_file_atomically(path, modify);
+	}
+	return modify_file_in_place(path, modify);
+}
+
+/// Wraps a reader so that its position at the time of wrapping appears as
+/// the start of the data, e.g. for an image that is embedded in a larger
+/// stream. Required as the readers of the different formats seek to absolute
+/// positions.
+pub(crate) struct
+SubReader<R>
+{
+	inner: R,
+	start: u64,
+}
+
+impl<R: Seek> SubReader<R>
+{
+	pub(crate) fn
+	new
+	(
+		mut inner: R
+	)
+	-> Result<Self, std::io::Error>
+	{
+		let start = inner.stream_position()?;
+		return Ok(SubReader { inner, start });
+	}
+}
+
+impl<R: Read> Read
+for SubReader<R>
+{
+	fn
+	read
+	(
+		&mut self,
+		buf: &mut [u8]
+	)
+	-> Result<usize, std::io::Error>
+	{
+		return self.inner.read(buf);
+	}
+}
+
+impl<R: Seek> Seek
+for SubReader<R>
+{
+	fn
+	seek
+	(
+		&mut self,
+		pos: SeekFrom
+	)
+	-> Result<u64, std::io::Error>
+	{
+		let pos = match pos
+		{
+			SeekFrom::Start(offset) => SeekFrom::Start(self.start + offset),
+			_                       => pos,
+		};
+
+		let position = self.inner.seek(pos)?;
+		if position < self.start
+		{
+			self.inner.seek(SeekFrom::Start(self.start))?;
+			return io_error!(InvalidInput, "Can't seek to a position before the start of the data!");
+		}
+
+		return Ok(position - self.start);
+	}
+}
+
+/// Constructs the path of the temporary file used by `write_file_atomically`,
+/// which is a hidden file in the same directory as the given file, so that
+/// both are located on the same file system
+fn
+get_temp_path
+(
+	path: &Path
+)
+-> Result<PathBuf, Error>
+{
+	let file_name = match path.file_name()
+	{
+		Some(file_name) => file_name.to_string_lossy(),
+		None            => return io_error!(InvalidInput, "Can't write file - Path does not point to a file!"),
+	};
+
+	return Ok(path.with_file_name(
+		format!(".{}.{}.little_exif.tmp", file_name, std::process::id())
+	));
+}
+
 pub(crate) use perform_file_action;
 pub(crate) use io_error;
\ No newline at end of file
@@ -4,6 +4,8 @@
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::endian::Endian;
 use crate::u8conversion::U8conversion;
 use crate::u8conversion::to_u8_vec_macro;
@@ -37,7 +39,7 @@ BoxHeader
     (
         cursor: &mut T
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         // Read in the size
         let box_size = read_be_u32(cursor)?;
@@ -95,6 +97,24 @@ BoxHeader
         return self.box_size;
     }
 
+    /// Size of the box without its header, i.e. the number of bytes that
+    /// still need to be read after the header
+    pub(super) fn
+    get_data_size
+    (
+        &self
+    )
+    -> Result<usize, Error>
+    {
+        if let Some(data_size) = self.box_size.checked_sub(self.header_size)
+        {
+            return Ok(data_size);
+        }
+        return container_error!(
+            format!("Box size {} is smaller than its header!", self.box_size)
+        );
+    }
+
     pub(super) fn
     get_box_type
     (
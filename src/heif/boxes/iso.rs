@@ -4,7 +4,9 @@
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
 use crate::debug_println;
+use crate::util::read_n_bytes;
 
 use crate::heif::box_header::BoxHeader;
 use crate::heif::boxes::GenericIsoBox;
@@ -29,7 +31,7 @@ IsoBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<IsoBox, std::io::Error> 
+    -> Result<IsoBox, Error> 
     {
         debug_println!("Constructing generic ISO box for type {:?}", header.get_box_type());
 
@@ -45,16 +47,37 @@ IsoBox
             });
         }
 
-        let data_left_to_read = header.get_box_size() - header.get_header_size();
-
-        let mut buffer = vec![0u8; data_left_to_read];
-        cursor.read_exact(&mut buffer)?;
+        let data_left_to_read = header.get_data_size()?;
+        let buffer            = read_n_bytes(cursor, data_left_to_read)?;
 
         return Ok(IsoBox {
             header: header,
             data:   buffer
         });
     }
+
+    pub(crate) fn
+    get_data
+    (
+        &self
+    )
+    -> &[u8]
+    {
+        return &self.data;
+    }
+
+    /// Replaces the data of the box and updates its size accordingly
+    pub(crate) fn
+    set_data
+    (
+        &mut self,
+        data: Vec<u8>
+    )
+    {
+        let new_size = self.header.get_header_size() + data.len();
+        self.header.set_box_size(new_size);
+        self.data = data;
+    }
 }
 
 impl
@@ -69,7 +92,7 @@ IsoBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error> 
+    -> Result<Box<dyn GenericIsoBox>, Error> 
     {
         return Ok(Box::new(IsoBox::construct_from_cursor_unboxed(
             cursor, 
@@ -4,6 +4,8 @@
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::debug_println;
 
 use crate::endian::Endian;
@@ -11,6 +13,7 @@ use crate::u8conversion::U8conversion;
 use crate::u8conversion::to_u8_vec_macro;
 use crate::util::read_be_u16;
 use crate::util::read_be_u32;
+use crate::util::read_n_bytes;
 use crate::util::read_null_terminated_string;
 
 use crate::heif::box_header::BoxHeader;
@@ -29,6 +32,9 @@ use crate::heif::boxes::ParsableIsoBox;
 // content_type and the optional content_encoding, however, the practical
 // examples did *not* have any of this
 
+/// Content type (including its null terminator) of `mime` items holding XMP
+const XMP_CONTENT_TYPE: &[u8] = b"application/rdf+xml\0";
+
 #[allow(dead_code)]
 pub struct
 ItemInfoEntryBox
@@ -66,7 +72,7 @@ ItemInfoEntryBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let item_id               = read_be_u16(cursor)?;
         let item_protection_index = read_be_u16(cursor)?;
@@ -77,10 +83,13 @@ ItemInfoEntryBox
             + 2                    // item_id
             + 2                    // item_protection_index
             + item_name.len() + 1; // string len + null terminator
-        let data_left_to_read = header.get_box_size() - data_read_so_far;
+        let data_left_to_read = match header.get_box_size().checked_sub(data_read_so_far)
+        {
+            Some(data_left_to_read) => data_left_to_read,
+            None                    => return container_error!("Item info entry is too small!"),
+        };
 
-        let mut additional_data = vec![0u8; data_left_to_read];
-        cursor.read_exact(&mut additional_data)?;
+        let additional_data = read_n_bytes(cursor, data_left_to_read)?;
 
         debug_println!("ID: {}, Name: {}", item_id, item_name);
 
@@ -106,7 +115,7 @@ ItemInfoEntryBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    -> Result<Box<dyn GenericIsoBox>, Error>
     {
         return Ok(Box::new(ItemInfoEntryBox::construct_from_cursor_unboxed(
             cursor, 
@@ -123,11 +132,34 @@ ItemInfoBox
     (
         &self
     )
-    -> &ItemInfoEntryBox
+    -> Result<&ItemInfoEntryBox, Error>
     {
-        return self.items.iter()
+        if let Some(exif_item) = self.items.iter()
             .find(|item| item.item_name == "Exif")
-            .unwrap();
+        {
+            return Ok(exif_item);
+        }
+        return Err(Error::NoExifData);
+    }
+
+    /// XMP data is stored in an item of type `mime` with the content type
+    /// "application/rdf+xml", which is the start of the additional data
+    pub fn
+    get_xmp_item
+    (
+        &self
+    )
+    -> Result<&ItemInfoEntryBox, Error>
+    {
+        if let Some(xmp_item) = self.items.iter()
+            .find(|item| 
+                item.item_name == "mime" 
+                && item.additional_data.starts_with(XMP_CONTENT_TYPE)
+            )
+        {
+            return Ok(xmp_item);
+        }
+        return Err(Error::NoXmpData);
     }
 }
 
@@ -143,7 +175,7 @@ ItemInfoBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    -> Result<Box<dyn GenericIsoBox>, Error>
     {
         let item_count;
 
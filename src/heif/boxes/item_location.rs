@@ -4,6 +4,8 @@
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::debug_println;
 use crate::endian::Endian;
 use crate::u8conversion::U8conversion;
@@ -117,7 +119,7 @@ ItemLocationEntryExtentEntry
         length_size: u8,
         index_size:  u8,
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let extent_index = if 
             (header.get_version() == 1 || header.get_version() == 2)
@@ -128,7 +130,7 @@ ItemLocationEntryExtentEntry
             {
                 4 => Some(read_be_u32(cursor)? as u64),
                 8 => Some(read_be_u64(cursor)?),
-                _ => panic!("Invalid index_size!")
+                _ => return container_error!("Invalid index_size!")
             }
         }
         else
@@ -141,7 +143,7 @@ ItemLocationEntryExtentEntry
             0 => 0,
             4 => read_be_u32(cursor)? as u64,
             8 => read_be_u64(cursor)?,
-            _ => panic!("Invalid offset_size!")
+            _ => return container_error!("Invalid offset_size!")
         };
 
         let extent_length = match length_size
@@ -149,7 +151,7 @@ ItemLocationEntryExtentEntry
             0 => 0,
             4 => read_be_u32(cursor)? as u64,
             8 => read_be_u64(cursor)?,
-            _ => panic!("Invalid length_size!")
+            _ => return container_error!("Invalid length_size!")
         };
 
         return Ok(Self{extent_index, extent_offset, extent_length});
@@ -195,13 +197,13 @@ ItemLocationEntry
         base_offset_size:  u8,
         index_size:        u8,
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let item_id = match header.get_version()
         {
             0 | 1 => read_be_u16(cursor)? as u32,
             2     => read_be_u32(cursor)?,
-            _     => panic!("Invalid version for ItemLocationEntry decode!")
+            _     => return container_error!("Invalid version for ItemLocationEntry decode!")
         };
 
         let reserved_and_construction_method = if 
@@ -214,7 +216,7 @@ ItemLocationEntry
             0 => 0,
             4 => read_be_u32(cursor)? as u64,
             8 => read_be_u64(cursor)?,
-            _ => panic!("Invalid base_offset_size!")
+            _ => return container_error!("Invalid base_offset_size!")
         };
 
         let extent_count = read_be_u16(cursor)?;
@@ -251,14 +253,14 @@ ItemLocationEntry
     (
         &self
     )
-    -> ItemConstructionMethod
+    -> Result<ItemConstructionMethod, Error>
     {
         return match self.reserved_and_construction_method as u8 & 0x0f
         {
-            0 => ItemConstructionMethod::FILE,
-            1 => ItemConstructionMethod::IDAT,
-            2 => ItemConstructionMethod::ITEM,
-            _ => panic!("Unknown item construction method!")
+            0 => Ok(ItemConstructionMethod::FILE),
+            1 => Ok(ItemConstructionMethod::IDAT),
+            2 => Ok(ItemConstructionMethod::ITEM),
+            _ => container_error!("Unknown item construction method!")
         };
     }
 
@@ -326,7 +328,7 @@ ItemLocationBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let temp = read_be_u16(cursor)?;
         let (offset_size,        length_size,              base_offset_size) =
@@ -341,7 +343,7 @@ ItemLocationBox
         {
             0 | 1 => read_be_u16(cursor)? as u32,
             2     => read_be_u32(cursor)?,
-            _     => panic!("Invalid version for ItemLocationBox decode!")
+            _     => return container_error!("Invalid version for ItemLocationBox decode!")
         };
 
         let mut items = Vec::new();
@@ -381,7 +383,7 @@ ItemLocationBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error> 
+    -> Result<Box<dyn GenericIsoBox>, Error> 
     {
         return Ok(Box::new(ItemLocationBox::construct_from_cursor_unboxed(
             cursor, 
@@ -0,0 +1,485 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::error::unsupported_format;
+use crate::endian::Endian;
+use crate::u8conversion::U8conversion;
+use crate::u8conversion::from_u8_vec_macro;
+use crate::u8conversion::to_u8_vec_macro;
+
+// The item properties box `iprp` consists of the item property container
+// box `ipco`, which holds the properties (e.g. the colour information `colr`
+// or the image spatial extents `ispe`) one after another, and one or more
+// item property association boxes `ipma`, which assign the properties to the
+// items by referring to their 1-based index within the `ipco` box.
+// See ISO/IEC 23008-12:2017, § 9.3
+//
+// As the properties are only needed for reading and writing the ICC profile,
+// the `iprp` box is kept as a generic box and its data is only processed
+// here, on demand.
+
+/// Colour types of `colr` properties holding an (unrestricted or restricted)
+/// ICC profile, see ISO/IEC 14496-12:2015, § 12.1.5
+const ICC_COLOUR_TYPES: [&[u8; 4]; 2] = [b"prof", b"rICC"];
+
+/// Properties that transform the image. These have to be associated with an
+/// item after all descriptive properties like `colr`
+const TRANSFORMATIVE_PROPERTIES: [&[u8; 4]; 4] = [b"clap", b"irot", b"imir", b"iscl"];
+
+/// Position of a box within some data
+struct
+RawBox
+{
+    box_type:   [u8; 4],
+    start:      usize,
+    data_start: usize,
+    end:        usize,
+}
+
+/// An entry of an `ipma` box, with each association consisting of the
+/// essential flag and the index of the property
+struct
+AssociationEntry
+{
+    item_id:      u32,
+    associations: Vec<(bool, u16)>,
+}
+
+/// Splits the given data into the boxes that are stored one after another
+fn
+split_into_boxes
+(
+    data: &[u8]
+)
+-> Result<Vec<RawBox>, Error>
+{
+    let mut boxes    = Vec::new();
+    let mut position = 0;
+
+    while position < data.len()
+    {
+        if data.len() - position < 8
+        {
+            return container_error!("Item property box is truncated!");
+        }
+
+        let mut box_type = [0u8; 4];
+        box_type.copy_from_slice(&data[position+4..position+8]);
+
+        let (box_size, header_size) = match from_u8_vec_macro!(u32, &data[position..position+4], &Endian::Big)
+        {
+            // Box extends to the end of the data
+            0 => (data.len() - position, 8),
+
+            // Uses largesize box size
+            1 if data.len() - position >= 16 => (
+                from_u8_vec_macro!(u64, &data[position+8..position+16], &Endian::Big) as usize,
+                16
+            ),
+
+            box_size => (box_size as usize, 8),
+        };
+
+        if box_size < header_size || data.len() - position < box_size
+        {
+            return container_error!("Invalid size of item property box!");
+        }
+
+        boxes.push(RawBox {
+            box_type,
+            start:      position,
+            data_start: position + header_size,
+            end:        position + box_size,
+        });
+
+        position += box_size;
+    }
+
+    return Ok(boxes);
+}
+
+fn
+encode_box
+(
+    box_type: &[u8; 4],
+    data:     &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+    let box_size = match u32::try_from(8 + data.len())
+    {
+        Ok(box_size) => box_size,
+        Err(_)       => return container_error!("Item property box is too large!"),
+    };
+
+    let mut encoded = to_u8_vec_macro!(u32, &box_size, &Endian::Big);
+    encoded.extend(box_type.iter());
+    encoded.extend(data.iter());
+
+    return Ok(encoded);
+}
+
+fn
+is_icc_colour_property
+(
+    data:     &[u8],
+    property: &RawBox
+)
+-> bool
+{
+    return &property.box_type == b"colr"
+        && property.end - property.data_start >= 4
+        && ICC_COLOUR_TYPES.iter().any(|colour_type|
+            data[property.data_start..property.data_start+4] == colour_type[..]
+        );
+}
+
+/// Gets the position of the `ipco` box within the `iprp` data as well as the
+/// properties stored in it, with positions relative to the `ipco` data
+fn
+get_properties
+(
+    iprp_data: &[u8]
+)
+-> Result<(RawBox, Vec<RawBox>), Error>
+{
+    let ipco = match split_into_boxes(iprp_data)?.into_iter()
+        .find(|child| &child.box_type == b"ipco")
+    {
+        Some(ipco) => ipco,
+        None       => return container_error!("Could not find 'ipco' box!"),
+    };
+
+    let properties = split_into_boxes(&iprp_data[ipco.data_start..ipco.end])?;
+
+    return Ok((ipco, properties));
+}
+
+/// Gets the ICC profile stored in the first `colr` property with an ICC
+/// colour type, if there is such a property
+pub(crate) fn
+get_icc_profile
+(
+    iprp_data: &[u8]
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+    let (ipco, properties) = get_properties(iprp_data)?;
+    let     ipco_data      = &iprp_data[ipco.data_start..ipco.end];
+
+    return Ok(properties.iter()
+        .find(|property| is_icc_colour_property(ipco_data, property))
+        .map(|property| ipco_data[property.data_start+4..property.end].to_vec())
+    );
+}
+
+/// How the associations of the `ipma` box(es) need to be updated after the
+/// properties of the `ipco` box changed
+enum
+AssociationUpdate
+{
+    /// Associate the property with the given index with the given item
+    Add { property_index: u16, item_id: u32 },
+
+    /// Remove the properties with the given indices
+    Remove(Vec<u16>),
+}
+
+/// Constructs the new data of the `iprp` box with the ICC profile replaced
+/// by the given one. If there is no ICC profile yet, a new `colr` property
+/// gets associated with the primary item. If no profile is given, all `colr`
+/// properties with an ICC profile get removed.
+pub(crate) fn
+set_icc_profile
+(
+    iprp_data:       &[u8],
+    icc_profile:     Option<&[u8]>,
+    primary_item_id: u32
+)
+-> Result<Vec<u8>, Error>
+{
+    let (ipco, properties) = get_properties(iprp_data)?;
+    let     ipco_data      = &iprp_data[ipco.data_start..ipco.end];
+
+    let icc_property_indices = properties.iter()
+        .enumerate()
+        .filter(|(_, property)| is_icc_colour_property(ipco_data, property))
+        .map(|(index, _)| index as u16 + 1)
+        .collect::<Vec<u16>>();
+
+    let mut new_ipco_data = Vec::new();
+    let     update;
+
+    match icc_profile
+    {
+        Some(icc_profile) => {
+            let new_colr_property = encode_box(b"colr", &[&b"prof"[..], icc_profile].concat())?;
+
+            if icc_property_indices.is_empty()
+            {
+                new_ipco_data.extend(ipco_data.iter());
+                new_ipco_data.extend(new_colr_property);
+                update = Some(AssociationUpdate::Add { 
+                    property_index: properties.len() as u16 + 1, 
+                    item_id:        primary_item_id 
+                });
+            }
+            else
+            {
+                // Replace the existing properties, keeping their associations
+                for (index, property) in properties.iter().enumerate()
+                {
+                    if icc_property_indices.contains(&(index as u16 + 1))
+                    {
+                        new_ipco_data.extend(new_colr_property.iter());
+                    }
+                    else
+                    {
+                        new_ipco_data.extend(ipco_data[property.start..property.end].iter());
+                    }
+                }
+                update = None;
+            }
+        },
+
+        None => {
+            if icc_property_indices.is_empty()
+            {
+                return Ok(iprp_data.to_vec());
+            }
+
+            for (index, property) in properties.iter().enumerate()
+            {
+                if !icc_property_indices.contains(&(index as u16 + 1))
+                {
+                    new_ipco_data.extend(ipco_data[property.start..property.end].iter());
+                }
+            }
+            update = Some(AssociationUpdate::Remove(icc_property_indices));
+        },
+    }
+
+    let property_types = properties.iter()
+        .map(|property| property.box_type)
+        .collect::<Vec<[u8; 4]>>();
+
+    // Construct the new `iprp` data. A new association is only added to the
+    // first `ipma` box
+    let mut new_iprp_data     = Vec::new();
+    let mut associations_updated = false;
+
+    for child in split_into_boxes(iprp_data)?
+    {
+        let update_this_child = match &update
+        {
+            Some(AssociationUpdate::Add { .. }) => !associations_updated,
+            Some(AssociationUpdate::Remove(_))  => true,
+            None                                => false,
+        };
+
+        if &child.box_type == b"ipco"
+        {
+            new_iprp_data.extend(encode_box(b"ipco", &new_ipco_data)?);
+        }
+        else if &child.box_type == b"ipma" && update_this_child
+        {
+            let (full_box_header, mut entries) = decode_ipma(&iprp_data[child.data_start..child.end])?;
+            update_associations(
+                &mut entries, 
+                full_box_header[3] & 1 == 1, 
+                &property_types, 
+                update.as_ref().unwrap()
+            )?;
+
+            let mut new_ipma_data = full_box_header.to_vec();
+            new_ipma_data.extend(encode_ipma_entries(&full_box_header, &entries)?);
+            new_iprp_data.extend(encode_box(b"ipma", &new_ipma_data)?);
+
+            associations_updated = true;
+        }
+        else
+        {
+            new_iprp_data.extend(iprp_data[child.start..child.end].iter());
+        }
+    }
+
+    if !associations_updated && update.is_some()
+    {
+        return container_error!("Could not find 'ipma' box!");
+    }
+
+    return Ok(new_iprp_data);
+}
+
+fn
+update_associations
+(
+    entries:        &mut Vec<AssociationEntry>,
+    large_indices:  bool,
+    property_types: &[[u8; 4]],
+    update:         &AssociationUpdate
+)
+-> Result<(), Error>
+{
+    match update
+    {
+        AssociationUpdate::Add { property_index, item_id } => {
+            let max_index = if large_indices { 0x7fff } else { 0x7f };
+            if *property_index > max_index
+            {
+                return unsupported_format!("HEIF: Too many item properties for adding an ICC profile!");
+            }
+
+            // The entries are ordered by their item IDs
+            let entry_index = entries.iter()
+                .position(|entry| entry.item_id >= *item_id)
+                .unwrap_or(entries.len());
+            if entries.get(entry_index).map_or(true, |entry| entry.item_id != *item_id)
+            {
+                entries.insert(entry_index, AssociationEntry {
+                    item_id:      *item_id,
+                    associations: Vec::new(),
+                });
+            }
+
+            let associations = &mut entries[entry_index].associations;
+            if associations.len() >= u8::MAX as usize
+            {
+                return unsupported_format!("HEIF: Too many properties associated with the primary item!");
+            }
+
+            // Descriptive properties like `colr` need to be associated before
+            // any transformative property
+            let insert_position = associations.iter()
+                .position(|(_, index)| property_types
+                    .get((*index as usize).wrapping_sub(1))
+                    .map_or(false, |property_type| TRANSFORMATIVE_PROPERTIES.contains(&property_type))
+                )
+                .unwrap_or(associations.len());
+            associations.insert(insert_position, (false, *property_index));
+        },
+
+        AssociationUpdate::Remove(removed_indices) => {
+            // The remaining properties move forward
+            for entry in entries.iter_mut()
+            {
+                entry.associations.retain(|(_, index)| !removed_indices.contains(index));
+                for (_, index) in entry.associations.iter_mut()
+                {
+                    *index -= removed_indices.iter().filter(|removed| *removed < index).count() as u16;
+                }
+            }
+        },
+    }
+
+    return Ok(());
+}
+
+/// Decodes the data of an `ipma` box into its version and flags and its
+/// entries
+fn
+decode_ipma
+(
+    data: &[u8]
+)
+-> Result<([u8; 4], Vec<AssociationEntry>), Error>
+{
+    if data.len() < 8
+    {
+        return container_error!("Item property association box is truncated!");
+    }
+
+    let mut full_box_header = [0u8; 4];
+    full_box_header.copy_from_slice(&data[0..4]);
+    let entry_count = from_u8_vec_macro!(u32, &data[4..8], &Endian::Big);
+
+    let item_id_size     = if full_box_header[0] < 1     { 2 } else { 4 };
+    let association_size = if full_box_header[3] & 1 == 1 { 2 } else { 1 };
+
+    let mut entries  = Vec::new();
+    let mut position = 8;
+
+    for _ in 0..entry_count
+    {
+        if data.len() < position + item_id_size + 1
+        {
+            return container_error!("Item property association box is truncated!");
+        }
+
+        let item_id = match item_id_size
+        {
+            2 => from_u8_vec_macro!(u16, &data[position..position+2], &Endian::Big) as u32,
+            _ => from_u8_vec_macro!(u32, &data[position..position+4], &Endian::Big),
+        };
+        let association_count = data[position + item_id_size] as usize;
+        position += item_id_size + 1;
+
+        if data.len() < position + association_count * association_size
+        {
+            return container_error!("Item property association box is truncated!");
+        }
+
+        // The highest bit is the essential flag, the others form the index
+        let associations = data[position..position + association_count * association_size]
+            .chunks_exact(association_size)
+            .map(|association| match association_size
+            {
+                2 => (association[0] & 0x80 != 0, from_u8_vec_macro!(u16, association, &Endian::Big) & 0x7fff),
+                _ => (association[0] & 0x80 != 0, (association[0] & 0x7f) as u16),
+            })
+            .collect();
+        position += association_count * association_size;
+
+        entries.push(AssociationEntry { item_id, associations });
+    }
+
+    return Ok((full_box_header, entries));
+}
+
+/// Encodes the entry count and the entries of an `ipma` box with the given
+/// version and flags
+fn
+encode_ipma_entries
+(
+    full_box_header: &[u8; 4],
+    entries:         &[AssociationEntry]
+)
+-> Result<Vec<u8>, Error>
+{
+    let mut encoded = to_u8_vec_macro!(u32, &(entries.len() as u32), &Endian::Big);
+
+    for entry in entries
+    {
+        if full_box_header[0] < 1
+        {
+            if entry.item_id > u16::MAX as u32
+            {
+                return container_error!("Item ID too large for item property association box!");
+            }
+            encoded.extend(to_u8_vec_macro!(u16, &(entry.item_id as u16), &Endian::Big));
+        }
+        else
+        {
+            encoded.extend(to_u8_vec_macro!(u32, &entry.item_id, &Endian::Big));
+        }
+
+        encoded.push(entry.associations.len() as u8);
+
+        for (essential, index) in &entry.associations
+        {
+            if full_box_header[3] & 1 == 1
+            {
+                let association = ((*essential as u16) << 15) | index;
+                encoded.extend(to_u8_vec_macro!(u16, &association, &Endian::Big));
+            }
+            else
+            {
+                encoded.push(((*essential as u8) << 7) | (*index as u8));
+            }
+        }
+    }
+
+    return Ok(encoded);
+}
@@ -5,10 +5,13 @@ use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::endian::Endian;
 use crate::u8conversion::U8conversion;
 use crate::u8conversion::to_u8_vec_macro;
 use crate::util::read_be_u32;
+use crate::util::read_n_bytes;
 
 use crate::heif::box_header::BoxHeader;
 use crate::heif::boxes::GenericIsoBox;
@@ -45,12 +48,11 @@ MetaBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    -> Result<Box<dyn GenericIsoBox>, Error>
     {
         // Read in the remaining bytes for this box
-        let     remaining_bytes = header.get_box_size() - header.get_header_size();
-        let mut meta_box_bytes  = vec![0u8; remaining_bytes];
-        cursor.read_exact(&mut meta_box_bytes)?;
+        let remaining_bytes = header.get_data_size()?;
+        let meta_box_bytes  = read_n_bytes(cursor, remaining_bytes)?;
 
         // Construct local cursor for these bytes
         let mut local_cursor = Cursor::new(meta_box_bytes);
@@ -104,7 +106,7 @@ HandlerBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let pre_defined  = read_be_u32(cursor)?;
         let handler_type = read_be_u32(cursor)?;
@@ -114,15 +116,18 @@ HandlerBox
             read_be_u32(cursor)?
         ];
 
-        let number_of_bytes_that_form_the_name = header.get_box_size() 
-            - header.get_header_size() // header
-            - 4                        // pre_defined
-            - 4                        // handler_type
-            - 12                       // reserved
-            ;
+        let number_of_bytes_that_form_the_name = match header.get_data_size()?
+            .checked_sub(
+                4                      // pre_defined
+                + 4                    // handler_type
+                + 12                   // reserved
+            )
+        {
+            Some(name_length) => name_length,
+            None              => return container_error!("Handler box is too small!"),
+        };
 
-        let mut name_buffer = vec![0u8; number_of_bytes_that_form_the_name];
-        cursor.read_exact(&mut name_buffer)?;
+        let name_buffer = read_n_bytes(cursor, number_of_bytes_that_form_the_name)?;
 
         return Ok(HandlerBox { 
             header:       header, 
@@ -4,6 +4,7 @@
 use std::io::Read;
 use std::io::Seek;
 
+use crate::error::Error;
 use crate::debug_println;
 
 use super::box_type::BoxType;
@@ -13,6 +14,7 @@ pub(super) mod iso;
 pub(super) mod meta;
 pub(super) mod item_info;
 pub(super) mod item_location;
+pub(super) mod item_properties;
 
 use iso::IsoBox;
 use meta::MetaBox;
@@ -40,7 +42,7 @@ ParsableIsoBox: GenericIsoBox
         cursor: &mut T,
         header:  BoxHeader
     )
-    -> Result<Box<dyn GenericIsoBox>, std::io::Error>;
+    -> Result<Box<dyn GenericIsoBox>, Error>;
 }
 
 
@@ -52,7 +54,7 @@ read_box_based_on_header
     cursor: &mut T,
     header:  BoxHeader
 )
--> Result<Box<dyn GenericIsoBox>, std::io::Error>
+-> Result<Box<dyn GenericIsoBox>, Error>
 {
     return match header.get_box_type()
     {
@@ -69,7 +71,7 @@ read_next_box
 (
     cursor: &mut T,
 )
--> Result<Box<dyn GenericIsoBox>, std::io::Error>
+-> Result<Box<dyn GenericIsoBox>, Error>
 {
     let header = BoxHeader::read_box_header(cursor)?;
 
@@ -4,20 +4,30 @@
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
 
+use crate::error::Error;
+use crate::error::container_error;
+use crate::error::unsupported_format;
 use crate::general_file_io::EXIF_HEADER;
+use crate::heif::box_header::BoxHeader;
 use crate::heif::box_type::BoxType;
 use crate::heif::boxes::item_location::ItemConstructionMethod;
 use crate::heif::boxes::item_location::ItemLocationEntry;
 use crate::heif::boxes::meta::MetaBox;
+use crate::heif::boxes::read_box_based_on_header;
 use crate::heif::read_next_box;
 
 use crate::metadata::Metadata;
+use crate::util::copy_n_bytes;
 use crate::util::insert_multiple_at;
 use crate::util::range_remove;
 use crate::util::read_be_u32;
 
 use super::boxes::GenericIsoBox;
+use super::boxes::item_properties;
+use super::boxes::iso::IsoBox;
 use super::boxes::item_info::ItemInfoBox;
 use super::boxes::item_location::ItemLocationBox;
 
@@ -36,7 +46,7 @@ HeifContainer
     (
         cursor: &mut T,
     )
-    -> Result<Self, std::io::Error>
+    -> Result<Self, Error>
     {
         let mut boxes = Vec::new();
 
@@ -55,21 +65,80 @@ HeifContainer
         return Ok(Self { boxes })
     }
 
+    /// Like `construct_from_cursor_unboxed`, but only the `meta` boxes are
+    /// read in completely. Of all other top level boxes (e.g. the `mdat` box
+    /// holding the image data) only the header is read, so that their data
+    /// does not need to be loaded into memory. 
+    /// Also returns the position and header of every top level box.
+    pub(super) fn
+    construct_from_cursor_meta_only
+    <T: Seek + Read>
+    (
+        cursor: &mut T,
+    )
+    -> Result<(Self, Vec<(u64, BoxHeader)>), Error>
+    {
+        let     input_length    = cursor.seek(SeekFrom::End(0))?;
+        let mut position        = cursor.seek(SeekFrom::Start(0))?;
+        let mut boxes           = Vec::new();
+        let mut top_level_boxes = Vec::new();
+
+        // Just like `construct_from_cursor_unboxed`, stop at the first box
+        // that can't be read
+        while let Ok(mut header) = BoxHeader::read_box_header(cursor)
+        {
+            if header.get_box_type() == BoxType::meta
+            {
+                match read_box_based_on_header(cursor, header.clone())
+                {
+                    Ok(meta_box) => boxes.push(meta_box),
+                    Err(_)       => break,
+                }
+            }
+            else
+            {
+                // A box size of 0 indicates that this is the last box, which
+                // extends to the end of the file
+                // See also: ISO/IEC 14496-12:2015, § 4.2
+                if header.get_box_size() == 0
+                {
+                    header.set_box_size((input_length - position) as usize);
+                }
+
+                let box_end = match position.checked_add(header.get_box_size() as u64)
+                {
+                    Some(box_end) if box_end <= input_length => box_end,
+                    _                                        => break,
+                };
+                if header.get_data_size().is_err()
+                {
+                    break;
+                }
+                cursor.seek(SeekFrom::Start(box_end))?;
+            }
+
+            top_level_boxes.push((position, header));
+            position = cursor.stream_position()?;
+        }
+
+        return Ok((Self { boxes }, top_level_boxes));
+    }
+
     fn
     get_meta_box
     (
         &self
     )
-    -> &MetaBox
+    -> Result<&MetaBox, Error>
     {
         return match self.boxes.iter()
             .find(|b| b.get_header().get_box_type() == BoxType::meta)
-            .unwrap()
-            .as_any()
-            .downcast_ref::<MetaBox>() {
-                Some(unboxed) => unboxed,
-                None          => panic!("Can't unbox ItemInfoBox!")
-            };
+            .map(|b| b.as_any().downcast_ref::<MetaBox>())
+        {
+            Some(Some(unboxed)) => Ok(unboxed),
+            Some(None)          => container_error!("Can't unbox MetaBox!"),
+            None                => container_error!("Could not find 'meta' box!"),
+        };
     }
 
     fn
@@ -77,16 +146,16 @@ HeifContainer
     (
         &mut self
     )
-    -> &mut MetaBox
+    -> Result<&mut MetaBox, Error>
     {
         return match self.boxes.iter_mut()
             .find(|b| b.get_header().get_box_type() == BoxType::meta)
-            .unwrap()
-            .as_any_mut()
-            .downcast_mut::<MetaBox>() {
-                Some(unboxed) => unboxed,
-                None          => panic!("Can't unbox ItemInfoBox!")
-            };
+            .map(|b| b.as_any_mut().downcast_mut::<MetaBox>())
+        {
+            Some(Some(unboxed)) => Ok(unboxed),
+            Some(None)          => container_error!("Can't unbox MetaBox!"),
+            None                => container_error!("Could not find 'meta' box!"),
+        };
     }
 
     fn
@@ -94,16 +163,16 @@ HeifContainer
     (
         &self
     )
-    -> &ItemInfoBox
+    -> Result<&ItemInfoBox, Error>
     {
-        return match self.get_meta_box().other_boxes.iter()
+        return match self.get_meta_box()?.other_boxes.iter()
             .find(|b| b.get_header().get_box_type() == BoxType::iinf)
-            .unwrap()
-            .as_any()
-            .downcast_ref::<ItemInfoBox>() {
-                Some(unboxed) => unboxed,
-                None          => panic!("Can't unbox ItemInfoBox!")
-            };
+            .map(|b| b.as_any().downcast_ref::<ItemInfoBox>())
+        {
+            Some(Some(unboxed)) => Ok(unboxed),
+            Some(None)          => container_error!("Can't unbox ItemInfoBox!"),
+            None                => container_error!("Could not find 'iinf' box!"),
+        };
     }
 
     fn
@@ -111,9 +180,9 @@ HeifContainer
     (
         &self
     )
-    -> u16
+    -> Result<u16, Error>
     {
-        return self.get_item_info_box().get_exif_item().item_id;
+        return Ok(self.get_item_info_box()?.get_exif_item()?.item_id);
     }
 
     fn
@@ -121,16 +190,16 @@ HeifContainer
     (
         &self
     )
-    -> &ItemLocationBox
+    -> Result<&ItemLocationBox, Error>
     {
-        return match self.get_meta_box().other_boxes.iter()
+        return match self.get_meta_box()?.other_boxes.iter()
             .find(|b| b.get_header().get_box_type() == BoxType::iloc)
-            .unwrap()
-            .as_any()
-            .downcast_ref::<ItemLocationBox>() {
-                Some(unboxed) => unboxed,
-                None          => panic!("Can't unbox ItemLocationBox!")
-            };
+            .map(|b| b.as_any().downcast_ref::<ItemLocationBox>())
+        {
+            Some(Some(unboxed)) => Ok(unboxed),
+            Some(None)          => container_error!("Can't unbox ItemLocationBox!"),
+            None                => container_error!("Could not find 'iloc' box!"),
+        };
     }
 
     fn
@@ -138,65 +207,80 @@ HeifContainer
     (
         &mut self
     )
-    -> &mut ItemLocationBox
+    -> Result<&mut ItemLocationBox, Error>
     {
-        return match self.get_meta_box_mut().other_boxes.iter_mut()
+        return match self.get_meta_box_mut()?.other_boxes.iter_mut()
             .find(|b| b.get_header().get_box_type() == BoxType::iloc)
-            .unwrap()
-            .as_any_mut()
-            .downcast_mut::<ItemLocationBox>() {
-                Some(unboxed) => unboxed,
-                None          => panic!("Can't unbox ItemLocationBox!")
-            };
+            .map(|b| b.as_any_mut().downcast_mut::<ItemLocationBox>())
+        {
+            Some(Some(unboxed)) => Ok(unboxed),
+            Some(None)          => container_error!("Can't unbox ItemLocationBox!"),
+            None                => container_error!("Could not find 'iloc' box!"),
+        };
+    }
+
+    fn
+    get_item_id_xmp_data
+    (
+        &self
+    )
+    -> Result<u16, Error>
+    {
+        return Ok(self.get_item_info_box()?.get_xmp_item()?.item_id);
     }
 
     fn
-    get_exif_item_location_entry
+    get_item_location_entry
     (
         &self,
-        exif_item_id: u16,
+        item_id: u16,
     )
-    -> &ItemLocationEntry
+    -> Result<&ItemLocationEntry, Error>
     {
-        return self.get_item_location_box().items.iter()
-            .find(|item| item.item_id == exif_item_id as u32)
-            .unwrap();
+        if let Some(entry) = self.get_item_location_box()?.items.iter()
+            .find(|item| item.item_id == item_id as u32)
+        {
+            return Ok(entry);
+        }
+        return container_error!(format!("Could not find location of item {}!", item_id));
     }
 
+    /// Gets the position and length of the data of the item with the given
+    /// ID, e.g. the EXIF or XMP item, which has to consist of a single extent
     fn
-    get_exif_data_pos_and_len
+    get_item_data_pos_and_len
     (
         &self,
-        exif_item_id: u16,
+        item_id: u16,
     )
-    -> (u64, u64)
+    -> Result<(u64, u64), Error>
     {
-        let exif_item    = self.get_exif_item_location_entry(exif_item_id);
-        let exif_extents = &exif_item.extents;
+        let item    = self.get_item_location_entry(item_id)?;
+        let extents = &item.extents;
 
-        if exif_extents.len() != 1
+        if extents.len() != 1
         {
-            panic!("Expected exactly one EXIF extent info entry! Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+            return unsupported_format!("Expected exactly one extent info entry for EXIF/XMP item! Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
         }
 
-        match exif_item.get_construction_method()
+        match item.get_construction_method()?
         {
             super::boxes::item_location::ItemConstructionMethod::FILE => {
 
                 // Unwrap is ok here as we have previously established that 
-                // this first element must exist via if exif_extents.len() != 1
-                return (
-                    exif_extents.first().unwrap().extent_offset + exif_item.base_offset,
-                    exif_extents.first().unwrap().extent_length
-                );
+                // this first element must exist via if extents.len() != 1
+                return Ok((
+                    extents.first().unwrap().extent_offset + item.base_offset,
+                    extents.first().unwrap().extent_length
+                ));
             },
 
             super::boxes::item_location::ItemConstructionMethod::IDAT => {
-                panic!("HEIF: item constr. method 'IDAT' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+                return unsupported_format!("HEIF: item constr. method 'IDAT' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
             },
 
             super::boxes::item_location::ItemConstructionMethod::ITEM => {
-                panic!("HEIF: item constr. method 'ITEM' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+                return unsupported_format!("HEIF: item constr. method 'ITEM' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
             },
         }
     }
@@ -208,28 +292,34 @@ HeifContainer
         &self,
         cursor: &mut T,
     )
-    -> Result<Vec<u8>, std::io::Error>
+    -> Result<Vec<u8>, Error>
     {
         // Locate exif data
-        let exif_item_id    = self.get_item_id_exif_data();
-        let (start, length) = self.get_exif_data_pos_and_len(exif_item_id);
+        let exif_item_id    = self.get_item_id_exif_data()?;
+        let (start, length) = self.get_item_data_pos_and_len(exif_item_id)?;
 
         // Reset cursor to start of exif data
         cursor.seek(std::io::SeekFrom::Start(start))?;
 
         // Read in the first 4 bytes, which gives the offset to the start
         // of the TIFF header and seek to that
-        let exif_tiff_header_offset = read_be_u32(cursor)? as usize;
+        let exif_tiff_header_offset = read_be_u32(cursor)? as u64;
 
         cursor.seek(std::io::SeekFrom::Current(exif_tiff_header_offset as i64))?;
 
-        // Read in the remaining bytes
-        let mut exif_buffer = vec![0u8; 
-            length as usize 
-            - 4                       // the 4 bytes that store the offset
-            - exif_tiff_header_offset // the actual offset
-        ];
-        cursor.read_exact(&mut exif_buffer)?;
+        // Read in the remaining bytes, i.e. without the 4 bytes that store
+        // the offset and the actual offset
+        let remaining_length = match length.checked_sub(4 + exif_tiff_header_offset)
+        {
+            Some(remaining_length) => remaining_length,
+            None                   => return container_error!("EXIF item is shorter than its TIFF header offset!"),
+        };
+        let mut exif_buffer = Vec::new();
+        cursor.take(remaining_length).read_to_end(&mut exif_buffer)?;
+        if exif_buffer.len() as u64 != remaining_length
+        {
+            return container_error!("Could not read EXIF item data!");
+        }
 
         // Stick a EXIF_HEADER in the front
         let mut full_exif_data = EXIF_HEADER.to_vec();
@@ -249,26 +339,34 @@ HeifContainer
         cursor:   &mut T,
         metadata: &Metadata
     )
-    -> Result<(Vec<u8>, i64), std::io::Error>
+    -> Result<(Vec<u8>, i64), Error>
     {
         // Locate old exif data
-        let exif_item_id    = self.get_item_id_exif_data();
-        let (start, length) = self.get_exif_data_pos_and_len(exif_item_id);
+        let exif_item_id    = self.get_item_id_exif_data()?;
+        let (start, length) = self.get_item_data_pos_and_len(exif_item_id)?;
 
         // Reset cursor to start of exif data
         cursor.seek(std::io::SeekFrom::Start(start))?;
 
         // Read in all of this area
-        let mut exif_buffer = vec![0u8; length as usize];
-        cursor.read_exact(&mut exif_buffer)?;
+        let mut exif_buffer = Vec::new();
+        cursor.take(length).read_to_end(&mut exif_buffer)?;
+        if exif_buffer.len() as u64 != length
+        {
+            return container_error!("Could not read EXIF item data!");
+        }
 
         // Decode the first 4 bytes, which tells us where to cut off the old 
         // data and replace with the new one
-        let mut local_cursor            = Cursor::new(exif_buffer[0..4].to_vec());
+        let mut local_cursor            = Cursor::new(&exif_buffer);
         let     exif_tiff_header_offset = read_be_u32(&mut local_cursor)?;
 
         // Cut off data, starting at the old TIFF header and replace with new
-        let mut new_exif_buffer = exif_buffer[0..exif_tiff_header_offset as usize + 4].to_vec();
+        let mut new_exif_buffer = match exif_buffer.get(0..exif_tiff_header_offset as usize + 4)
+        {
+            Some(kept_data) => kept_data.to_vec(),
+            None            => return container_error!("EXIF item is shorter than its TIFF header offset!"),
+        };
         new_exif_buffer.append(&mut metadata.encode()?);
 
         let delta = new_exif_buffer.len() as i64 - length as i64;
@@ -279,45 +377,34 @@ HeifContainer
         ));
     }
 
-    pub(super) fn
-    generic_write_metadata
+    /// Updates the location data in the iloc box after the data area of an
+    /// item (e.g. EXIF) located at the given position changed its length by
+    /// `delta` bytes
+    fn
+    update_item_locations
     (
         &mut self,
-        file_buffer: &mut Vec<u8>,
-        metadata:    &    Metadata
+        old_item_pos: u64,
+        delta:        i64
     )
-    -> Result<(), std::io::Error>
+    -> Result<(), Error>
     {
-        // Find out where old exif is located, needed to determine which iloc
-        // entries need to be updated
-        let id                           = self.get_item_id_exif_data();
-        let (old_exif_pos, old_exif_len) = self.get_exif_data_pos_and_len(id);
-
-        let mut cursor = Cursor::new(file_buffer);
-
-        // Construct new exif data area
-        let (mut new_exif_area, delta) = self.construct_new_exif_data_area(
-            &mut cursor, 
-            metadata
-        )?;
-
-        // Update the location data in the iloc box
-        for item in self.get_item_location_box_mut().items.iter_mut()
+        for item in self.get_item_location_box_mut()?.items.iter_mut()
         {
             // First, check if any extent of this item has the same offset as
-            // the old exif data area. In that case, there must be only one
+            // the old item data area. In that case, there must be only one
             // extent - other cases can't be handled right now
             if item.extents.iter()
                 .any(|extent| {
-                    item.base_offset + extent.extent_offset == old_exif_pos
+                    item.base_offset + extent.extent_offset == old_item_pos
                 })
             {
                 if item.extents.len() != 1
                 {
-                    panic!("Expect to have exactly one extent info for EXIF!");
+                    return unsupported_format!("Expect to have exactly one extent info for EXIF/XMP!");
                 }
 
-                // In case of the EXIF extent information we need to update
+                // In case of the item's extent information we need to update
                 // the length information, not the offset!
                 let new_ext_len = (
                     item.extents.first().unwrap().extent_length as i64
@@ -328,7 +415,7 @@ HeifContainer
                 continue;
             }
 
-            if item.get_construction_method() == ItemConstructionMethod::IDAT
+            if item.get_construction_method()? == ItemConstructionMethod::IDAT
             {
                 // In this case the offset information is relative to the
                 // position of an idat box -> not affected by change in length
@@ -336,7 +423,7 @@ HeifContainer
                 continue;
             }
 
-            if item.get_construction_method() == ItemConstructionMethod::ITEM
+            if item.get_construction_method()? == ItemConstructionMethod::ITEM
             {
                 // Offset is relative to another item's extent
                 // Also nothing to do here (for now...)
@@ -347,11 +434,11 @@ HeifContainer
             {
                 // Potentially modify the entire base offset 
                 // however, we can only do that if all complete offsets
-                // point to an area after the exif data area
+                // point to an area after the item data area
                 // So we need to check that first:
                 if item.extents.iter()
                     .all(|extent| {
-                        item.base_offset + extent.extent_offset >= old_exif_pos
+                        item.base_offset + extent.extent_offset >= old_item_pos
                     })
                 {
                     item.base_offset = (item.base_offset as i64 + delta) as u64;
@@ -365,65 +452,311 @@ HeifContainer
             {
                 let complete_offset = item.base_offset + extent.extent_offset;
 
-                if complete_offset > old_exif_pos
+                if complete_offset > old_item_pos
                 {
                     extent.extent_offset = (extent.extent_offset as i64 + delta) as u64;
                 }
             }
         }
 
+        return Ok(());
+    }
+
+    pub(super) fn
+    generic_write_metadata
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        metadata:    &    Metadata
+    )
+    -> Result<(), Error>
+    {
+        // Find out where old exif is located, needed to determine which iloc
+        // entries need to be updated
+        let id                           = self.get_item_id_exif_data()?;
+        let (old_exif_pos, old_exif_len) = self.get_item_data_pos_and_len(id)?;
+
+        let mut cursor = Cursor::new(&mut *file_buffer);
+
+        // Construct new exif data area
+        let (new_exif_area, _) = self.construct_new_exif_data_area(
+            &mut cursor, 
+            metadata
+        )?;
+
+        return self.replace_item_data(file_buffer, old_exif_pos, old_exif_len, new_exif_area);
+    }
+
+    /// Replaces the data of the XMP item with the given XMP packet. Only
+    /// works if the file already contains such an item
+    pub(super) fn
+    generic_write_xmp
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        xmp_packet:  &[u8]
+    )
+    -> Result<(), Error>
+    {
+        let id                         = self.get_item_id_xmp_data()?;
+        let (old_xmp_pos, old_xmp_len) = self.get_item_data_pos_and_len(id)?;
+
+        return self.replace_item_data(file_buffer, old_xmp_pos, old_xmp_len, xmp_packet.to_vec());
+    }
+
+    /// Gets the XMP packet stored in the XMP item
+    pub(super) fn
+    get_xmp_data
+    <T: Seek + Read>
+    (
+        &self,
+        cursor: &mut T,
+    )
+    -> Result<Vec<u8>, Error>
+    {
+        let xmp_item_id     = self.get_item_id_xmp_data()?;
+        let (start, length) = self.get_item_data_pos_and_len(xmp_item_id)?;
+
+        cursor.seek(std::io::SeekFrom::Start(start))?;
+
+        let mut xmp_buffer = Vec::new();
+        cursor.take(length).read_to_end(&mut xmp_buffer)?;
+        if xmp_buffer.len() as u64 != length
+        {
+            return container_error!("Could not read XMP item data!");
+        }
+
+        return Ok(xmp_buffer);
+    }
+
+    /// Gets the ID of the primary item, i.e. the main image, from the `pitm`
+    /// box
+    fn
+    get_primary_item_id
+    (
+        &self
+    )
+    -> Result<u32, Error>
+    {
+        let pitm = match self.get_meta_box()?.other_boxes.iter()
+            .find(|b| b.get_header().get_box_type() == BoxType::pitm)
+            .map(|b| (b.get_header().get_version(), b.as_any().downcast_ref::<IsoBox>()))
+        {
+            Some((version, Some(unboxed))) => (version, unboxed.get_data()),
+            Some((_,       None))          => return container_error!("Can't unbox 'pitm' box!"),
+            None                           => return container_error!("Could not find 'pitm' box!"),
+        };
+
+        return match pitm
+        {
+            (0, data) if data.len() >= 2 => Ok(u16::from_be_bytes([data[0], data[1]]) as u32),
+            (_, data) if data.len() >= 4 => Ok(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+            _                            => container_error!("'pitm' box is truncated!"),
+        };
+    }
+
+    /// Gets the item properties box, if there is one. Files without any
+    /// image items (e.g. only holding EXIF data) don't need one
+    fn
+    get_item_properties_box
+    (
+        &self
+    )
+    -> Result<Option<&IsoBox>, Error>
+    {
+        return match self.get_meta_box()?.other_boxes.iter()
+            .find(|b| b.get_header().get_box_type() == BoxType::iprp)
+            .map(|b| b.as_any().downcast_ref::<IsoBox>())
+        {
+            Some(Some(unboxed)) => Ok(Some(unboxed)),
+            Some(None)          => container_error!("Can't unbox 'iprp' box!"),
+            None                => Ok(None),
+        };
+    }
+
+    /// Gets the ICC profile stored in the colour information property `colr`
+    pub(super) fn
+    get_icc_profile
+    (
+        &self
+    )
+    -> Result<Vec<u8>, Error>
+    {
+        return match self.get_item_properties_box()?
+        {
+            Some(iprp) => item_properties::get_icc_profile(iprp.get_data())?.ok_or(Error::NoIccProfile),
+            None       => Err(Error::NoIccProfile),
+        };
+    }
+
+    /// Replaces the ICC profile stored in the colour information property
+    /// `colr` or removes it if no profile is given. As this changes the size
+    /// of the `meta` box, the locations of the items stored after it (e.g. 
+    /// in the `mdat` box) get updated.
+    pub(super) fn
+    generic_write_icc_profile
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        icc_profile: Option<&[u8]>
+    )
+    -> Result<(), Error>
+    {
+        if icc_profile.is_none() && self.get_item_properties_box()?.is_none()
+        {
+            return Ok(());
+        }
+
+        let primary_item_id = self.get_primary_item_id()?;
+
+        // Data after the parsed boxes is kept as it is
+        let parsed_length = self.boxes.iter()
+            .map(|iso_box| iso_box.get_header().get_box_size())
+            .sum::<usize>();
+        let mut unparsed_data = match self.boxes.last()
+        {
+            Some(last_box) if last_box.get_header().get_box_size() != 0
+                => file_buffer.split_off(parsed_length.min(file_buffer.len())),
+            _
+                => Vec::new(),
+        };
+
+        // Determine the position of the `iprp` box within the file
+        let meta_index = match self.boxes.iter()
+            .position(|b| b.get_header().get_box_type() == BoxType::meta)
+        {
+            Some(index) => index,
+            None        => return container_error!("Could not find 'meta' box!"),
+        };
+        let meta_position = self.boxes[..meta_index].iter()
+            .map(|iso_box| iso_box.get_header().get_box_size() as u64)
+            .sum::<u64>();
+
+        let meta_box   = self.get_meta_box_mut()?;
+        let iprp_index = match meta_box.other_boxes.iter()
+            .position(|b| b.get_header().get_box_type() == BoxType::iprp)
+        {
+            Some(index) => index,
+            None        => return container_error!("Could not find 'iprp' box!"),
+        };
+        let iprp_position = meta_position
+            + meta_box.get_header().get_box_size() as u64
+            - meta_box.other_boxes[iprp_index..].iter()
+                .map(|iso_box| iso_box.get_header().get_box_size() as u64)
+                .sum::<u64>();
+
+        let iprp_box = match meta_box.other_boxes[iprp_index].as_any_mut().downcast_mut::<IsoBox>()
+        {
+            Some(unboxed) => unboxed,
+            None          => return container_error!("Can't unbox 'iprp' box!"),
+        };
+
+        let new_iprp_data = item_properties::set_icc_profile(
+            iprp_box.get_data(), 
+            icc_profile, 
+            primary_item_id
+        )?;
+        let delta = new_iprp_data.len() as i64 - iprp_box.get_data().len() as i64;
+        iprp_box.set_data(new_iprp_data);
+
+        let new_meta_size = (meta_box.get_header().get_box_size() as i64 + delta) as usize;
+        meta_box.get_header_mut().set_box_size(new_meta_size);
+
+        // No item is located at the position of the `iprp` box, so only the
+        // items after it get moved
+        self.update_item_locations(iprp_position, delta)?;
+
+        file_buffer.clear();
+        for iso_box in &self.boxes
+        {
+            file_buffer.extend(iso_box.serialize());
+        }
+        file_buffer.append(&mut unparsed_data);
+
+        return Ok(());
+    }
+
+    /// Replaces the data area of an item (e.g. EXIF or XMP) located at the
+    /// given position with the new data, updates the item locations and 
+    /// reconstructs the file from the parsed boxes
+    fn
+    replace_item_data
+    (
+        &mut self,
+        file_buffer:      &mut Vec<u8>,
+        old_item_pos:     u64,
+        old_item_len:     u64,
+        mut new_item_area: Vec<u8>
+    )
+    -> Result<(), Error>
+    {
+        // The file gets reconstructed from the parsed boxes, so the item data
+        // has to be located within them - otherwise it would get lost
+        let parsed_length = self.boxes.iter()
+            .map(|iso_box| iso_box.get_header().get_box_size() as u64)
+            .sum::<u64>();
+        if old_item_pos.saturating_add(old_item_len) > parsed_length
+        {
+            return container_error!("Item data is not located within the boxes of the file!");
+        }
+
+        let delta = new_item_area.len() as i64 - old_item_len as i64;
+
+        // Update the location data in the iloc box
+        self.update_item_locations(old_item_pos, delta)?;
+
         // Now we clear the vec and write the boxes to it
         // Keep track of how many bytes were written so we know when to 
-        // replace old exif data with new
-        cursor.get_mut().clear();
+        // replace old item data with new
+        file_buffer.clear();
 
         let mut written_bytes    = 0usize;
-        let mut new_exif_written = false;
-        let     end_of_old_exif  = (old_exif_pos + old_exif_len) as usize;
+        let mut new_item_written = false;
+        let     end_of_old_item  = (old_item_pos + old_item_len) as usize;
 
         for iso_box in &mut self.boxes
         {
             let mut serialized = iso_box.serialize();
 
-            // If this box encompasses the exif data area, update its size and
+            // If this box encompasses the item data area, update its size and
             // serialize it again
             // TODO: As this is not the cleanest approach (e.g. what if the
-            // exif area is not in this top level box but some nested box? 
+            // item area is not in this top level box but some nested box? 
             // -> requires update of size fields of all boxes "downward") some
             // other solution needs to be found for this
             // In the meantime, this should work for the majority of HEIFs
             if 
-                written_bytes + serialized.len() >= end_of_old_exif 
+                written_bytes + serialized.len() >= end_of_old_item 
                 && 
-                !new_exif_written
+                !new_item_written
             {
                 let new_size = (iso_box.get_header().get_box_size() as i64 + delta) as usize;
                 iso_box.get_header_mut().set_box_size(new_size);
                 serialized = iso_box.serialize();
 
-                // Write the serialized box with the OLD exif data
-                cursor.get_mut().extend(&serialized);
+                // Write the serialized box with the OLD item data
+                file_buffer.extend(&serialized);
 
-                // Remove old exif data
+                // Remove old item data
                 range_remove(
-                    cursor.get_mut(), 
-                    old_exif_pos as usize, 
-                    (old_exif_pos + old_exif_len) as usize
+                    file_buffer, 
+                    old_item_pos as usize, 
+                    (old_item_pos + old_item_len) as usize
                 );
 
-                // Insert new exif data
+                // Insert new item data
                 insert_multiple_at(
-                    cursor.get_mut(),
-                    old_exif_pos as usize, 
-                    &mut new_exif_area
+                    file_buffer,
+                    old_item_pos as usize, 
+                    &mut new_item_area
                 );
 
-                new_exif_written = true;
+                new_item_written = true;
             }
             else
             {
                 // Just extend with the serialized box contents
-                cursor.get_mut().extend(&serialized);
+                file_buffer.extend(&serialized);
             }
 
             written_bytes = written_bytes + serialized.len();
@@ -432,13 +765,155 @@ HeifContainer
         return Ok(());
     }
 
+    /// Streaming counterpart to `generic_write_metadata` for a container that
+    /// got constructed via `construct_from_cursor_meta_only`. Apart from the
+    /// `meta` boxes, which get serialized again, the top level boxes are 
+    /// copied from the input to the output, with the exif data area being
+    /// replaced on the way.
+    pub(super) fn
+    generic_write_metadata_to_stream
+    <R: Read + Seek, W: Write>
+    (
+        &mut self,
+        top_level_boxes: &[(u64, BoxHeader)],
+        input:           &mut R,
+        output:          &mut W,
+        metadata:        &Metadata
+    )
+    -> Result<(), Error>
+    {
+        // Find out where old exif is located, needed to determine which iloc
+        // entries need to be updated
+        let id                           = self.get_item_id_exif_data()?;
+        let (old_exif_pos, old_exif_len) = self.get_item_data_pos_and_len(id)?;
+        let end_of_old_exif              = old_exif_pos.saturating_add(old_exif_len);
+
+        // The exif data area has to be located within the data of one of 
+        // the boxes that are copied
+        let exif_box_index = match top_level_boxes.iter()
+            .position(|(position, header)| position + header.get_box_size() as u64 >= end_of_old_exif)
+        {
+            Some(index) => index,
+            None        => return container_error!("EXIF data is not located within the boxes of the file!"),
+        };
+
+        let (exif_box_position, exif_box_header) = &top_level_boxes[exif_box_index];
+        let exif_box_data_position = exif_box_position + exif_box_header.get_header_size() as u64;
+
+        if old_exif_pos < exif_box_data_position
+        {
+            return container_error!("EXIF data is not located within a single box of the file!");
+        }
+
+        if exif_box_header.get_box_type() == BoxType::meta
+        {
+            return unsupported_format!("HEIF: EXIF data within the 'meta' box is currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+        }
+
+        // Construct new exif data area and update the location data in the
+        // iloc box
+        let (new_exif_area, delta) = self.construct_new_exif_data_area(
+            input, 
+            metadata
+        )?;
+        self.update_item_locations(old_exif_pos, delta)?;
+
+        let mut meta_boxes = self.boxes.iter();
+
+        for (index, (position, header)) in top_level_boxes.iter().enumerate()
+        {
+            if header.get_box_type() == BoxType::meta
+            {
+                // Unwrap is ok as the constructor adds a box for every 
+                // top level meta box
+                output.write_all(&meta_boxes.next().unwrap().serialize())?;
+                continue;
+            }
+
+            input.seek(SeekFrom::Start(*position))?;
+
+            if index != exif_box_index
+            {
+                copy_n_bytes(input, output, header.get_box_size() as u64)?;
+                continue;
+            }
+
+            // Write the header with the updated size, followed by the data
+            // of the box with the new exif data instead of the old one
+            let mut new_header = header.clone();
+            new_header.set_box_size((header.get_box_size() as i64 + delta) as usize);
+            output.write_all(&new_header.serialize())?;
+
+            input.seek(SeekFrom::Start(exif_box_data_position))?;
+            copy_n_bytes(input, output, old_exif_pos - exif_box_data_position)?;
+            output.write_all(&new_exif_area)?;
+
+            input.seek(SeekFrom::Start(end_of_old_exif))?;
+            copy_n_bytes(input, output, position + header.get_box_size() as u64 - end_of_old_exif)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Streaming removal of the top level box with the given index for a
+    /// container that got constructed via `construct_from_cursor_meta_only`.
+    /// The item locations pointing behind the removed box get updated, so 
+    /// the `meta` boxes get serialized again while all other boxes are 
+    /// copied from the input to the output.
+    pub(super) fn
+    generic_remove_top_level_box
+    <R: Read + Seek, W: Write>
+    (
+        &mut self,
+        top_level_boxes: &[(u64, BoxHeader)],
+        index:           usize,
+        input:           &mut R,
+        output:          &mut W
+    )
+    -> Result<(), Error>
+    {
+        let (removed_position, removed_header) = &top_level_boxes[index];
+        if removed_header.get_box_type() == BoxType::meta
+        {
+            return container_error!("Can't remove the 'meta' box of a HEIF file!");
+        }
+
+        self.update_item_locations(
+            *removed_position, 
+            -(removed_header.get_box_size() as i64)
+        )?;
+
+        let mut meta_boxes = self.boxes.iter();
+
+        for (current_index, (position, header)) in top_level_boxes.iter().enumerate()
+        {
+            if current_index == index
+            {
+                continue;
+            }
+
+            if header.get_box_type() == BoxType::meta
+            {
+                // Unwrap is ok as the constructor adds a box for every 
+                // top level meta box
+                output.write_all(&meta_boxes.next().unwrap().serialize())?;
+                continue;
+            }
+
+            input.seek(SeekFrom::Start(*position))?;
+            copy_n_bytes(input, output, header.get_box_size() as u64)?;
+        }
+
+        return Ok(());
+    }
+
     pub(super) fn 
     generic_clear_metadata
     (
         &mut self,
         file_buffer: &mut Vec<u8>,
     )
-    -> Result<(), std::io::Error>
+    -> Result<(), Error>
     {
         // Instead of truly clearing the metadata, just write an empty 
         // exif data area
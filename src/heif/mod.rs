@@ -14,36 +14,113 @@ mod container;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
 
+use crate::error::Error;
+use crate::error::container_error;
+use crate::error::unsupported_format;
 use crate::general_file_io::open_read_file;
 use crate::general_file_io::open_write_file;
 
 use crate::general_file_io::EXIF_HEADER;
 use crate::metadata::Metadata;
+use crate::util::copy_n_bytes;
 
+use crate::heif::box_header::BoxHeader;
+use crate::heif::box_type::BoxType;
 use crate::heif::boxes::read_next_box;
 use crate::heif::container::HeifContainer;
 
+/// Brands (major or compatible) of an `ftyp` box that indicate image data 
+/// that can be handled using the HEIF container logic
+const HEIF_BRANDS: [&[u8; 4]; 11] = [
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"hevm", b"hevs",
+    b"mif1", b"msf1", b"mif2"
+];
+
+/// User type of the top level `uuid` box holding the C2PA manifest store
+const C2PA_UUID: [u8; 16] = [
+    0xd8, 0xfe, 0xc3, 0xd6, 0x1b, 0x0e, 0x48, 0x3c, 
+    0x92, 0x97, 0x58, 0x28, 0x87, 0x7e, 0xc4, 0x81
+];
+
+/// Checks if the given data starts with an `ftyp` box, regardless of its
+/// brands, i.e. if it is some kind of ISO BMFF file
+pub(crate) fn
+starts_with_ftyp_box
+(
+    file_buffer: &[u8]
+)
+-> bool
+{
+    return file_buffer.get(4..8) == Some(b"ftyp");
+}
+
+/// Checks if the given data starts with an `ftyp` box whose major brand or
+/// one of its compatible brands indicate a HEIF file
+pub(crate) fn
+starts_with_heif_signature
+(
+    file_buffer: &[u8]
+)
+-> bool
+{
+    if file_buffer.len() < 16 || &file_buffer[4..8] != b"ftyp"
+    {
+        return false;
+    }
+
+    // The box may be larger than what we were given, so only check the
+    // brands that are actually available
+    let box_size = u32::from_be_bytes([
+        file_buffer[0], file_buffer[1], file_buffer[2], file_buffer[3]
+    ]) as usize;
+    let box_end  = std::cmp::min(box_size, file_buffer.len());
+
+    // Major brand at bytes 8..12, minor version at 12..16, followed by the 
+    // list of compatible brands
+    let major_brand       = std::iter::once(&file_buffer[8..12]);
+    let compatible_brands = file_buffer.get(16..box_end)
+        .unwrap_or(&[])
+        .chunks_exact(4);
+
+    return major_brand
+        .chain(compatible_brands)
+        .any(|brand| HEIF_BRANDS.iter().any(|heif_brand| &heif_brand[..] == brand));
+}
+
 fn
 generic_read_metadata
 <T: Seek + Read>
 (
     cursor: &mut T
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
-    let container = HeifContainer::construct_from_cursor_unboxed(cursor)?;
+    // Only the meta box is required to locate the EXIF data
+    let (container, _) = HeifContainer::construct_from_cursor_meta_only(cursor)?;
     return Ok(container.get_exif_data(cursor)?);
 }
 
+pub(crate) fn
+read_metadata_from_stream
+<R: Read + Seek>
+(
+    input: &mut R
+)
+-> Result<Vec<u8>, Error>
+{
+    return generic_read_metadata(input);
+}
+
 pub(crate) fn
 read_metadata
 (
     file_buffer: &[u8]
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
     let mut cursor = Cursor::new(file_buffer);
     return generic_read_metadata(&mut cursor);
@@ -54,7 +131,7 @@ file_read_metadata
 (
     path: &Path
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
     let mut file = open_read_file(path)?;
     return generic_read_metadata(&mut file);
@@ -68,7 +145,7 @@ write_metadata
 	file_buffer: &mut Vec<u8>,
 	metadata:    &Metadata
 )
--> Result<(), std::io::Error> 
+-> Result<(), Error> 
 {
     let mut cursor    = Cursor::new(file_buffer);
     let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
@@ -76,13 +153,36 @@ write_metadata
     return container.generic_write_metadata(cursor.get_mut(), metadata);
 }
 
+/// Writes the HEIF image read from the input to the output while replacing 
+/// the metadata. Only the `meta` boxes are loaded into memory, all other 
+/// boxes - including the image data - get copied through.
+pub(crate) fn
+write_metadata_to_stream
+<R: Read + Seek, W: Write>
+(
+    input:    &mut R,
+    output:   &mut W,
+    metadata: &Metadata
+)
+-> Result<(), Error>
+{
+    let (mut container, top_level_boxes) = HeifContainer::construct_from_cursor_meta_only(input)?;
+
+    return container.generic_write_metadata_to_stream(
+        &top_level_boxes, 
+        input, 
+        output, 
+        metadata
+    );
+}
+
 pub(crate) fn
 file_write_metadata
 (
     path:     &Path,
     metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
     // Load the entire file into memory instead of performing multiple read, 
     // seek and write operations
@@ -132,7 +232,7 @@ clear_metadata
 (
     file_buffer: &mut Vec<u8>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
     let mut cursor    = Cursor::new(file_buffer);
     let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
@@ -145,7 +245,7 @@ file_clear_metadata
 (
     path: &Path
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
     // Load the entire file into memory instead of performing multiple read, 
     // seek and write operations
@@ -165,4 +265,178 @@ file_clear_metadata
     file.set_len(cursor.get_ref().len() as u64)?;
 
     return Ok(());
-}
\ No newline at end of file
+}
+
+/// Reads the XMP packet stored in the `mime` item with the content type
+/// "application/rdf+xml"
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+    let mut cursor = Cursor::new(file_buffer);
+    let (container, _) = HeifContainer::construct_from_cursor_meta_only(&mut cursor)?;
+    return container.get_xmp_data(&mut cursor);
+}
+
+/// Replaces the data of the XMP item with the given packet. Adding an XMP
+/// item to a file that doesn't have one yet is not supported (yet)
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_packet:  &[u8]
+)
+-> Result<(), Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return match container.generic_write_xmp(cursor.get_mut(), xmp_packet)
+    {
+        Err(Error::NoXmpData) => unsupported_format!("HEIF: Adding an XMP item is currently not supported!"),
+        result                => result,
+    };
+}
+
+/// Removing the XMP item requires removing its entries from the various 
+/// boxes of the `meta` box, which is not supported (yet)
+pub(crate) fn
+clear_xmp
+(
+    _file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+    return unsupported_format!("HEIF: Removing the XMP item is currently not supported!");
+}
+
+/// Reads the ICC profile stored in the colour information property `colr`
+pub(crate) fn
+read_icc_profile
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+    let mut cursor = Cursor::new(file_buffer);
+    let (container, _) = HeifContainer::construct_from_cursor_meta_only(&mut cursor)?;
+    return container.get_icc_profile();
+}
+
+/// Replaces the ICC profile or, if the file does not have one yet, adds a
+/// new colour information property for the primary item
+pub(crate) fn
+write_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+    icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_icc_profile(cursor.get_mut(), Some(icc_profile));
+}
+
+/// Removes the colour information properties holding an ICC profile
+pub(crate) fn
+clear_icc_profile
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_icc_profile(cursor.get_mut(), None);
+}
+
+/// Reads the C2PA manifest store from the top level `uuid` box with the C2PA
+/// user type. Only the `meta` boxes and the data of that box are read.
+pub(crate) fn
+read_c2pa_jumbf
+<R: Read + Seek>
+(
+    input: &mut R
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+    let (_, top_level_boxes) = HeifContainer::construct_from_cursor_meta_only(input)?;
+
+    let (position, header) = match top_level_boxes.iter().find(|(_, header)| is_c2pa_box(header))
+    {
+        Some(c2pa_box) => c2pa_box,
+        None           => return Ok(None),
+    };
+
+    input.seek(SeekFrom::Start(position + header.get_header_size() as u64))?;
+    let mut box_data = Vec::new();
+    copy_n_bytes(input, &mut box_data, header.get_data_size()? as u64)?;
+
+    // The box data starts with the version and flags of a full box, followed
+    // by the NUL terminated purpose of the box. For the purpose "manifest",
+    // the 8 bytes of the offset to the merkle tree data come next and only 
+    // then the manifest store
+    let purpose_end = match box_data.iter().skip(4).position(|byte| *byte == 0x00)
+    {
+        Some(purpose_length) => 4 + purpose_length,
+        None                 => return container_error!("Invalid C2PA box in HEIF file!"),
+    };
+
+    let mut jumbf_start = purpose_end + 1;
+    if &box_data[4..purpose_end] == b"manifest"
+    {
+        jumbf_start += 8;
+    }
+
+    return match box_data.get(jumbf_start..)
+    {
+        Some(jumbf) => Ok(Some(jumbf.to_vec())),
+        None        => container_error!("Invalid C2PA box in HEIF file!"),
+    };
+}
+
+/// Removes the top level `uuid` box holding the C2PA manifest store and
+/// updates the item locations pointing behind it
+pub(crate) fn
+clear_c2pa_jumbf
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+    let mut cursor = Cursor::new(file_buffer.as_slice());
+    let (mut container, top_level_boxes) = HeifContainer::construct_from_cursor_meta_only(&mut cursor)?;
+
+    let index = match top_level_boxes.iter().position(|(_, header)| is_c2pa_box(header))
+    {
+        Some(index) => index,
+        None        => return Ok(()),
+    };
+
+    let mut new_file_buffer = Vec::new();
+    container.generic_remove_top_level_box(
+        &top_level_boxes, 
+        index, 
+        &mut cursor, 
+        &mut new_file_buffer
+    )?;
+
+    *file_buffer = new_file_buffer;
+    return Ok(());
+}
+
+fn
+is_c2pa_box
+(
+    header: &BoxHeader
+)
+-> bool
+{
+    return header.get_box_type() == BoxType::uuid { usertype: C2PA_UUID };
+}
@@ -0,0 +1,230 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Reading and writing the ICC profile of an image, which describes the
+//! color space of the image data. The profile is handled as raw bytes and
+//! stored as follows:
+//! - HEIF: colour information property `colr` of the primary item
+//! - JPEG: `ICC_PROFILE` APP2 segments, split into chunks if necessary
+//! - PNG: zlib compressed in the `iCCP` chunk
+//! - TIFF: InterColorProfile tag (34675) of IFD0
+//! - WebP: `ICCP` chunk, announced by the flag in the VP8X chunk
+//!
+//! # Examples
+//! ```no_run
+//! use little_exif::filetype::FileExtension;
+//! use little_exif::icc::read_icc_profile;
+//! use little_exif::icc::write_icc_profile;
+//!
+//! let original  = std::fs::read("image.jpg").unwrap();
+//! let mut thumb = std::fs::read("thumbnail.png").unwrap();
+//!
+//! let icc_profile = read_icc_profile(&original, FileExtension::JPEG).unwrap();
+//! write_icc_profile(&mut thumb, &icc_profile, FileExtension::PNG { as_zTXt_chunk: true }).unwrap();
+//! ```
+
+use std::path::Path;
+
+use crate::c2pa;
+use crate::c2pa::C2paPolicy;
+use crate::error::Error;
+use crate::error::unsupported_format;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file;
+use crate::general_file_io::modify_file_atomically;
+use crate::metadata::WriteOptions;
+
+use crate::heif;
+use crate::jpg;
+use crate::png;
+use crate::tiff;
+use crate::webp;
+
+/// Reads the ICC profile of an image that is stored in memory. Returns
+/// `Error::NoIccProfile` if the image has no ICC profile.
+#[allow(unreachable_patterns)]
+pub fn
+read_icc_profile
+<T: AsRef<[u8]> + ?Sized>
+(
+	file_buffer: &T,
+	file_type:   FileExtension
+)
+-> Result<Vec<u8>, Error>
+{
+	let file_buffer = file_buffer.as_ref();
+
+	return match file_type
+	{
+		FileExtension::HEIF
+			=> heif::read_icc_profile(file_buffer),
+		FileExtension::JPEG
+			=>  jpg::read_icc_profile(file_buffer),
+		FileExtension::PNG { as_zTXt_chunk: _ }
+			=>  png::read_icc_profile(file_buffer),
+		FileExtension::TIFF
+			=> tiff::vec::read_icc_profile(file_buffer),
+		FileExtension::WEBP
+			=> webp::vec::read_icc_profile(file_buffer),
+		_
+			=> unsupported_format!(
+				format!(
+					"Function 'read_icc_profile' not yet implemented for {:?}",
+					file_type
+				)
+			),
+	};
+}
+
+/// Reads the ICC profile of the image at the specified path
+pub fn
+file_read_icc_profile
+(
+	path: &Path
+)
+-> Result<Vec<u8>, Error>
+{
+	let file_type   = get_file_type(path)?;
+	let file_buffer = std::fs::read(path)?;
+	return read_icc_profile(&file_buffer, file_type);
+}
+
+/// Writes the given ICC profile to an image stored as a `Vec<u8>`, replacing
+/// the profile that is already present. For PNG images, the sRGB chunk gets
+/// removed as well, as it would take precedence over the profile.
+/// A C2PA manifest of the image is preserved, see `WriteOptions`
+pub fn
+write_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8],
+	file_type:   FileExtension
+)
+-> Result<(), Error>
+{
+	return write_icc_profile_with_options(file_buffer, icc_profile, file_type, &WriteOptions::default());
+}
+
+/// Same as `write_icc_profile`, with the C2PA policy of the given options
+/// deciding what happens to a C2PA manifest of the image. The other options
+/// have no effect here.
+#[allow(unreachable_patterns)]
+pub fn
+write_icc_profile_with_options
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8],
+	file_type:   FileExtension,
+	options:     &WriteOptions
+)
+-> Result<(), Error>
+{
+	c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
+	return match file_type
+	{
+		FileExtension::HEIF
+			=> heif::write_icc_profile(file_buffer, icc_profile),
+		FileExtension::JPEG
+			=>  jpg::write_icc_profile(file_buffer, icc_profile),
+		FileExtension::PNG { as_zTXt_chunk: _ }
+			=>  png::write_icc_profile(file_buffer, icc_profile),
+		FileExtension::TIFF
+			=> tiff::vec::write_icc_profile(file_buffer, icc_profile),
+		FileExtension::WEBP
+			=> webp::vec::write_icc_profile(file_buffer, icc_profile),
+		_
+			=> unsupported_format!(
+				format!(
+					"Function 'write_icc_profile' not yet implemented for {:?}",
+					file_type
+				)
+			),
+	};
+}
+
+/// Writes the given ICC profile to the image at the specified path, which
+/// gets replaced atomically
+pub fn
+file_write_icc_profile
+(
+	path:        &Path,
+	icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+	return file_write_icc_profile_with_options(path, icc_profile, &WriteOptions::default());
+}
+
+/// Writes the given ICC profile to the image at the specified path, with the
+/// given options deciding whether the file gets replaced atomically or 
+/// modified in place and what happens to a C2PA manifest of the image
+pub fn
+file_write_icc_profile_with_options
+(
+	path:        &Path,
+	icc_profile: &[u8],
+	options:     &WriteOptions
+)
+-> Result<(), Error>
+{
+	let file_type = get_file_type(path)?;
+	return modify_file(
+		path,
+		options.atomic,
+		|file_buffer| write_icc_profile_with_options(file_buffer, icc_profile, file_type, options)
+	);
+}
+
+/// Removes the ICC profile from an image stored as a `Vec<u8>`. Images
+/// without an ICC profile are left untouched. A C2PA manifest of the image
+/// is preserved, see `WriteOptions`
+#[allow(unreachable_patterns)]
+pub fn
+clear_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	file_type:   FileExtension
+)
+-> Result<(), Error>
+{
+	c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
+	return match file_type
+	{
+		FileExtension::HEIF
+			=> heif::clear_icc_profile(file_buffer),
+		FileExtension::JPEG
+			=>  jpg::clear_icc_profile(file_buffer),
+		FileExtension::PNG { as_zTXt_chunk: _ }
+			=>  png::clear_icc_profile(file_buffer),
+		FileExtension::TIFF
+			=> tiff::vec::clear_icc_profile(file_buffer),
+		FileExtension::WEBP
+			=> webp::vec::clear_icc_profile(file_buffer),
+		_
+			=> unsupported_format!(
+				format!(
+					"Function 'clear_icc_profile' not yet implemented for {:?}",
+					file_type
+				)
+			),
+	};
+}
+
+/// Removes the ICC profile from the image at the specified path, which gets
+/// replaced atomically
+pub fn
+file_clear_icc_profile
+(
+	path: &Path
+)
+-> Result<(), Error>
+{
+	let file_type = get_file_type(path)?;
+	return modify_file_atomically(
+		path,
+		|file_buffer| clear_icc_profile(file_buffer, file_type)
+	);
+}
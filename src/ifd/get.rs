@@ -49,10 +49,13 @@ ImageFileDirectory
 	{
 		match self.ifd_type
 		{
-			ExifTagGroup::GENERIC  => None,
-			ExifTagGroup::EXIF     => Some((ExifTagGroup::GENERIC, ExifTag::ExifOffset(   Vec::new()))),
-			ExifTagGroup::GPS      => Some((ExifTagGroup::GENERIC, ExifTag::GPSInfo(      Vec::new()))),
-			ExifTagGroup::INTEROP  => Some((ExifTagGroup::EXIF,    ExifTag::InteropOffset(Vec::new()))),
+			ExifTagGroup::GENERIC    => None,
+			ExifTagGroup::EXIF       => Some((ExifTagGroup::GENERIC, ExifTag::ExifOffset(   Vec::new()))),
+			ExifTagGroup::GPS        => Some((ExifTagGroup::GENERIC, ExifTag::GPSInfo(      Vec::new()))),
+			ExifTagGroup::INTEROP    => Some((ExifTagGroup::EXIF,    ExifTag::InteropOffset(Vec::new()))),
+
+			// Not linked via an offset tag, but decoded from the MakerNote
+			ExifTagGroup::MAKERNOTES => None,
 		}
 	}
 
@@ -0,0 +1,500 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! The MakerNote tag is stored as an opaque blob. However, most vendors put
+//! an IFD into it whose value offsets are relative to the start of the TIFF
+//! data and *not* to the start of the maker note, so these offsets become
+//! invalid as soon as the maker note is written to a different position.
+//! Others (e.g. Nikon or Fujifilm) use offsets relative to the maker note,
+//! which is why their maker notes can be moved around freely.
+//! For the known layouts, the IFD of the maker note also gets decoded into
+//! the MAKERNOTES group, so that e.g. the lens or shutter count can be read.
+
+use std::io::Cursor;
+
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::exif_tag::ExifTag;
+use crate::exif_tag_format::ExifTagFormat;
+use crate::metadata::DecodeOptions;
+use crate::metadata::decode_options::DecodeContext;
+use crate::u8conversion::from_u8_vec_macro;
+use crate::u8conversion::to_u8_vec_macro;
+use crate::u8conversion::U8conversion;
+
+use super::ExifTagGroup;
+use super::ImageFileDirectory;
+
+/// The vendors whose maker notes can be decoded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum
+MakerNoteVendor
+{
+	Canon,
+	Nikon,
+	Sony,
+	Fujifilm,
+	Olympus,
+	Panasonic,
+	Apple,
+}
+
+impl
+MakerNoteVendor
+{
+	/// Checks if there is a named MAKERNOTES tag with this hex value for the
+	/// vendor, as different vendors use the same hex values for other things
+	pub(crate) fn
+	knows_tag
+	(
+		&self,
+		hex_value: u16
+	)
+	-> bool
+	{
+		let known_tags: &[u16] = match self
+		{
+			MakerNoteVendor::Canon     => &[0x0006, 0x0007, 0x000c, 0x0095, 0x0096],
+			MakerNoteVendor::Nikon     => &[0x001d, 0x0083, 0x0084, 0x00a7],
+			MakerNoteVendor::Sony      => &[0xb001, 0xb027],
+			MakerNoteVendor::Fujifilm  => &[0x0000, 0x0010, 0x1438],
+			MakerNoteVendor::Olympus   => &[0x0207, 0x0404],
+			MakerNoteVendor::Panasonic => &[0x0025, 0x0051, 0x0052],
+			MakerNoteVendor::Apple     => &[0x000b, 0x0011, 0x0015],
+		};
+
+		return known_tags.contains(&hex_value);
+	}
+}
+
+/// Where the byte order of the IFD in the maker note comes from
+enum
+ByteOrder
+{
+	Exif,
+	Little,
+	At(usize),
+}
+
+/// Where the IFD is located within the maker note: Either at a fixed
+/// position or at an offset (relative to the offset base) stored there
+enum
+IfdPosition
+{
+	Fixed(usize),
+	StoredAt(usize),
+}
+
+struct
+MakerNoteLayout
+{
+	vendor:      MakerNoteVendor,
+
+	// Maker notes without a header are identified via the (uppercase) value
+	// of the Make tag instead
+	header:      &'static [u8],
+	make:        &'static str,
+
+	byte_order:  ByteOrder,
+
+	// Position within the maker note that its offsets are relative to, or
+	// `None` if they are relative to the start of the TIFF data
+	offset_base: Option<usize>,
+
+	ifd:         IfdPosition,
+}
+
+const LAYOUTS: [MakerNoteLayout; 11] = [
+	MakerNoteLayout { vendor: MakerNoteVendor::Nikon,     header: b"Nikon\0\x02",      make: "",      byte_order: ByteOrder::At(10), offset_base: Some(10), ifd: IfdPosition::StoredAt(14) },
+	MakerNoteLayout { vendor: MakerNoteVendor::Fujifilm,  header: b"FUJIFILM",          make: "",      byte_order: ByteOrder::Little, offset_base: Some(0),  ifd: IfdPosition::StoredAt(8)  },
+	MakerNoteLayout { vendor: MakerNoteVendor::Olympus,   header: b"OLYMPUS\0",         make: "",      byte_order: ByteOrder::At(8),  offset_base: Some(0),  ifd: IfdPosition::Fixed(12)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Olympus,   header: b"OM SYSTEM\0",       make: "",      byte_order: ByteOrder::At(12), offset_base: Some(0),  ifd: IfdPosition::Fixed(16)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Apple,     header: b"Apple iOS\0",       make: "",      byte_order: ByteOrder::At(12), offset_base: Some(0),  ifd: IfdPosition::Fixed(14)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Sony,      header: b"SONY DSC \0\0\0",   make: "",      byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(12)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Sony,      header: b"SONY CAM \0\0\0",   make: "",      byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(12)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Panasonic, header: b"Panasonic\0\0\0",   make: "",      byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(12)    },
+	MakerNoteLayout { vendor: MakerNoteVendor::Olympus,   header: b"OLYMP\0",           make: "",      byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(8)     },
+	MakerNoteLayout { vendor: MakerNoteVendor::Canon,     header: b"",                  make: "CANON", byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(0)     },
+	MakerNoteLayout { vendor: MakerNoteVendor::Sony,      header: b"",                  make: "SONY",  byte_order: ByteOrder::Exif,   offset_base: None,     ifd: IfdPosition::Fixed(0)     },
+];
+
+impl
+MakerNoteLayout
+{
+	/// Determines the layout via the header of the maker note or, if it has
+	/// none, via the Make tag (if given)
+	fn
+	find
+	(
+		maker_note: &[u8],
+		make:       Option<&str>
+	)
+	-> Option<&'static MakerNoteLayout>
+	{
+		if let Some(layout) = LAYOUTS.iter()
+			.find(|layout| !layout.header.is_empty() && maker_note.starts_with(layout.header))
+		{
+			return Some(layout);
+		}
+
+		let make = make?.to_uppercase();
+		return LAYOUTS.iter()
+			.find(|layout| layout.header.is_empty() && make.starts_with(layout.make));
+	}
+
+	fn
+	get_endian
+	(
+		&self,
+		maker_note:  &[u8],
+		exif_endian: &Endian
+	)
+	-> Option<Endian>
+	{
+		return match self.byte_order
+		{
+			ByteOrder::Exif         => Some(exif_endian.clone()),
+			ByteOrder::Little       => Some(Endian::Little),
+			ByteOrder::At(position) => match maker_note.get(position..position+2)?
+			{
+				b"II" => Some(Endian::Little),
+				b"MM" => Some(Endian::Big),
+				_     => None,
+			},
+		};
+	}
+
+	/// Position of the IFD, relative to the start of the maker note
+	fn
+	get_ifd_position
+	(
+		&self,
+		maker_note: &[u8],
+		endian:     &Endian
+	)
+	-> Option<usize>
+	{
+		return match self.ifd
+		{
+			IfdPosition::Fixed(position)    => Some(position),
+			IfdPosition::StoredAt(position) => {
+				let offset = from_u8_vec_macro!(u32, &maker_note.get(position..position+4)?, endian);
+				Some(self.offset_base.unwrap_or(0) + offset as usize)
+			},
+		};
+	}
+}
+
+/// Adapts the maker note that was located at `old_offset` (relative to the
+/// start of the TIFF data) so that it is valid at `new_offset`. Maker notes
+/// without a known header (e.g. the ones by Canon) are expected to start with
+/// the IFD directly.
+/// Returns `None` if the layout of the maker note is not known or its IFD
+/// does not look like one that uses offsets relative to the TIFF data. In
+/// that case, the maker note can't be moved without breaking it.
+pub(crate) fn
+relocate_maker_note
+(
+	maker_note: &[u8],
+	endian:     &Endian,
+	old_offset: u32,
+	new_offset: u32,
+)
+-> Option<Vec<u8>>
+{
+	let ifd_position = match MakerNoteLayout::find(maker_note, None)
+	{
+		Some(layout) if layout.offset_base.is_some() => return Some(maker_note.to_vec()),
+		Some(layout)                                 => layout.get_ifd_position(maker_note, endian)?,
+		None                                         => 0,
+	};
+
+	let mut relocated = maker_note.to_vec();
+	relocate_ifd_offsets(&mut relocated, ifd_position, endian, old_offset, new_offset)?;
+
+	// Canon stores the original position of the maker note in a footer that
+	// consists of the TIFF byte order information and that offset
+	let footer_position = relocated.len().saturating_sub(8);
+	if relocated.len() >= 8
+	&& relocated[footer_position..footer_position+4] == endian.header()[0..4]
+	&& from_u8_vec_macro!(u32, &relocated[footer_position+4..], endian) == old_offset
+	{
+		relocated[footer_position+4..].copy_from_slice(
+			&to_u8_vec_macro!(u32, &new_offset, endian)
+		);
+	}
+
+	return Some(relocated);
+}
+
+/// Shifts the value offsets of the entries of the IFD at the given position.
+/// Every offset has to point into the maker note at its old position, which
+/// prevents random data from being interpreted as an IFD.
+fn
+relocate_ifd_offsets
+(
+	maker_note:   &mut [u8],
+	ifd_position:      usize,
+	endian:       &    Endian,
+	old_offset:        u32,
+	new_offset:        u32,
+)
+-> Option<()>
+{
+	if maker_note.len() < ifd_position + 2
+	{
+		return None;
+	}
+
+	let number_of_entries = from_u8_vec_macro!(u16, &maker_note[ifd_position..ifd_position+2], endian) as usize;
+	if number_of_entries == 0 || maker_note.len() < ifd_position + 2 + 12 * number_of_entries
+	{
+		return None;
+	}
+
+	let mut new_offsets = Vec::new();
+	for entry_nr in 0..number_of_entries
+	{
+		let entry = &maker_note[ifd_position + 2 + 12 * entry_nr..][..12];
+
+		let format               = ExifTagFormat::from_u16(from_u8_vec_macro!(u16, &entry[2..4], endian))?;
+		let hex_component_number = from_u8_vec_macro!(u32, &entry[4..8], endian);
+		let byte_count           = format.bytes_per_component() as u64 * hex_component_number as u64;
+
+		if byte_count <= 4
+		{
+			continue;
+		}
+
+		let offset = from_u8_vec_macro!(u32, &entry[8..12], endian);
+		if offset < old_offset || (offset - old_offset) as u64 + byte_count > maker_note.len() as u64
+		{
+			return None;
+		}
+
+		new_offsets.push((entry_nr, (offset - old_offset).checked_add(new_offset)?));
+	}
+
+	for (entry_nr, new_value_offset) in new_offsets
+	{
+		let position = ifd_position + 2 + 12 * entry_nr + 8;
+		maker_note[position..position+4].copy_from_slice(
+			&to_u8_vec_macro!(u32, &new_value_offset, endian)
+		);
+	}
+
+	return Some(());
+}
+
+impl
+ImageFileDirectory
+{
+	/// Decodes the IFDs of the maker notes of all ExifIFDs into MAKERNOTES
+	/// IFDs, as far as their layout is known. Maker notes are not covered by
+	/// any specification and often contain rather odd data, so problems never
+	/// stop the decoding of the remaining metadata and are only recorded as
+	/// warnings.
+	pub(crate) fn
+	decode_maker_notes
+	(
+		data_cursor:         &mut Cursor<&[u8]>,
+		data_begin_position:      u64,
+		endian:              &    Endian,
+		insert_into:         &mut Vec<ImageFileDirectory>,
+		context:             &mut DecodeContext,
+	)
+	{
+		let exif_ifds = insert_into.iter()
+			.filter(|ifd| ifd.get_ifd_type() == ExifTagGroup::EXIF)
+			.map(|ifd| (ifd.get_generic_ifd_nr(), ifd.maker_note_offset, ifd.get_tags().clone()))
+			.collect::<Vec<_>>();
+
+		for (generic_ifd_nr, maker_note_offset, tags) in exif_ifds
+		{
+			let (maker_note, original_offset) = match (
+				tags.iter().find_map(|tag| if let ExifTag::MakerNote(data) = tag { Some(data) } else { None }),
+				maker_note_offset
+			)
+			{
+				(Some(maker_note), Some(offset)) => (maker_note, offset),
+				_                                => continue,
+			};
+
+			let make = insert_into.iter()
+				.find(|ifd| ifd.get_ifd_type() == ExifTagGroup::GENERIC && ifd.get_generic_ifd_nr() == generic_ifd_nr)
+				.and_then(|ifd| ifd.get_tags().iter().find_map(|tag| 
+					if let ExifTag::Make(make) = tag { Some(make.as_str()) } else { None }
+				))
+				.map(|make| make.to_string());
+
+			let layout = match MakerNoteLayout::find(maker_note, make.as_deref())
+			{
+				Some(layout) => layout,
+				None         => continue,
+			};
+
+			let mut maker_note_context = DecodeContext::new(&DecodeOptions { strict: false });
+			maker_note_context.maker_note_vendor = Some(layout.vendor);
+
+			let decode_result = match (
+				layout.get_endian(maker_note, endian),
+				maker_note_offset_schema(&tags)
+			)
+			{
+				(Some(maker_note_endian), schema) => match layout.offset_base
+				{
+					// Decode the copy of the maker note, which is addressed
+					// as if it was the entire TIFF data
+					Some(offset_base) => {
+						let mut maker_note_cursor = Cursor::new(maker_note.as_slice());
+						decode_maker_note_ifd(
+							&mut maker_note_cursor,
+							offset_base as i64,
+							layout.get_ifd_position(maker_note, &maker_note_endian)
+								.and_then(|position| position.checked_sub(offset_base)),
+							&maker_note_endian,
+							generic_ifd_nr,
+							insert_into,
+							&mut maker_note_context
+						)
+					},
+
+					// The offsets refer to the TIFF data, assuming that the
+					// maker note is still at its original offset
+					None => {
+						let backup_position = data_cursor.position();
+						let result = decode_maker_note_ifd(
+							data_cursor,
+							data_begin_position as i64 + schema,
+							layout.get_ifd_position(maker_note, &maker_note_endian)
+								.map(|position| original_offset as usize + position),
+							&maker_note_endian,
+							generic_ifd_nr,
+							insert_into,
+							&mut maker_note_context
+						);
+						data_cursor.set_position(backup_position);
+						result
+					},
+				},
+				(None, _) => Err(Error::MalformedIfd { 
+					offset: original_offset as u64, 
+					reason: "Invalid byte order information in MakerNote".to_string() 
+				}),
+			};
+
+			if let Err(error) = decode_result
+			{
+				let _ = maker_note_context.recover(error, ExifTagGroup::MAKERNOTES, generic_ifd_nr, None);
+			}
+
+			context.warnings.extend(maker_note_context.warnings);
+		}
+	}
+}
+
+/// Decodes the IFD of a maker note located at `ifd_position`, which is
+/// relative to `data_begin_position` just like all offsets in the IFD.
+/// A negative start position means that the maker note has been moved to a
+/// position that the offsets can't refer to.
+fn
+decode_maker_note_ifd
+(
+	data_cursor:         &mut Cursor<&[u8]>,
+	data_begin_position:      i64,
+	ifd_position:             Option<usize>,
+	endian:              &    Endian,
+	generic_ifd_nr:           u32,
+	insert_into:         &mut Vec<ImageFileDirectory>,
+	context:             &mut DecodeContext,
+)
+-> Result<(), Error>
+{
+	let (data_begin_position, ifd_position) = match (u64::try_from(data_begin_position), ifd_position)
+	{
+		(Ok(data_begin_position), Some(ifd_position)) => (data_begin_position, ifd_position as u64),
+		_ => return Err(Error::MalformedIfd { 
+			offset: 0, 
+			reason: "Could not locate IFD of MakerNote".to_string() 
+		}),
+	};
+
+	data_cursor.set_position(data_begin_position + ifd_position);
+	ImageFileDirectory::decode_ifd(
+		data_cursor,
+		data_begin_position,
+		endian,
+		&ExifTagGroup::MAKERNOTES,
+		generic_ifd_nr,
+		insert_into,
+		context
+	)?;
+
+	return Ok(());
+}
+
+/// Gets the value of the OffsetSchema tag, which tells how far the MakerNote
+/// has been moved since its offsets were written
+pub(super) fn
+maker_note_offset_schema
+(
+	tags: &[ExifTag]
+)
+-> i64
+{
+	return tags.iter()
+		.find_map(|tag| match tag
+		{
+			ExifTag::OffsetSchema(schema) if schema.len() == 1 => Some(schema[0] as i64),
+			_                                                  => None,
+		})
+		.unwrap_or(0);
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	/// A Canon style maker note located at offset 100 with one entry whose
+	/// 8 bytes of data are stored right after the IFD
+	fn
+	canon_maker_note
+	()
+	-> Vec<u8>
+	{
+		let mut maker_note = vec![0x01, 0x00];
+		maker_note.extend([0x01, 0x00, 0x01, 0x00, 0x08, 0x00, 0x00, 0x00]);
+		maker_note.extend(to_u8_vec_macro!(u32, &(100 + 18), &Endian::Little));
+		maker_note.extend([0x00; 4]);
+		maker_note.extend(b"CanonPwr");
+		maker_note.extend([0x49, 0x49, 0x2a, 0x00, 100, 0x00, 0x00, 0x00]);
+		return maker_note;
+	}
+
+	#[test]
+	fn
+	relocate_absolute_offsets()
+	{
+		let relocated = relocate_maker_note(&canon_maker_note(), &Endian::Little, 100, 300).unwrap();
+
+		assert_eq!(relocated[10..14], to_u8_vec_macro!(u32, &(300 + 18), &Endian::Little)[..]);
+		assert_eq!(relocated[relocated.len()-4..], [0x2c, 0x01, 0x00, 0x00]);
+		assert_eq!(relocated[18..26], b"CanonPwr"[..]);
+	}
+
+	#[test]
+	fn
+	relocate_unknown_or_relative_layout()
+	{
+		// Offset pointing outside of the maker note at its old position
+		assert!(relocate_maker_note(&canon_maker_note(), &Endian::Little, 200, 300).is_none());
+
+		let mut nikon_maker_note = b"Nikon\0\x02\x10\0\0MM\0\x2a\0\0\0\x08".to_vec();
+		nikon_maker_note.extend([0x12, 0x34]);
+		assert_eq!(
+			relocate_maker_note(&nikon_maker_note, &Endian::Little, 100, 300).unwrap(),
+			nikon_maker_note
+		);
+	}
+}
@@ -3,6 +3,7 @@
 
 pub mod get;
 pub mod set;
+mod makernote;
 
 use core::panic;
 use std::io::Cursor;
@@ -12,17 +13,23 @@ use std::vec;
 
 use log::warn;
 
+use crate::error::Error;
 use crate::endian::*;
 use crate::exif_tag::decode::decode_tag_with_format_exceptions;
 use crate::exif_tag::ExifTag;
 use crate::exif_tag::TagType;
 use crate::exif_tag_format::ExifTagFormat;
-use crate::general_file_io::io_error;
 use crate::metadata::Metadata;
+use crate::metadata::decode_options::DecodeContext;
 use crate::u8conversion::from_u8_vec_macro;
 use crate::u8conversion::to_u8_vec_macro;
 use crate::u8conversion::U8conversion;
 
+use makernote::maker_note_offset_schema;
+use makernote::relocate_maker_note;
+
+pub(crate) use makernote::MakerNoteVendor;
+
 /// Useful constants for dealing with IFDs: The length of a single IFD entry is
 /// equal to 12 bytes, as the entry consists of the tags hex value (2 byte), 
 /// the format (2 byte), the number of components (4 byte) and the value/offset
@@ -32,6 +39,11 @@ use crate::u8conversion::U8conversion;
 const IFD_ENTRY_LENGTH: u32     = 12;
 const IFD_END_NO_LINK:  [u8; 4] = [0x00, 0x00, 0x00, 0x00];
 
+/// The maximum number of bytes inserted in front of a MakerNote to keep it at
+/// its original offset. A MakerNote that would need more gets moved instead,
+/// as e.g. the EXIF data of a JPEG is limited to 64 KB.
+const MAX_MAKER_NOTE_PADDING: u32 = 4096;
+
 /// The different types of Image File Directories (IFD). A generic IFD is one
 /// without further specialization, like e.g. IFD0. The generic IFDs start
 /// with IFD0, which is located via the offset at the start of the TIFF data. 
@@ -40,6 +52,9 @@ const IFD_END_NO_LINK:  [u8; 4] = [0x00, 0x00, 0x00, 0x00];
 /// Other IFDs, like e.g. the ExifIFD, are linked via offset tags (in case of 
 /// the ExifIFD offset: 0x8769) that are located in the respective generic IFD 
 /// (most of them in IFD0).
+/// The MAKERNOTES IFD is decoded from the data of the MakerNote tag for known
+/// vendors, e.g. to get the lens or shutter count. It is only meant for
+/// reading, as the MakerNote tag itself is what gets written.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
 #[allow(non_snake_case, non_camel_case_types)]
 pub enum
@@ -48,7 +63,7 @@ ExifTagGroup
 	GENERIC,
 	EXIF,
 	INTEROP,
-	// MAKERNOTES, // TODO: Decide what to do with maker notes stuff...
+	MAKERNOTES,
 	GPS,
 }
 
@@ -62,6 +77,11 @@ ImageFileDirectory
 	tags:                      Vec<ExifTag>,
 	ifd_type:                  ExifTagGroup,
 	belongs_to_generic_ifd_nr: u32,
+
+	// The offset the data of the MakerNote had when it was decoded, relative
+	// to the start of the TIFF data and corrected by the OffsetSchema tag.
+	// Internal offsets of the maker note are usually based on this value.
+	maker_note_offset:         Option<u32>,
 }
 
 impl
@@ -77,7 +97,7 @@ ImageFileDirectory
 	)
 	-> Self
 	{
-		ImageFileDirectory { tags: tags, ifd_type: group, belongs_to_generic_ifd_nr: nr }
+		ImageFileDirectory { tags, ifd_type: group, belongs_to_generic_ifd_nr: nr, maker_note_offset: None }
 	}
 
 	/// Sorts the tags according to their hex value
@@ -95,19 +115,52 @@ ImageFileDirectory
 		);
 	}
 
+	/// Reads `byte_count` bytes of data located at the given offset, which is
+	/// relative to the start of the TIFF data. Checks that this data is 
+	/// actually available before allocating any memory for it. 
+	/// Note that this leaves the cursor positioned after the read data.
+	fn
+	read_data_at_offset
+	(
+		data_cursor:         &mut Cursor<&[u8]>,
+		data_begin_position:      u64,
+		offset:                   u64,
+		byte_count:               u64,
+	)
+	-> Result<Vec<u8>, Error>
+	{
+		let start = data_begin_position + offset;
+		let end   = start + byte_count;
+
+		if end > data_cursor.get_ref().len() as u64
+		{
+			return Err(Error::MalformedIfd {
+				offset,
+				reason: format!("Data with {} bytes at offset {} exceeds available data", byte_count, offset)
+			});
+		}
+
+		let mut data_buffer = vec![0u8; byte_count as usize];
+		data_cursor.set_position(start);
+		data_cursor.read_exact(&mut data_buffer)?;
+
+		return Ok(data_buffer);
+	}
+
 	/// If everything goes Ok and there is enough data to unpack, this returns
 	/// the offset to the next generic IFD that needs to be processed.
 	pub(crate) fn
 	decode_ifd
 	(
-		data_cursor:         &mut Cursor<&Vec<u8>>,
+		data_cursor:         &mut Cursor<&[u8]>,
 		data_begin_position:      u64,                                          // Stays the same for all calls to this function while decoding
 		endian:              &    Endian,
 		group:               &    ExifTagGroup,
 		generic_ifd_nr:           u32,                                          // Reuse value for recursive calls; only gets incremented by caller
 		insert_into:         &mut Vec<ImageFileDirectory>,                      // Stays the same for all calls to this function while decoding
+		context:             &mut DecodeContext,                                // Stays the same for all calls to this function while decoding
 	)
-	-> Result<Option<u32>, std::io::Error>
+	-> Result<Option<u32>, Error>
 	{
 		////////////////////////////////////////////////////////////////////////
 		// PREPARATION 
@@ -124,9 +177,10 @@ ImageFileDirectory
 		// The first two bytes give us the number of entries in this IFD
 		let mut number_of_entries_buffer = vec![0u8; 2];
 		data_cursor.read_exact(&mut number_of_entries_buffer)?;
-		let number_of_entries = from_u8_vec_macro!(u16, &number_of_entries_buffer.to_vec(), endian);
+		let number_of_entries = from_u8_vec_macro!(u16, &number_of_entries_buffer, endian);
 
 		// Check that there is enough data to unpack
+		let mut number_of_entries = number_of_entries;
 		let required = 0
 			+ 2
 			+ IFD_ENTRY_LENGTH as usize * number_of_entries as usize 
@@ -137,7 +191,18 @@ ImageFileDirectory
 
 		if required > available
 		{
-			return io_error!(Other, format!("Not enough data to decode IFD! Required: {} Available: {}", required, available));
+			context.recover(
+				Error::MalformedIfd {
+					offset: data_cursor_entry_position - data_begin_position,
+					reason: format!("Not enough data to decode IFD! Required: {} Available: {}", required, available)
+				},
+				*group,
+				generic_ifd_nr,
+				None
+			)?;
+
+			// Continue with the entries that are still available
+			number_of_entries = ((available - 2) / IFD_ENTRY_LENGTH as usize) as u16;
 		}
 
 		// Temporarily storing specific tags that have been decoded
@@ -146,9 +211,14 @@ ImageFileDirectory
 		// For example, for decoding the StripOffsets we also need the 
 		// StripByteCounts to know how many bytes each strip has
 		let mut strip_tags:     (Option<ExifTag>, Option<ExifTag>) = (None, None);
+		let mut tile_tags:      (Option<ExifTag>, Option<ExifTag>) = (None, None);
+		let mut free_tags:      (Option<ExifTag>, Option<ExifTag>) = (None, None);
 		let mut thumbnail_info: (Option<ExifTag>, Option<ExifTag>) = (None, None);
 		// Others following here in the future...
 
+		// Location of the MakerNote data, needed for keeping its offsets valid
+		let mut maker_note_offset: Option<u32> = None;
+
 		////////////////////////////////////////////////////////////////////////
 		// TAG-DECODING
 
@@ -164,9 +234,9 @@ ImageFileDirectory
 			data_cursor.read_exact(&mut entry_buffer)?;
 
 			// Decode the first 8 bytes with the tag, format and component number
-			let hex_tag              = from_u8_vec_macro!(u16, &entry_buffer[0..2].to_vec(), endian);
-			let hex_format           = from_u8_vec_macro!(u16, &entry_buffer[2..4].to_vec(), endian);
-			let hex_component_number = from_u8_vec_macro!(u32, &entry_buffer[4..8].to_vec(), endian);
+			let hex_tag              = from_u8_vec_macro!(u16, &entry_buffer[0..2], endian);
+			let hex_format           = from_u8_vec_macro!(u16, &entry_buffer[2..4], endian);
+			let hex_component_number = from_u8_vec_macro!(u32, &entry_buffer[4..8], endian);
 
 			// Decode the format
 			// TODO: What to do in case these two differ but the given format
@@ -178,7 +248,16 @@ ImageFileDirectory
 			}
 			else
 			{
-				return io_error!(Other, format!("Illegal format value: {}", hex_format));
+				context.recover(
+					Error::MalformedIfd {
+						offset: data_cursor.position() - IFD_ENTRY_LENGTH as u64 - data_begin_position,
+						reason: format!("Illegal format value: {}", hex_format)
+					},
+					*group,
+					generic_ifd_nr,
+					Some(hex_tag)
+				)?;
+				continue;
 			}
 
 			// Calculating the number of required bytes to determine if next
@@ -188,26 +267,39 @@ ImageFileDirectory
 			// data even if the given format in the image file is not the
 			// right/default one for the currently processed tag according to 
 			// the exif specification. 
-			let byte_count = format.bytes_per_component() * hex_component_number;
+			let byte_count = format.bytes_per_component() as u64 * hex_component_number as u64;
 
 			let raw_data;
 			if byte_count > 4
 			{
 				// Compute the offset
-				let hex_offset = from_u8_vec_macro!(u32, &entry_buffer[8..12].to_vec(), endian);
+				let hex_offset = from_u8_vec_macro!(u32, &entry_buffer[8..12], endian);
 
-				// Backup current position & go to offset position
-				let backup_position = data_cursor.position();
-				data_cursor.set_position(data_begin_position);
-				data_cursor.seek(std::io::SeekFrom::Current(hex_offset as i64))?;
+				if *group == ExifTagGroup::EXIF && hex_tag == 0x927c
+				{
+					maker_note_offset = Some(hex_offset);
+				}
 
-				// Read the raw data
-				let mut raw_data_buffer = vec![0u8; byte_count as usize];
-				data_cursor.read_exact(&mut raw_data_buffer)?;
-				raw_data = raw_data_buffer.to_vec();
+				// Backup current position, read the raw data at the offset
+				let backup_position = data_cursor.position();
+				let read_result     = Self::read_data_at_offset(
+					data_cursor, 
+					data_begin_position, 
+					hex_offset as u64, 
+					byte_count
+				);
 			
 				// Rewind the cursor to the start of the next entry
 				data_cursor.set_position(backup_position);
+
+				match read_result
+				{
+					Ok(data) => raw_data = data,
+					Err(error) => {
+						context.recover(error, *group, generic_ifd_nr, Some(hex_tag))?;
+						continue;
+					}
+				}
 			}
 			else
 			{
@@ -216,22 +308,27 @@ ImageFileDirectory
 				raw_data = entry_buffer[8..(8+byte_count as usize)].to_vec();
 			}
 
-			// Try to get the tag via its hex value
-			let tag_result = ExifTag::from_u16(hex_tag, group);
+			// Try to get the tag via its hex value. Tags of maker notes are
+			// only known for the vendor that wrote them
+			let tag_result = match context.maker_note_vendor
+			{
+				Some(vendor) if !vendor.knows_tag(hex_tag) => Err(String::from("Unknown tag of this vendor")),
+				_                                          => ExifTag::from_u16(hex_tag, group),
+			};
 
 			// Start of by checking if this is an unknown tag
 			if tag_result.is_err()
 			{
-				// Note: `from_u16_with_data` can NOT be called initially due
+				// Note: This can NOT be done initially due
 				// to some possible conversion of data needed, e.g. INT16U to
 				// INT32U, which is not accounted for yet at this stage
-				tags.push(ExifTag::from_u16_with_data(
+				tags.push(ExifTag::unknown_from_u8_vec(
 					hex_tag, 
 					&format, 
 					&raw_data, 
 					&endian, 
 					group
-				).unwrap());
+				));
 				continue;
 			}
 
@@ -241,6 +338,21 @@ ImageFileDirectory
 			// If this is an IFD offset tag, perform a recursive call
 			if let TagType::IFD_OFFSET(subifd_group) = tag.get_tag_type()
 			{
+				// The offset to the SubIFD needs to be a single 4 byte value
+				if raw_data.len() != 4
+				{
+					context.recover(
+						Error::MalformedIfd {
+							offset: data_cursor.position() - IFD_ENTRY_LENGTH as u64 - data_begin_position,
+							reason: format!("Invalid offset to SubIFD {:?}", subifd_group)
+						},
+						*group,
+						generic_ifd_nr,
+						Some(hex_tag)
+					)?;
+					continue;
+				}
+
 				// Compute the offset to the SubIFD and save the current position
 				let offset          = from_u8_vec_macro!(u32, &raw_data, endian) as usize;
 				let backup_position = data_cursor.position();
@@ -256,9 +368,11 @@ ImageFileDirectory
 					&subifd_group,
 					generic_ifd_nr,
 					insert_into,
+					context,
 				);
 
 				// Check that this actually worked
+				data_cursor.set_position(backup_position);
 				if let Ok(_subifd_result) = subifd_decode_result
 				{
 					// Assert result, restore old cursor position & continue
@@ -277,25 +391,46 @@ ImageFileDirectory
 					// So, for now we just assume that `subifd_result` is not
 					// of relevance until evidence suggests otherwise.
 					
-					data_cursor.set_position(backup_position);
 					continue;
 				}
 				else
 				{
-					return io_error!(Other, format!("Could not decode SubIFD {:?}:\n  {}", subifd_group, subifd_decode_result.err().unwrap()));
+					// Add information about the SubIFD to the error if possible
+					let error = match subifd_decode_result.err().unwrap()
+					{
+						Error::MalformedIfd { offset, reason }
+							=> Error::MalformedIfd { 
+								offset, 
+								reason: format!("Could not decode SubIFD {:?}: {}", subifd_group, reason) 
+							},
+						error
+							=> error,
+					};
+
+					// In lenient mode the SubIFD is dropped, together with
+					// the offset tag pointing to it
+					context.recover(error, subifd_group, generic_ifd_nr, None)?;
+					continue;
 				}
 			}
 
 			// At this point we check if the format is actually what we expect
 			// it to be and convert it if possible
-			tag = decode_tag_with_format_exceptions(
+			match decode_tag_with_format_exceptions(
 				&tag,
 				format,
 				&raw_data,
 				endian,
 				hex_tag,
 				group
-			)?;
+			)
+			{
+				Ok(decoded_tag) => tag = decoded_tag,
+				Err(error) => {
+					context.recover(error, *group, generic_ifd_nr, Some(hex_tag))?;
+					continue;
+				}
+			}
 
 			// Now we have at least confirmed that the format is ok (or has
 			// been corrected). Next, we need to differ between the two other
@@ -310,6 +445,18 @@ ImageFileDirectory
 					ExifTag::StripByteCounts(_) => {
 						strip_tags.1 = Some(tag);
 					},
+					ExifTag::TileOffsets(_, _) => {
+						tile_tags.0 = Some(tag);
+					},
+					ExifTag::TileByteCounts(_) => {
+						tile_tags.1 = Some(tag);
+					},
+					ExifTag::FreeOffsets(_, _) => {
+						free_tags.0 = Some(tag);
+					},
+					ExifTag::FreeByteCounts(_) => {
+						free_tags.1 = Some(tag);
+					},
 					ExifTag::ThumbnailOffset(_, _) => {
 						thumbnail_info.0 = Some(tag);
 					},
@@ -335,44 +482,57 @@ ImageFileDirectory
 		// At this stage we have decoded the tags themselves. 
 		// However, the data offset tags need further processing (i.e. their 
 		// data needs to be read as well)
-		if strip_tags.0.is_some() && strip_tags.1.is_some()
+		// Strips and tiles hold the image data of a TIFF, free space is kept
+		// as well so that its offsets remain valid
+		for (offset_tag, byte_count_tag) in [strip_tags, tile_tags, free_tags]
 		{
-			// 0 -> offsets
-			// 1 -> byte counts
-			if let 
-				(
-					TagType::DATA_OFFSET(offsets),
-					TagType::DATA_OFFSET(byte_counts)
-				)
-				= 
-				(
-					strip_tags.0.unwrap().get_tag_type(),
-					strip_tags.1.unwrap().get_tag_type()
-				)
+			if let (Some(offset_tag), Some(byte_count_tag)) = (offset_tag, byte_count_tag)
 			{
-				let backup_position = data_cursor.position();
-
-				let mut strip_data = Vec::new();
-
-				// Gather the data from the offsets
-				for (offset, byte_count) in offsets.iter().zip(byte_counts.iter())
+				if let 
+					(
+						TagType::DATA_OFFSET(offsets),
+						TagType::DATA_OFFSET(byte_counts)
+					)
+					= 
+					(
+						offset_tag.get_tag_type(),
+						byte_count_tag.get_tag_type()
+					)
 				{
-					data_cursor.set_position(data_begin_position);
-					data_cursor.seek(std::io::SeekFrom::Current(*offset as i64))?;
+					let backup_position = data_cursor.position();
+
+					// Gather the data from the offsets
+					let offset_data = offsets.iter()
+						.zip(byte_counts.iter())
+						.map(|(offset, byte_count)| Self::read_data_at_offset(
+							data_cursor, 
+							data_begin_position, 
+							*offset     as u64, 
+							*byte_count as u64
+						))
+						.collect::<Result<Vec<Vec<u8>>, Error>>();
+
+					match offset_data
+					{
+						Ok(offset_data) => {
+							// Push offset tag with the gathered data and the
+							// byte counts tag to tags vector
+							tags.push(match offset_tag
+							{
+								ExifTag::TileOffsets(_, _) => ExifTag::TileOffsets( Vec::new(), offset_data),
+								ExifTag::FreeOffsets(_, _) => ExifTag::FreeOffsets( Vec::new(), offset_data),
+								_                          => ExifTag::StripOffsets(Vec::new(), offset_data),
+							});
+							tags.push(byte_count_tag);
+						},
+						Err(error) => {
+							context.recover(error, *group, generic_ifd_nr, Some(offset_tag.as_u16()))?;
+						}
+					}
 
-					let mut data_buffer = vec![0u8; *byte_count as usize];
-					data_cursor.read_exact(&mut data_buffer)?;
-					strip_data.push(data_buffer);
+					// Restore backup position
+					data_cursor.set_position(backup_position);
 				}
-
-				// Push StripOffset tag to tags vector
-				tags.push(ExifTag::StripOffsets(Vec::new(), strip_data));
-
-				// Push StripByteCounts tag to tags vector
-				tags.push(ExifTag::StripByteCounts(byte_counts));
-
-				// Restore backup position
-				data_cursor.set_position(backup_position);
 			}
 		}
 
@@ -395,18 +555,25 @@ ImageFileDirectory
 
 				if offset.len() == 1 && length.len() == 1
 				{
-					let mut thumbnail_data = vec![0u8; length[0] as usize];
-
 					// Gather the data at the offset
-					data_cursor.set_position(data_begin_position);
-					data_cursor.seek(std::io::SeekFrom::Current(offset[0] as i64))?;
-					data_cursor.read_exact(&mut thumbnail_data)?;
-
-					// Push ThumbnailOffset tag to tags vector
-					tags.push(ExifTag::ThumbnailOffset(Vec::new(), thumbnail_data));
+					match Self::read_data_at_offset(
+						data_cursor, 
+						data_begin_position, 
+						offset[0] as u64, 
+						length[0] as u64
+					)
+					{
+						Ok(thumbnail_data) => {
+							// Push ThumbnailOffset tag to tags vector
+							tags.push(ExifTag::ThumbnailOffset(Vec::new(), thumbnail_data));
 
-					// Also push ThumbnailLength tag to tags vector
-					tags.push(ExifTag::ThumbnailLength(length));
+							// Also push ThumbnailLength tag to tags vector
+							tags.push(ExifTag::ThumbnailLength(length));
+						},
+						Err(error) => {
+							context.recover(error, *group, generic_ifd_nr, Some(0x0201))?;
+						}
+					}
 				}
 				else
 				{
@@ -420,14 +587,41 @@ ImageFileDirectory
 
 		// Other offset tags here in the future...
 
+		// If the MakerNote has been moved before, e.g. by Windows, the tag
+		// OffsetSchema tells us where its internal offsets expect it to be
+		if let Some(offset) = maker_note_offset
+		{
+			maker_note_offset = u32::try_from(offset as i64 - maker_note_offset_schema(&tags)).ok();
+		}
+
 		// At this point we are done with decoding the tags of this IFD and its
 		// associated SubIFDs! 
 
-		// Put the current IFD into the given, referenced vector
+		// Put the current IFD into the given, referenced vector, unless there
+		// already is one for the same group (e.g. due to multiple offset tags
+		// pointing to the same SubIFD)
+		if insert_into.iter().any(|ifd| 
+			ifd.get_ifd_type()       == *group && 
+			ifd.get_generic_ifd_nr() == generic_ifd_nr
+		)
+		{
+			context.recover(
+				Error::MalformedIfd {
+					offset: data_cursor_entry_position - data_begin_position,
+					reason: format!("Duplicate IFD for group {:?}", group)
+				},
+				*group,
+				generic_ifd_nr,
+				None
+			)?;
+			return Ok(None);
+		}
+
 		insert_into.push(ImageFileDirectory { 
 			tags: tags, 
 			ifd_type: *group, 
-			belongs_to_generic_ifd_nr: generic_ifd_nr
+			belongs_to_generic_ifd_nr: generic_ifd_nr,
+			maker_note_offset
 		});
 
 		// Read in the link to the next IFD and check if its zero
@@ -466,20 +660,85 @@ ImageFileDirectory
 		encode_vec:                 &mut Vec<u8>,
 		current_offset:             &mut u32
 	)
-	-> Result<(u64, Vec<u8>), std::io::Error>
+	-> Result<(u64, Vec<u8>), Error>
+	{
+		let encode_vec_length = encode_vec.len();
+		let start_offset      = *current_offset;
+
+		let (link_position, ifd_offset_vec, offset_schema) = self.encode_ifd_with_offset_schema(
+			data, 
+			ifds_with_offset_info_only, 
+			encode_vec, 
+			current_offset, 
+			false
+		)?;
+
+		if offset_schema == 0
+		{
+			return Ok((link_position, ifd_offset_vec));
+		}
+
+		// A MakerNote that had to be moved without adjusting its offsets 
+		// needs the OffsetSchema tag, as this is the only way for other 
+		// readers to find the data it is referring to. Adding the tag changes
+		// the layout, so start over.
+		warn!("Could not keep the MakerNote at its original offset, its internal offsets are now only valid in combination with OffsetSchema");
+		encode_vec.truncate(encode_vec_length);
+		*current_offset = start_offset;
+
+		let (link_position, ifd_offset_vec, _) = self.encode_ifd_with_offset_schema(
+			data, 
+			ifds_with_offset_info_only, 
+			encode_vec, 
+			current_offset, 
+			true
+		)?;
+
+		return Ok((link_position, ifd_offset_vec));
+	}
+
+	/// Encodes the IFD as described for `encode_ifd`, optionally adding the
+	/// OffsetSchema tag if not already present. Additionally returns the
+	/// difference between the new and the original offset of the MakerNote
+	/// that could not be compensated.
+	fn
+	encode_ifd_with_offset_schema
+	(
+		&self,
+		data:                       &Metadata,
+		ifds_with_offset_info_only: &mut Vec<ImageFileDirectory>,
+		encode_vec:                 &mut Vec<u8>,
+		current_offset:             &mut u32,
+		add_offset_schema:          bool
+	)
+	-> Result<(u64, Vec<u8>, i64), Error>
 	{
 
 		// Store all relevant tags (IFD tags + offset tags) in a temporary 
 		// location and sort them there
-		let all_relevant_tags = self.tags.iter().chain(ifds_with_offset_info_only
+		let mut all_relevant_tags = self.tags.iter().chain(ifds_with_offset_info_only
 			.iter()
-			.filter(|ifd| 
+			.find(|ifd| 
 				ifd.get_generic_ifd_nr() == self.get_generic_ifd_nr() &&
 				ifd.get_ifd_type()       == self.get_ifd_type()
 			)
-			.next().unwrap().get_tags()
+			.unwrap().get_tags()
 			.iter()).cloned().collect::<Vec<ExifTag>>();
 
+		if add_offset_schema
+		&& !all_relevant_tags.iter().any(|tag| matches!(tag, ExifTag::OffsetSchema(_)))
+		{
+			all_relevant_tags.push(ExifTag::OffsetSchema(vec![0]));
+		}
+
+		// The offset tags as well as the data offset tags (e.g. for strips or
+		// tiles, which are decoded last) are not necessarily in order
+		all_relevant_tags.sort_by_key(|tag| tag.as_u16());
+
+		// Difference between the new and the original offset of the MakerNote
+		// that could not be compensated
+		let mut offset_schema: i64 = 0;
+
 		// Start writing this IFD by adding the number of entries
 		let count_entries = all_relevant_tags.iter().filter(
 			|tag| tag.is_writable() || 
@@ -532,13 +791,15 @@ ImageFileDirectory
 				TagType::DATA_OFFSET(_) => {
 					match tag
 					{
-						ExifTag::StripOffsets(_, strip_data) => {
+						ExifTag::StripOffsets(_, strip_data) |
+						ExifTag::TileOffsets( _, strip_data) |
+						ExifTag::FreeOffsets( _, strip_data) => {
 							let mut value = Vec::new();
 							for strip in strip_data
 							{
-								// Store the current offset where the strip is
-								// pushed, push the strip and account for its length
-								// in the offset variable
+								// Store the current offset where the strip (or
+								// tile) is pushed, push the strip and account for 
+								// its length in the offset variable
 								value.extend(
 									to_u8_vec_macro!(u32, &current_offset.clone(), &data.get_endian())
 								);
@@ -595,6 +856,44 @@ ImageFileDirectory
 			let alignment_count = (4 - *current_offset % 4) % 4;
 			*current_offset += alignment_count;
 			ifd_offset_area.extend(vec![0u8; alignment_count as usize]);
+
+			// The offsets within the MakerNote usually refer to the position
+			// of its data in the original file. Try to keep the data at that
+			// position if this only requires a bit of padding, otherwise
+			// adjust these offsets if the layout of the MakerNote is known,
+			// or record the difference in OffsetSchema
+			let value = match (tag, self.maker_note_offset)
+			{
+				(ExifTag::MakerNote(_), Some(original_offset)) if value.len() > 4 => {
+					if *current_offset <= original_offset
+					&& original_offset - *current_offset <= MAX_MAKER_NOTE_PADDING
+					{
+						ifd_offset_area.extend(vec![0u8; (original_offset - *current_offset) as usize]);
+						*current_offset = original_offset;
+						value
+					}
+					else if let Some(relocated) = relocate_maker_note(
+						&value, 
+						&data.get_endian(), 
+						original_offset, 
+						*current_offset
+					)
+					{
+						relocated
+					}
+					else
+					{
+						offset_schema = *current_offset as i64 - original_offset as i64;
+						value
+					}
+				},
+
+				(ExifTag::OffsetSchema(_), Some(_)) => {
+					ExifTag::OffsetSchema(vec![offset_schema as i32]).value_as_u8_vec(&data.get_endian())
+				},
+
+				_ => value,
+			};
 			
 			
 			// Add Tag & Data Format /                                          2 + 2 bytes
@@ -605,20 +904,14 @@ ImageFileDirectory
 			let number_of_components: u32 = tag.number_of_components();
 			encode_vec.extend(to_u8_vec_macro!(u32, &number_of_components, &data.get_endian()).iter());
 
-			// Optional string padding (i.e. string is shorter than it should be)
-			let mut string_padding: Vec<u8> = Vec::new();
-			if tag.is_string()
-			{
-				for _ in 0..(number_of_components - value.len() as u32)
-				{
-					string_padding.push(0x00);
-				}
-			}
-
 			// Add offset or value /                                            4 bytes
 			// Depending on the amount of data, either put it directly into
 			// next 4 bytes or write an offset where the data can be found 
 			let byte_count: u32 = number_of_components * tag.format().bytes_per_component();
+
+			// Optional padding (i.e. string is shorter than it should be or
+			// the tag has no value but a predefined number of components)
+			let string_padding = vec![0x00u8; (byte_count as usize).saturating_sub(value.len())];
 			if byte_count > 4
 			{
 				encode_vec.extend(to_u8_vec_macro!(u32, current_offset, &data.get_endian()).iter());
@@ -652,6 +945,10 @@ ImageFileDirectory
 		encode_vec.extend(IFD_END_NO_LINK.iter());
 		encode_vec.extend(ifd_offset_area.iter());
 
-		return Ok(((ifd_offset + 2 + IFD_ENTRY_LENGTH * count_entries as u32) as u64, ifd_offset_vec));
+		return Ok((
+			(ifd_offset + 2 + IFD_ENTRY_LENGTH * count_entries as u32) as u64, 
+			ifd_offset_vec,
+			offset_schema
+		));
 	}
 }
@@ -26,6 +26,12 @@ ImageFileDirectory
 		{
 			warn!("The tag {:?} is set in an IFD that has not a matching group.", input_tag);
 		}
+
+		// A new MakerNote can't refer to the position of the original one
+		if let ExifTag::MakerNote(_) = input_tag
+		{
+			self.maker_note_offset = None;
+		}
 		self.tags.retain(|tag| tag.as_u16() != input_tag.as_u16());
 		self.tags.push(input_tag);
 		self.sort_tags();
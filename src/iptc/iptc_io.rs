@@ -0,0 +1,222 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::c2pa;
+use crate::c2pa::C2paPolicy;
+use crate::error::Error;
+use crate::error::unsupported_format;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file;
+use crate::general_file_io::modify_file_atomically;
+use crate::metadata::WriteOptions;
+
+use crate::jpg;
+use crate::tiff;
+
+use super::Iptc;
+
+impl
+Iptc
+{
+	/// Gets the raw IPTC-IIM data of an image that is stored in memory,
+	/// without parsing it. Returns `Error::NoIptcData` if the image has no
+	/// IPTC data. Only supported for JPEG (Photoshop APP13 segment) and TIFF
+	/// (IPTC-NAA tag) images.
+	#[allow(unreachable_patterns)]
+	pub fn
+	read_data
+	<T: AsRef<[u8]> + ?Sized>
+	(
+		file_buffer: &T,
+		file_type:   FileExtension
+	)
+	-> Result<Vec<u8>, Error>
+	{
+		let file_buffer = file_buffer.as_ref();
+
+		return match file_type
+		{
+			FileExtension::JPEG
+				=>  jpg::read_iptc(file_buffer),
+			FileExtension::TIFF
+				=> tiff::vec::read_iptc(file_buffer),
+			_
+				=> unsupported_format!(
+					format!(
+						"Function 'read_data' not available for {:?} (only JPEG and TIFF)",
+						file_type
+					)
+				),
+		};
+	}
+
+	/// Constructs a new `Iptc` object with the IPTC data of an image that is
+	/// stored in memory. Returns `Error::NoIptcData` if the image has no IPTC
+	/// data and `Error::MalformedIptc` if the data can't be parsed.
+	/// # Examples
+	/// ```no_run
+	/// use little_exif::filetype::FileExtension;
+	/// use little_exif::iptc::Iptc;
+	/// use little_exif::iptc::IptcTag;
+	///
+	/// let file_data = std::fs::read("image.jpg").unwrap();
+	/// let iptc = Iptc::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+	/// println!("{:?}", iptc.get_strings(IptcTag::Keywords));
+	/// ```
+	pub fn
+	new_from_vec
+	<T: AsRef<[u8]> + ?Sized>
+	(
+		file_buffer: &T,
+		file_type:   FileExtension
+	)
+	-> Result<Iptc, Error>
+	{
+		return Self::new_from_bytes(&Self::read_data(file_buffer, file_type)?);
+	}
+
+	/// Constructs a new `Iptc` object with the IPTC data of the image at the
+	/// specified path
+	pub fn
+	new_from_path
+	(
+		path: &Path
+	)
+	-> Result<Iptc, Error>
+	{
+		let file_type   = get_file_type(path)?;
+		let file_buffer = std::fs::read(path)?;
+		return Self::new_from_vec(&file_buffer, file_type);
+	}
+
+	/// Writes the IPTC data to an image stored as a `Vec<u8>`, replacing any
+	/// IPTC data that is already present. For JPEG images, the other image
+	/// resources of the Photoshop APP13 segment are preserved.
+	pub fn
+	write_to_vec
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension
+	)
+	-> Result<(), Error>
+	{
+		return self.write_to_vec_with_options(file_buffer, file_type, &WriteOptions::default());
+	}
+
+	/// Same as `write_to_vec`, with the C2PA policy of the given options
+	/// deciding what happens to a C2PA manifest of the image. The other 
+	/// options have no effect here.
+	#[allow(unreachable_patterns)]
+	pub fn
+	write_to_vec_with_options
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension,
+		options:     &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
+		let iptc_data = self.encode();
+
+		return match file_type
+		{
+			FileExtension::JPEG
+				=>  jpg::write_iptc(file_buffer, &iptc_data),
+			FileExtension::TIFF
+				=> tiff::vec::write_iptc(file_buffer, &iptc_data),
+			_
+				=> unsupported_format!(
+					format!(
+						"Function 'write_to_vec' not available for {:?} (only JPEG and TIFF)",
+						file_type
+					)
+				),
+		};
+	}
+
+	/// Writes the IPTC data to the image at the specified path, which gets
+	/// replaced atomically
+	pub fn
+	write_to_file
+	(
+		&self,
+		path: &Path
+	)
+	-> Result<(), Error>
+	{
+		return self.write_to_file_with_options(path, &WriteOptions::default());
+	}
+
+	/// Writes the IPTC data to the image at the specified path, with the given
+	/// options deciding whether the file gets replaced atomically or modified
+	/// in place and what happens to a C2PA manifest of the image
+	pub fn
+	write_to_file_with_options
+	(
+		&self,
+		path:    &Path,
+		options: &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		let file_type = get_file_type(path)?;
+		return modify_file(
+			path,
+			options.atomic,
+			|file_buffer| self.write_to_vec_with_options(file_buffer, file_type, options)
+		);
+	}
+
+	/// Removes the IPTC data from an image stored as a `Vec<u8>`. Images
+	/// without IPTC data are left untouched. A C2PA manifest of the image is
+	/// preserved, see `WriteOptions`
+	#[allow(unreachable_patterns)]
+	pub fn
+	clear_iptc
+	(
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension
+	)
+	-> Result<(), Error>
+	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
+		return match file_type
+		{
+			FileExtension::JPEG
+				=>  jpg::clear_iptc(file_buffer),
+			FileExtension::TIFF
+				=> tiff::vec::clear_iptc(file_buffer),
+			_
+				=> unsupported_format!(
+					format!(
+						"Function 'clear_iptc' not available for {:?} (only JPEG and TIFF)",
+						file_type
+					)
+				),
+		};
+	}
+
+	/// Removes the IPTC data from the image at the specified path, which
+	/// gets replaced atomically
+	pub fn
+	file_clear_iptc
+	(
+		path: &Path
+	)
+	-> Result<(), Error>
+	{
+		let file_type = get_file_type(path)?;
+		return modify_file_atomically(
+			path,
+			|file_buffer| Self::clear_iptc(file_buffer, file_type)
+		);
+	}
+}
@@ -0,0 +1,459 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+mod iptc_io;
+mod tag;
+
+pub use tag::IptcTag;
+
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::u8conversion::*;
+
+/// Every dataset starts with this tag marker
+const TAG_MARKER: u8 = 0x1c;
+
+/// Value of the CodedCharacterSet dataset (1:90) that marks the text of the
+/// datasets as UTF-8, i.e. the ISO 2022 escape sequence `ESC % G`
+const UTF8_MARKER: [u8; 3] = [0x1b, 0x25, 0x47];
+
+/// Version of the application record that gets added when creating the
+/// first dataset of that record
+const RECORD_VERSION: [u8; 2] = [0x00, 0x04];
+
+/// A single IPTC-IIM dataset, consisting of its record and dataset number
+/// (together, they determine the tag) and the raw data
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+IptcDataSet
+{
+	pub record:  u8,
+	pub dataset: u8,
+	pub data:    Vec<u8>,
+}
+
+impl
+IptcDataSet
+{
+	pub fn
+	get_tag
+	(
+		&self
+	)
+	-> IptcTag
+	{
+		return IptcTag::from_record_and_dataset(self.record, self.dataset);
+	}
+}
+
+/// The IPTC-IIM data of an image, as stored e.g. in the Photoshop APP13
+/// segment of JPEG files.
+/// Text is decoded as UTF-8 if the CodedCharacterSet dataset (1:90) says so
+/// and as ISO 8859-1 otherwise. When setting text that can't be represented
+/// in ASCII, the data gets converted to UTF-8.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::filetype::FileExtension;
+/// use little_exif::iptc::Iptc;
+/// use little_exif::iptc::IptcTag;
+///
+/// let mut file_data = std::fs::read("image.jpg").unwrap();
+/// let mut iptc = Iptc::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+///
+/// println!("{:?}", iptc.get_string(IptcTag::CaptionAbstract));
+/// iptc.set_strings(IptcTag::Keywords, &["Graz", "Schloßberg"]);
+/// iptc.write_to_vec(&mut file_data, FileExtension::JPEG).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+Iptc
+{
+	datasets: Vec<IptcDataSet>,
+}
+
+impl
+Iptc
+{
+	/// Constructs a new, empty `Iptc` object
+	pub fn
+	new
+	()
+	-> Iptc
+	{
+		return Iptc::default();
+	}
+
+	/// Parses the given IPTC-IIM data, i.e. a sequence of datasets. Trailing
+	/// zero bytes (e.g. padding) are ignored
+	pub fn
+	new_from_bytes
+	(
+		data: &[u8]
+	)
+	-> Result<Iptc, Error>
+	{
+		let mut datasets = Vec::new();
+		let mut position = 0;
+
+		while position < data.len()
+		{
+			if data[position..].iter().all(|byte| *byte == 0)
+			{
+				break;
+			}
+
+			if data[position] != TAG_MARKER
+			{
+				return malformed_iptc(format!("Expected tag marker at position {}!", position));
+			}
+
+			if data.len() - position < 5
+			{
+				return malformed_iptc("Dataset header is truncated!");
+			}
+
+			let record  = data[position + 1];
+			let dataset = data[position + 2];
+			let length  = from_u8_vec_macro!(u16, &data[position+3..position+5], &Endian::Big);
+			position += 5;
+
+			// Extended datasets: The lower 15 bits give the number of bytes
+			// that make up the actual length
+			let data_length = if length & 0x8000 != 0
+			{
+				let length_of_length = (length & 0x7fff) as usize;
+				if length_of_length > 4 || data.len() - position < length_of_length
+				{
+					return malformed_iptc("Invalid length of extended dataset!");
+				}
+				let data_length = data[position..position+length_of_length].iter()
+					.fold(0usize, |data_length, byte| (data_length << 8) | *byte as usize);
+				position += length_of_length;
+				data_length
+			}
+			else
+			{
+				length as usize
+			};
+
+			if data.len() - position < data_length
+			{
+				return malformed_iptc(format!("Dataset {}:{} is truncated!", record, dataset));
+			}
+
+			datasets.push(IptcDataSet {
+				record,
+				dataset,
+				data: data[position..position+data_length].to_vec(),
+			});
+			position += data_length;
+		}
+
+		return Ok(Iptc { datasets });
+	}
+
+	/// Encodes the datasets into IPTC-IIM data
+	pub fn
+	encode
+	(
+		&self
+	)
+	-> Vec<u8>
+	{
+		let mut encoded = Vec::new();
+
+		for dataset in &self.datasets
+		{
+			encoded.push(TAG_MARKER);
+			encoded.push(dataset.record);
+			encoded.push(dataset.dataset);
+
+			if dataset.data.len() <= 0x7fff
+			{
+				encoded.extend(to_u8_vec_macro!(u16, &(dataset.data.len() as u16), &Endian::Big));
+			}
+			else
+			{
+				// Extended dataset with a 4 byte length
+				encoded.extend(to_u8_vec_macro!(u16, &0x8004u16,                   &Endian::Big));
+				encoded.extend(to_u8_vec_macro!(u32, &(dataset.data.len() as u32), &Endian::Big));
+			}
+
+			encoded.extend(dataset.data.iter());
+		}
+
+		return encoded;
+	}
+
+	/// Gets the datasets in the order they are stored
+	pub fn
+	get_datasets
+	(
+		&self
+	)
+	-> &[IptcDataSet]
+	{
+		return &self.datasets;
+	}
+
+	/// Gets the raw data of all datasets with the given tag
+	pub fn
+	get_data
+	(
+		&self,
+		tag: IptcTag
+	)
+	-> Vec<&[u8]>
+	{
+		let (record, dataset) = tag.get_record_and_dataset();
+		return self.datasets.iter()
+			.filter(|entry| entry.record == record && entry.dataset == dataset)
+			.map(|entry| entry.data.as_slice())
+			.collect();
+	}
+
+	/// Replaces all datasets with the given tag by new ones holding the given
+	/// raw data. The datasets are kept in ascending order of their record and
+	/// dataset number
+	pub fn
+	set_data
+	(
+		&mut self,
+		tag:    IptcTag,
+		values: Vec<Vec<u8>>
+	)
+	{
+		self.remove(tag);
+
+		let (record, dataset) = tag.get_record_and_dataset();
+
+		// Applications expect the record version to be the first dataset of
+		// the application record
+		if record == 2 && dataset != 0 && self.get_data(IptcTag::RecordVersion).is_empty()
+		{
+			self.set_data(IptcTag::RecordVersion, vec![RECORD_VERSION.to_vec()]);
+		}
+
+		let insert_position = self.datasets.iter()
+			.position(|entry| (entry.record, entry.dataset) > (record, dataset))
+			.unwrap_or(self.datasets.len());
+
+		self.datasets.splice(
+			insert_position..insert_position,
+			values.into_iter().map(|data| IptcDataSet { record, dataset, data })
+		);
+	}
+
+	/// Removes all datasets with the given tag
+	pub fn
+	remove
+	(
+		&mut self,
+		tag: IptcTag
+	)
+	{
+		let (record, dataset) = tag.get_record_and_dataset();
+		self.datasets.retain(|entry| entry.record != record || entry.dataset != dataset);
+	}
+
+	/// Checks if the text of the datasets is encoded as UTF-8
+	pub fn
+	is_utf8
+	(
+		&self
+	)
+	-> bool
+	{
+		return self.get_data(IptcTag::CodedCharacterSet).first() == Some(&UTF8_MARKER.as_slice());
+	}
+
+	/// Gets the text of all datasets with the given tag
+	pub fn
+	get_strings
+	(
+		&self,
+		tag: IptcTag
+	)
+	-> Vec<String>
+	{
+		let is_utf8 = self.is_utf8();
+		return self.get_data(tag).into_iter()
+			.map(|data| decode_text(data, is_utf8))
+			.collect();
+	}
+
+	/// Gets the text of the first dataset with the given tag
+	pub fn
+	get_string
+	(
+		&self,
+		tag: IptcTag
+	)
+	-> Option<String>
+	{
+		return self.get_strings(tag).into_iter().next();
+	}
+
+	/// Replaces all datasets with the given tag by new ones holding the given
+	/// texts. If a text can't be represented in ASCII, all text datasets get
+	/// converted to UTF-8 first
+	pub fn
+	set_strings
+	(
+		&mut self,
+		tag:    IptcTag,
+		values: &[&str]
+	)
+	{
+		if !self.is_utf8() && values.iter().any(|value| !value.is_ascii())
+		{
+			self.convert_to_utf8();
+		}
+
+		let is_utf8 = self.is_utf8();
+		self.set_data(
+			tag,
+			values.iter().map(|value| encode_text(value, is_utf8)).collect()
+		);
+	}
+
+	/// Replaces all datasets with the given tag by a single one holding the
+	/// given text
+	pub fn
+	set_string
+	(
+		&mut self,
+		tag:   IptcTag,
+		value: &str
+	)
+	{
+		self.set_strings(tag, &[value]);
+	}
+
+	/// Re-encodes the text datasets from ISO 8859-1 to UTF-8 and marks the
+	/// data as UTF-8 using the CodedCharacterSet dataset
+	fn
+	convert_to_utf8
+	(
+		&mut self
+	)
+	{
+		for entry in self.datasets.iter_mut()
+		{
+			if entry.get_tag().is_text()
+			{
+				entry.data = decode_text(&entry.data, false).into_bytes();
+			}
+		}
+		self.set_data(IptcTag::CodedCharacterSet, vec![UTF8_MARKER.to_vec()]);
+	}
+}
+
+fn
+decode_text
+(
+	data:    &[u8],
+	is_utf8: bool
+)
+-> String
+{
+	if is_utf8
+	{
+		return String::from_utf8_lossy(data).to_string();
+	}
+
+	// The first 256 code points of Unicode are the ones of ISO 8859-1
+	return data.iter().map(|byte| *byte as char).collect();
+}
+
+/// Encodes text that is either ASCII (which is the same in both encodings)
+/// or has to be stored as UTF-8
+fn
+encode_text
+(
+	value:   &str,
+	is_utf8: bool
+)
+-> Vec<u8>
+{
+	if is_utf8 || value.is_ascii()
+	{
+		return value.as_bytes().to_vec();
+	}
+
+	// Only reached if the data is not UTF-8 and the text is not ASCII,
+	// which `set_strings` prevents by converting the data first
+	return value.chars()
+		.map(|character| u8::try_from(character as u32).unwrap_or(b'?'))
+		.collect();
+}
+
+fn
+malformed_iptc
+<T>
+(
+	reason: impl ToString
+)
+-> Result<T, Error>
+{
+	return Err(Error::MalformedIptc(reason.to_string()));
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn
+	parse_and_encode()
+	{
+		let mut iptc = Iptc::new();
+		iptc.set_strings(IptcTag::Keywords, &["one", "two"]);
+		iptc.set_string(IptcTag::City, "Graz");
+		iptc.set_data(IptcTag::Unknown(2, 200), vec![vec![0xab; 0x9000]]);
+
+		// Record version was added and the datasets are in order
+		let tags = iptc.get_datasets().iter().map(|dataset| dataset.get_tag()).collect::<Vec<_>>();
+		assert_eq!(tags, vec![
+			IptcTag::RecordVersion,
+			IptcTag::Keywords,
+			IptcTag::Keywords,
+			IptcTag::City,
+			IptcTag::Unknown(2, 200),
+		]);
+
+		let mut encoded = iptc.encode();
+		encoded.extend([0, 0, 0]);
+		assert_eq!(Iptc::new_from_bytes(&encoded).unwrap(), iptc);
+
+		assert!(matches!(
+			Iptc::new_from_bytes(&encoded[..20]),
+			Err(Error::MalformedIptc(_))
+		));
+	}
+
+	#[test]
+	fn
+	charset()
+	{
+		// "Wien" and "Bärnbach" in ISO 8859-1
+		let mut iptc = Iptc::new_from_bytes(&[
+			0x1c, 0x02, 0x5a, 0x00, 0x04, b'W', b'i', b'e', b'n',
+			0x1c, 0x02, 0x5c, 0x00, 0x08, b'B', 0xe4, b'r', b'n', b'b', b'a', b'c', b'h',
+		]).unwrap();
+		assert!(!iptc.is_utf8());
+		assert_eq!(iptc.get_string(IptcTag::SubLocation).unwrap(), "Bärnbach");
+
+		// Setting ASCII text keeps the encoding, everything else converts it
+		iptc.set_string(IptcTag::Credit, "ASCII");
+		assert!(!iptc.is_utf8());
+		iptc.set_string(IptcTag::City, "Köflach");
+		assert!(iptc.is_utf8());
+
+		assert_eq!(iptc.get_data(IptcTag::SubLocation), vec!["Bärnbach".as_bytes()]);
+		assert_eq!(iptc.get_string(IptcTag::City).unwrap(), "Köflach");
+		assert_eq!(iptc.get_datasets()[0].get_tag(), IptcTag::CodedCharacterSet);
+	}
+}
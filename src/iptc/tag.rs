@@ -0,0 +1,165 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+/// Commonly used IPTC-IIM datasets, see the "IPTC - NAA Information
+/// Interchange Model", version 4.2, chapter 6 (envelope record) and chapter
+/// 7 (application record). Datasets that are not listed here can be accessed
+/// using `Unknown` with their record and dataset number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+IptcTag
+{
+	/// 1:90, the character set used by the datasets
+	CodedCharacterSet,
+	/// 2:00, binary version number of the application record
+	RecordVersion,
+	/// 2:05, also known as title
+	ObjectName,
+	/// 2:07
+	EditStatus,
+	/// 2:10
+	Urgency,
+	/// 2:15
+	Category,
+	/// 2:20, repeatable
+	SupplementalCategories,
+	/// 2:25, repeatable
+	Keywords,
+	/// 2:40
+	SpecialInstructions,
+	/// 2:55, formatted as CCYYMMDD
+	DateCreated,
+	/// 2:60, formatted as HHMMSS±HHMM
+	TimeCreated,
+	/// 2:80, also known as author or creator, repeatable
+	Byline,
+	/// 2:85, repeatable
+	BylineTitle,
+	/// 2:90
+	City,
+	/// 2:92
+	SubLocation,
+	/// 2:95
+	ProvinceState,
+	/// 2:100
+	CountryCode,
+	/// 2:101
+	CountryName,
+	/// 2:103
+	OriginalTransmissionReference,
+	/// 2:105
+	Headline,
+	/// 2:110
+	Credit,
+	/// 2:115
+	Source,
+	/// 2:116
+	CopyrightNotice,
+	/// 2:118, repeatable
+	Contact,
+	/// 2:120, also known as description
+	CaptionAbstract,
+	/// 2:122, repeatable
+	WriterEditor,
+	/// Any other dataset, given by its record and dataset number
+	Unknown(u8, u8),
+}
+
+const KNOWN_TAGS: [(IptcTag, u8, u8); 26] = [
+	(IptcTag::CodedCharacterSet,             1,  90),
+	(IptcTag::RecordVersion,                 2,   0),
+	(IptcTag::ObjectName,                    2,   5),
+	(IptcTag::EditStatus,                    2,   7),
+	(IptcTag::Urgency,                       2,  10),
+	(IptcTag::Category,                      2,  15),
+	(IptcTag::SupplementalCategories,        2,  20),
+	(IptcTag::Keywords,                      2,  25),
+	(IptcTag::SpecialInstructions,           2,  40),
+	(IptcTag::DateCreated,                   2,  55),
+	(IptcTag::TimeCreated,                   2,  60),
+	(IptcTag::Byline,                        2,  80),
+	(IptcTag::BylineTitle,                   2,  85),
+	(IptcTag::City,                          2,  90),
+	(IptcTag::SubLocation,                   2,  92),
+	(IptcTag::ProvinceState,                 2,  95),
+	(IptcTag::CountryCode,                   2, 100),
+	(IptcTag::CountryName,                   2, 101),
+	(IptcTag::OriginalTransmissionReference, 2, 103),
+	(IptcTag::Headline,                      2, 105),
+	(IptcTag::Credit,                        2, 110),
+	(IptcTag::Source,                        2, 115),
+	(IptcTag::CopyrightNotice,               2, 116),
+	(IptcTag::Contact,                       2, 118),
+	(IptcTag::CaptionAbstract,               2, 120),
+	(IptcTag::WriterEditor,                  2, 122),
+];
+
+impl
+IptcTag
+{
+	/// Gets the tag for the given record and dataset number
+	pub fn
+	from_record_and_dataset
+	(
+		record:  u8,
+		dataset: u8
+	)
+	-> IptcTag
+	{
+		return KNOWN_TAGS.iter()
+			.find(|(_, known_record, known_dataset)| *known_record == record && *known_dataset == dataset)
+			.map(|(tag, _, _)| *tag)
+			.unwrap_or(IptcTag::Unknown(record, dataset));
+	}
+
+	/// Gets the record and dataset number of the tag
+	pub fn
+	get_record_and_dataset
+	(
+		&self
+	)
+	-> (u8, u8)
+	{
+		if let IptcTag::Unknown(record, dataset) = self
+		{
+			return (*record, *dataset);
+		}
+
+		return KNOWN_TAGS.iter()
+			.find(|(tag, _, _)| tag == self)
+			.map(|(_, record, dataset)| (*record, *dataset))
+			.unwrap_or_default();
+	}
+
+	/// Checks if the dataset may occur more than once
+	pub fn
+	is_repeatable
+	(
+		&self
+	)
+	-> bool
+	{
+		return matches!(self,
+			IptcTag::SupplementalCategories |
+			IptcTag::Keywords               |
+			IptcTag::Byline                 |
+			IptcTag::BylineTitle            |
+			IptcTag::Contact                |
+			IptcTag::WriterEditor
+		);
+	}
+
+	/// Checks if the dataset holds text, which is the case for the datasets
+	/// of the application record except for the record version and the
+	/// (binary) object preview datasets
+	pub(crate) fn
+	is_text
+	(
+		&self
+	)
+	-> bool
+	{
+		let (record, dataset) = self.get_record_and_dataset();
+		return record == 2 && dataset != 0 && dataset < 200;
+	}
+}
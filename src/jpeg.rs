@@ -0,0 +1,41 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Functionality that only exists for JPEG files:
+//! - APP1 segments, which hold EXIF as well as (extended) XMP data
+//! - Images listed in the MPF segment (Multi-Picture Format), e.g. previews
+//!   or the gain map of an Ultra HDR image
+//! - Data after the end of the image, like the video of a Motion Photo
+//!
+//! # Examples
+//! ```no_run
+//! use little_exif::jpeg::read_mpf_images;
+//!
+//! let file_buffer = std::fs::read("image.jpg").unwrap();
+//! for image in read_mpf_images(&file_buffer).unwrap()
+//! {
+//!     let data = image.get_data(&file_buffer).unwrap();
+//! }
+//! ```
+
+pub use crate::jpg::App1Segment;
+pub use crate::jpg::read_app1_segments;
+pub use crate::jpg::file_read_app1_segments;
+
+pub use crate::jpg::MpfImage;
+pub use crate::jpg::read_mpf_images;
+pub use crate::jpg::file_read_mpf_images;
+
+pub use crate::jpg::ContainerItem;
+pub use crate::jpg::read_trailer;
+pub use crate::jpg::file_read_trailer;
+pub use crate::jpg::read_container_directory;
+pub use crate::jpg::read_motion_photo_video;
+
+pub use crate::jpg::GainMapMetadata;
+pub use crate::jpg::is_ultra_hdr;
+pub use crate::jpg::read_gain_map;
+pub use crate::jpg::read_gain_map_metadata;
+pub use crate::jpg::file_read_gain_map_metadata;
+pub use crate::jpg::write_gain_map_metadata;
+pub use crate::jpg::file_write_gain_map_metadata;
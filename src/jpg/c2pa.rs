@@ -0,0 +1,208 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! C2PA manifest stores in JPEG images, which are embedded as JUMBF boxes in
+//! APP11 segments as defined by JPEG XT (ISO/IEC 18477-3). As a segment is
+//! limited to 64kb, a box may be split into several packets. Each packet
+//! starts with the common identifier "JP", the box instance number and the
+//! packet sequence number, followed by the header of the box (repeated for
+//! every packet) and the next part of its contents.
+
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+
+use crate::c2pa::is_manifest_store;
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::error::container_error;
+use crate::u8conversion::*;
+
+use super::check_signature;
+use super::clear_segment_if;
+use super::walk_segments;
+
+const JPG_APP11_MARKER:       u8      = 0xeb;
+const JPEG_XT_IDENTIFIER:     &[u8]   = b"JP";
+const JPEG_XT_HEADER_LENGTH:  usize   = 8;
+
+/// A part of a JUMBF box stored in an APP11 segment
+struct
+JumbfPacket
+{
+	box_instance:    u16,
+	sequence_number: u32,
+	data:            Vec<u8>,
+}
+
+impl
+JumbfPacket
+{
+	/// Decodes the packet from the payload of an APP11 segment, returning
+	/// `None` if the segment does not hold a JUMBF packet
+	fn
+	from_payload
+	(
+		payload: &[u8]
+	)
+	-> Option<Self>
+	{
+		if !payload.starts_with(JPEG_XT_IDENTIFIER) || payload.len() < JPEG_XT_HEADER_LENGTH + 8
+		{
+			return None;
+		}
+
+		return Some(JumbfPacket {
+			box_instance:    from_u8_vec_macro!(u16, &payload[2..4], &Endian::Big),
+			sequence_number: from_u8_vec_macro!(u32, &payload[4..8], &Endian::Big),
+			data:            payload[JPEG_XT_HEADER_LENGTH..].to_vec(),
+		});
+	}
+
+	/// Length of the box header at the start of the packet data, which
+	/// includes the 8 bytes of the XLBox field if the length field is 1
+	fn
+	box_header_length
+	(
+		&self
+	)
+	-> usize
+	{
+		if from_u8_vec_macro!(u32, &self.data[0..4], &Endian::Big) == 1
+		{
+			return 16;
+		}
+		return 8;
+	}
+}
+
+/// Reads the C2PA manifest store from the APP11 segments by reassembling the
+/// packets of the box whose first packet holds the "c2pa" description box
+pub(crate) fn
+read_c2pa_jumbf
+<T: Seek + Read>
+(
+	input: &mut T
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let packets = read_c2pa_packets(input)?;
+	if packets.is_empty()
+	{
+		return Ok(None);
+	}
+
+	// Only reassemble the first manifest store, there should not be others
+	let box_instance = packets[0].box_instance;
+	let mut packets  = packets.into_iter()
+		.filter(|packet| packet.box_instance == box_instance)
+		.collect::<Vec<JumbfPacket>>();
+	packets.sort_by_key(|packet| packet.sequence_number);
+
+	let mut jumbf = Vec::new();
+	for (index, packet) in packets.iter().enumerate()
+	{
+		if packet.sequence_number as usize != index + 1
+		{
+			return container_error!("Packets of C2PA manifest store don't fit together!");
+		}
+
+		// The box header is repeated in every packet, but only needed once
+		let start = if index == 0 { 0 } else { packet.box_header_length() };
+		if packet.data.len() < start
+		{
+			return container_error!("Invalid packet of C2PA manifest store!");
+		}
+		jumbf.extend(&packet.data[start..]);
+	}
+
+	return Ok(Some(jumbf));
+}
+
+/// Removes the APP11 segments holding the packets of the C2PA manifest store
+pub(crate) fn
+clear_c2pa_jumbf
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	let box_instances = read_c2pa_box_instances(&mut Cursor::new(file_buffer.as_slice()))?;
+
+	if box_instances.is_empty()
+	{
+		return Ok(());
+	}
+
+	return clear_segment_if(
+		file_buffer,
+		JPG_APP11_MARKER,
+		|payload| is_c2pa_segment(JPG_APP11_MARKER, payload, &box_instances)
+	);
+}
+
+/// Determines the box instance numbers of the C2PA manifest stores
+pub(super) fn
+read_c2pa_box_instances
+<T: Seek + Read>
+(
+	input: &mut T
+)
+-> Result<HashSet<u16>, Error>
+{
+	return Ok(read_c2pa_packets(input)?
+		.into_iter()
+		.map(|packet| packet.box_instance)
+		.collect()
+	);
+}
+
+/// Checks if the segment with the given marker and payload holds a packet of
+/// one of the boxes determined by `read_c2pa_box_instances`
+pub(super) fn
+is_c2pa_segment
+(
+	segment_marker: u8,
+	payload:        &[u8],
+	box_instances:  &HashSet<u16>
+)
+-> bool
+{
+	return segment_marker == JPG_APP11_MARKER && JumbfPacket::from_payload(payload)
+		.map_or(false, |packet| box_instances.contains(&packet.box_instance));
+}
+
+/// Collects the JUMBF packets of all boxes that are C2PA manifest stores,
+/// which is determined using the first packet of each box
+fn
+read_c2pa_packets
+<T: Seek + Read>
+(
+	input: &mut T
+)
+-> Result<Vec<JumbfPacket>, Error>
+{
+	let mut signature_buffer = [0u8; 2];
+	input.read_exact(&mut signature_buffer)?;
+	check_signature(&signature_buffer)?;
+
+	let mut packets = Vec::new();
+	walk_segments(input, JPG_APP11_MARKER, |app11_buffer| {
+		if let Some(packet) = JumbfPacket::from_payload(&app11_buffer)
+		{
+			packets.push(packet);
+		}
+		return false;
+	})?;
+
+	let manifest_store_instances = packets.iter()
+		.filter(|packet| packet.sequence_number == 1 && is_manifest_store(&packet.data))
+		.map(|packet| packet.box_instance)
+		.collect::<HashSet<u16>>();
+
+	return Ok(packets.into_iter()
+		.filter(|packet| manifest_store_instances.contains(&packet.box_instance))
+		.collect()
+	);
+}
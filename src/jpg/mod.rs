@@ -0,0 +1,1528 @@
+// Copyright © 2024 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::endian::Endian;
+use crate::metadata::Metadata;
+use crate::tiff::starts_with_tiff_signature;
+use crate::u8conversion::*;
+use crate::general_file_io::*;
+use crate::photoshop::IPTC_DIGEST_RESOURCE_ID;
+use crate::photoshop::IPTC_RESOURCE_ID;
+use crate::photoshop::ImageResource;
+use crate::photoshop::PhotoshopResources;
+use crate::util::copy_n_bytes;
+
+mod c2pa;
+mod mpf;
+mod trailer;
+mod ultra_hdr;
+
+pub(crate) use c2pa::read_c2pa_jumbf;
+pub(crate) use c2pa::clear_c2pa_jumbf;
+use c2pa::is_c2pa_segment;
+use c2pa::read_c2pa_box_instances;
+pub use mpf::MpfImage;
+pub use mpf::read_mpf_images;
+pub use mpf::file_read_mpf_images;
+use mpf::has_mpf_segment;
+use mpf::preserve_mpf_offsets;
+use mpf::preserve_mpf_offsets_before_image_data;
+pub use trailer::ContainerItem;
+pub use trailer::read_trailer;
+pub use trailer::file_read_trailer;
+pub use trailer::read_container_directory;
+pub use trailer::read_motion_photo_video;
+pub use ultra_hdr::GainMapMetadata;
+pub use ultra_hdr::is_ultra_hdr;
+pub use ultra_hdr::read_gain_map;
+pub use ultra_hdr::read_gain_map_metadata;
+pub use ultra_hdr::file_read_gain_map_metadata;
+pub use ultra_hdr::write_gain_map_metadata;
+pub use ultra_hdr::file_write_gain_map_metadata;
+
+pub(crate) const JPG_SIGNATURE: [u8; 2] = [0xff, 0xd8];
+
+const JPG_MARKER_PREFIX: u8  = 0xff;
+const JPG_APP1_MARKER:   u16 = 0xffe1;
+const JPG_APP2_MARKER:   u16 = 0xffe2;
+const JPG_APP13_MARKER:  u16 = 0xffed;
+
+const XMP_APP1_IDENTIFIER:          &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const EXTENDED_XMP_APP1_IDENTIFIER: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+const PHOTOSHOP_APP13_IDENTIFIER:   &[u8] = b"Photoshop 3.0\0";
+const ICC_PROFILE_APP2_IDENTIFIER:  &[u8] = b"ICC_PROFILE\0";
+
+// The length field of a segment also includes its own two bytes
+const MAX_SEGMENT_PAYLOAD_LENGTH: usize = u16::MAX as usize - 2;
+
+// Each portion of an extended XMP packet starts with the GUID (32 ASCII 
+// characters), the full length of the packet and the offset of the portion
+const EXTENDED_XMP_GUID_LENGTH:           usize = 32;
+const EXTENDED_XMP_PORTION_HEADER_LENGTH: usize = EXTENDED_XMP_GUID_LENGTH + 4 + 4;
+
+/// The maximum length of an XMP packet that fits into a single APP1 segment,
+/// i.e. 65504 bytes. Larger packets need to be split into a standard and an
+/// extended part, see the XMP specification part 3, section 1.1.3.1
+pub(crate) const MAX_XMP_PACKET_LENGTH: usize = 
+	MAX_SEGMENT_PAYLOAD_LENGTH - XMP_APP1_IDENTIFIER.len();
+
+const MAX_EXTENDED_XMP_PORTION_LENGTH: usize = 
+	MAX_SEGMENT_PAYLOAD_LENGTH 
+	- EXTENDED_XMP_APP1_IDENTIFIER.len() 
+	- EXTENDED_XMP_PORTION_HEADER_LENGTH;
+
+// Each chunk of an ICC profile starts with its sequence number (starting at
+// 1) and the total number of chunks, see the ICC specification, annex B.4
+const MAX_ICC_PROFILE_CHUNK_LENGTH: usize = 
+	MAX_SEGMENT_PAYLOAD_LENGTH - ICC_PROFILE_APP2_IDENTIFIER.len() - 2;
+
+/// The payload of an APP1 segment of a JPEG file. As APP1 segments are used
+/// for storing different kinds of data, the identifier at the start of their
+/// payload determines the variant. The identifier is not part of the data
+/// stored in the variants:
+/// - `Exif`: The TIFF structure holding the EXIF data, which can e.g. be 
+///   decoded via `Metadata::new_from_vec` using `FileExtension::TIFF`
+/// - `Xmp`: The XMP packet
+/// - `ExtendedXmp`: A portion of an extended XMP packet, starting with the
+///   GUID, the full length and the offset of that portion
+/// - `Unknown`: The entire payload of an APP1 segment with an unknown 
+///   identifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum
+App1Segment
+{
+	Exif(Vec<u8>),
+	Xmp(Vec<u8>),
+	ExtendedXmp(Vec<u8>),
+	Unknown(Vec<u8>),
+}
+
+impl
+App1Segment
+{
+	fn
+	from_payload
+	(
+		payload: &[u8]
+	)
+	-> Self
+	{
+		if let Some(exif_data) = payload.strip_prefix(&EXIF_HEADER)
+		{
+			return App1Segment::Exif(exif_data.to_vec());
+		}
+		if starts_with_tiff_signature(payload)
+		{
+			// Some writers omit the EXIF header
+			return App1Segment::Exif(payload.to_vec());
+		}
+		if let Some(xmp_data) = payload.strip_prefix(XMP_APP1_IDENTIFIER)
+		{
+			return App1Segment::Xmp(xmp_data.to_vec());
+		}
+		if let Some(extended_xmp_data) = payload.strip_prefix(EXTENDED_XMP_APP1_IDENTIFIER)
+		{
+			return App1Segment::ExtendedXmp(extended_xmp_data.to_vec());
+		}
+		return App1Segment::Unknown(payload.to_vec());
+	}
+
+	/// Returns the data of the segment without its identifier
+	pub fn
+	data
+	(
+		&self
+	)
+	-> &[u8]
+	{
+		return match self
+		{
+			App1Segment::Exif(data)        => data,
+			App1Segment::Xmp(data)         => data,
+			App1Segment::ExtendedXmp(data) => data,
+			App1Segment::Unknown(data)     => data,
+		};
+	}
+}
+
+/// Checks if the payload of an APP1 segment holds EXIF data, i.e. starts with
+/// the EXIF header or, as some writers omit that header, a TIFF header
+fn
+is_exif_app1_payload
+(
+	payload: &[u8]
+)
+-> bool
+{
+	return payload.starts_with(&EXIF_HEADER) || starts_with_tiff_signature(payload);
+}
+
+
+
+/// Maximum length of the general encoded EXIF data that fits into an APP1
+/// segment, after the EXIF header
+pub(crate) const MAX_EXIF_LENGTH: usize = MAX_SEGMENT_PAYLOAD_LENGTH - EXIF_HEADER.len();
+
+/// Encodes the metadata for an APP1 segment. Fails if it doesn't fit, as the
+/// length field of the segment would overflow otherwise. Reducing the size
+/// happens beforehand, see `Metadata::reduce_for_jpeg`.
+pub(crate) fn
+encode_exif
+(
+	metadata: &Metadata
+)
+-> Result<Vec<u8>, Error>
+{
+	let encoded = metadata.encode()?;
+	if encoded.len() > MAX_EXIF_LENGTH
+	{
+		return Err(Error::ExifTooLarge { size: encoded.len(), maximum: MAX_EXIF_LENGTH });
+	}
+
+	return Ok(encoded);
+}
+
+fn
+encode_metadata_jpg
+(
+	exif_vec: &Vec<u8>
+)
+-> Vec<u8>
+{
+	// vector storing the data that will be returned
+	let mut jpg_exif: Vec<u8> = Vec::new();
+
+	// Compute the length of the exif data (includes the two bytes of the
+	// actual length field)
+	let length = 2u16 + (EXIF_HEADER.len() as u16) + (exif_vec.len() as u16);
+
+	// Start with the APP1 marker and the length of the data
+	// Then copy the previously encoded EXIF data 
+	jpg_exif.extend(to_u8_vec_macro!(u16, &JPG_APP1_MARKER, &Endian::Big));
+	jpg_exif.extend(to_u8_vec_macro!(u16, &length, &Endian::Big));
+	jpg_exif.extend(EXIF_HEADER.iter());
+	jpg_exif.extend(exif_vec.iter());
+
+	return jpg_exif;
+}
+
+
+
+pub(crate) fn
+check_signature
+(
+	file_buffer: &[u8]
+)
+-> Result<(), Error>
+{
+	if file_buffer.len() < JPG_SIGNATURE.len()
+	{
+		return container_error!("Can't open JPG file - Not enough data for signature!");
+	}
+
+	// Check the signature
+	let signature_is_valid = file_buffer[0..2].iter()
+		.zip(JPG_SIGNATURE.iter())
+		.filter(|&(read, constant)| read == constant)
+		.count() == JPG_SIGNATURE.len();
+
+	if !signature_is_valid
+	{
+		return container_error!("Can't open JPG file - Wrong signature!");
+	}
+
+	// Signature is valid - can proceed using as JPG file
+	return Ok(());
+}
+
+fn
+file_check_signature
+(
+	path: &Path
+)
+-> Result<File, Error>
+{
+	let mut file = open_read_file(path)?;
+	
+	// Check the signature
+	let mut signature_buffer = [0u8; 2];
+	file.read(&mut signature_buffer)?;
+	check_signature(&signature_buffer)?;
+
+	// Signature is valid - can proceed using the file as JPG file
+	return Ok(file);
+}
+
+
+/// Computes the length of the segment data that follows after the two bytes
+/// of the length field, which are included in the length value itself
+fn
+get_remaining_segment_length
+(
+	length: u16
+)
+-> Result<usize, Error>
+{
+	if let Some(remaining_length) = length.checked_sub(2)
+	{
+		return Ok(remaining_length as usize);
+	}
+
+	return container_error!(format!("Invalid JPG segment length: {}", length));
+}
+
+
+pub(crate) fn
+clear_metadata
+(
+	file_buffer: &mut Vec<u8>,
+)
+-> Result<(), Error>
+{
+	// Only remove the APP1 segments holding EXIF data, but not e.g. XMP
+	return clear_segment_if(file_buffer, 0xe1, is_exif_app1_payload);
+}
+
+
+pub(crate) fn
+clear_segment
+(
+	file_buffer:    &mut Vec<u8>,
+	segment_marker: u8,
+)
+-> Result<(), Error>
+{
+	return clear_segment_if(file_buffer, segment_marker, |_| true);
+}
+
+/// Removes all segments with the given marker for which the predicate 
+/// returns true when given the payload of the segment
+fn
+clear_segment_if
+<F: Fn(&[u8]) -> bool>
+(
+	file_buffer:    &mut Vec<u8>,
+	segment_marker: u8,
+	predicate:      F,
+)
+-> Result<(), Error>
+{
+	check_signature(&file_buffer)?;
+
+	return preserve_mpf_offsets(
+		file_buffer, 
+		|file_buffer| remove_segments_if(file_buffer, segment_marker, predicate)
+	);
+}
+
+fn
+remove_segments_if
+<F: Fn(&[u8]) -> bool>
+(
+	file_buffer:    &mut Vec<u8>,
+	segment_marker: u8,
+	predicate:      F,
+)
+-> Result<(), Error>
+{
+
+	// Setup of variables necessary for going through the file
+	let mut byte_buffer = [0u8; 1];                                             // A buffer for reading in a byte of data from the file
+	let mut previous_byte_was_marker_prefix = false;                            // A boolean for remembering if the previous byte was a marker prefix (0xFF)
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip 0xFFD8 at the start
+	cursor.seek(SeekFrom::Current(2))?;
+
+	loop
+	{
+		// Read next byte into buffer
+		cursor.read_exact(&mut byte_buffer)?;
+
+		if previous_byte_was_marker_prefix
+		{
+			// Check if this is the end of the file. In that case, the length
+			// data can't be read and we need to return prematurely. 
+			if byte_buffer[0] == 0xd9                                           // EOI marker
+			{
+				// No more data to read in
+				return Ok(());
+			}
+
+			// Read in the length of the segment
+			// (which follows immediately after the marker)
+			let mut length_buffer = [0u8; 2];
+			cursor.read_exact(&mut length_buffer)?;
+
+			// Decode the length to determine how much more data there is
+			let length = from_u8_vec_macro!(u16, &length_buffer, &Endian::Big);
+			let remaining_length = get_remaining_segment_length(length)?;
+
+			let payload_start = cursor.position() as usize;
+			let payload_end   = (payload_start + remaining_length).min(cursor.get_ref().len());
+			let payload       = &cursor.get_ref()[payload_start..payload_end];
+
+			if byte_buffer[0] == segment_marker && predicate(payload)           // Given marker, e.g. for APP1
+			{
+				// Backup current position, account for the 4 bytes already read
+				let backup_position = cursor.position() - 4;
+
+				// Skip the segment
+				cursor.seek(SeekFrom::Current(remaining_length as i64))?;
+
+				// Copy data from there onwards into a buffer
+				let mut temp_buffer = Vec::new();
+				cursor.read_to_end(&mut temp_buffer)?;
+
+				// Overwrite segment
+				cursor.set_position(backup_position);
+				cursor.write_all(&temp_buffer)?;
+
+				// Cut off right-most bytes that are now duplicates due 
+				// to the previous shift-to-left operation
+				let cutoff_index = backup_position as usize + temp_buffer.len();
+				cursor.get_mut().truncate(cutoff_index);
+
+				// Seek to start of next segment
+				cursor.set_position(backup_position);
+			}
+			else if byte_buffer[0] == 0xda
+			{
+				// See `generic_read_metadata`
+				cursor.seek(SeekFrom::Current(remaining_length as i64))?;
+				skip_ecs(&mut cursor)?;
+			}
+			else
+			{
+				// Skip this segment
+				cursor.seek(SeekFrom::Current(remaining_length as i64))?;
+			}
+
+			previous_byte_was_marker_prefix = false;
+		}
+		else
+		{
+			previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+		}
+	}
+}
+
+pub(crate) fn
+file_clear_metadata
+(
+	path: &Path
+)
+-> Result<(), Error>
+{
+	// Load the entire file into memory instead of reading one byte at a time
+	// to improve the overall speed
+	// Thanks to Xuf3r for this improvement!
+	let mut file_buffer: Vec<u8> = std::fs::read(path)?;
+
+	// Clear the metadata in the APP1 segment from the file buffer
+	clear_metadata(&mut file_buffer)?;
+	
+	// Write the file
+	// Possible to optimize further by returning the purged bytestream itself?
+	let mut file = std::fs::OpenOptions::new().write(true).truncate(true).open(path)?;
+	perform_file_action!(file.write_all(&file_buffer));
+
+	return Ok(());
+}
+
+
+/// Provides the JPEG specific encoding result as vector of bytes to be used
+/// by the user (e.g. in combination with another library)
+pub(crate) fn
+as_u8_vec
+(
+	general_encoded_metadata: &Vec<u8>
+)
+-> Vec<u8>
+{
+	encode_metadata_jpg(general_encoded_metadata)
+}
+
+
+
+pub(crate) fn
+write_metadata
+(
+	file_buffer: &mut Vec<u8>,
+	metadata:    &Metadata
+)
+-> Result<(), Error>
+{
+	// Encode the data specifically for JPG, before modifying the file in 
+	// case the data is too large
+	let encoded_metadata = encode_metadata_jpg(&encode_exif(metadata)?);
+
+	// Remove old metadata
+	clear_metadata(file_buffer)?;
+
+	// Insert the metadata right after the signature
+	insert_segments(file_buffer, 2, encoded_metadata)?;
+
+	return Ok(());
+}
+
+/// Writes the given generally encoded metadata to the JP(E)G image file at 
+/// the specified path. 
+/// Note that any previously stored metadata under the APP1 marker gets removed
+/// first before writing the "new" metadata. 
+pub(crate) fn
+file_write_metadata
+(
+	path:     &Path,
+	metadata: &Metadata
+)
+-> Result<(), Error>
+{
+	// Load the entire file into memory instead of performing multiple read, 
+	// seek and write operations
+	let mut file = open_write_file(path)?;
+	let mut file_buffer: Vec<u8> = Vec::new();
+	file.read_to_end(&mut file_buffer)?;
+
+	// Writes the metadata to the file_buffer vec
+	// The called function handles the removal of old metadata and the JPG
+	// specific encoding, so we pass only the generally encoded metadata here
+	write_metadata(&mut file_buffer, metadata)?;
+
+	// Seek back to start & write the file
+	// If the new metadata is smaller, the file needs to be cut off as the
+	// remaining bytes would otherwise end up after the trailer data
+	file.seek(SeekFrom::Start(0))?;
+	file.write_all(&file_buffer)?;
+	file.set_len(file_buffer.len() as u64)?;
+
+	return Ok(());
+}
+
+/// Writes the image data read from the input to the output while replacing
+/// any EXIF APP1 segments with the given metadata, see `write_metadata`. 
+/// If requested, the APP11 segments holding a C2PA manifest store get removed
+/// as well, see `clear_c2pa_jumbf`.
+/// In contrast to `file_write_metadata`, the image is not loaded into memory
+/// but copied through segment by segment. Only the segments in front of the
+/// image data are collected first, as the offsets of an MPF segment among
+/// them need to be updated before writing it. In that case, any segments
+/// after the start of the image data are copied as they are, as removing them
+/// would move the appended images once more.
+/// As the entropy-coded segments are processed one byte at a time, both input
+/// and output should be buffered.
+pub(crate) fn
+write_metadata_to_stream
+<R: Read + Seek, W: Write>
+(
+	input:      &mut R,
+	output:     &mut W,
+	metadata:   &Metadata,
+	strip_c2pa: bool
+)
+-> Result<(), Error>
+{
+	let mut encoded_metadata = encode_metadata_jpg(&encode_exif(metadata)?);
+
+	let c2pa_box_instances = if strip_c2pa
+	{
+		let start_position = input.stream_position()?;
+		let box_instances  = read_c2pa_box_instances(input)?;
+		input.seek(SeekFrom::Start(start_position))?;
+		box_instances
+	}
+	else
+	{
+		HashSet::new()
+	};
+
+	// Drop the old metadata, but keep other APP1 segments like XMP
+	let is_removed = |marker: u8, payload: &[u8]| 
+		(marker == 0xe1 && is_exif_app1_payload(payload)) 
+		|| is_c2pa_segment(marker, payload, &c2pa_box_instances);
+
+	let mut header = vec![0u8; JPG_SIGNATURE.len()];
+	input.read_exact(&mut header)?;
+	check_signature(&header)?;
+
+	// Collect the segments up to the SOS or EOI marker
+	let mut removed_segments = Vec::new();
+	let mut marker           = read_marker(input, &mut header)?;
+	while marker != 0xda && marker != 0xd9
+	{
+		let segment_start = header.len();
+		read_segment(input, &mut header, marker)?;
+
+		if is_removed(marker, &header[segment_start+4..])
+		{
+			removed_segments.push(segment_start..header.len());
+		}
+
+		marker = read_marker(input, &mut header)?;
+	}
+
+	// Insert the metadata right after the signature
+	preserve_mpf_offsets_before_image_data(
+		&mut header,
+		|header| {
+			for segment in removed_segments.into_iter().rev()
+			{
+				header.drain(segment);
+			}
+			crate::util::insert_multiple_at(header, JPG_SIGNATURE.len(), &mut encoded_metadata);
+			return Ok(());
+		}
+	)?;
+	output.write_all(&header)?;
+
+	let keep_all_segments = has_mpf_segment(&header)?;
+
+	loop
+	{
+		if marker == 0xd9                                                       // EOI marker
+		{
+			// Copy the marker and whatever might follow after it
+			output.write_all(&[JPG_MARKER_PREFIX, marker])?;
+			std::io::copy(input, output)?;
+			return Ok(());
+		}
+
+		let mut segment = Vec::new();
+		read_segment(input, &mut segment, marker)?;
+
+		if keep_all_segments || !is_removed(marker, &segment[4..])
+		{
+			output.write_all(&segment)?;
+		}
+
+		if marker == 0xda                                                       // SOS marker
+		{
+			copy_ecs(input, output)?;
+		}
+
+		marker = read_marker(input, output)?;
+	}
+}
+
+/// Reads the input up to and including the next marker, which gets returned
+/// without its prefix. Any other data in front of the marker gets copied to
+/// the output.
+fn
+read_marker
+<R: Read, W: Write>
+(
+	input:  &mut R,
+	output: &mut W
+)
+-> Result<u8, Error>
+{
+	let mut byte_buffer = [0u8; 1];
+	let mut previous_byte_was_marker_prefix = false;
+
+	loop
+	{
+		input.read_exact(&mut byte_buffer)?;
+
+		if previous_byte_was_marker_prefix
+		{
+			return Ok(byte_buffer[0]);
+		}
+
+		// A marker prefix only gets written together with its marker
+		previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+		if !previous_byte_was_marker_prefix
+		{
+			output.write_all(&byte_buffer)?;
+		}
+	}
+}
+
+/// Reads the length and payload of the segment with the given marker from 
+/// the input and appends the entire segment, including the marker, to the
+/// given buffer
+fn
+read_segment
+<R: Read>
+(
+	input:          &mut R,
+	segment_buffer: &mut Vec<u8>,
+	marker:         u8
+)
+-> Result<(), Error>
+{
+	let mut length_buffer = [0u8; 2];
+	input.read_exact(&mut length_buffer)?;
+
+	let length = from_u8_vec_macro!(u16, &length_buffer, &Endian::Big);
+	let remaining_length = get_remaining_segment_length(length)?;
+
+	segment_buffer.extend_from_slice(&[JPG_MARKER_PREFIX, marker]);
+	segment_buffer.extend_from_slice(&length_buffer);
+	copy_n_bytes(input, segment_buffer, remaining_length as u64)?;
+
+	return Ok(());
+}
+
+pub(crate) fn
+read_metadata
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	return generic_read_metadata(&mut cursor);
+}
+
+pub(crate) fn
+file_read_metadata
+(
+	path: &Path
+)
+-> Result<Vec<u8>, Error>
+{
+	// Use a buffered reader to speed up operations, see issue #21
+	let mut buffered_file = BufReader::new(file_check_signature(path)?);
+	return generic_read_metadata(&mut buffered_file);
+}
+
+/// Reads the raw EXIF data from the JPEG data provided by the given reader.
+/// As the data is processed one byte at a time, the reader should be buffered
+pub(crate) fn
+read_metadata_from_stream
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Vec<u8>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(JPG_SIGNATURE.len() as u64).read_to_end(&mut signature_buffer)?;
+	check_signature(&signature_buffer)?;
+
+	return generic_read_metadata(input);
+}
+
+/// Reads the payloads of all APP1 segments of the given JPEG data, e.g. to 
+/// get the XMP packet stored next to the EXIF data
+/// # Examples
+/// ```no_run
+/// use little_exif::jpeg::App1Segment;
+/// use little_exif::jpeg::read_app1_segments;
+/// 
+/// let file_data = std::fs::read("image.jpg").unwrap();
+/// for segment in read_app1_segments(&file_data).unwrap()
+/// {
+///     if let App1Segment::Xmp(xmp_packet) = segment
+///     {
+///         println!("{}", String::from_utf8_lossy(&xmp_packet));
+///     }
+/// }
+/// ```
+pub fn
+read_app1_segments
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<App1Segment>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	return generic_read_app1_segments(&mut cursor);
+}
+
+/// Same as `read_app1_segments`, but for the JPEG file at the given path
+pub fn
+file_read_app1_segments
+(
+	path: &Path
+)
+-> Result<Vec<App1Segment>, Error>
+{
+	let mut buffered_file = BufReader::new(file_check_signature(path)?);
+	return generic_read_app1_segments(&mut buffered_file);
+}
+
+/// Reads the XMP packet stored in the (first) XMP APP1 segment. This is the
+/// standard XMP packet, which may refer to an extended one using the 
+/// `xmpNote:HasExtendedXMP` property, see `read_extended_xmp`
+pub(crate) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	let mut xmp_packet = None;
+	walk_app1_segments(&mut cursor, |app1_buffer| {
+		if let Some(packet) = app1_buffer.strip_prefix(XMP_APP1_IDENTIFIER)
+		{
+			xmp_packet = Some(packet.to_vec());
+			return true;
+		}
+		return false;
+	})?;
+
+	return match xmp_packet
+	{
+		Some(xmp_packet) => Ok(xmp_packet),
+		None             => Err(Error::NoXmpData),
+	};
+}
+
+/// Computes the GUID of an extended XMP packet, which is its MD5 digest 
+/// given as 32 uppercase hexadecimal digits
+pub(crate) fn
+get_extended_xmp_guid
+(
+	extended_xmp_packet: &[u8]
+)
+-> String
+{
+	return format!("{:X}", md5::compute(extended_xmp_packet));
+}
+
+/// Reassembles the extended XMP packet with the given GUID from the portions
+/// stored in the extended XMP APP1 segments. Returns `Error::NoXmpData` if
+/// there are no such segments at all
+pub(crate) fn
+read_extended_xmp
+(
+	file_buffer: &[u8],
+	guid:        &str
+)
+-> Result<Vec<u8>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	// Collect the full length, the offset and the data of the portions
+	let mut portions = Vec::new();
+	walk_app1_segments(&mut cursor, |app1_buffer| {
+		if let Some(portion) = app1_buffer.strip_prefix(EXTENDED_XMP_APP1_IDENTIFIER)
+		{
+			if portion.len() >= EXTENDED_XMP_PORTION_HEADER_LENGTH
+				&& portion[..EXTENDED_XMP_GUID_LENGTH] == *guid.as_bytes()
+			{
+				portions.push((
+					from_u8_vec_macro!(u32, &portion[32..36], &Endian::Big) as usize,
+					from_u8_vec_macro!(u32, &portion[36..40], &Endian::Big) as usize,
+					portion[EXTENDED_XMP_PORTION_HEADER_LENGTH..].to_vec()
+				));
+			}
+		}
+		return false;
+	})?;
+
+	if portions.is_empty()
+	{
+		return Err(Error::NoXmpData);
+	}
+
+	// The portions are not required to be stored in order
+	portions.sort_by_key(|(_, offset, _)| *offset);
+
+	let full_length = portions[0].0;
+	let mut extended_xmp_packet = Vec::new();
+	for (portion_full_length, offset, data) in portions
+	{
+		if portion_full_length != full_length || offset != extended_xmp_packet.len()
+		{
+			return container_error!("Portions of extended XMP packet don't fit together!");
+		}
+		extended_xmp_packet.extend(data);
+	}
+
+	if extended_xmp_packet.len() != full_length
+	{
+		return container_error!("Extended XMP packet is incomplete!");
+	}
+
+	if get_extended_xmp_guid(&extended_xmp_packet) != guid
+	{
+		return container_error!("Extended XMP packet does not match its GUID!");
+	}
+
+	return Ok(extended_xmp_packet);
+}
+
+/// Removes the standard and extended XMP APP1 segments, but keeps e.g. the
+/// EXIF data
+pub(crate) fn
+clear_xmp
+(
+	file_buffer: &mut Vec<u8>,
+)
+-> Result<(), Error>
+{
+	return clear_segment_if(
+		file_buffer, 
+		0xe1, 
+		|payload| 
+			payload.starts_with(XMP_APP1_IDENTIFIER) 
+			|| payload.starts_with(EXTENDED_XMP_APP1_IDENTIFIER)
+	);
+}
+
+/// Replaces the XMP APP1 segments with ones holding the given packets. The 
+/// new segments are placed after the JFIF and EXIF segments at the start of
+/// the image, as the XMP specification demands for the JFIF segment.
+/// If an extended packet is given, it gets split into portions that are 
+/// stored in extended XMP APP1 segments. In that case, the standard packet
+/// is expected to refer to it via the `xmpNote:HasExtendedXMP` property, 
+/// whose value can be computed using `get_extended_xmp_guid`
+pub(crate) fn
+write_xmp
+(
+	file_buffer:         &mut Vec<u8>,
+	xmp_packet:          &[u8],
+	extended_xmp_packet: Option<&[u8]>
+)
+-> Result<(), Error>
+{
+	if xmp_packet.len() > MAX_XMP_PACKET_LENGTH
+	{
+		return container_error!(format!(
+			"XMP packet too large for a single APP1 segment ({} bytes)!", 
+			xmp_packet.len()
+		));
+	}
+
+	clear_xmp(file_buffer)?;
+
+	let mut xmp_segments = Vec::new();
+	xmp_segments.extend(encode_segment(JPG_APP1_MARKER, XMP_APP1_IDENTIFIER, xmp_packet));
+
+	if let Some(extended_xmp_packet) = extended_xmp_packet
+	{
+		if extended_xmp_packet.len() > u32::MAX as usize
+		{
+			return container_error!("Extended XMP packet too large!");
+		}
+
+		let guid        = get_extended_xmp_guid(extended_xmp_packet);
+		let full_length = extended_xmp_packet.len() as u32;
+
+		for (index, data) in extended_xmp_packet
+			.chunks(MAX_EXTENDED_XMP_PORTION_LENGTH)
+			.enumerate()
+		{
+			let offset = (index * MAX_EXTENDED_XMP_PORTION_LENGTH) as u32;
+
+			let mut portion = Vec::new();
+			portion.extend(guid.bytes());
+			portion.extend(to_u8_vec_macro!(u32, &full_length, &Endian::Big));
+			portion.extend(to_u8_vec_macro!(u32, &offset,      &Endian::Big));
+			portion.extend(data.iter());
+
+			xmp_segments.extend(encode_segment(JPG_APP1_MARKER, EXTENDED_XMP_APP1_IDENTIFIER, &portion));
+		}
+	}
+
+	let insert_position = find_insert_position(
+		file_buffer, 
+		|marker, payload| marker == 0xe0 || (marker == 0xe1 && is_exif_app1_payload(payload))
+	)?;
+	insert_segments(file_buffer, insert_position, xmp_segments)?;
+
+	return Ok(());
+}
+
+/// Encodes a segment with the given marker (e.g. APP1) consisting of the 
+/// given identifier and data. Assumes that the payload does not exceed the
+/// maximum segment length
+fn
+encode_segment
+(
+	marker:     u16,
+	identifier: &[u8],
+	data:       &[u8]
+)
+-> Vec<u8>
+{
+	let length = (2 + identifier.len() + data.len()) as u16;
+
+	let mut segment = Vec::new();
+	segment.extend(to_u8_vec_macro!(u16, &marker, &Endian::Big));
+	segment.extend(to_u8_vec_macro!(u16, &length,          &Endian::Big));
+	segment.extend(identifier.iter());
+	segment.extend(data.iter());
+
+	return segment;
+}
+
+/// Reads the Photoshop image resource blocks stored in the APP13 segments.
+/// Large resources may be split across several segments, so the data of all
+/// of them gets concatenated. Returns no data if there are no such segments
+pub(crate) fn
+read_photoshop_resources
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	let mut resource_data = Vec::new();
+	walk_segments(&mut cursor, 0xed, |app13_buffer| {
+		if let Some(data) = app13_buffer.strip_prefix(PHOTOSHOP_APP13_IDENTIFIER)
+		{
+			resource_data.extend(data.iter());
+		}
+		return false;
+	})?;
+
+	return Ok(resource_data);
+}
+
+/// Replaces the Photoshop APP13 segments with ones holding the given image
+/// resource blocks, which are split across several segments if necessary.
+/// The segments are placed after the other APPn segments at the start of the
+/// image. If there is no data, the segments are only removed
+pub(crate) fn
+write_photoshop_resources
+(
+	file_buffer:   &mut Vec<u8>,
+	resource_data: &[u8]
+)
+-> Result<(), Error>
+{
+	clear_segment_if(
+		file_buffer, 
+		0xed, 
+		|payload| payload.starts_with(PHOTOSHOP_APP13_IDENTIFIER)
+	)?;
+
+	let mut app13_segments = Vec::new();
+	for data in resource_data.chunks(MAX_SEGMENT_PAYLOAD_LENGTH - PHOTOSHOP_APP13_IDENTIFIER.len())
+	{
+		app13_segments.extend(encode_segment(JPG_APP13_MARKER, PHOTOSHOP_APP13_IDENTIFIER, data));
+	}
+
+	let insert_position = find_insert_position(
+		file_buffer, 
+		|marker, _| (0xe0..=0xec).contains(&marker)
+	)?;
+	insert_segments(file_buffer, insert_position, app13_segments)?;
+
+	return Ok(());
+}
+
+/// Reads the IPTC-IIM data stored in the image resource 0x0404 of the 
+/// Photoshop APP13 segments
+pub(crate) fn
+read_iptc
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	let resources = PhotoshopResources::new_from_bytes(&read_photoshop_resources(file_buffer)?)?;
+
+	return match resources.get_resource(IPTC_RESOURCE_ID)
+	{
+		Some(iptc_resource) => Ok(iptc_resource.data.clone()),
+		None                => Err(Error::NoIptcData),
+	};
+}
+
+/// Removes the IPTC-IIM data (and its digest), but keeps the other image 
+/// resources of the Photoshop APP13 segments
+pub(crate) fn
+clear_iptc
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	let mut resources = PhotoshopResources::new_from_bytes(&read_photoshop_resources(file_buffer)?)?;
+	resources.remove_resource(IPTC_RESOURCE_ID);
+	resources.remove_resource(IPTC_DIGEST_RESOURCE_ID);
+
+	return write_photoshop_resources(file_buffer, &resources.encode());
+}
+
+/// Replaces the IPTC-IIM data stored in the Photoshop APP13 segments while
+/// keeping the other image resources. An existing digest of the IPTC data is
+/// updated as well, so that Photoshop does not consider the data outdated
+pub(crate) fn
+write_iptc
+(
+	file_buffer: &mut Vec<u8>,
+	iptc_data:   &[u8]
+)
+-> Result<(), Error>
+{
+	let mut resources = PhotoshopResources::new_from_bytes(&read_photoshop_resources(file_buffer)?)?;
+
+	resources.set_resource(ImageResource::new(IPTC_RESOURCE_ID, iptc_data.to_vec()));
+
+	if resources.get_resource(IPTC_DIGEST_RESOURCE_ID).is_some()
+	{
+		resources.set_resource(ImageResource::new(
+			IPTC_DIGEST_RESOURCE_ID, 
+			md5::compute(iptc_data).0.to_vec()
+		));
+	}
+
+	return write_photoshop_resources(file_buffer, &resources.encode());
+}
+
+/// Reassembles the ICC profile from the chunks stored in the APP2 segments,
+/// which are ordered by their sequence numbers. Returns 
+/// `Error::NoIccProfile` if there are no such segments at all
+pub(crate) fn
+read_icc_profile
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	// Collect the sequence number, the number of chunks and the data
+	let mut chunks = Vec::new();
+	walk_segments(&mut cursor, 0xe2, |app2_buffer| {
+		if let Some(chunk) = app2_buffer.strip_prefix(ICC_PROFILE_APP2_IDENTIFIER)
+		{
+			if chunk.len() >= 2
+			{
+				chunks.push((chunk[0], chunk[1], chunk[2..].to_vec()));
+			}
+		}
+		return false;
+	})?;
+
+	if chunks.is_empty()
+	{
+		return Err(Error::NoIccProfile);
+	}
+
+	// The chunks are not required to be stored in order
+	chunks.sort_by_key(|(sequence_number, _, _)| *sequence_number);
+
+	let chunk_count = chunks[0].1;
+	if chunks.len() != chunk_count as usize
+	{
+		return container_error!("ICC profile is incomplete!");
+	}
+
+	let mut icc_profile = Vec::new();
+	for (index, (sequence_number, number_of_chunks, data)) in chunks.into_iter().enumerate()
+	{
+		if number_of_chunks != chunk_count || sequence_number as usize != index + 1
+		{
+			return container_error!("Chunks of ICC profile don't fit together!");
+		}
+		icc_profile.extend(data);
+	}
+
+	return Ok(icc_profile);
+}
+
+/// Removes the APP2 segments holding the ICC profile
+pub(crate) fn
+clear_icc_profile
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return clear_segment_if(
+		file_buffer, 
+		0xe2, 
+		|payload| payload.starts_with(ICC_PROFILE_APP2_IDENTIFIER)
+	);
+}
+
+/// Replaces the ICC profile with the given one, which gets split into chunks
+/// that are stored in APP2 segments. These are placed after the JFIF and 
+/// EXIF/XMP segments at the start of the image
+pub(crate) fn
+write_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+	let chunk_count = icc_profile.chunks(MAX_ICC_PROFILE_CHUNK_LENGTH).count();
+	if chunk_count > u8::MAX as usize
+	{
+		return container_error!(format!(
+			"ICC profile too large for APP2 segments ({} bytes)!", 
+			icc_profile.len()
+		));
+	}
+
+	clear_icc_profile(file_buffer)?;
+
+	let mut app2_segments = Vec::new();
+	for (index, data) in icc_profile.chunks(MAX_ICC_PROFILE_CHUNK_LENGTH).enumerate()
+	{
+		let mut chunk = vec![index as u8 + 1, chunk_count as u8];
+		chunk.extend(data.iter());
+
+		app2_segments.extend(encode_segment(JPG_APP2_MARKER, ICC_PROFILE_APP2_IDENTIFIER, &chunk));
+	}
+
+	let insert_position = find_insert_position(
+		file_buffer, 
+		|marker, _| marker == 0xe0 || marker == 0xe1
+	)?;
+	insert_segments(file_buffer, insert_position, app2_segments)?;
+
+	return Ok(());
+}
+
+/// Inserts the given encoded segments at the given position, which has to be
+/// located before the image data
+fn
+insert_segments
+(
+	file_buffer:  &mut Vec<u8>,
+	position:     usize,
+	mut segments: Vec<u8>
+)
+-> Result<(), Error>
+{
+	return preserve_mpf_offsets(
+		file_buffer, 
+		|file_buffer| {
+			crate::util::insert_multiple_at(file_buffer, position, &mut segments);
+			return Ok(());
+		}
+	);
+}
+
+/// Determines the position right after the segments that directly follow the
+/// signature and are considered as leading segments by the given function,
+/// which gets the marker and the payload of a segment, e.g. to place the XMP
+/// segment after the APP0 and EXIF APP1 segments
+fn
+find_insert_position
+<F: Fn(u8, &[u8]) -> bool>
+(
+	file_buffer:        &[u8],
+	is_leading_segment: F
+)
+-> Result<usize, Error>
+{
+	let mut position = JPG_SIGNATURE.len();
+
+	while position + 4 <= file_buffer.len() && file_buffer[position] == JPG_MARKER_PREFIX
+	{
+		let marker = file_buffer[position + 1];
+		let length = from_u8_vec_macro!(u16, &file_buffer[position+2..position+4], &Endian::Big);
+		let payload_start = position + 4;
+		let payload_end   = payload_start + get_remaining_segment_length(length)?;
+
+		if payload_end > file_buffer.len()
+		{
+			return container_error!("JPG segment exceeds the file size!");
+		}
+
+		if !is_leading_segment(marker, &file_buffer[payload_start..payload_end])
+		{
+			break;
+		}
+
+		position = payload_end;
+	}
+
+	return Ok(position);
+}
+
+/// Skips the entropy-coded segment (ECS) that is followed by a start of scan
+/// segment (SOS) and positions the cursor at the start of the next segment,
+/// i.e. a 0xFF byte that is followed by a marker that is NOT 0xD0-0xD7 or 0x00.
+/// Assumes that the given cursor is positioned at the start of the ECS
+fn 
+skip_ecs
+<T: Seek + Read>
+(
+	cursor: &mut T
+)
+-> Result<(), Error>
+{
+
+	let mut byte_buffer = [0u8; 1];                                             // A buffer for reading in a byte of data from the file
+	let mut previous_byte_was_marker_prefix = false;                            // A boolean for remembering if the previous byte was a marker prefix (0xFF)
+
+	loop
+	{
+		// Read next byte into buffer
+		cursor.read_exact(&mut byte_buffer)?;
+
+		if previous_byte_was_marker_prefix
+		{
+			match byte_buffer[0]
+			{
+				0xd0 |
+				0xd1 |
+				0xd2 |
+				0xd3 |
+				0xd4 |
+				0xd5 |
+				0xd6 |
+				0xd7 |
+				0x00 => {
+					// Do nothing
+				},
+
+				_ => {
+					// Position back to where the 0xFF byte is located
+					cursor.seek(SeekFrom::Current(-2))?;
+					return Ok(()); 
+				},
+			}
+
+			previous_byte_was_marker_prefix = false;
+		}
+		else
+		{
+			previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+		}
+	}
+}
+
+
+/// Same as `skip_ecs`, but copies the entropy-coded segment to the output. 
+/// Afterwards, the input is positioned at the start of the next segment.
+fn
+copy_ecs
+<R: Read + Seek, W: Write>
+(
+	input:  &mut R,
+	output: &mut W
+)
+-> Result<(), Error>
+{
+	let mut byte_buffer = [0u8; 1];
+	let mut previous_byte_was_marker_prefix = false;
+
+	loop
+	{
+		input.read_exact(&mut byte_buffer)?;
+
+		if previous_byte_was_marker_prefix
+		{
+			match byte_buffer[0]
+			{
+				0xd0..=0xd7 |
+				0x00 => {
+					output.write_all(&[JPG_MARKER_PREFIX, byte_buffer[0]])?;
+				},
+
+				_ => {
+					// Position back to where the 0xFF byte is located, which
+					// has not been written yet
+					input.seek(SeekFrom::Current(-2))?;
+					return Ok(());
+				},
+			}
+
+			previous_byte_was_marker_prefix = false;
+		}
+		else
+		{
+			previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+			if !previous_byte_was_marker_prefix
+			{
+				output.write_all(&byte_buffer)?;
+			}
+		}
+	}
+}
+
+
+fn
+generic_read_metadata
+<T: Seek + Read>
+(
+	cursor: &mut T
+)
+-> Result<Vec<u8>, Error>
+{
+	let mut exif_data = None;
+
+	// Stop at the first APP1 segment with EXIF data and not e.g. an XMP packet
+	walk_app1_segments(cursor, |app1_buffer| {
+		if is_exif_app1_payload(&app1_buffer)
+		{
+			exif_data = Some(app1_buffer);
+			return true;
+		}
+		return false;
+	})?;
+
+	return match exif_data
+	{
+		Some(exif_data) => Ok(exif_data),
+		None            => Err(Error::NoExifData),
+	};
+}
+
+fn
+generic_read_app1_segments
+<T: Seek + Read>
+(
+	cursor: &mut T
+)
+-> Result<Vec<App1Segment>, Error>
+{
+	let mut app1_segments = Vec::new();
+
+	walk_app1_segments(cursor, |app1_buffer| {
+		app1_segments.push(App1Segment::from_payload(&app1_buffer));
+		return false;
+	})?;
+
+	return Ok(app1_segments);
+}
+
+/// Goes through the segments of the JPEG data and passes the payload of each
+/// APP1 segment to the given function, until that returns true or the end of
+/// the image is reached. Assumes that the cursor is positioned after the
+/// signature
+fn
+walk_app1_segments
+<T: Seek + Read, F: FnMut(Vec<u8>) -> bool>
+(
+	cursor: &mut T,
+	visit:  F
+)
+-> Result<(), Error>
+{
+	return walk_segments(cursor, 0xe1, visit);
+}
+
+/// Same as `walk_app1_segments`, but for the segments with the given marker
+fn
+walk_segments
+<T: Seek + Read, F: FnMut(Vec<u8>) -> bool>
+(
+	cursor:         &mut T,
+	segment_marker: u8,
+	mut visit:      F
+)
+-> Result<(), Error>
+{
+	// Setup of variables necessary for going through the data
+	let mut byte_buffer = [0u8; 1];                                             // A buffer for reading in a byte of data from the file
+	let mut previous_byte_was_marker_prefix = false;                            // A boolean for remembering if the previous byte was a marker prefix (0xFF)
+
+	loop
+	{
+		// Read next byte into buffer
+		cursor.read_exact(&mut byte_buffer)?;
+
+		if previous_byte_was_marker_prefix
+		{
+			// Check if this is the end of the file. In that case, the length
+			// data can't be read and we need to return prematurely. 
+			// This is why this case can't be included in the match afterwards.
+			if byte_buffer[0] == 0xd9                                           // EOI marker
+			{
+				// No more data to read in
+				return Ok(());
+			}
+
+			// Read in the length of the segment
+			// (which follows immediately after the marker)
+			let mut length_buffer = [0u8; 2];
+			cursor.read_exact(&mut length_buffer)?;
+
+			// Decode the length to determine how much more data there is
+			let length = from_u8_vec_macro!(u16, &length_buffer, &Endian::Big);
+			let remaining_length = get_remaining_segment_length(length)?;
+
+			match byte_buffer[0]
+			{
+				marker if marker == segment_marker => {                         // Given marker, e.g. for APP1
+					// Read in the remaining data and hand it over
+					let mut segment_buffer = Vec::new();
+					copy_n_bytes(cursor, &mut segment_buffer, remaining_length as u64)?;
+
+					if visit(segment_buffer)
+					{
+						return Ok(());
+					}
+				},
+
+				0xda => {                                                       // SOS marker
+					// The start of scan (SOS) segment is followed by a blob of
+					// image data, the entropy-coded segment (ECS), which has no
+					// information regarding its length (as it may easily be 
+					// bigger than the max segment length of 64kb)
+
+					// So, we have to scan byte-for-byte at this point until
+					// a marker prefix comes up that is NOT
+					// - followed by a restart marker (D0 - D7) or 
+					// - a data FF (followed by 00)
+
+					// So, start by skipping the SOS segment
+					cursor.seek(SeekFrom::Current(remaining_length as i64))?;
+
+					// And skip the ECS
+					skip_ecs(cursor)?;
+				}
+
+				_ => {                                                          // Every other marker
+					// Skip this segment
+					cursor.seek(SeekFrom::Current(remaining_length as i64))?;
+				},
+			}
+
+			previous_byte_was_marker_prefix = false;
+		}
+		else
+		{
+			previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+		}
+	}
+}
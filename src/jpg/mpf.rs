@@ -0,0 +1,474 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Support for the Multi-Picture Format (MPF), which phone cameras use for
+//! appending secondary images (e.g. previews, depth maps or gain maps) to the
+//! primary JPEG image. These images are indexed by the MP Index IFD stored in
+//! an APP2 segment of the primary image, which refers to them via offsets
+//! relative to the start of its TIFF header (the MP header).
+
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::error::container_error;
+use crate::filetype::FileExtension;
+use crate::ifd::ExifTagGroup;
+use crate::ifd::ImageFileDirectory;
+use crate::metadata::DecodeOptions;
+use crate::metadata::Metadata;
+use crate::metadata::decode_options::DecodeContext;
+use crate::u8conversion::*;
+
+use super::JPG_MARKER_PREFIX;
+use super::JPG_SIGNATURE;
+use super::check_signature;
+use super::get_remaining_segment_length;
+
+const MPF_APP2_IDENTIFIER: &[u8] = b"MPF\0";
+
+const NUMBER_OF_IMAGES_TAG: u16   = 0xb001;
+const MP_ENTRY_TAG:         u16   = 0xb002;
+const MP_ENTRY_LENGTH:      usize = 16;
+
+/// An image listed in the MP Index IFD of a JPEG file. The first entry is the
+/// primary image itself, the others are appended after its end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+MpfImage
+{
+	/// The individual image attribute, consisting of flags and the type code
+	pub attribute:        u32,
+
+	/// The size of the image in bytes
+	pub size:             u32,
+
+	/// The position of the image in the file. This is 0 for the primary image
+	pub position:         usize,
+
+	/// The entry numbers of the dependent images, or 0 if there are none
+	pub dependent_images: [u16; 2],
+}
+
+impl
+MpfImage
+{
+	/// Returns the type code of the image, e.g. 0x030000 for the primary
+	/// image, 0x010001 for a large thumbnail or 0x020002 for the image of a
+	/// disparity (stereo) pair
+	pub fn
+	get_type_code
+	(
+		&self
+	)
+	-> u32
+	{
+		return self.attribute & 0x00ffffff;
+	}
+
+	/// Returns the data of the image, which is a JPEG image of its own
+	pub fn
+	get_data<'a>
+	(
+		&self,
+		file_buffer: &'a [u8]
+	)
+	-> Result<&'a [u8], Error>
+	{
+		let end = self.position + self.size as usize;
+		if end > file_buffer.len()
+		{
+			return container_error!(format!(
+				"MPF image at position {} exceeds the file size!",
+				self.position
+			));
+		}
+
+		return Ok(&file_buffer[self.position..end]);
+	}
+
+	/// Decodes the EXIF data of the image
+	pub fn
+	get_metadata
+	(
+		&self,
+		file_buffer: &[u8]
+	)
+	-> Result<Metadata, Error>
+	{
+		return Metadata::new_from_vec(self.get_data(file_buffer)?, FileExtension::JPEG);
+	}
+}
+
+/// Reads the list of images from the MP Index IFD of a JPEG image that is
+/// stored in memory. Returns an empty list if the image has no MPF segment.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::jpeg::read_mpf_images;
+///
+/// let file_buffer = std::fs::read("image.jpg").unwrap();
+/// for image in read_mpf_images(&file_buffer).unwrap()
+/// {
+///     println!("0x{:06x} at {}", image.get_type_code(), image.position);
+///     let metadata = image.get_metadata(&file_buffer);
+/// }
+/// ```
+pub fn
+read_mpf_images
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<MpfImage>, Error>
+{
+	return Ok(MpfIndex::read(file_buffer)?
+		.map(|mpf_index| mpf_index.images)
+		.unwrap_or_default()
+	);
+}
+
+/// Same as `read_mpf_images`, but for the JPEG file at the given path
+pub fn
+file_read_mpf_images
+(
+	path: &Path
+)
+-> Result<Vec<MpfImage>, Error>
+{
+	return read_mpf_images(&std::fs::read(path)?);
+}
+
+/// Performs the given modification of the segments of the primary image and
+/// afterwards updates the offsets stored in the MP Index IFD, as the appended
+/// images get moved if any segment between them and the MP header changes
+/// its size. An MPF segment that can't be decoded is left as it is.
+pub(super) fn
+preserve_mpf_offsets
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	file_buffer: &mut Vec<u8>,
+	modify:      F
+)
+-> Result<(), Error>
+{
+	return generic_preserve_mpf_offsets(file_buffer, false, modify);
+}
+
+/// Same as `preserve_mpf_offsets`, but for the segments of the primary image
+/// up to the start of its image data. The image data and the appended images
+/// follow after the end of the given buffer and get moved by the same amount
+/// as its length changes.
+pub(super) fn
+preserve_mpf_offsets_before_image_data
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	header: &mut Vec<u8>,
+	modify: F
+)
+-> Result<(), Error>
+{
+	return generic_preserve_mpf_offsets(header, true, modify);
+}
+
+/// Determines whether the given JPEG data has an MPF segment
+pub(super) fn
+has_mpf_segment
+(
+	file_buffer: &[u8]
+)
+-> Result<bool, Error>
+{
+	return Ok(find_mpf_header(file_buffer)?.is_some());
+}
+
+fn
+generic_preserve_mpf_offsets
+<F: FnOnce(&mut Vec<u8>) -> Result<(), Error>>
+(
+	file_buffer:   &mut Vec<u8>,
+	images_follow: bool,
+	modify:        F
+)
+-> Result<(), Error>
+{
+	let mpf_index       = MpfIndex::read(file_buffer).unwrap_or(None);
+	let previous_length = file_buffer.len();
+
+	modify(file_buffer)?;
+
+	if let Some(mpf_index) = mpf_index
+	{
+		return mpf_index.relocate_images(file_buffer, previous_length, images_follow);
+	}
+	return Ok(());
+}
+
+/// Updates the MP entries after the appended image at the given position got
+/// replaced by one of a different size, which also moves the images after it
+pub(super) fn
+resize_mpf_image
+(
+	file_buffer: &mut [u8],
+	position:    usize,
+	old_size:    usize,
+	new_size:    usize
+)
+-> Result<(), Error>
+{
+	// The offsets stored in the file still refer to the old positions
+	let mpf_index = match MpfIndex::read(file_buffer)?
+	{
+		Some(mpf_index) => mpf_index,
+		None            => return Ok(()),
+	};
+
+	for (index, image) in mpf_index.images.iter().enumerate()
+	{
+		let entry_position = mpf_index.entries_position + index * MP_ENTRY_LENGTH;
+
+		let (field_position, value) = if image.position == position
+		{
+			(entry_position + 4, new_size)
+		}
+		else if image.position > position
+		{
+			(entry_position + 8, image.position + new_size - old_size - mpf_index.header_position)
+		}
+		else
+		{
+			continue;
+		};
+
+		if value > u32::MAX as usize
+		{
+			return container_error!("MPF size or offset exceeds 4 bytes!");
+		}
+
+		file_buffer[field_position..field_position+4].copy_from_slice(
+			&to_u8_vec_macro!(u32, &(value as u32), &mpf_index.endian)
+		);
+	}
+
+	return Ok(());
+}
+
+/// The decoded MP Index IFD together with the positions in the file that are
+/// needed for updating the offsets
+struct
+MpfIndex
+{
+	header_position:  usize,
+	entries_position: usize,
+	endian:           Endian,
+	images:           Vec<MpfImage>,
+}
+
+impl
+MpfIndex
+{
+	fn
+	read
+	(
+		file_buffer: &[u8]
+	)
+	-> Result<Option<Self>, Error>
+	{
+		check_signature(file_buffer)?;
+
+		let (header_position, end) = match find_mpf_header(file_buffer)?
+		{
+			Some(range) => range,
+			None        => return Ok(None),
+		};
+		let mp_header = &file_buffer[header_position..end];
+
+		if mp_header.len() < 8
+		{
+			return container_error!("MPF segment is too short!");
+		}
+
+		let endian = match mp_header[0..2]
+		{
+			[0x49, 0x49] => Endian::Little,
+			[0x4d, 0x4d] => Endian::Big,
+			_            => return container_error!("Illegal endian information in MPF segment!"),
+		};
+		let ifd_offset = from_u8_vec_macro!(u32, &mp_header[4..8], &endian) as usize;
+
+		// The MP Index IFD is structured like any other IFD, with the MP
+		// header taking the role of the TIFF header
+		let mut ifds        = Vec::new();
+		let mut data_cursor = Cursor::new(mp_header);
+		data_cursor.set_position(ifd_offset as u64);
+		ImageFileDirectory::decode_ifd(
+			&mut data_cursor,
+			0,
+			&endian,
+			&ExifTagGroup::GENERIC,
+			0,
+			&mut ifds,
+			&mut DecodeContext::new(&DecodeOptions::default())
+		)?;
+
+		let get_value = |hex_tag: u16| ifds.first()
+			.and_then(|ifd| ifd.get_tags().iter().find(|tag| tag.as_u16() == hex_tag))
+			.map(|tag| tag.value_as_u8_vec(&endian));
+
+		let (number_of_images, mp_entries) = match (get_value(NUMBER_OF_IMAGES_TAG), get_value(MP_ENTRY_TAG))
+		{
+			(Some(number_of_images), Some(mp_entries)) if number_of_images.len() == 4
+				=> (from_u8_vec_macro!(u32, &number_of_images, &endian) as usize, mp_entries),
+			_
+				=> return container_error!("MP Index IFD lacks the list of images!"),
+		};
+
+		if mp_entries.len() != number_of_images * MP_ENTRY_LENGTH
+		{
+			return container_error!("Number of MP entries does not match the number of images!");
+		}
+
+		let mut images = Vec::new();
+		for entry in mp_entries.chunks_exact(MP_ENTRY_LENGTH)
+		{
+			// The offset of the primary image is 0 as it starts with the file
+			let offset = from_u8_vec_macro!(u32, &entry[8..12], &endian) as usize;
+			images.push(MpfImage {
+				attribute:        from_u8_vec_macro!(u32, &entry[0..4],   &endian),
+				size:             from_u8_vec_macro!(u32, &entry[4..8],   &endian),
+				position:         if offset == 0 { 0 } else { header_position + offset },
+				dependent_images: [
+					from_u8_vec_macro!(u16, &entry[12..14], &endian),
+					from_u8_vec_macro!(u16, &entry[14..16], &endian),
+				],
+			});
+		}
+
+		let entries_position = header_position + find_mp_entries_offset(mp_header, ifd_offset, &endian)?;
+
+		return Ok(Some(MpfIndex { header_position, entries_position, endian, images }));
+	}
+
+	/// Updates the offsets in the MPF segment of the modified data. Only the
+	/// segments of the primary image get modified, so the appended images
+	/// move by the change in length of the data. If the data only consists
+	/// of the segments before the image data, these images follow after it.
+	fn
+	relocate_images
+	(
+		&self,
+		file_buffer:     &mut [u8],
+		previous_length: usize,
+		images_follow:   bool
+	)
+	-> Result<(), Error>
+	{
+		// The modification may also have removed the MPF segment
+		let new_index = match MpfIndex::read(file_buffer)?
+		{
+			Some(new_index) => new_index,
+			None            => return Ok(()),
+		};
+
+		if new_index.images.len() != self.images.len()
+		{
+			return container_error!("MPF segment changed while modifying the image!");
+		}
+
+		for (index, image) in self.images.iter().enumerate()
+		{
+			// Skip the primary image as well as offsets that were invalid
+			let is_valid = match images_follow
+			{
+				true  => image.position >= previous_length,
+				false => image.position <= previous_length,
+			};
+			if image.position <= self.header_position || !is_valid
+			{
+				continue;
+			}
+
+			let new_position = image.position + file_buffer.len() - previous_length;
+			let new_offset   = new_position - new_index.header_position;
+			if new_offset > u32::MAX as usize
+			{
+				return container_error!("MPF offset exceeds 4 bytes!");
+			}
+
+			let field_position = new_index.entries_position + index * MP_ENTRY_LENGTH + 8;
+			file_buffer[field_position..field_position+4].copy_from_slice(
+				&to_u8_vec_macro!(u32, &(new_offset as u32), &new_index.endian)
+			);
+		}
+
+		return Ok(());
+	}
+}
+
+/// Determines the start of the MP header in the first MPF APP2 segment and
+/// the end of that segment. Only the segments before the image data are
+/// checked, as the MPF segment has to be placed among these.
+fn
+find_mpf_header
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<(usize, usize)>, Error>
+{
+	let mut position = JPG_SIGNATURE.len();
+
+	while position + 4 <= file_buffer.len() && file_buffer[position] == JPG_MARKER_PREFIX
+	{
+		let marker = file_buffer[position + 1];
+		if marker == 0xda || marker == 0xd9                                     // SOS or EOI marker
+		{
+			break;
+		}
+
+		let length = from_u8_vec_macro!(u16, &file_buffer[position+2..position+4], &Endian::Big);
+		let payload_start = position + 4;
+		let payload_end   = payload_start + get_remaining_segment_length(length)?;
+
+		if payload_end > file_buffer.len()
+		{
+			return container_error!("JPG segment exceeds the file size!");
+		}
+
+		if marker == 0xe2 && file_buffer[payload_start..payload_end].starts_with(MPF_APP2_IDENTIFIER)
+		{
+			return Ok(Some((payload_start + MPF_APP2_IDENTIFIER.len(), payload_end)));
+		}
+
+		position = payload_end;
+	}
+
+	return Ok(None);
+}
+
+/// Determines where the MP entries are stored, relative to the MP header.
+/// As the entries of all images take up more than 4 bytes, the IFD entry of
+/// the MPEntry tag holds an offset to them
+fn
+find_mp_entries_offset
+(
+	mp_header:  &[u8],
+	ifd_offset: usize,
+	endian:     &Endian
+)
+-> Result<usize, Error>
+{
+	let number_of_entries = from_u8_vec_macro!(u16, &mp_header[ifd_offset..ifd_offset+2], endian) as usize;
+
+	for index in 0..number_of_entries
+	{
+		let entry_start = ifd_offset + 2 + index * 12;
+		let entry       = &mp_header[entry_start..entry_start+12];
+
+		if from_u8_vec_macro!(u16, &entry[0..2], endian) == MP_ENTRY_TAG
+		{
+			return Ok(from_u8_vec_macro!(u32, &entry[8..12], endian) as usize);
+		}
+	}
+
+	return container_error!("MP Index IFD lacks the list of images!");
+}
@@ -0,0 +1,313 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Data stored after the EOI marker of a JPEG image, e.g. the video of a
+//! Motion Photo, the SEFT trailer of Samsung images or the secondary images
+//! of the Multi-Picture Format. Editing the metadata only affects the
+//! segments in front of the image data, so this data is kept as it is.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::filetype::FileExtension;
+use crate::xmp::namespace;
+use crate::xmp::Xmp;
+use crate::xmp::XmpProperty;
+use crate::xmp::XmpValue;
+
+use super::check_signature;
+use super::walk_segments;
+
+/// A media item listed in the `Container:Directory` of the XMP data of a
+/// Motion Photo. The first item is the primary image itself, the others are
+/// appended after its end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+ContainerItem
+{
+	/// The MIME type of the item, e.g. "video/mp4"
+	pub mime:     String,
+
+	/// The meaning of the item, e.g. "Primary", "MotionPhoto" or "GainMap"
+	pub semantic: String,
+
+	/// The position of the item in the file
+	pub position: usize,
+
+	/// The length of the item in bytes
+	pub length:   usize,
+}
+
+impl
+ContainerItem
+{
+	/// Returns the data of the item
+	pub fn
+	get_data<'a>
+	(
+		&self,
+		file_buffer: &'a [u8]
+	)
+	-> Result<&'a [u8], Error>
+	{
+		let end = match self.position.checked_add(self.length)
+		{
+			Some(end) if end <= file_buffer.len() => end,
+			_ => return container_error!(format!(
+				"Container item at position {} exceeds the file size!",
+				self.position
+			)),
+		};
+
+		return Ok(&file_buffer[self.position..end]);
+	}
+}
+
+/// Returns the data stored after the EOI marker of the (primary) image,
+/// which is empty if there is no such data
+pub fn
+read_trailer
+(
+	file_buffer: &[u8]
+)
+-> Result<&[u8], Error>
+{
+	return Ok(&file_buffer[find_end_of_image(file_buffer)?..]);
+}
+
+/// Same as `read_trailer`, but for the JPEG file at the given path
+pub fn
+file_read_trailer
+(
+	path: &Path
+)
+-> Result<Vec<u8>, Error>
+{
+	return Ok(read_trailer(&std::fs::read(path)?)?.to_vec());
+}
+
+/// Reads the media items listed in the `Container:Directory` of the XMP
+/// data, which is used by Motion Photos to locate the embedded video.
+/// Returns an empty list if the image has no such directory.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::jpeg::read_container_directory;
+///
+/// let file_buffer = std::fs::read("motion_photo.jpg").unwrap();
+/// for item in read_container_directory(&file_buffer).unwrap()
+/// {
+///     println!("{} ({}): {} bytes", item.semantic, item.mime, item.length);
+/// }
+/// ```
+pub fn
+read_container_directory
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<ContainerItem>, Error>
+{
+	return match read_xmp(file_buffer)?
+	{
+		Some(xmp) => get_container_items(file_buffer, &xmp),
+		None      => Ok(Vec::new()),
+	};
+}
+
+/// Returns the video embedded in a Motion Photo, which is located either via
+/// the `Container:Directory` or, for older files, via the offset from the
+/// end of the file given by `GCamera:MicroVideoOffset`
+pub fn
+read_motion_photo_video
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<&[u8]>, Error>
+{
+	let xmp = match read_xmp(file_buffer)?
+	{
+		Some(xmp) => xmp,
+		None      => return Ok(None),
+	};
+
+	if let Some(video) = get_container_items(file_buffer, &xmp)?
+		.into_iter()
+		.find(|item| item.semantic == "MotionPhoto")
+	{
+		return Ok(Some(video.get_data(file_buffer)?));
+	}
+
+	if let Some(offset) = xmp.get_text(namespace::GCAMERA, "MicroVideoOffset")
+	{
+		let offset = parse_number(offset)?;
+		if offset > read_trailer(file_buffer)?.len()
+		{
+			return container_error!("MicroVideoOffset exceeds the trailer data!");
+		}
+		return Ok(Some(&file_buffer[file_buffer.len()-offset..]));
+	}
+
+	return Ok(None);
+}
+
+/// Determines the position right after the EOI marker of the primary image
+pub(super) fn
+find_end_of_image
+(
+	file_buffer: &[u8]
+)
+-> Result<usize, Error>
+{
+	check_signature(file_buffer)?;
+
+	let mut cursor = Cursor::new(file_buffer);
+
+	// Skip signature
+	cursor.set_position(2);
+
+	// As the walk stops at the EOI marker, no segment gets visited
+	if let Err(error) = walk_segments(&mut cursor, 0xd9, |_| false)
+	{
+		if error.is_unexpected_eof()
+		{
+			return container_error!("Could not find the EOI marker of the JPG image!");
+		}
+		return Err(error);
+	}
+
+	return Ok(cursor.position() as usize);
+}
+
+/// Reads the XMP data of the JPEG image, if there is any
+pub(super) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<Xmp>, Error>
+{
+	return match Xmp::new_from_vec(file_buffer, FileExtension::JPEG)
+	{
+		Ok(xmp)               => Ok(Some(xmp)),
+		Err(Error::NoXmpData) => Ok(None),
+		Err(error)            => Err(error),
+	};
+}
+
+/// Locates the items of the `Container:Directory`. The secondary items are
+/// stored back to back at the end of the file, each one possibly followed by
+/// the padding given by the previous item, so their positions are determined
+/// starting from the end of the file.
+fn
+get_container_items
+(
+	file_buffer: &[u8],
+	xmp:         &Xmp
+)
+-> Result<Vec<ContainerItem>, Error>
+{
+	let entries = match xmp.get_property(namespace::CONTAINER, "Directory")
+	{
+		Some(XmpValue::Array(_, entries)) => entries,
+		_                                 => return Ok(Vec::new()),
+	};
+
+	// Each entry is a struct holding a `Container:Item` struct
+	let mut items = Vec::new();
+	for entry in entries
+	{
+		let item_value = match entry
+		{
+			XmpValue::Struct(fields) => fields.iter()
+				.find(|field| field.namespace == namespace::CONTAINER && field.name == "Item")
+				.map(|field| &field.value),
+			_ => None,
+		};
+
+		let item_fields = match item_value
+		{
+			Some(XmpValue::Struct(item_fields)) => item_fields,
+			_ => return Err(Error::MalformedXmp("Invalid entry in Container:Directory!".to_string())),
+		};
+
+		items.push((
+			get_item_field(item_fields, "Mime").unwrap_or_default().to_string(),
+			get_item_field(item_fields, "Semantic").unwrap_or_default().to_string(),
+			get_item_field(item_fields, "Length").map(parse_number).transpose()?.unwrap_or(0),
+			get_item_field(item_fields, "Padding").map(parse_number).transpose()?.unwrap_or(0),
+		));
+	}
+
+	if items.is_empty()
+	{
+		return Ok(Vec::new());
+	}
+
+	let end_of_image  = find_end_of_image(file_buffer)?;
+	let mut end       = file_buffer.len();
+	let mut positions = vec![0; items.len()];
+
+	for index in (1..items.len()).rev()
+	{
+		let (_, _, length, _) = &items[index];
+		let (_, _, _, padding) = &items[index - 1];
+
+		// The values are taken from the XMP data as they are, so they might
+		// be large enough to overflow
+		let required = end_of_image
+			.checked_add(*length)
+			.and_then(|required| required.checked_add(*padding));
+
+		if required.map_or(true, |required| end < required)
+		{
+			return container_error!("Items of Container:Directory exceed the trailer data!");
+		}
+
+		positions[index] = end - length;
+		end              = positions[index] - padding;
+	}
+
+	return Ok(items.into_iter()
+		.zip(positions)
+		.enumerate()
+		.map(|(index, ((mime, semantic, length, _), position))| ContainerItem {
+			mime,
+			semantic,
+			position,
+			// The primary image is the JPEG image itself
+			length: if index == 0 { end_of_image } else { length },
+		})
+		.collect()
+	);
+}
+
+fn
+get_item_field<'a>
+(
+	item_fields: &'a [XmpProperty],
+	name:        &str
+)
+-> Option<&'a str>
+{
+	return item_fields.iter()
+		.find(|field| field.namespace == namespace::CONTAINER_ITEM && field.name == name)
+		.and_then(|field| match &field.value
+		{
+			XmpValue::Simple(value) => Some(value.as_str()),
+			_                       => None,
+		});
+}
+
+fn
+parse_number
+(
+	value: &str
+)
+-> Result<usize, Error>
+{
+	return value.trim().parse::<usize>().map_err(|_|
+		Error::MalformedXmp(format!("Invalid length or offset in Motion Photo XMP data: {}", value))
+	);
+}
@@ -0,0 +1,405 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Support for Ultra HDR images, which consist of a regular (SDR) JPEG image
+//! and an appended gain map image that allows to reconstruct an HDR version.
+//! The gain map is listed both in the `Container:Directory` of the XMP data
+//! and the MPF segment of the primary image, while its parameters are stored
+//! in the `hdrgm` namespace of the XMP data of the gain map image itself.
+
+use std::path::Path;
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file_atomically;
+use crate::xmp::namespace;
+use crate::xmp::Xmp;
+use crate::xmp::XmpArrayType;
+use crate::xmp::XmpValue;
+
+use super::mpf::read_mpf_images;
+use super::mpf::resize_mpf_image;
+use super::trailer::read_container_directory;
+use super::trailer::read_xmp;
+
+const GAIN_MAP_SEMANTIC: &str = "GainMap";
+
+/// The parameters of a gain map as defined by the `hdrgm` XMP namespace.
+/// Parameters that may be given per color channel are stored as arrays with
+/// the values for red, green and blue, which are identical if the file only
+/// provides a single value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct
+GainMapMetadata
+{
+	/// The version of the gain map format, e.g. "1.0"
+	pub version:               String,
+
+	/// Whether the primary image is the HDR rendition
+	pub base_rendition_is_hdr: bool,
+
+	/// The minimum of the log2 encoded gain (default: 0)
+	pub gain_map_min:          [f64; 3],
+
+	/// The maximum of the log2 encoded gain
+	pub gain_map_max:          [f64; 3],
+
+	/// The gamma applied to the gain map values (default: 1)
+	pub gamma:                 [f64; 3],
+
+	/// The offset added to the SDR rendition (default: 1/64)
+	pub offset_sdr:            [f64; 3],
+
+	/// The offset added to the HDR rendition (default: 1/64)
+	pub offset_hdr:            [f64; 3],
+
+	/// The log2 encoded HDR capacity below which the gain map is not
+	/// applied at all (default: 0)
+	pub hdr_capacity_min:      f64,
+
+	/// The log2 encoded HDR capacity at which the gain map is fully applied
+	pub hdr_capacity_max:      f64,
+}
+
+impl
+GainMapMetadata
+{
+	/// Decodes the parameters from the given XMP data. Returns `None` if the
+	/// data has no `hdrgm:Version` property
+	fn
+	from_xmp
+	(
+		xmp: &Xmp
+	)
+	-> Result<Option<Self>, Error>
+	{
+		let version = match xmp.get_text(namespace::HDRGM, "Version")
+		{
+			Some(version) => version.to_string(),
+			None          => return Ok(None),
+		};
+
+		let base_rendition_is_hdr = match xmp.get_text(namespace::HDRGM, "BaseRenditionIsHDR")
+		{
+			Some(value) => value.eq_ignore_ascii_case("true"),
+			None        => false,
+		};
+
+		let required = |name: &str| get_channel_values(xmp, name)?
+			.ok_or_else(|| Error::MalformedXmp(format!("Gain map lacks hdrgm:{}!", name)));
+		let optional = |name: &str, default: f64| get_channel_values(xmp, name)
+			.map(|values| values.unwrap_or([default; 3]));
+
+		return Ok(Some(GainMapMetadata {
+			version,
+			base_rendition_is_hdr,
+			gain_map_min:     optional("GainMapMin", 0.0)?,
+			gain_map_max:     required("GainMapMax")?,
+			gamma:            optional("Gamma", 1.0)?,
+			offset_sdr:       optional("OffsetSDR", 1.0 / 64.0)?,
+			offset_hdr:       optional("OffsetHDR", 1.0 / 64.0)?,
+			hdr_capacity_min: optional("HDRCapacityMin", 0.0)?[0],
+			hdr_capacity_max: required("HDRCapacityMax")?[0],
+		}));
+	}
+
+	fn
+	set_in_xmp
+	(
+		&self,
+		xmp: &mut Xmp
+	)
+	{
+		let simple = |text: String| XmpValue::Simple(text);
+
+		xmp.set_property(namespace::HDRGM, "Version", simple(self.version.clone()));
+		xmp.set_property(
+			namespace::HDRGM,
+			"BaseRenditionIsHDR",
+			simple(if self.base_rendition_is_hdr { "True" } else { "False" }.to_string())
+		);
+
+		for (name, values) in [
+			("GainMapMin", &self.gain_map_min),
+			("GainMapMax", &self.gain_map_max),
+			("Gamma",      &self.gamma),
+			("OffsetSDR",  &self.offset_sdr),
+			("OffsetHDR",  &self.offset_hdr),
+		]
+		{
+			// A single value is used if it applies to all channels
+			let value = if values.iter().all(|value| *value == values[0])
+			{
+				simple(values[0].to_string())
+			}
+			else
+			{
+				XmpValue::Array(
+					XmpArrayType::Seq,
+					values.iter().map(|value| simple(value.to_string())).collect()
+				)
+			};
+			xmp.set_property(namespace::HDRGM, name, value);
+		}
+
+		xmp.set_property(namespace::HDRGM, "HDRCapacityMin", simple(self.hdr_capacity_min.to_string()));
+		xmp.set_property(namespace::HDRGM, "HDRCapacityMax", simple(self.hdr_capacity_max.to_string()));
+	}
+}
+
+/// Checks if the JPEG image stored in memory is an Ultra HDR image, i.e. has
+/// the `hdrgm:Version` property in its XMP data and an appended gain map
+pub fn
+is_ultra_hdr
+(
+	file_buffer: &[u8]
+)
+-> Result<bool, Error>
+{
+	let has_version = read_xmp(file_buffer)?
+		.map(|xmp| xmp.get_text(namespace::HDRGM, "Version").is_some())
+		.unwrap_or(false);
+
+	return Ok(has_version && locate_gain_map(file_buffer)?.is_some());
+}
+
+/// Returns the gain map image, which is a JPEG image of its own
+pub fn
+read_gain_map
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<&[u8]>, Error>
+{
+	return Ok(locate_gain_map(file_buffer)?.map(|(start, end)| &file_buffer[start..end]));
+}
+
+/// Reads the parameters of the gain map, which are stored in the XMP data of
+/// the gain map image. Returns `None` if the image has no gain map.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::jpeg::read_gain_map_metadata;
+///
+/// let file_buffer = std::fs::read("ultra_hdr.jpg").unwrap();
+/// if let Some(gain_map_metadata) = read_gain_map_metadata(&file_buffer).unwrap()
+/// {
+///     println!("{}", gain_map_metadata.hdr_capacity_max);
+/// }
+/// ```
+pub fn
+read_gain_map_metadata
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<GainMapMetadata>, Error>
+{
+	return match read_gain_map(file_buffer)?
+	{
+		Some(gain_map) => match read_xmp(gain_map)?
+		{
+			Some(xmp) => GainMapMetadata::from_xmp(&xmp),
+			None      => Ok(None),
+		},
+		None => Ok(None),
+	};
+}
+
+/// Same as `read_gain_map_metadata`, but for the JPEG file at the given path
+pub fn
+file_read_gain_map_metadata
+(
+	path: &Path
+)
+-> Result<Option<GainMapMetadata>, Error>
+{
+	return read_gain_map_metadata(&std::fs::read(path)?);
+}
+
+/// Writes the parameters to the XMP data of the gain map image. As this
+/// changes the size of the gain map, its entries in the MPF segment and the
+/// `Container:Directory` of the primary image get updated as well.
+pub fn
+write_gain_map_metadata
+(
+	file_buffer:       &mut Vec<u8>,
+	gain_map_metadata: &GainMapMetadata
+)
+-> Result<(), Error>
+{
+	let (start, end) = match locate_gain_map(file_buffer)?
+	{
+		Some(range) => range,
+		None        => return container_error!("Image has no gain map!"),
+	};
+
+	let mut gain_map = file_buffer[start..end].to_vec();
+	let mut xmp      = read_xmp(&gain_map)?.unwrap_or_default();
+	gain_map_metadata.set_in_xmp(&mut xmp);
+	xmp.write_to_vec(&mut gain_map, FileExtension::JPEG)?;
+
+	let new_size = gain_map.len();
+	file_buffer.splice(start..end, gain_map);
+	resize_mpf_image(file_buffer, start, end - start, new_size)?;
+
+	return update_container_length(file_buffer, new_size);
+}
+
+/// Same as `write_gain_map_metadata`, but for the JPEG file at the given
+/// path, which gets replaced atomically
+pub fn
+file_write_gain_map_metadata
+(
+	path:              &Path,
+	gain_map_metadata: &GainMapMetadata
+)
+-> Result<(), Error>
+{
+	return modify_file_atomically(
+		path,
+		|file_buffer| write_gain_map_metadata(file_buffer, gain_map_metadata)
+	);
+}
+
+/// Determines the start and end of the gain map image, which is primarily
+/// given by the `Container:Directory`. Otherwise, the MPF segment is checked
+/// for an image with gain map parameters
+fn
+locate_gain_map
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<(usize, usize)>, Error>
+{
+	if let Some(item) = read_container_directory(file_buffer)?
+		.into_iter()
+		.find(|item| item.semantic == GAIN_MAP_SEMANTIC)
+	{
+		item.get_data(file_buffer)?;
+		return Ok(Some((item.position, item.position + item.length)));
+	}
+
+	for image in read_mpf_images(file_buffer)?.into_iter().skip(1)
+	{
+		let has_version = read_xmp(image.get_data(file_buffer)?)?
+			.map(|xmp| xmp.get_text(namespace::HDRGM, "Version").is_some())
+			.unwrap_or(false);
+
+		if has_version
+		{
+			return Ok(Some((image.position, image.position + image.size as usize)));
+		}
+	}
+
+	return Ok(None);
+}
+
+/// Sets the length of the gain map item in the `Container:Directory` of the
+/// primary image, if there is such an item
+fn
+update_container_length
+(
+	file_buffer: &mut Vec<u8>,
+	length:      usize
+)
+-> Result<(), Error>
+{
+	let mut xmp = match read_xmp(file_buffer)?
+	{
+		Some(xmp) => xmp,
+		None      => return Ok(()),
+	};
+
+	let mut directory = match xmp.get_property(namespace::CONTAINER, "Directory")
+	{
+		Some(directory) => directory.clone(),
+		None            => return Ok(()),
+	};
+
+	let mut found = false;
+	if let XmpValue::Array(_, entries) = &mut directory
+	{
+		for entry in entries
+		{
+			let entry_fields = match entry
+			{
+				XmpValue::Struct(entry_fields) => entry_fields,
+				_                              => continue,
+			};
+
+			for item in entry_fields.iter_mut()
+				.filter(|field| field.namespace == namespace::CONTAINER && field.name == "Item")
+			{
+				let item_fields = match &mut item.value
+				{
+					XmpValue::Struct(item_fields) => item_fields,
+					_                             => continue,
+				};
+
+				let is_gain_map = item_fields.iter().any(|field|
+					field.namespace == namespace::CONTAINER_ITEM
+					&& field.name   == "Semantic"
+					&& field.value  == XmpValue::Simple(GAIN_MAP_SEMANTIC.to_string())
+				);
+
+				if let Some(length_field) = item_fields.iter_mut()
+					.find(|field| is_gain_map && field.namespace == namespace::CONTAINER_ITEM && field.name == "Length")
+				{
+					length_field.value = XmpValue::Simple(length.to_string());
+					found = true;
+				}
+			}
+		}
+	}
+
+	if !found
+	{
+		return Ok(());
+	}
+
+	xmp.set_property(namespace::CONTAINER, "Directory", directory);
+	return xmp.write_to_vec(file_buffer, FileExtension::JPEG);
+}
+
+/// Gets the value of a parameter that is either given as a single value or
+/// as a sequence with one value per color channel
+fn
+get_channel_values
+(
+	xmp:  &Xmp,
+	name: &str
+)
+-> Result<Option<[f64; 3]>, Error>
+{
+	let texts = match xmp.get_property(namespace::HDRGM, name)
+	{
+		Some(XmpValue::Simple(text))    => vec![text.as_str()],
+		Some(XmpValue::Array(_, items)) => items.iter()
+			.filter_map(|item| match item
+			{
+				XmpValue::Simple(text) => Some(text.as_str()),
+				_                      => None,
+			})
+			.collect(),
+		Some(_)                         => Vec::new(),
+		None                            => return Ok(None),
+	};
+
+	let mut values = Vec::new();
+	for text in texts
+	{
+		match text.trim().parse::<f64>()
+		{
+			Ok(value) => values.push(value),
+			Err(_)    => return Err(Error::MalformedXmp(format!("Invalid value for hdrgm:{}: {}", name, text))),
+		}
+	}
+
+	return match values.len()
+	{
+		1 => Ok(Some([values[0]; 3])),
+		3 => Ok(Some([values[0], values[1], values[2]])),
+		_ => Err(Error::MalformedXmp(format!("Invalid number of values for hdrgm:{}", name))),
+	};
+}
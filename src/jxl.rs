@@ -9,10 +9,14 @@ use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
 
+use crate::c2pa::is_manifest_store_contents;
+use crate::error::Error;
+use crate::error::container_error;
 use crate::endian::Endian;
 use crate::metadata::Metadata;
 use crate::u8conversion::*;
 use crate::general_file_io::*;
+use crate::util::copy_n_bytes;
 use crate::util::insert_multiple_at;
 use crate::util::range_remove;
 
@@ -40,9 +44,11 @@ struct IsoBmffBoxType;
 
 impl IsoBmffBoxType {
     pub const EXIF: [u8; 4] = [0x45, 0x78, 0x69, 0x66]; // "Exif"
+	pub const XML:  [u8; 4] = [0x78, 0x6d, 0x6c, 0x20]; // "xml "
 	pub const FTYP: [u8; 4] = [0x66, 0x74, 0x79, 0x70]; // "ftyp"
 	pub const JXL:  [u8; 4] = [0x4a, 0x58, 0x4c, 0x20]; // "JXL "
 	pub const JXLC: [u8; 4] = [0x6a, 0x78, 0x6c, 0x63]; // "jxlc"
+	pub const JUMB: [u8; 4] = [0x6a, 0x75, 0x6d, 0x62]; // "jumb"
 }
 
 /// Checks if the given file buffer vector starts with the necessary bytes that
@@ -53,13 +59,18 @@ impl IsoBmffBoxType {
 /// - n-8 bytes of data
 /// These 12 bytes are for checking the first box that is the same for all such
 /// stored JXL images
-fn
+pub(crate) fn
 starts_with_iso_bmff_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
 -> bool
 {
+	if file_buffer.len() < ISO_BMFF_JXL_SIGNATURE.len()
+	{
+		return false;
+	}
+
 	return file_buffer[0..12].iter()
 		.zip(ISO_BMFF_JXL_SIGNATURE.iter())
 		.filter(|&(read, constant)| read == constant)
@@ -73,13 +84,18 @@ starts_with_iso_bmff_signature
 /// include EXIF metadata. 
 /// If this function returns true, the image needs to be converted first before
 /// it is able to hold any metadata
-fn
+pub(crate) fn
 starts_with_jxl_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
 -> bool
 {
+	if file_buffer.len() < JXL_SIGNATURE.len()
+	{
+		return false;
+	}
+
 	return file_buffer[0..2].iter()
 		.zip(JXL_SIGNATURE.iter())
 		.filter(|&(read, constant)| read == constant)
@@ -89,18 +105,19 @@ starts_with_jxl_signature
 fn
 check_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	if starts_with_jxl_signature(file_buffer)
 	{
-		return io_error!(Other, "Simple JXL codestream file - No metadata!");
+		// Simple JXL codestream file that can't contain any metadata
+		return Err(Error::NoExifData);
 	}
 
 	if !starts_with_iso_bmff_signature(file_buffer)
 	{
-		return io_error!(Other, "This isn't ISO BMFF JXL data!");
+		return container_error!("This isn't ISO BMFF JXL data!");
 	}
 
 	return Ok(());
@@ -111,14 +128,13 @@ file_check_signature
 (
 	path: &Path
 )
--> Result<File, std::io::Error>
+-> Result<File, Error>
 {
 	let mut file = open_write_file(path)?;
 
 	let mut signature_buffer = [0u8; 12];
-	let bytes_read = file.read(&mut signature_buffer)?;
-	assert_eq!(bytes_read, 12);
-	check_signature(&signature_buffer.to_vec())?;
+	file.read_exact(&mut signature_buffer)?;
+	check_signature(&signature_buffer)?;
 
 	return Ok(file);
 }
@@ -130,10 +146,27 @@ clear_metadata
 (
 	file_buffer: &mut Vec<u8>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	check_signature(file_buffer)?;
 
+	return clear_boxes_if(
+		file_buffer, 
+		|type_buffer, box_data| box_contains_exif(&mut Cursor::new(box_data), type_buffer)
+	);
+}
+
+/// Removes all boxes for which the predicate returns true when given the box
+/// type and data
+fn
+clear_boxes_if
+<F: Fn([u8; 4], &[u8]) -> Result<bool, Error>>
+(
+	file_buffer: &mut Vec<u8>,
+	predicate:   F
+)
+-> Result<(), Error>
+{
 	let mut position = 0;
 
 	loop
@@ -141,25 +174,24 @@ clear_metadata
 		if position >= file_buffer.len() { return Ok(()); }
 
 		// Get the first 4 bytes at the current cursor position to determine
-		// the length of the current box 
-		let length_buffer = file_buffer[position..position+4].to_vec();
-		let length        = from_u8_vec_macro!(u32, &length_buffer, &Endian::Big) as usize;
+		// the length of the current box (including the 8 bytes of length and
+		// box type) and make sure the entire box is available
+		let length_buffer = get_box_data(file_buffer, position, position+4)?;
+		let length        = get_box_data_length(
+			&[length_buffer[0], length_buffer[1], length_buffer[2], length_buffer[3]]
+		)? as usize + 8;
+		let box_buffer    = get_box_data(file_buffer, position, position+length)?;
 
 		// Next, read the box type
-		let type_buffer = file_buffer[position+4..position+8].to_vec();
-
-		if box_contains_exif(
-			&mut Cursor::new(
-				file_buffer[position+8..position+12].to_vec()
-			), 
-			[type_buffer[0], type_buffer[1], type_buffer[2], type_buffer[3]]
-		)?
+		let type_buffer = [box_buffer[4], box_buffer[5], box_buffer[6], box_buffer[7]];
+
+		if predicate(type_buffer, &box_buffer[8..])?
 		{
 			range_remove(file_buffer, position, position+length);
 		}
 		else
 		{
-			// Not an EXIF box so skip it
+			// Not a box to remove so skip it
 			position += length;
 		}
 	}
@@ -170,7 +202,7 @@ file_clear_metadata
 (
 	path: &Path
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	let mut file = file_check_signature(path)?;
 
@@ -180,18 +212,24 @@ file_clear_metadata
 	loop
 	{
 		let position        = file.stream_position()?;
-		let old_file_length = file.metadata().unwrap().len();
+		let old_file_length = file.metadata()?.len();
 		if position >= old_file_length { return Ok(()); }
 
 		file.read_exact(&mut length_buffer)?;
 		file.read_exact(&mut type_buffer)?;
 
-		let length = from_u8_vec_macro!(u32, &length_buffer.to_vec(), &Endian::Big) as usize;
+		let data_length = get_box_data_length(&length_buffer)? as u64;
+		let length      = data_length + 8;
+
+		if position + length > old_file_length
+		{
+			return container_error!("JXL box exceeds the file size!");
+		}
 
 		if box_contains_exif(&mut file, type_buffer)?
 		{
 			// Seek past the EXIF box ...
-			file.seek(SeekFrom::Current((length-8) as i64))?;
+			file.seek(SeekFrom::Current(data_length as i64))?;
 
 
 			// ... copy everything from here onwards into a buffer ...
@@ -207,13 +245,12 @@ file_clear_metadata
 
 			// ... and finally update the file size - otherwise there will be
 			// duplicate bytes at the end!
-			perform_file_action!(file.set_len(old_file_length - length as u64));
+			perform_file_action!(file.set_len(old_file_length - length));
 		}
 		else
 		{
 			// Not an EXIF box so skip it
-			assert_eq!(position+8, file.stream_position()?);
-			file.seek(SeekFrom::Current((length-8) as i64))?;
+			file.seek(SeekFrom::Current(data_length as i64))?;
 		}
 	}
 }
@@ -225,7 +262,7 @@ check_brob_type_for_exif
 (
 	cursor: &mut T
 )
--> Result<bool, std::io::Error>
+-> Result<bool, Error>
 {
 	// Check if the next for 4 bytes say 'Exif'
 	let mut brob_type = [0u8; 4];
@@ -247,7 +284,7 @@ box_contains_exif
 	cursor:      &mut T,
 	type_buffer:  [u8; 4],
 )
--> Result<bool, std::io::Error>
+-> Result<bool, Error>
 {
 	if type_buffer == EXIF
 	{
@@ -263,13 +300,53 @@ box_contains_exif
 
 
 
+/// Decodes the box size given by the 4 bytes of the length buffer and returns
+/// the length of the box data (i.e. without the 8 bytes of the length field
+/// and the box type)
+fn
+get_box_data_length
+(
+	length_buffer: &[u8; 4]
+)
+-> Result<u32, Error>
+{
+	let box_length = from_u8_vec_macro!(u32, length_buffer, &Endian::Big);
+
+	if let Some(data_length) = box_length.checked_sub(8)
+	{
+		return Ok(data_length);
+	}
+
+	return container_error!(format!("Invalid JXL box size: {}", box_length));
+}
+
+/// Gets the data of a box in the given range, if the file contains that range
+fn
+get_box_data
+(
+	file_buffer: &[u8],
+	start:       usize,
+	end:         usize
+)
+-> Result<&[u8], Error>
+{
+	if let Some(box_data) = file_buffer.get(start..end)
+	{
+		return Ok(box_data);
+	}
+
+	return container_error!("JXL box exceeds the file size!");
+}
+
+
+
 /// Read 
 pub(crate) fn
 read_metadata
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	check_signature(file_buffer)?;
 
@@ -277,12 +354,18 @@ read_metadata
 
 	loop
 	{
+		// Reached the end of the file without encountering an EXIF box
+		if cursor.position() >= file_buffer.len() as u64
+		{
+			return Err(Error::NoExifData);
+		}
+
 		// Get the first 4 bytes at the current cursor position to determine
 		// the length of the current box (and account for the 8 bytes of length
 		// and box type)
 		let mut length_buffer = [0u8; 4];
 		cursor.read_exact(&mut length_buffer)?;
-		let length = from_u8_vec_macro!(u32, &length_buffer.to_vec(), &Endian::Big) - 8;
+		let length = get_box_data_length(&length_buffer)?;
 
 		// Next, read the box type
 		let mut type_buffer = [0u8; 4];
@@ -295,8 +378,8 @@ read_metadata
 				let position = cursor.position() as usize;
 
 				// Ignore the next 4 bytes (because that's the minor version???)
-				let exif_buffer = file_buffer[position+4..position + length as usize].to_vec();
-				return Ok(exif_buffer);
+				let exif_buffer = get_box_data(file_buffer, position + 4, position + length as usize)?;
+				return Ok(exif_buffer.to_vec());
 			},
 
 			BROB_BOX => { // -> Brotli encoded data
@@ -305,10 +388,11 @@ read_metadata
 
 				if check_brob_type_for_exif(&mut cursor)?
 				{
-					let compressed_exif_buffer = file_buffer[
-						position + 4 ..
+					let compressed_exif_buffer = get_box_data(
+						file_buffer,
+						position + 4,
 						position + length as usize
-					].to_vec();
+					)?.to_vec();
 
 					let mut decompressed_exif_buffer = Vec::new();
 
@@ -318,11 +402,15 @@ read_metadata
 					) 
 					{
 						Ok(_)  => (),
-						Err(e) => return Err(e)
+						Err(e) => return Err(e.into())
 					};
 
 					// Ignore the next 4 bytes (I guess for the same reason 
 					// as above - some sort of minor version?)
+					if decompressed_exif_buffer.len() < 4
+					{
+						return container_error!("Decompressed EXIF data of JXL file is too short!");
+					}
 					return Ok(decompressed_exif_buffer[4..].to_vec());
 				}
 				else 
@@ -344,23 +432,32 @@ file_read_metadata
 (
 	path: &Path
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	let mut file = open_read_file(path)?;
 
 	// Read first 12 bytes and check that we have a ISO BMFF file
 	let mut first_12_bytes = [0u8; 12];
-	file.read(&mut first_12_bytes).unwrap();
-	check_signature(&first_12_bytes.to_vec())?;
+	file.read_exact(&mut first_12_bytes)?;
+	check_signature(&first_12_bytes)?;
 
 	loop
 	{
 		// Get the first 4 bytes at the current cursor position to determine
 		// the length of the current box (and account for the 8 bytes of length
 		// and box type)
+		// Reaching the end of the file at a box boundary means that there is
+		// no EXIF box
 		let mut length_buffer = [0u8; 4];
-		file.read_exact(&mut length_buffer)?;
-		let length = from_u8_vec_macro!(u32, &length_buffer.to_vec(), &Endian::Big) - 8;
+		if let Err(error) = file.read_exact(&mut length_buffer)
+		{
+			if error.kind() == std::io::ErrorKind::UnexpectedEof
+			{
+				return Err(Error::NoExifData);
+			}
+			return Err(error.into());
+		}
+		let length = get_box_data_length(&length_buffer)?;
 
 		// Next, read the box type
 		let mut type_buffer = [0u8; 4];
@@ -370,6 +467,13 @@ file_read_metadata
 		{
 			EXIF => {
 
+				// Check that the file actually contains the announced data
+				// before allocating a buffer for it
+				if length < 4 || file.stream_position()? + length as u64 > file.metadata()?.len()
+				{
+					return container_error!("Invalid size of JXL EXIF box!");
+				}
+
 				// Skip the next 4 bytes (which contain the minor version???)
 				file.seek(SeekFrom::Current(4))?;
 
@@ -388,6 +492,90 @@ file_read_metadata
 	}
 }
 
+/// Reads the EXIF data from the JXL data provided by the given reader by only
+/// reading the box headers and the data of the EXIF box, which may also be
+/// brotli compressed
+pub(crate) fn
+read_metadata_from_stream
+<T: Seek + Read>
+(
+	input: &mut T
+)
+-> Result<Vec<u8>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(12).read_to_end(&mut signature_buffer)?;
+	check_signature(&signature_buffer)?;
+
+	let input_length = input.seek(SeekFrom::End(0))?;
+	input.seek(SeekFrom::Start(12))?;
+
+	loop
+	{
+		// Reached the end of the data without encountering an EXIF box
+		let position = input.stream_position()?;
+		if position >= input_length
+		{
+			return Err(Error::NoExifData);
+		}
+
+		let mut length_buffer = [0u8; 4];
+		input.read_exact(&mut length_buffer)?;
+		let length = get_box_data_length(&length_buffer)?;
+
+		let mut type_buffer = [0u8; 4];
+		input.read_exact(&mut type_buffer)?;
+
+		// Check that the data actually contains the box before reading or
+		// allocating anything for it
+		if position + 8 + length as u64 > input_length
+		{
+			return container_error!("JXL box exceeds the file size!");
+		}
+
+		match type_buffer
+		{
+			EXIF => {
+				// Ignore the next 4 bytes (minor version), see `read_metadata`
+				if length < 4
+				{
+					return container_error!("Invalid size of JXL EXIF box!");
+				}
+				input.seek(SeekFrom::Current(4))?;
+
+				let mut exif_buffer = Vec::new();
+				copy_n_bytes(input, &mut exif_buffer, (length - 4) as u64)?;
+				return Ok(exif_buffer);
+			},
+
+			BROB_BOX if length >= 4 && check_brob_type_for_exif(input)? => {
+				input.seek(SeekFrom::Current(4))?;
+
+				let mut decompressed_exif_buffer = Vec::new();
+				match brotli::BrotliDecompress(
+					&mut input.take((length - 4) as u64),
+					&mut decompressed_exif_buffer
+				)
+				{
+					Ok(_)  => (),
+					Err(e) => return Err(e.into())
+				};
+
+				if decompressed_exif_buffer.len() < 4
+				{
+					return container_error!("Decompressed EXIF data of JXL file is too short!");
+				}
+				return Ok(decompressed_exif_buffer[4..].to_vec());
+			},
+
+			_ => {
+				// Not an EXIF box so skip it
+				input.seek(SeekFrom::Start(position + 8 + length as u64))?;
+			}
+		}
+	}
+}
+
 fn
 encode_metadata_jxl
 (
@@ -416,20 +604,26 @@ encode_metadata_jxl
 fn
 find_insert_position
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<usize, std::io::Error>
+-> Result<usize, Error>
 {
 	let mut cursor = Cursor::new(file_buffer);
 
 	loop
 	{
+		// Reached the end of the file, so place the EXIF box there
+		if cursor.position() >= file_buffer.len() as u64
+		{
+			return Ok(file_buffer.len());
+		}
+
 		// Get the first 4 bytes at the current cursor position to determine
 		// the length of the current box (and account for the 8 bytes of length
 		// and box type)
 		let mut length_buffer = [0u8; 4];
 		cursor.read_exact(&mut length_buffer)?;
-		let length = from_u8_vec_macro!(u32, &length_buffer.to_vec(), &Endian::Big) - 8;
+		let length = get_box_data_length(&length_buffer)?;
 
 		// Next, read the box type
 		let mut type_buffer = [0u8; 4];
@@ -455,7 +649,28 @@ write_metadata
 	file_buffer: &mut Vec<u8>,
 	metadata:    &Metadata
 )
--> Result<(), std::io::Error> 
+-> Result<(), Error> 
+{
+	convert_to_container(file_buffer);
+
+	// Remove old metadata
+	clear_metadata(file_buffer)?;
+	
+	// Insert new metadata
+	let mut encoded_metadata = encode_metadata_jxl(&metadata.encode()?);
+	let     insert_position  = find_insert_position(file_buffer)?;
+	insert_multiple_at(file_buffer, insert_position, &mut encoded_metadata);
+
+	return Ok(());
+}
+
+/// A simple JXL codestream can't store any metadata, so it needs to be put
+/// into an ISO BMFF container first
+fn
+convert_to_container
+(
+	file_buffer: &mut Vec<u8>
+)
 {
 	if starts_with_jxl_signature(file_buffer)
 	{
@@ -478,14 +693,94 @@ write_metadata
 		// Replace file buffer
 		*file_buffer = new_file_buffer;
 	}
+}
 
-	// Remove old metadata
-	clear_metadata(file_buffer)?;
-	
-	// Insert new metadata
-	let mut encoded_metadata = encode_metadata_jxl(&metadata.encode()?);
-	let     insert_position  = find_insert_position(file_buffer)?;
-	insert_multiple_at(file_buffer, insert_position, &mut encoded_metadata);
+/// Writes the JXL image read from the input to the output while replacing the
+/// EXIF box(es) with the given metadata, see `write_metadata`. 
+/// The boxes get copied one by one without loading them into memory.
+pub(crate) fn
+write_metadata_to_stream
+<R: Read + Seek, W: Write>
+(
+	input:    &mut R,
+	output:   &mut W,
+	metadata: &Metadata
+)
+-> Result<(), Error>
+{
+	let input_length = input.seek(SeekFrom::End(0))?;
+	input.seek(SeekFrom::Start(0))?;
+
+	let mut signature_buffer = Vec::new();
+	input.take(ISO_BMFF_JXL_SIGNATURE.len() as u64).read_to_end(&mut signature_buffer)?;
+	input.seek(SeekFrom::Start(0))?;
+
+	let encoded_metadata = encode_metadata_jxl(&metadata.encode()?);
+
+	if starts_with_jxl_signature(&signature_buffer)
+	{
+		// Put the codestream into an ISO BMFF container, with the EXIF box
+		// placed after the signature and ftyp boxes
+		let jxlc_box_length = match u32::try_from(input_length + 8)
+		{
+			Ok(jxlc_box_length) => jxlc_box_length,
+			Err(_)              => return container_error!("JXL codestream is too large for a box!"),
+		};
+
+		output.write_all(&ISO_BMFF_JXL_SIGNATURE)?;
+		output.write_all(&FTYP_BOX)?;
+		output.write_all(&encoded_metadata)?;
+		output.write_all(&to_u8_vec_macro!(u32, &jxlc_box_length, &Endian::Big))?;
+		output.write_all(&IsoBmffBoxType::JXLC)?;
+		copy_n_bytes(input, output, input_length)?;
+
+		return Ok(());
+	}
+
+	check_signature(&signature_buffer)?;
+
+	let mut length_buffer    = [0u8; 4];
+	let mut type_buffer      = [0u8; 4];
+	let mut exif_box_written = false;
+
+	loop
+	{
+		let position = input.stream_position()?;
+		if position >= input_length { break; }
+
+		input.read_exact(&mut length_buffer)?;
+		input.read_exact(&mut type_buffer)?;
+
+		let data_length = get_box_data_length(&length_buffer)? as u64;
+
+		if position + data_length + 8 > input_length
+		{
+			return container_error!("JXL box exceeds the file size!");
+		}
+
+		// Drop the old metadata by not copying the box
+		if box_contains_exif(input, type_buffer)?
+		{
+			input.seek(SeekFrom::Current(data_length as i64))?;
+			continue;
+		}
+
+		// Place the EXIF box after the signature and ftyp boxes
+		if !exif_box_written && type_buffer != IsoBmffBoxType::JXL && type_buffer != IsoBmffBoxType::FTYP
+		{
+			output.write_all(&encoded_metadata)?;
+			exif_box_written = true;
+		}
+
+		output.write_all(&length_buffer)?;
+		output.write_all(&type_buffer)?;
+		copy_n_bytes(input, output, data_length)?;
+	}
+
+	if !exif_box_written
+	{
+		output.write_all(&encoded_metadata)?;
+	}
 
 	return Ok(());
 }
@@ -496,7 +791,7 @@ file_write_metadata
 	path:     &Path,
 	metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Load the entire file into memory instead of performing multiple read, 
 	// seek and write operations
@@ -514,4 +809,191 @@ file_write_metadata
 	perform_file_action!(file.write_all(&file_buffer));
 
 	return Ok(());
-}
\ No newline at end of file
+}
+
+
+
+/// Reads the XMP packet from the (first) "xml " box, which may also be brotli
+/// compressed
+pub(crate) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	if starts_with_jxl_signature(file_buffer)
+	{
+		// Simple JXL codestream file that can't contain any metadata
+		return Err(Error::NoXmpData);
+	}
+	check_signature(file_buffer)?;
+
+	let mut position = 0;
+
+	while position < file_buffer.len()
+	{
+		let length_buffer = get_box_data(file_buffer, position, position+4)?;
+		let length        = get_box_data_length(
+			&[length_buffer[0], length_buffer[1], length_buffer[2], length_buffer[3]]
+		)? as usize + 8;
+		let box_buffer    = get_box_data(file_buffer, position, position+length)?;
+		let type_buffer   = [box_buffer[4], box_buffer[5], box_buffer[6], box_buffer[7]];
+
+		if type_buffer == IsoBmffBoxType::XML
+		{
+			return Ok(box_buffer[8..].to_vec());
+		}
+
+		if type_buffer == BROB_BOX && box_buffer[8..].starts_with(&IsoBmffBoxType::XML)
+		{
+			let mut decompressed_xmp_buffer = Vec::new();
+			match brotli::BrotliDecompress(
+				&mut Cursor::new(&box_buffer[12..]), 
+				&mut decompressed_xmp_buffer
+			) 
+			{
+				Ok(_)  => (),
+				Err(e) => return Err(e.into())
+			};
+			return Ok(decompressed_xmp_buffer);
+		}
+
+		position += length;
+	}
+
+	return Err(Error::NoXmpData);
+}
+
+/// Removes all (possibly brotli compressed) "xml " boxes
+pub(crate) fn
+clear_xmp
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	if starts_with_jxl_signature(file_buffer)
+	{
+		return Ok(());
+	}
+	check_signature(file_buffer)?;
+
+	return clear_boxes_if(
+		file_buffer, 
+		|type_buffer, box_data| Ok(
+			type_buffer == IsoBmffBoxType::XML 
+			|| (type_buffer == BROB_BOX && box_data.starts_with(&IsoBmffBoxType::XML))
+		)
+	);
+}
+
+/// Replaces the XMP data with an uncompressed "xml " box holding the given
+/// packet, placed after the signature and ftyp boxes
+pub(crate) fn
+write_xmp
+(
+	file_buffer: &mut Vec<u8>,
+	xmp_packet:  &[u8]
+)
+-> Result<(), Error>
+{
+	let xml_box_length = match u32::try_from(xmp_packet.len() + 8)
+	{
+		Ok(xml_box_length) => xml_box_length,
+		Err(_)             => return container_error!("XMP packet is too large for a box!"),
+	};
+
+	convert_to_container(file_buffer);
+	clear_xmp(file_buffer)?;
+
+	let mut xml_box = to_u8_vec_macro!(u32, &xml_box_length, &Endian::Big);
+	xml_box.extend(IsoBmffBoxType::XML);
+	xml_box.extend(xmp_packet);
+
+	let insert_position = find_insert_position(file_buffer)?;
+	insert_multiple_at(file_buffer, insert_position, &mut xml_box);
+
+	return Ok(());
+}
+
+/// Reads the C2PA manifest store, which is the top level "jumb" box that is
+/// labeled "c2pa", by only reading the box headers and the data of that box
+pub(crate) fn
+read_c2pa_jumbf
+<T: Seek + Read>
+(
+	input: &mut T
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(12).read_to_end(&mut signature_buffer)?;
+	if starts_with_jxl_signature(&signature_buffer)
+	{
+		// Simple JXL codestream file that can't contain any metadata
+		return Ok(None);
+	}
+	check_signature(&signature_buffer)?;
+
+	let input_length = input.seek(SeekFrom::End(0))?;
+	input.seek(SeekFrom::Start(12))?;
+
+	loop
+	{
+		let position = input.stream_position()?;
+		if position >= input_length
+		{
+			return Ok(None);
+		}
+
+		let mut length_buffer = [0u8; 4];
+		input.read_exact(&mut length_buffer)?;
+		let length = get_box_data_length(&length_buffer)?;
+
+		let mut type_buffer = [0u8; 4];
+		input.read_exact(&mut type_buffer)?;
+
+		if position + 8 + length as u64 > input_length
+		{
+			return container_error!("JXL box exceeds the file size!");
+		}
+
+		if type_buffer == IsoBmffBoxType::JUMB
+		{
+			let mut box_buffer = Vec::new();
+			box_buffer.extend(length_buffer);
+			box_buffer.extend(type_buffer);
+			copy_n_bytes(input, &mut box_buffer, length as u64)?;
+
+			if is_manifest_store_contents(&box_buffer[8..])
+			{
+				return Ok(Some(box_buffer));
+			}
+		}
+
+		input.seek(SeekFrom::Start(position + 8 + length as u64))?;
+	}
+}
+
+/// Removes the top level "jumb" box holding the C2PA manifest store
+pub(crate) fn
+clear_c2pa_jumbf
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	if starts_with_jxl_signature(file_buffer)
+	{
+		return Ok(());
+	}
+	check_signature(file_buffer)?;
+
+	return clear_boxes_if(
+		file_buffer, 
+		|type_buffer, box_data| Ok(
+			type_buffer == IsoBmffBoxType::JUMB && is_manifest_store_contents(box_data)
+		)
+	);
+}
@@ -33,13 +33,18 @@
 mod general_file_io;
 pub mod ifd;
 
+pub mod c2pa;
 mod png;
 mod heif;
+pub mod icc;
+pub mod iptc;
 mod jpg;
+pub mod jpeg;
 mod jxl;
+pub mod photoshop;
 mod tiff;
 mod webp;
-mod xmp;
+pub mod xmp;
 mod util;
 
 pub mod endian;
@@ -48,4 +53,7 @@ pub mod u8conversion;
 pub mod exif_tag;
 pub mod exif_tag_format;
 pub mod filetype;
-pub mod metadata;
\ No newline at end of file
+pub mod metadata;
+pub mod error;
+
+pub use error::Error;
\ No newline at end of file
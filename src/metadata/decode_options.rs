@@ -0,0 +1,151 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::fmt;
+
+use log::warn;
+
+use crate::error::Error;
+use crate::ifd::ExifTagGroup;
+use crate::ifd::MakerNoteVendor;
+
+/// Options that control how the EXIF data of an image gets decoded.
+///
+/// By default, decoding is strict: The first problem that is encountered
+/// (e.g. an entry pointing outside of the EXIF data) results in an error and
+/// no `Metadata` is returned at all.
+/// With `strict` set to `false`, entries and SubIFDs that can't be decoded
+/// are skipped instead and everything else is kept. What got dropped is
+/// reported via a list of `DecodeWarning`s.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::metadata::Metadata;
+/// use little_exif::metadata::DecodeOptions;
+///
+/// let (metadata, warnings) = Metadata::new_from_path_with_options(
+///     std::path::Path::new("damaged.jpg"),
+///     &DecodeOptions { strict: false }
+/// ).unwrap();
+///
+/// for warning in warnings
+/// {
+///     println!("{}", warning);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct
+DecodeOptions
+{
+	pub strict: bool,
+}
+
+impl
+Default
+for
+DecodeOptions
+{
+	fn
+	default
+	()
+	-> Self
+	{
+		DecodeOptions { strict: true }
+	}
+}
+
+/// Describes a part of the EXIF data that got dropped while decoding in
+/// lenient mode, i.e. with `DecodeOptions::strict` set to `false`.
+#[derive(Debug)]
+pub struct
+DecodeWarning
+{
+	/// The group of the IFD in which the problem occurred
+	pub group:          ExifTagGroup,
+
+	/// The number of the generic IFD this IFD belongs to (e.g. `0` for IFD0
+	/// and its SubIFDs like the ExifIFD)
+	pub generic_ifd_nr: u32,
+
+	/// The hex value of the entry that got skipped. `None` if not a single
+	/// entry but e.g. an entire IFD got dropped
+	pub tag:            Option<u16>,
+
+	/// What went wrong
+	pub error:          Error,
+}
+
+impl
+fmt::Display
+for
+DecodeWarning
+{
+	fn
+	fmt
+	(
+		&self,
+		f: &mut fmt::Formatter<'_>
+	)
+	-> fmt::Result
+	{
+		match self.tag
+		{
+			Some(tag)
+				=> write!(f, "Skipped tag 0x{:04x} in {:?} IFD {}: {}", tag, self.group, self.generic_ifd_nr, self.error),
+			None
+				=> write!(f, "Skipped data of {:?} IFD {}: {}", self.group, self.generic_ifd_nr, self.error),
+		}
+	}
+}
+
+/// State that gets passed along while decoding the IFDs
+pub(crate) struct
+DecodeContext
+{
+	strict:                       bool,
+	pub(crate) warnings:          Vec<DecodeWarning>,
+
+	// Set while decoding the IFD of a maker note
+	pub(crate) maker_note_vendor: Option<MakerNoteVendor>,
+}
+
+impl
+DecodeContext
+{
+	pub(crate) fn
+	new
+	(
+		options: &DecodeOptions
+	)
+	-> Self
+	{
+		DecodeContext { strict: options.strict, warnings: Vec::new(), maker_note_vendor: None }
+	}
+
+	/// Decides what happens with an error that only affects a part of the
+	/// EXIF data: In strict mode the error is returned so that decoding stops,
+	/// otherwise it gets recorded as warning and the caller may continue by
+	/// skipping the affected part.
+	pub(crate) fn
+	recover
+	(
+		&mut self,
+		error:          Error,
+		group:          ExifTagGroup,
+		generic_ifd_nr: u32,
+		tag:            Option<u16>,
+	)
+	-> Result<(), Error>
+	{
+		if self.strict
+		{
+			return Err(error);
+		}
+
+		let warning = DecodeWarning { group, generic_ifd_nr, tag, error };
+		warn!("{}", warning);
+		self.warnings.push(warning);
+
+		return Ok(());
+	}
+}
@@ -11,7 +11,7 @@ Metadata
 {
 	/// Reduces the `Metadata` struct to the absolute minimum required for 
 	/// TIFF compliance without losing important data (see table in exif_tag.rs,
-	/// strip and thumbnail data) which is all assumed to be in GENERIC IFDs.
+	/// strip, tile and thumbnail data) which is all assumed to be in GENERIC IFDs.
 	/// If this is not the case for one of your images, please open a new issue
 	pub fn
 	reduce_to_a_minimum
@@ -33,6 +33,8 @@ Metadata
 				{
 					ExifTag::StripOffsets(_, _)
 					| ExifTag::StripByteCounts(_)
+					| ExifTag::TileOffsets(_, _)
+					| ExifTag::TileByteCounts(_)
 					| ExifTag::ThumbnailOffset(_, _)
 					| ExifTag::ThumbnailLength(_)
 					| ExifTag::ImageWidth(_)
@@ -42,6 +44,8 @@ Metadata
 					| ExifTag::PhotometricInterpretation(_)
 					| ExifTag::SamplesPerPixel(_)
 					| ExifTag::RowsPerStrip(_)
+					| ExifTag::TileWidth(_)
+					| ExifTag::TileLength(_)
 					| ExifTag::XResolution(_)
 					| ExifTag::YResolution(_)
 					| ExifTag::ResolutionUnit(_)
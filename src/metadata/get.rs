@@ -60,6 +60,8 @@ Metadata
 	}
 
 	/// Gets the maximum generic ifd number that any of the struct's IFDs has
+	/// (or 0 if there are no generic IFDs at all, e.g. as the decoded data
+	/// did not contain an IFD0)
 	pub fn
 	get_max_generic_ifd_number
 	(
@@ -69,9 +71,9 @@ Metadata
 	{
 		self.image_file_directories.iter()
 			.filter(|ifd| ifd.get_ifd_type() == ExifTagGroup::GENERIC)
-			.max_by(|ifd1, ifd2| ifd1.get_generic_ifd_nr().cmp(&ifd2.get_generic_ifd_nr()))
-			.unwrap()
-			.get_generic_ifd_nr()
+			.map(|ifd| ifd.get_generic_ifd_nr())
+			.max()
+			.unwrap_or(0)
 	}
 
 	/// Gets an image file directory that is of a specific group an is
@@ -1,12 +1,26 @@
 // Copyright © 2024 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
 use std::path::Path;
 
+use crate::error::Error;
 use crate::filetype::get_file_type;
 use crate::filetype::FileExtension;
-use crate::general_file_io::io_error;
+use crate::general_file_io::open_read_file;
+use crate::general_file_io::modify_file_atomically;
+use crate::general_file_io::write_file_atomically;
+use crate::general_file_io::modify_file_in_place;
+use crate::general_file_io::SubReader;
+use crate::error::unsupported_format;
 
+use crate::c2pa;
+use crate::c2pa::C2paPolicy;
 use crate::heif;
 use crate::jpg;
 use crate::jxl;
@@ -15,13 +29,21 @@ use crate::tiff;
 use crate::webp;
 
 use super::Metadata;
+use super::DecodeOptions;
+use super::DecodeWarning;
+use super::WriteOptions;
+use super::ExifSizeReduction;
 
 impl
 Metadata
 {
-	/// Constructs a new `Metadata` object with the metadata from an image that is stored as a `Vec<u8>`
-	/// - If unable to handle the file vector (e.g. unsupported file type, etc.), this (currently) panics.
-	/// - If unable to decode the metadata, a new, empty object gets created and returned.
+	/// Constructs a new `Metadata` object with the metadata from an image that is stored in memory,
+	/// e.g. as a `Vec<u8>`, a `&[u8]` or a memory mapped file. The image data does not get copied.
+	/// # Errors
+	/// - `Error::NoExifData` if the image does not contain any EXIF data.
+	/// - `Error::UnsupportedFormat` or `Error::ContainerError` if the image itself can't be handled.
+	/// - `Error::MalformedIfd` or `Error::InvalidTagFormat` if the EXIF data can't be decoded,
+	///   see `new_from_vec_with_options` for skipping broken entries instead.
 	/// # Examples
 	/// ```no_run
 	/// use std::fs;
@@ -31,15 +53,54 @@ Metadata
 	/// let file_data = fs::read("image.jpg").unwrap();
 	/// let mut metadata: Metadata = Metadata::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
 	/// ```
-	#[allow(unreachable_patterns)]
 	pub fn
 	new_from_vec
+	<T: AsRef<[u8]> + ?Sized>
 	(
-		file_buffer: &Vec<u8>,
+		file_buffer: &T,
 		file_type:   FileExtension
 	)
-	-> Result<Metadata, std::io::Error>
+	-> Result<Metadata, Error>
+	{
+		let (metadata, _) = Self::new_from_vec_with_options(
+			file_buffer, 
+			file_type, 
+			&DecodeOptions::default()
+		)?;
+		return Ok(metadata);
+	}
+
+	/// Same as `new_from_vec`, but allows to specify how the metadata should
+	/// be decoded via the given `DecodeOptions`. Besides the `Metadata`, a
+	/// list of warnings about the parts of the data that had to be skipped
+	/// gets returned (which is always empty in strict mode).
+	/// # Examples
+	/// ```no_run
+	/// use std::fs;
+	/// use little_exif::metadata::Metadata;
+	/// use little_exif::metadata::DecodeOptions;
+	/// use little_exif::filetype::FileExtension;
+	/// 
+	/// let file_data = fs::read("image.jpg").unwrap();
+	/// let (metadata, warnings) = Metadata::new_from_vec_with_options(
+	///     &file_data, 
+	///     FileExtension::JPEG,
+	///     &DecodeOptions { strict: false }
+	/// ).unwrap();
+	/// ```
+	#[allow(unreachable_patterns)]
+	pub fn
+	new_from_vec_with_options
+	<T: AsRef<[u8]> + ?Sized>
+	(
+		file_buffer: &T,
+		file_type:   FileExtension,
+		options:     &DecodeOptions,
+	)
+	-> Result<(Metadata, Vec<DecodeWarning>), Error>
 	{
+		let file_buffer = file_buffer.as_ref();
+
 		let raw_pre_decode_general = match file_type
 		{
 			FileExtension::HEIF
@@ -51,12 +112,13 @@ Metadata
 			FileExtension::PNG { as_zTXt_chunk: _ }
 				=>  png::read_metadata(file_buffer),
 			FileExtension::TIFF
-				=> tiff::vec::read_metadata(file_buffer),
+				// The TIFF data itself is the EXIF data, so decode it 
+				// directly instead of copying it first
+				=> return Self::general_decoding_wrapper_with_options(Ok(file_buffer), options),
 			FileExtension::WEBP
 				=> webp::vec::read_metadata(file_buffer),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'new_from_vec' not yet implemented for {:?}", 
 						file_type
@@ -64,12 +126,13 @@ Metadata
 				),
 		};
 
-		return Self::general_decoding_wrapper(raw_pre_decode_general);
+		return Self::general_decoding_wrapper_with_options(raw_pre_decode_general, options);
 	}
 
 	/// Constructs a new `Metadata` object with the metadata from the image at the specified path.
-	/// - If unable to read the file (e.g. does not exist, unsupported file type, etc.), this (currently) panics.
-	/// - If unable to decode the metadata, a new, empty object gets created and returned.
+	/// # Errors
+	/// - `Error::Io` if the file can't be read, e.g. because it does not exist.
+	/// - Otherwise the same as for `new_from_vec`.
 	///
 	/// # Examples
 	/// ```no_run
@@ -77,13 +140,45 @@ Metadata
 	/// 
 	/// let mut metadata: Metadata = Metadata::new_from_path(std::path::Path::new("image.png")).unwrap();
 	/// ```
-	#[allow(unreachable_patterns)]
 	pub fn
 	new_from_path
 	(
 		path: &Path
 	)
-	-> Result<Metadata, std::io::Error>
+	-> Result<Metadata, Error>
+	{
+		let (metadata, _) = Self::new_from_path_with_options(
+			path, 
+			&DecodeOptions::default()
+		)?;
+		return Ok(metadata);
+	}
+
+	/// Same as `new_from_path`, but allows to specify how the metadata should
+	/// be decoded via the given `DecodeOptions`. Besides the `Metadata`, a
+	/// list of warnings about the parts of the data that had to be skipped
+	/// gets returned (which is always empty in strict mode).
+	/// This allows to still get e.g. the camera model and date from a file
+	/// where a single broken entry would otherwise prevent decoding.
+	///
+	/// # Examples
+	/// ```no_run
+	/// use little_exif::metadata::Metadata;
+	/// use little_exif::metadata::DecodeOptions;
+	/// 
+	/// let (metadata, warnings) = Metadata::new_from_path_with_options(
+	///     std::path::Path::new("image.png"),
+	///     &DecodeOptions { strict: false }
+	/// ).unwrap();
+	/// ```
+	#[allow(unreachable_patterns)]
+	pub fn
+	new_from_path_with_options
+	(
+		path:    &Path,
+		options: &DecodeOptions,
+	)
+	-> Result<(Metadata, Vec<DecodeWarning>), Error>
 	{
 		let file_type = get_file_type(path)?;
 
@@ -104,8 +199,7 @@ Metadata
 			FileExtension::WEBP 
 				=> webp::file::read_metadata(path),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'new_from_path' not yet implemented for {:?}", 
 						file_type
@@ -113,18 +207,121 @@ Metadata
 				),
 		};
 
-		return Self::general_decoding_wrapper(raw_pre_decode_general);
+		return Self::general_decoding_wrapper_with_options(raw_pre_decode_general, options);
 	}
 
+	/// Constructs a new `Metadata` object with the metadata read from the
+	/// given reader, e.g. a `File` or a `Cursor`. The image is expected to 
+	/// start at the current position of the reader. Unlike `new_from_vec`, 
+	/// the image does not have to be loaded into memory first, as only the 
+	/// parts of the data needed for finding the EXIF data get read - with the
+	/// exception of TIFF, which is read in full as the EXIF data is the 
+	/// entire image.
+	/// # Errors
+	/// - `Error::Io` if reading from the reader fails.
+	/// - Otherwise the same as for `new_from_vec`.
+	///
+	/// # Examples
+	/// ```no_run
+	/// use std::fs::File;
+	/// use little_exif::metadata::Metadata;
+	/// use little_exif::filetype::FileExtension;
+	/// 
+	/// let mut file = File::open("image.png").unwrap();
+	/// let metadata = Metadata::new_from_reader(
+	///     &mut file, 
+	///     FileExtension::PNG { as_zTXt_chunk: true }
+	/// ).unwrap();
+	/// ```
+	pub fn
+	new_from_reader
+	<R: Read + Seek>
+	(
+		reader:    &mut R,
+		file_type: FileExtension
+	)
+	-> Result<Metadata, Error>
+	{
+		let (metadata, _) = Self::new_from_reader_with_options(
+			reader, 
+			file_type, 
+			&DecodeOptions::default()
+		)?;
+		return Ok(metadata);
+	}
+
+	/// Same as `new_from_reader`, but allows to specify how the metadata 
+	/// should be decoded via the given `DecodeOptions`, see 
+	/// `new_from_vec_with_options`.
 	#[allow(unreachable_patterns)]
 	pub fn
+	new_from_reader_with_options
+	<R: Read + Seek>
+	(
+		reader:    &mut R,
+		file_type: FileExtension,
+		options:   &DecodeOptions,
+	)
+	-> Result<(Metadata, Vec<DecodeWarning>), Error>
+	{
+		// The readers seek a lot but mostly read small headers, so buffering
+		// avoids a syscall for each of them in case of e.g. a `File`
+		let mut input = BufReader::new(SubReader::new(reader)?);
+
+		let raw_pre_decode_general = match file_type
+		{
+			FileExtension::HEIF
+				=> heif::read_metadata_from_stream(&mut input),
+			FileExtension::JPEG 
+				=>  jpg::read_metadata_from_stream(&mut input),
+			FileExtension::JXL
+				=>  jxl::read_metadata_from_stream(&mut input),
+			FileExtension::PNG { as_zTXt_chunk: _ }
+				=>  png::read_metadata_from_stream(&mut input),
+			FileExtension::TIFF
+				=> tiff::generic_read_metadata(&mut input),
+			FileExtension::WEBP
+				=> webp::stream::read_metadata(&mut input),
+			_
+				=> return unsupported_format!(
+					format!(
+						"Function 'new_from_reader' not yet implemented for {:?}", 
+						file_type
+					)
+				),
+		};
+
+		return Self::general_decoding_wrapper_with_options(raw_pre_decode_general, options);
+	}
+
+	/// Clears the metadata of an image stored as a `Vec<u8>`. A C2PA manifest
+	/// of the image is preserved, see `WriteOptions`
+	pub fn
 	clear_metadata
 	(
 		file_buffer: &mut Vec<u8>,
 		file_type:   FileExtension
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
+	{
+		return Self::clear_metadata_with_options(file_buffer, file_type, &WriteOptions::default());
+	}
+
+	/// Same as `clear_metadata`, with the C2PA policy of the given options 
+	/// deciding what happens to a C2PA manifest of the image. The other 
+	/// options have no effect here.
+	#[allow(unreachable_patterns)]
+	pub fn
+	clear_metadata_with_options
+	(
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension,
+		options:     &WriteOptions
+	)
+	-> Result<(), Error>
 	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
 		match file_type
 		{
 			FileExtension::HEIF
@@ -140,8 +337,7 @@ Metadata
 			FileExtension::WEBP
 				=> webp::vec::clear_metadata(file_buffer),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'clear_metadata' not yet implemented for {:?}", 
 						file_type
@@ -161,15 +357,16 @@ Metadata
 		file_buffer: &mut Vec<u8>,
 		file_type:   FileExtension
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
 		match file_type
 		{
 			FileExtension::JPEG 
 				=>  jpg::clear_segment(file_buffer, 0xec),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'clear_app12_segment' not available for {:?} (only relevant for JPEG)", 
 						file_type
@@ -181,7 +378,9 @@ Metadata
 	/// Clears the APP13 segment in a JPEG file that contains data resulting
 	/// from exporting the file via Photoshop. This may be required in order
 	/// for other software to see e.g. the ImageDescription written in the
-	/// APP1 exif segment by little_exif
+	/// APP1 exif segment by little_exif.
+	/// To only remove some of the data (e.g. the IPTC data), see
+	/// `little_exif::photoshop::PhotoshopResources`
 	#[allow(unreachable_patterns)]
 	pub fn
 	clear_app13_segment
@@ -189,15 +388,16 @@ Metadata
 		file_buffer: &mut Vec<u8>,
 		file_type:   FileExtension
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
 		match file_type
 		{
 			FileExtension::JPEG 
 				=>  jpg::clear_segment(file_buffer, 0xed),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'clear_app13_segment' not available for {:?} (only relevant for JPEG)", 
 						file_type
@@ -216,17 +416,19 @@ Metadata
 	(
 		path: &Path
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
 		let file_type = get_file_type(path)?;
 
 		match file_type
 		{
 			FileExtension::JPEG 
-				=>  jpg::file_clear_segment(path, 0xec),
+				=> modify_file_atomically(
+					path, 
+					|file_buffer| Self::clear_app12_segment(file_buffer, file_type)
+				),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'file_clear_app12_segment' not available for {:?} (only relevant for JPEG)", 
 						file_type
@@ -245,17 +447,19 @@ Metadata
 	(
 		path: &Path
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
 		let file_type = get_file_type(path)?;
 
 		match file_type
 		{
 			FileExtension::JPEG 
-				=>  jpg::file_clear_segment(path, 0xed),
+				=> modify_file_atomically(
+					path, 
+					|file_buffer| Self::clear_app13_segment(file_buffer, file_type)
+				),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'file_clear_app13_segment' not available for {:?} (only relevant for JPEG)", 
 						file_type
@@ -264,16 +468,52 @@ Metadata
 		}
 	}
 
-	#[allow(unreachable_patterns)]
+	/// Clears the metadata of the specified file. 
+	/// The file gets replaced atomically, see `WriteOptions`.
 	pub fn
 	file_clear_metadata
 	(
 		path: &Path
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
+	{
+		return Self::file_clear_metadata_with_options(path, &WriteOptions::default());
+	}
+
+	/// Clears the metadata of the specified file, with the given options 
+	/// deciding whether the file gets replaced atomically or modified in place
+	/// and what happens to a C2PA manifest of the image
+	#[allow(unreachable_patterns)]
+	pub fn
+	file_clear_metadata_with_options
+	(
+		path:    &Path,
+		options: &WriteOptions
+	)
+	-> Result<(), Error>
 	{
 		let file_type = get_file_type(path)?;
 
+		if options.atomic
+		{
+			return modify_file_atomically(
+				path, 
+				|file_buffer| Self::clear_metadata_with_options(file_buffer, file_type, options)
+			);
+		}
+
+		// Only the structure of the file is read for checking the manifest
+		let strip_manifest = c2pa::apply_write_policy(
+			&mut BufReader::new(open_read_file(path)?), 
+			file_type, 
+			options.c2pa
+		)?;
+
+		if strip_manifest
+		{
+			modify_file_in_place(path, |file_buffer| c2pa::clear_manifest(file_buffer, file_type))?;
+		}
+
 		match file_type
 		{
 			FileExtension::HEIF
@@ -289,8 +529,7 @@ Metadata
 			FileExtension::WEBP 
 				=> webp::file::clear_metadata(path),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'file_clear_metadata' not yet implemented for {:?}", 
 						file_type
@@ -313,9 +552,15 @@ Metadata
 		&self,
 		for_file_type: FileExtension
 	)
-	-> Result<Vec<u8>, std::io::Error>
+	-> Result<Vec<u8>, Error>
 	{
-		let general_encoded_metadata = self.encode()?;
+		// For JPEG the data needs to fit into a single APP1 segment
+		let metadata = self.reduced_for_file_type(for_file_type, &ExifSizeReduction::default())?;
+		let general_encoded_metadata = match for_file_type
+		{
+			FileExtension::JPEG => jpg::encode_exif(&metadata)?,
+			_                   => metadata.encode()?,
+		};
 
 		Ok(match for_file_type
 		{
@@ -334,8 +579,7 @@ Metadata
 	}
 
 	/// Writes the metadata to an image stored as a Vec<u8>
-	/// For now, this only works for JPGs
-	#[allow(unreachable_patterns)]
+	/// A C2PA manifest of the image is preserved, see `WriteOptions`
 	pub fn
 	write_to_vec
 	(
@@ -343,7 +587,41 @@ Metadata
 		file_buffer: &mut Vec<u8>,
 		file_type:   FileExtension
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
+	{
+		return self.write_to_vec_with_options(file_buffer, file_type, &WriteOptions::default());
+	}
+
+	/// Writes the metadata to an image stored as a Vec<u8>, with the C2PA 
+	/// policy of the given options deciding what happens to a C2PA manifest
+	/// of the image. The `atomic` option has no effect here.
+	pub fn
+	write_to_vec_with_options
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension,
+		options:     &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		// Done first so that the image stays untouched if the data is too large
+		let metadata = self.reduced_for_file_type(file_type, &options.exif_size_reduction)?;
+
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
+		return metadata.generic_write_to_vec(file_buffer, file_type);
+	}
+
+	#[allow(unreachable_patterns)]
+	fn
+	generic_write_to_vec
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension
+	)
+	-> Result<(), Error>
 	{
 		match file_type
 		{
@@ -360,8 +638,7 @@ Metadata
 			FileExtension::WEBP
 				=> webp::vec::write_metadata(file_buffer, self),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'file_clear_metadata' not yet implemented for {:?}", 
 						file_type
@@ -370,39 +647,212 @@ Metadata
 		}
 	}
 
+	/// Writes the image read from `input` with the metadata replaced by this
+	/// one to `output`. In contrast to `write_to_vec` and `write_to_file`, 
+	/// the image data is copied through in chunks instead of loading the
+	/// entire image into memory, making this suitable for large files.
+	/// The output is equivalent to what `write_to_vec` produces, except that
+	/// for a JPEG with an MPF segment, EXIF data located after the start of
+	/// the image data is kept, as the images listed by that segment would
+	/// move otherwise. A C2PA manifest of the image is preserved, see
+	/// `WriteOptions`
+	/// # Examples
+	/// ```no_run
+	/// use std::fs::File;
+	/// use little_exif::metadata::Metadata;
+	/// use little_exif::filetype::FileExtension;
+	/// 
+	/// let metadata   = Metadata::new();
+	/// let mut input  = File::open("image.jpg").unwrap();
+	/// let mut output = File::create("image_with_new_metadata.jpg").unwrap();
+	/// 
+	/// metadata.write_to_stream(&mut input, &mut output, FileExtension::JPEG).unwrap();
+	/// ```
+	pub fn
+	write_to_stream
+	<R: Read + Seek, W: Write>
+	(
+		&self,
+		input:     &mut R,
+		output:    &mut W,
+		file_type: FileExtension
+	)
+	-> Result<(), Error>
+	{
+		return self.write_to_stream_with_options(input, output, file_type, &WriteOptions::default());
+	}
+
+	/// Same as `write_to_stream`, with the C2PA policy and the size reduction
+	/// of the given options deciding what happens to a C2PA manifest of the
+	/// image and to EXIF data that is too large. If the manifest needs to be
+	/// stripped from an image that is not a JPEG, the image gets loaded into
+	/// memory after all. The `atomic` option has no effect here.
+	pub fn
+	write_to_stream_with_options
+	<R: Read + Seek, W: Write>
+	(
+		&self,
+		input:     &mut R,
+		output:    &mut W,
+		file_type: FileExtension,
+		options:   &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		// Done first so that nothing gets written if the data is too large
+		let metadata = self.reduced_for_file_type(file_type, &options.exif_size_reduction)?;
+
+		// Only the structure of the image is read for checking the manifest
+		let start_position = input.stream_position()?;
+		let strip_manifest = c2pa::apply_write_policy(
+			&mut BufReader::new(&mut *input), 
+			file_type, 
+			options.c2pa
+		)?;
+		input.seek(SeekFrom::Start(start_position))?;
+
+		return metadata.generic_write_to_stream(input, output, file_type, strip_manifest);
+	}
+
+	#[allow(unreachable_patterns)]
+	fn
+	generic_write_to_stream
+	<R: Read + Seek, W: Write>
+	(
+		&self,
+		input:          &mut R,
+		output:         &mut W,
+		file_type:      FileExtension,
+		strip_manifest: bool
+	)
+	-> Result<(), Error>
+	{
+		// Only the JPEG specific function removes the manifest on the way, 
+		// for the other formats the image gets loaded into memory instead
+		if strip_manifest && !matches!(file_type, FileExtension::JPEG)
+		{
+			let mut file_buffer = Vec::new();
+			input.read_to_end(&mut file_buffer)?;
+
+			c2pa::clear_manifest(&mut file_buffer, file_type)?;
+			self.generic_write_to_vec(&mut file_buffer, file_type)?;
+
+			output.write_all(&file_buffer)?;
+			return Ok(());
+		}
+
+		// Some of the format specific functions process their input one byte
+		// at a time, so buffering is required for reasonable performance
+		let mut buffered_input  = BufReader::new(input);
+		let mut buffered_output = BufWriter::new(output);
+
+		match file_type
+		{
+			FileExtension::HEIF
+				=> heif::write_metadata_to_stream(&mut buffered_input, &mut buffered_output, self),
+			FileExtension::JPEG 
+				=>  jpg::write_metadata_to_stream(&mut buffered_input, &mut buffered_output, self, strip_manifest),
+			FileExtension::JXL 
+				=>  jxl::write_metadata_to_stream(&mut buffered_input, &mut buffered_output, self),
+			FileExtension::PNG { as_zTXt_chunk: _ }
+				=>  png::write_metadata_to_stream(&mut buffered_input, &mut buffered_output, self),
+			FileExtension::TIFF
+				=> tiff::generic_write_metadata_to_stream(&mut buffered_input, &mut buffered_output, self),
+			FileExtension::WEBP
+				=> webp::stream::write_metadata(&mut buffered_input, &mut buffered_output, self),
+			_
+				=> return unsupported_format!(
+					format!(
+						"Function 'write_to_stream' not yet implemented for {:?}", 
+						file_type
+					)
+				),
+		}?;
+
+		buffered_output.flush()?;
+
+		return Ok(());
+	}
+
 	/// Writes the metadata to the specified file.
+	/// The file gets replaced atomically, see `WriteOptions`.
 	/// This could return an error for multiple reasons:
 	/// - The file does not exist at the given path
 	/// - Interpreting the given path fails
 	/// - The file type is not supported
-	#[allow(unreachable_patterns)]
 	pub fn
 	write_to_file
 	(
 		&self,
 		path: &Path
 	)
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
+	{
+		return self.write_to_file_with_options(path, &WriteOptions::default());
+	}
+
+	/// Writes the metadata to the specified file, with the given options 
+	/// deciding whether the file gets replaced atomically or modified in place.
+	/// The atomic write copies the image into the temporary file in the same
+	/// way as `write_to_stream_with_options`, while modifying the file in
+	/// place loads it into memory. Both result in the same file as 
+	/// `write_to_vec_with_options`, except for the cases mentioned for 
+	/// `write_to_stream`.
+	#[allow(unreachable_patterns)]
+	pub fn
+	write_to_file_with_options
+	(
+		&self,
+		path:    &Path,
+		options: &WriteOptions
+	)
+	-> Result<(), Error>
 	{
 		let file_type = get_file_type(path)?;
 
+		// Done first so that the image stays untouched if the data is too large
+		let metadata = self.reduced_for_file_type(file_type, &options.exif_size_reduction)?;
+		let metadata = metadata.as_ref();
+
+		// Only the structure of the file is read for checking the manifest
+		let strip_manifest = c2pa::apply_write_policy(
+			&mut BufReader::new(open_read_file(path)?), 
+			file_type, 
+			options.c2pa
+		)?;
+
+		// The original file gets copied into the temporary one while writing
+		// the metadata, so that the image does not get loaded into memory
+		if options.atomic
+		{
+			let mut input = open_read_file(path)?;
+			return write_file_atomically(
+				path, 
+				|temp_file| metadata.generic_write_to_stream(&mut input, temp_file, file_type, strip_manifest)
+			);
+		}
+
+		if strip_manifest
+		{
+			modify_file_in_place(path, |file_buffer| c2pa::clear_manifest(file_buffer, file_type))?;
+		}
+
 		match file_type
 		{
 			FileExtension::HEIF
-				=> heif::file_write_metadata(path, self),
+				=> heif::file_write_metadata(path, metadata),
 			FileExtension::JPEG 
-				=>  jpg::file_write_metadata(path, self),
+				=>  jpg::file_write_metadata(path, metadata),
 			FileExtension::JXL 
-				=>  jxl::file_write_metadata(path, self),
+				=>  jxl::file_write_metadata(path, metadata),
 			FileExtension::PNG { as_zTXt_chunk: _ }
-				=>  png::file_write_metadata(path, self),
+				=>  png::file_write_metadata(path, metadata),
 			FileExtension::TIFF
-				=> tiff::file::write_metadata(path, self),
+				=> tiff::file::write_metadata(path, metadata),
 			FileExtension::WEBP 
-				=> webp::file::write_metadata(path, self),
+				=> webp::file::write_metadata(path, metadata),
 			_
-				=> return io_error!(
-					Other, 
+				=> return unsupported_format!(
 					format!(
 						"Function 'write_to_file' not yet implemented for {:?}", 
 						file_type
@@ -410,4 +860,4 @@ Metadata
 				),
 		}
 	}
-}
\ No newline at end of file
+}
@@ -2,12 +2,15 @@
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
 pub mod metadata_io;
+pub mod decode_options;
+pub mod write_options;
 pub mod iterator;
 pub mod edit;
 pub mod get;
 pub mod set;
+pub mod thumbnail;
+pub mod size_reduction;
 
-use core::panic;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
@@ -15,14 +18,21 @@ use std::io::Write;
 
 use log::error;
 
+use crate::error::Error;
 use crate::endian::*;
-use crate::general_file_io::io_error;
 use crate::general_file_io::EXIF_HEADER;
 use crate::ifd::ExifTagGroup;
 use crate::ifd::ImageFileDirectory;
 use crate::u8conversion::from_u8_vec_macro;
 use crate::u8conversion::U8conversion;
 
+pub use decode_options::DecodeOptions;
+pub use decode_options::DecodeWarning;
+pub use write_options::WriteOptions;
+pub use write_options::ExifSizeReduction;
+pub use write_options::RemovedExifData;
+use decode_options::DecodeContext;
+
 #[derive(Clone)]
 pub struct
 Metadata
@@ -83,20 +93,38 @@ Metadata
 
 	pub(crate) fn
 	general_decoding_wrapper
+	<T: AsRef<[u8]>>
+	(
+		raw_pre_decode_general: Result<T, Error>
+	)
+	-> Result<Metadata, Error>
+	{
+		let (data, _) = Self::general_decoding_wrapper_with_options(
+			raw_pre_decode_general, 
+			&DecodeOptions::default()
+		)?;
+		return Ok(data);
+	}
+
+	pub(crate) fn
+	general_decoding_wrapper_with_options
+	<T: AsRef<[u8]>>
 	(
-		raw_pre_decode_general: Result<Vec<u8>, std::io::Error>
+		raw_pre_decode_general: Result<T, Error>,
+		options:                &DecodeOptions,
 	)
-	-> Result<Metadata, std::io::Error>
+	-> Result<(Metadata, Vec<DecodeWarning>), Error>
 	{
 		if let Ok(pre_decode_general) = raw_pre_decode_general
 		{
-			let mut pre_decode_cursor = Cursor::new(&pre_decode_general);
-			let     decoding_result   = Self::decode(&mut pre_decode_cursor);
+			let mut pre_decode_cursor = Cursor::new(pre_decode_general.as_ref());
+			let mut context           = DecodeContext::new(options);
+			let     decoding_result   = Self::decode(&mut pre_decode_cursor, &mut context);
 			if let Ok((endian, image_file_directories)) = decoding_result
 			{
 				let mut data = Metadata { endian, image_file_directories };
 				data.sort_data();
-				return Ok(data);
+				return Ok((data, context.warnings));
 			}
 			else
 			{
@@ -120,7 +148,7 @@ Metadata
 	(
 		&self
 	)
-	-> Result<Vec<u8>, std::io::Error>
+	-> Result<Vec<u8>, Error>
 	{
 		// Prepare offset information
 		let mut ifds_with_offset_info_only: Vec<ImageFileDirectory> = Vec::new();
@@ -207,6 +235,14 @@ Metadata
 			}
 		}
 
+		// Without any generic IFD the header would point to data that does
+		// not exist. Add an empty IFD0 so that the result is always valid,
+		// even if it gets written over the start of an existing file.
+		if encode_vec.len() == 8
+		{
+			encode_vec.extend([0u8; 6]);
+		}
+
 		Ok(encode_vec)
 	}
 
@@ -226,9 +262,12 @@ Metadata
 			{
 				if a.get_ifd_type() == b.get_ifd_type()
 				{
-					panic!("Should not have two different IFDs with same group & number!");
+					// Should not have two different IFDs with same group & 
+					// number - `decode_ifd` rejects such data and 
+					// `create_ifd` checks if the IFD already exists
+					std::cmp::Ordering::Equal
 				}
-				if a.get_ifd_type() < b.get_ifd_type()
+				else if a.get_ifd_type() < b.get_ifd_type()
 				{
 					std::cmp::Ordering::Less
 				}
@@ -243,9 +282,10 @@ Metadata
 	fn
 	decode
 	(
-		data_cursor: &mut Cursor<&Vec<u8>>
+		data_cursor: &mut Cursor<&[u8]>,
+		context:     &mut DecodeContext,
 	)
-	-> Result<(Endian, Vec<ImageFileDirectory>), std::io::Error>
+	-> Result<(Endian, Vec<ImageFileDirectory>), Error>
 	{
 		// Get the start position
 		let mut data_start_position = data_cursor.position();
@@ -281,8 +321,8 @@ Metadata
 		{
 			[0x49, 0x49] => { Endian::Little },
 			[0x4d, 0x4d] => { Endian::Big },
-			[0x68, 0x74] => { return io_error!(Other, "Expected endian information, but found something that suspectedly is XMP data") }
-			_            => { return io_error!(Other, format!("Illegal endian information: {:?}", endian_buffer)) } 
+			[0x68, 0x74] => { return Err(Error::MalformedIfd { offset: 0, reason: "Expected endian information, but found something that suspectedly is XMP data".to_string() }) }
+			_            => { return Err(Error::MalformedIfd { offset: 0, reason: format!("Illegal endian information: {:?}", endian_buffer) }) } 
 		};
 
 		// Validate magic number
@@ -293,19 +333,36 @@ Metadata
 			(endian == Endian::Big    && magic_number_buffer == [0x00, 0x2a])
 		)
 		{
-			return io_error!(Other, "Could not verify magic number!");
+			return Err(Error::MalformedIfd { offset: 2, reason: "Could not verify magic number!".to_string() });
 		}
 
 		// Get offset to IFD0
 		let mut ifd0_offset_buffer = vec![0u8; 4];
 		data_cursor.read_exact(&mut ifd0_offset_buffer)?;
-		let mut ifd_offset_option = Some(from_u8_vec_macro!(u32, &ifd0_offset_buffer.to_vec(), &endian));
+		let mut ifd_offset_option = Some(from_u8_vec_macro!(u32, &ifd0_offset_buffer, &endian));
 
 		// Decode all the IFDs
 		let mut ifds = Vec::new();
 		let mut generic_ifd_nr = 0;
+		let mut visited_ifd_offsets = Vec::new();
 		while let Some(ifd_offset) = ifd_offset_option
 		{
+			// Prevent endless loops due to IFDs linking back to each other
+			if visited_ifd_offsets.contains(&ifd_offset)
+			{
+				context.recover(
+					Error::MalformedIfd { 
+						offset: ifd_offset as u64, 
+						reason: "IFD links to an already decoded IFD".to_string() 
+					},
+					ExifTagGroup::GENERIC,
+					generic_ifd_nr,
+					None
+				)?;
+				break;
+			}
+			visited_ifd_offsets.push(ifd_offset);
+
 			data_cursor.set_position(data_start_position);
 			data_cursor.seek(std::io::SeekFrom::Current(ifd_offset as i64))?;
 
@@ -315,23 +372,33 @@ Metadata
 				&endian,
 				&ExifTagGroup::GENERIC,
 				generic_ifd_nr,
-				&mut ifds
+				&mut ifds,
+				context
 			);
 
-			if let Ok(new_ifd_offset_option) = decode_result
-			{
-				ifd_offset_option = new_ifd_offset_option;
-			}
-			else
+			match decode_result
 			{
-				let error = decode_result.err().unwrap();
-				return Err(error);
+				Ok(new_ifd_offset_option) => {
+					ifd_offset_option = new_ifd_offset_option;
+				},
+				Err(error) => {
+					// In lenient mode, keep the IFDs decoded so far but don't
+					// follow the links any further
+					context.recover(error, ExifTagGroup::GENERIC, generic_ifd_nr, None)?;
+					break;
+				}
 			}
 
 			generic_ifd_nr += 1;
 		}
 
-
+		ImageFileDirectory::decode_maker_notes(
+			data_cursor,
+			data_start_position,
+			&endian,
+			&mut ifds,
+			context
+		);
 
 		return Ok((endian, ifds));
 	}
@@ -344,16 +411,23 @@ mod tests
 	use std::fs::read;
 	use std::io::Cursor;
 
-use super::Metadata;
+	use crate::error::Error;
+
+	use super::Metadata;
+	use super::DecodeContext;
+	use super::DecodeOptions;
 
 	#[test]
 	fn
 	new_test_1()
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
 		let image_data = read("tests/read_sample.tif").unwrap();
 
-		Metadata::decode(&mut Cursor::new(&image_data))?;
+		Metadata::decode(
+			&mut Cursor::new(image_data.as_slice()), 
+			&mut DecodeContext::new(&DecodeOptions::default())
+		)?;
 
 		Ok(())
 	}
@@ -362,12 +436,15 @@ use super::Metadata;
 	#[test]
 	fn
 	new_test_2()
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
 		// let image_data = read("tests/multi_page.tif").unwrap();
 		let image_data = read("tests/multi_page_mod.tif").unwrap();
 
-		let data = Metadata::decode(&mut Cursor::new(&image_data))?;
+		let data = Metadata::decode(
+			&mut Cursor::new(image_data.as_slice()), 
+			&mut DecodeContext::new(&DecodeOptions::default())
+		)?;
 
 		for ifd in data.1
 		{
@@ -2,6 +2,7 @@
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
 use crate::exif_tag::ExifTag;
+use crate::ifd::ExifTagGroup;
 
 use super::Metadata;
 
@@ -23,6 +24,10 @@ Metadata
 		input_tag: ExifTag
 	)
 	{
+		if let ExifTag::MakerNote(_) = input_tag
+		{
+			self.remove_decoded_maker_note(0);
+		}
 		self.get_ifd_mut(input_tag.get_group(), 0).set_tag(input_tag);
 	}
 
@@ -47,6 +52,11 @@ Metadata
 			// Does this IFD exist?
 			if self.get_ifd(remove_me.get_group(), ifd_number).is_some()
 			{
+				if let ExifTag::MakerNote(_) = remove_me
+				{
+					self.remove_decoded_maker_note(ifd_number);
+				}
+
 				// If so, get it as mutable and call remove_tag on it
 				self.get_ifd_mut(
 					remove_me.get_group(), ifd_number
@@ -58,4 +68,19 @@ Metadata
 
 		return removed_count;
 	}
-}
\ No newline at end of file
+
+	/// The tags of the MAKERNOTES group are decoded from the MakerNote tag, 
+	/// so they need to go as well if that tag gets replaced or removed
+	fn
+	remove_decoded_maker_note
+	(
+		&mut self,
+		generic_ifd_nr: u32
+	)
+	{
+		self.image_file_directories.retain(|ifd| 
+			ifd.get_ifd_type()       != ExifTagGroup::MAKERNOTES ||
+			ifd.get_generic_ifd_nr() != generic_ifd_nr
+		);
+	}
+}
@@ -0,0 +1,146 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::borrow::Cow;
+
+use log::warn;
+
+use crate::error::Error;
+use crate::exif_tag::ExifTag;
+use crate::filetype::FileExtension;
+use crate::ifd::ExifTagGroup;
+use crate::jpg::MAX_EXIF_LENGTH;
+
+use super::ExifSizeReduction;
+use super::Metadata;
+use super::RemovedExifData;
+
+impl
+Metadata
+{
+	/// Reduces the size of the metadata until its encoded EXIF data fits into
+	/// the APP1 segment of a JPEG (64 KB), using the given reductions in the
+	/// order of their fields. This is done automatically when writing to a
+	/// JPEG, see `WriteOptions`, but calling it beforehand allows to find out
+	/// what gets removed.
+	/// Returns what was removed, or `Error::ExifTooLarge` if the data doesn't
+	/// fit even after the reductions, in which case the metadata is unchanged.
+	///
+	/// # Examples
+	/// ```no_run
+	/// use little_exif::metadata::ExifSizeReduction;
+	/// use little_exif::metadata::Metadata;
+	///
+	/// let mut metadata = Metadata::new_from_path(std::path::Path::new("image.jpg")).unwrap();
+	/// for removed in metadata.reduce_for_jpeg(&ExifSizeReduction::all()).unwrap()
+	/// {
+	///     println!("Removed {:?}", removed);
+	/// }
+	/// ```
+	pub fn
+	reduce_for_jpeg
+	(
+		&mut self,
+		reduction: &ExifSizeReduction
+	)
+	-> Result<Vec<RemovedExifData>, Error>
+	{
+		let mut reduced = self.clone();
+		let mut removed = Vec::new();
+
+		let mut size = reduced.encode()?.len();
+
+		if size > MAX_EXIF_LENGTH
+		&& reduction.drop_thumbnail
+		&& reduced.remove_thumbnail()
+		{
+			removed.push(RemovedExifData::Thumbnail);
+			size = reduced.encode()?.len();
+		}
+
+		if size > MAX_EXIF_LENGTH
+		&& reduction.drop_maker_note
+		&& reduced.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_some()
+		{
+			reduced.remove_tag(ExifTag::MakerNote(Vec::new()));
+			removed.push(RemovedExifData::MakerNote);
+			size = reduced.encode()?.len();
+		}
+
+		while size > MAX_EXIF_LENGTH && reduction.drop_unknown_tags
+		{
+			match reduced.remove_largest_unknown_tag()
+			{
+				Some(removed_tag) => removed.push(removed_tag),
+				None              => break,
+			}
+			size = reduced.encode()?.len();
+		}
+
+		if size > MAX_EXIF_LENGTH
+		{
+			return Err(Error::ExifTooLarge { size, maximum: MAX_EXIF_LENGTH });
+		}
+
+		*self = reduced;
+		return Ok(removed);
+	}
+
+	/// Gets the metadata to write to a file of the given type, with its size
+	/// reduced if it would not fit otherwise
+	pub(crate) fn
+	reduced_for_file_type
+	(
+		&self,
+		file_type: FileExtension,
+		reduction: &ExifSizeReduction
+	)
+	-> Result<Cow<'_, Metadata>, Error>
+	{
+		if file_type != FileExtension::JPEG || self.encode()?.len() <= MAX_EXIF_LENGTH
+		{
+			return Ok(Cow::Borrowed(self));
+		}
+
+		let mut reduced = self.clone();
+		for removed in reduced.reduce_for_jpeg(reduction)?
+		{
+			warn!("EXIF data exceeds the maximum size of a JPEG APP1 segment, removed {:?}", removed);
+		}
+
+		return Ok(Cow::Owned(reduced));
+	}
+
+	/// Removes the largest unknown tag that is part of the encoded data and
+	/// returns what was removed
+	fn
+	remove_largest_unknown_tag
+	(
+		&mut self
+	)
+	-> Option<RemovedExifData>
+	{
+		let endian = self.endian.clone();
+
+		let (ifd, tag) = self.image_file_directories
+			.iter_mut()
+			.filter(|ifd| ifd.get_ifd_type() != ExifTagGroup::MAKERNOTES)
+			.flat_map(|ifd| ifd.get_tags()
+				.iter()
+				.filter(|tag| tag.is_unknown())
+				.map(|tag| (tag.value_as_u8_vec(&endian).len(), tag.clone()))
+				.max_by_key(|(length, _)| *length)
+				.map(|largest| (largest, ifd))
+			)
+			.max_by_key(|((length, _), _)| *length)
+			.map(|((_, tag), ifd)| (ifd, tag))?;
+
+		ifd.remove_tag(&tag);
+
+		return Some(RemovedExifData::UnknownTag {
+			hex:            tag.as_u16(),
+			group:          ifd.get_ifd_type(),
+			generic_ifd_nr: ifd.get_generic_ifd_nr(),
+		});
+	}
+}
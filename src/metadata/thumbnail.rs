@@ -0,0 +1,139 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::exif_tag::ExifTag;
+use crate::ifd::ExifTagGroup;
+use crate::jpg::JPG_SIGNATURE;
+use crate::rational::uR64;
+
+use super::Metadata;
+
+/// Value of the Compression tag for JPEG compressed thumbnails
+const JPEG_COMPRESSION: u16 = 6;
+
+impl
+Metadata
+{
+	/// Gets the JPEG thumbnail stored in IFD1, if there is any.
+	///
+	/// # Examples
+	/// ```no_run
+	/// use little_exif::metadata::Metadata;
+	///
+	/// let metadata = Metadata::new_from_path(std::path::Path::new("image.jpg")).unwrap();
+	/// if let Some(thumbnail) = metadata.thumbnail()
+	/// {
+	///     std::fs::write("thumbnail.jpg", thumbnail).unwrap();
+	/// }
+	/// ```
+	pub fn
+	thumbnail
+	(
+		&self
+	)
+	-> Option<&[u8]>
+	{
+		return self.get_ifd(ExifTagGroup::GENERIC, 1)?
+			.get_tags()
+			.iter()
+			.find_map(|tag| match tag
+			{
+				ExifTag::ThumbnailOffset(_, data) if !data.is_empty() => Some(data.as_slice()),
+				_                                                     => None,
+			});
+	}
+
+	/// Sets the JPEG thumbnail, replacing the previous one. IFD1 gets created
+	/// if necessary, together with the Compression tag and, if they don't
+	/// exist yet, the resolution tags (using 72 dpi).
+	/// Fails if the data is not a JPEG image or if IFD1 holds image data,
+	/// like the second page of a multi-page TIFF.
+	pub fn
+	set_thumbnail
+	(
+		&mut self,
+		jpeg_data: Vec<u8>
+	)
+	-> Result<(), Error>
+	{
+		if !jpeg_data.starts_with(&JPG_SIGNATURE)
+		{
+			return container_error!("The thumbnail needs to be a JPEG image!");
+		}
+
+		if self.ifd1_holds_image_data()
+		{
+			return container_error!("IFD1 holds image data and can't store a thumbnail!");
+		}
+
+		// IFD1 is linked via IFD0, so that one is needed as well
+		self.create_ifd(ExifTagGroup::GENERIC, 0);
+		let ifd1 = self.get_ifd_mut(ExifTagGroup::GENERIC, 1);
+
+		ifd1.set_tag(ExifTag::Compression(vec![JPEG_COMPRESSION]));
+
+		let default_resolution = uR64 { nominator: 72, denominator: 1 };
+		for default_tag in [
+			ExifTag::XResolution(vec![default_resolution.clone()]),
+			ExifTag::YResolution(vec![default_resolution]),
+			ExifTag::ResolutionUnit(vec![2]),                                   // Inches
+		]
+		{
+			if !ifd1.get_tags().iter().any(|tag| tag.as_u16() == default_tag.as_u16())
+			{
+				ifd1.set_tag(default_tag);
+			}
+		}
+
+		ifd1.set_tag(ExifTag::ThumbnailLength(vec![jpeg_data.len() as u32]));
+		ifd1.set_tag(ExifTag::ThumbnailOffset(Vec::new(), jpeg_data));
+
+		return Ok(());
+	}
+
+	/// Removes the JPEG thumbnail. As IFD1 has no other purpose in this case,
+	/// it gets removed entirely unless it holds image data or is followed by
+	/// further generic IFDs.
+	/// Returns whether there was a thumbnail to remove.
+	pub fn
+	remove_thumbnail
+	(
+		&mut self
+	)
+	-> bool
+	{
+		if self.thumbnail().is_none()
+		{
+			return false;
+		}
+
+		if self.ifd1_holds_image_data() || self.get_max_generic_ifd_number() > 1
+		{
+			let ifd1 = self.get_ifd_mut(ExifTagGroup::GENERIC, 1);
+			ifd1.remove_tag(&ExifTag::ThumbnailOffset(Vec::new(), Vec::new()));
+			ifd1.remove_tag(&ExifTag::ThumbnailLength(Vec::new()));
+		}
+		else
+		{
+			self.image_file_directories.retain(|ifd| ifd.get_generic_ifd_nr() != 1);
+		}
+
+		return true;
+	}
+
+	fn
+	ifd1_holds_image_data
+	(
+		&self
+	)
+	-> bool
+	{
+		return self.get_ifd(ExifTagGroup::GENERIC, 1).map_or(false, |ifd|
+			ifd.get_tags().iter().any(|tag| matches!(tag, 
+				ExifTag::StripOffsets(_, _) | ExifTag::TileOffsets(_, _)
+			))
+		);
+	}
+}
@@ -0,0 +1,153 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use crate::c2pa::C2paPolicy;
+use crate::ifd::ExifTagGroup;
+
+/// Options that control how an image file gets modified when writing or
+/// clearing its metadata.
+///
+/// By default, writing is atomic: The new contents are written to a temporary
+/// file next to the original, which is then synced to the disk and renamed to
+/// replace the original file (keeping its permissions). If anything goes 
+/// wrong along the way, the original file stays untouched.
+/// With `atomic` set to `false`, the file gets modified in place instead. This
+/// doesn't require additional disk space and keeps e.g. the inode and any
+/// hard links of the file, but a crash or full disk during the write may 
+/// leave the image damaged.
+///
+/// Writing the metadata invalidates a C2PA manifest of the image. By default,
+/// the manifest is preserved and a warning gets logged if it is signed, see
+/// `C2paPolicy` for the alternatives.
+///
+/// If the EXIF data is too large for the file format, parts of it may get
+/// removed as decided by the `ExifSizeReduction`.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::c2pa::C2paPolicy;
+/// use little_exif::metadata::Metadata;
+/// use little_exif::metadata::WriteOptions;
+///
+/// let metadata = Metadata::new();
+/// metadata.write_to_file_with_options(
+///     std::path::Path::new("image.jpg"),
+///     &WriteOptions { atomic: false, c2pa: C2paPolicy::Strip, ..WriteOptions::default() }
+/// ).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct
+WriteOptions
+{
+	pub atomic:              bool,
+	pub c2pa:                C2paPolicy,
+	pub exif_size_reduction: ExifSizeReduction,
+}
+
+impl
+Default
+for
+WriteOptions
+{
+	fn
+	default
+	()
+	-> Self
+	{
+		WriteOptions { 
+			atomic:              true, 
+			c2pa:                C2paPolicy::default(), 
+			exif_size_reduction: ExifSizeReduction::default(),
+		}
+	}
+}
+
+/// Decides what may be removed from the EXIF data if it is too large for the
+/// file format. This is currently only the case for JPEG, where it has to fit
+/// into a single APP1 segment of at most 64 KB.
+/// The enabled reductions are applied in the order of the fields until the
+/// data fits. If it still doesn't, writing fails with `Error::ExifTooLarge`
+/// instead of producing a broken image.
+/// By default, only the thumbnail may be dropped.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::metadata::ExifSizeReduction;
+/// use little_exif::metadata::Metadata;
+/// use little_exif::metadata::WriteOptions;
+///
+/// let metadata = Metadata::new();
+/// metadata.write_to_file_with_options(
+///     std::path::Path::new("image.jpg"),
+///     &WriteOptions { 
+///         exif_size_reduction: ExifSizeReduction::none(), 
+///         ..WriteOptions::default() 
+///     }
+/// ).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct
+ExifSizeReduction
+{
+	/// Removes the JPEG thumbnail of IFD1
+	pub drop_thumbnail:    bool,
+
+	/// Removes the MakerNote tag, which often holds several kilobytes of 
+	/// vendor specific data
+	pub drop_maker_note:   bool,
+
+	/// Removes tags unknown to little_exif, starting with the largest one
+	pub drop_unknown_tags: bool,
+}
+
+impl
+ExifSizeReduction
+{
+	/// Doesn't allow any reductions, so writing fails if the data is too large
+	pub fn
+	none
+	()
+	-> Self
+	{
+		ExifSizeReduction { drop_thumbnail: false, drop_maker_note: false, drop_unknown_tags: false }
+	}
+
+	/// Allows all reductions
+	pub fn
+	all
+	()
+	-> Self
+	{
+		ExifSizeReduction { drop_thumbnail: true, drop_maker_note: true, drop_unknown_tags: true }
+	}
+}
+
+impl
+Default
+for
+ExifSizeReduction
+{
+	fn
+	default
+	()
+	-> Self
+	{
+		ExifSizeReduction { drop_thumbnail: true, drop_maker_note: false, drop_unknown_tags: false }
+	}
+}
+
+/// Data that got removed to reduce the size of the EXIF data, see 
+/// `ExifSizeReduction`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum
+RemovedExifData
+{
+	Thumbnail,
+	MakerNote,
+	UnknownTag
+	{
+		hex:            u16,
+		group:          ExifTagGroup,
+		generic_ifd_nr: u32,
+	},
+}
@@ -0,0 +1,314 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+mod photoshop_io;
+
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::error::container_error;
+use crate::u8conversion::*;
+
+/// Resolution information (ResolutionInfo structure)
+pub const RESOLUTION_INFO_RESOURCE_ID: u16 = 0x03ed;
+
+/// IPTC-IIM data, see `little_exif::iptc::Iptc`
+pub const IPTC_RESOURCE_ID:            u16 = 0x0404;
+
+/// JPEG thumbnail, as written by Photoshop 5.0 and later
+pub const THUMBNAIL_RESOURCE_ID:       u16 = 0x040c;
+
+/// ICC profile
+pub const ICC_PROFILE_RESOURCE_ID:     u16 = 0x040f;
+
+/// List of URLs
+pub const URL_LIST_RESOURCE_ID:        u16 = 0x041e;
+
+/// Copy of the XMP packet
+pub const XMP_RESOURCE_ID:             u16 = 0x0424;
+
+/// MD5 digest of the IPTC-IIM data, which Photoshop uses to detect whether
+/// another application modified the IPTC data
+pub const IPTC_DIGEST_RESOURCE_ID:     u16 = 0x0425;
+
+/// Besides the usual `8BIM`, some (older) applications use other signatures
+/// for their image resource blocks
+const RESOURCE_SIGNATURES: [&[u8; 4]; 5] = [b"8BIM", b"MeSa", b"PHUT", b"AgHg", b"DCSR"];
+
+/// An image resource block as used by Photoshop, see the "Adobe Photoshop
+/// File Formats Specification", section "Image Resource Blocks":
+/// - signature (4 bytes), usually `8BIM`
+/// - ID of the resource (2 bytes)
+/// - name as Pascal string, padded to an even length
+/// - size of the data (4 bytes)
+/// - data, padded to an even length
+///
+/// The name is usually empty and kept as raw bytes, as its encoding is not
+/// specified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+ImageResource
+{
+	pub signature: [u8; 4],
+	pub id:        u16,
+	pub name:      Vec<u8>,
+	pub data:      Vec<u8>,
+}
+
+impl
+ImageResource
+{
+	/// Constructs a new `8BIM` image resource without a name
+	pub fn
+	new
+	(
+		id:   u16,
+		data: Vec<u8>
+	)
+	-> ImageResource
+	{
+		return ImageResource {
+			signature: *b"8BIM",
+			id,
+			name:      Vec::new(),
+			data,
+		};
+	}
+}
+
+/// The Photoshop image resources of an image, as stored e.g. in the APP13
+/// segments of JPEG files, which hold e.g. the IPTC data, a thumbnail or the
+/// resolution info.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::filetype::FileExtension;
+/// use little_exif::photoshop::PhotoshopResources;
+/// use little_exif::photoshop::THUMBNAIL_RESOURCE_ID;
+///
+/// let mut file_data = std::fs::read("image.jpg").unwrap();
+/// let mut resources = PhotoshopResources::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+///
+/// for resource in resources.get_resources()
+/// {
+///     println!("0x{:04x}: {} bytes", resource.id, resource.data.len());
+/// }
+///
+/// resources.remove_resource(THUMBNAIL_RESOURCE_ID);
+/// resources.write_to_vec(&mut file_data, FileExtension::JPEG).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+PhotoshopResources
+{
+	resources: Vec<ImageResource>,
+}
+
+impl
+PhotoshopResources
+{
+	/// Constructs a new, empty `PhotoshopResources` object
+	pub fn
+	new
+	()
+	-> PhotoshopResources
+	{
+		return PhotoshopResources::default();
+	}
+
+	/// Parses the image resource blocks stored one after another in the given
+	/// data. Trailing zero bytes (e.g. padding) are ignored
+	pub fn
+	new_from_bytes
+	(
+		data: &[u8]
+	)
+	-> Result<PhotoshopResources, Error>
+	{
+		let mut resources = Vec::new();
+		let mut position  = 0;
+
+		while position < data.len()
+		{
+			let remaining = &data[position..];
+
+			if remaining.iter().all(|byte| *byte == 0)
+			{
+				break;
+			}
+
+			if remaining.len() < 4 || !RESOURCE_SIGNATURES.iter().any(|signature| remaining.starts_with(*signature))
+			{
+				return container_error!(format!("Invalid image resource signature at position {}!", position));
+			}
+
+			// Signature, ID and the length byte of the name
+			if remaining.len() < 7
+			{
+				return container_error!("Image resource block is truncated!");
+			}
+
+			let mut signature = [0u8; 4];
+			signature.copy_from_slice(&remaining[0..4]);
+			let id = from_u8_vec_macro!(u16, &remaining[4..6], &Endian::Big);
+
+			// The name including its length byte is padded to an even length
+			let name_length       = remaining[6] as usize;
+			let name_field_length = (1 + name_length + 1) & !1;
+			let size_position     = 6 + name_field_length;
+
+			if remaining.len() < size_position + 4
+			{
+				return container_error!("Image resource block is truncated!");
+			}
+
+			let name      = remaining[7..7+name_length].to_vec();
+			let data_size = from_u8_vec_macro!(u32, &remaining[size_position..size_position+4], &Endian::Big) as usize;
+			let data_start = size_position + 4;
+
+			if remaining.len() - data_start < data_size
+			{
+				return container_error!(format!("Data of image resource 0x{:04x} is truncated!", id));
+			}
+
+			resources.push(ImageResource {
+				signature,
+				id,
+				name,
+				data: remaining[data_start..data_start+data_size].to_vec(),
+			});
+
+			// The padding byte of the data may be missing for the last resource
+			let padded_data_end = data_start + ((data_size + 1) & !1);
+			position += padded_data_end.min(remaining.len());
+		}
+
+		return Ok(PhotoshopResources { resources });
+	}
+
+	/// Encodes the image resource blocks
+	pub fn
+	encode
+	(
+		&self
+	)
+	-> Vec<u8>
+	{
+		let mut encoded = Vec::new();
+
+		for resource in &self.resources
+		{
+			// Pascal strings can't be longer than 255 bytes
+			let name_length = resource.name.len().min(u8::MAX as usize);
+
+			encoded.extend(resource.signature.iter());
+			encoded.extend(to_u8_vec_macro!(u16, &resource.id, &Endian::Big));
+			encoded.push(name_length as u8);
+			encoded.extend(resource.name[..name_length].iter());
+			if name_length % 2 == 0
+			{
+				encoded.push(0x00);
+			}
+
+			encoded.extend(to_u8_vec_macro!(u32, &(resource.data.len() as u32), &Endian::Big));
+			encoded.extend(resource.data.iter());
+			if resource.data.len() % 2 == 1
+			{
+				encoded.push(0x00);
+			}
+		}
+
+		return encoded;
+	}
+
+	/// Gets the image resources in the order they are stored
+	pub fn
+	get_resources
+	(
+		&self
+	)
+	-> &[ImageResource]
+	{
+		return &self.resources;
+	}
+
+	/// Gets the (first) image resource with the given ID
+	pub fn
+	get_resource
+	(
+		&self,
+		id: u16
+	)
+	-> Option<&ImageResource>
+	{
+		return self.resources.iter().find(|resource| resource.id == id);
+	}
+
+	/// Replaces the image resources with the same ID as the given one. If
+	/// there is no such resource, the new one is inserted before the first
+	/// resource with a larger ID, as Photoshop stores them in ascending order
+	pub fn
+	set_resource
+	(
+		&mut self,
+		resource: ImageResource
+	)
+	{
+		let insert_position = match self.resources.iter().position(|existing| existing.id == resource.id)
+		{
+			Some(position) => position,
+			None           => self.resources.iter()
+				.position(|existing| existing.id > resource.id)
+				.unwrap_or(self.resources.len()),
+		};
+
+		self.remove_resource(resource.id);
+		self.resources.insert(insert_position, resource);
+	}
+
+	/// Removes all image resources with the given ID and returns whether
+	/// there were any
+	pub fn
+	remove_resource
+	(
+		&mut self,
+		id: u16
+	)
+	-> bool
+	{
+		let count_before = self.resources.len();
+		self.resources.retain(|resource| resource.id != id);
+		return self.resources.len() != count_before;
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn
+	parse_and_encode()
+	{
+		let mut resources = PhotoshopResources::new();
+		resources.set_resource(ImageResource { signature: *b"PHUT", id: 0x0404, name: Vec::new(),      data: vec![3, 4, 5] });
+		resources.set_resource(ImageResource { signature: *b"8BIM", id: 0x03ed, name: b"Res".to_vec(), data: vec![1, 2] });
+
+		// Sorted by ID
+		assert_eq!(resources.get_resources()[0].id, 0x03ed);
+
+		let encoded = resources.encode();
+		assert_eq!(encoded.len() % 2, 0);
+		assert_eq!(PhotoshopResources::new_from_bytes(&encoded).unwrap(), resources);
+
+		// A missing padding byte at the end is tolerated, trailing garbage not
+		assert_eq!(PhotoshopResources::new_from_bytes(&encoded[..encoded.len()-1]).unwrap(), resources);
+		assert!(PhotoshopResources::new_from_bytes(&[encoded.clone(), b"junk".to_vec()].concat()).is_err());
+
+		// Replacing keeps the position
+		resources.set_resource(ImageResource::new(0x03ed, vec![6]));
+		assert_eq!(resources.get_resources()[0].data, vec![6]);
+		assert!( resources.remove_resource(0x0404));
+		assert!(!resources.remove_resource(0x0404));
+	}
+}
@@ -0,0 +1,153 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::c2pa;
+use crate::error::Error;
+use crate::error::unsupported_format;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file;
+use crate::metadata::WriteOptions;
+
+use crate::jpg;
+use crate::tiff;
+
+use super::PhotoshopResources;
+
+impl
+PhotoshopResources
+{
+	/// Constructs a new `PhotoshopResources` object with the image resources
+	/// of an image that is stored in memory. For JPEG images, these are
+	/// stored in the APP13 segments, for TIFF images in the Photoshop tag
+	/// (34377) of IFD0. Images without image resources result in an empty
+	/// object.
+	#[allow(unreachable_patterns)]
+	pub fn
+	new_from_vec
+	<T: AsRef<[u8]> + ?Sized>
+	(
+		file_buffer: &T,
+		file_type:   FileExtension
+	)
+	-> Result<PhotoshopResources, Error>
+	{
+		let file_buffer = file_buffer.as_ref();
+
+		let resource_data = match file_type
+		{
+			FileExtension::JPEG
+				=>  jpg::read_photoshop_resources(file_buffer)?,
+			FileExtension::TIFF
+				=> tiff::vec::read_photoshop_resources(file_buffer)?,
+			_
+				=> return unsupported_format!(
+					format!(
+						"Function 'new_from_vec' not available for {:?} (only JPEG and TIFF)",
+						file_type
+					)
+				),
+		};
+
+		return Self::new_from_bytes(&resource_data);
+	}
+
+	/// Constructs a new `PhotoshopResources` object with the image resources
+	/// of the image at the specified path
+	pub fn
+	new_from_path
+	(
+		path: &Path
+	)
+	-> Result<PhotoshopResources, Error>
+	{
+		let file_type   = get_file_type(path)?;
+		let file_buffer = std::fs::read(path)?;
+		return Self::new_from_vec(&file_buffer, file_type);
+	}
+
+	/// Writes the image resources to an image stored as a `Vec<u8>`,
+	/// replacing the ones that are already present. If there are no image
+	/// resources, the APP13 segments (JPEG) or the Photoshop tag (TIFF) get
+	/// removed.
+	pub fn
+	write_to_vec
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension
+	)
+	-> Result<(), Error>
+	{
+		return self.write_to_vec_with_options(file_buffer, file_type, &WriteOptions::default());
+	}
+
+	/// Same as `write_to_vec`, with the C2PA policy of the given options
+	/// deciding what happens to a C2PA manifest of the image. The other 
+	/// options have no effect here.
+	#[allow(unreachable_patterns)]
+	pub fn
+	write_to_vec_with_options
+	(
+		&self,
+		file_buffer: &mut Vec<u8>,
+		file_type:   FileExtension,
+		options:     &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
+		let resource_data = self.encode();
+
+		return match file_type
+		{
+			FileExtension::JPEG
+				=>  jpg::write_photoshop_resources(file_buffer, &resource_data),
+			FileExtension::TIFF
+				=> tiff::vec::write_photoshop_resources(file_buffer, &resource_data),
+			_
+				=> unsupported_format!(
+					format!(
+						"Function 'write_to_vec' not available for {:?} (only JPEG and TIFF)",
+						file_type
+					)
+				),
+		};
+	}
+
+	/// Writes the image resources to the image at the specified path, which
+	/// gets replaced atomically
+	pub fn
+	write_to_file
+	(
+		&self,
+		path: &Path
+	)
+	-> Result<(), Error>
+	{
+		return self.write_to_file_with_options(path, &WriteOptions::default());
+	}
+
+	/// Writes the image resources to the image at the specified path, with
+	/// the given options deciding whether the file gets replaced atomically or
+	/// modified in place and what happens to a C2PA manifest of the image
+	pub fn
+	write_to_file_with_options
+	(
+		&self,
+		path:    &Path,
+		options: &WriteOptions
+	)
+	-> Result<(), Error>
+	{
+		let file_type = get_file_type(path)?;
+		return modify_file(
+			path,
+			options.atomic,
+			|file_buffer| self.write_to_vec_with_options(file_buffer, file_type, options)
+		);
+	}
+}
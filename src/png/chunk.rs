@@ -112,6 +112,7 @@ build_png_chunk_type_enum![
     (pHYs,  false,      false,      BEFORE_IDAT),
     (sPLT,  false,      true,       BEFORE_IDAT),
 
+    (caBX,  false,      false,      NONE),
     (eXIf,  false,      false,      NONE), // not sure if ordering is correct
     (tIME,  false,      false,      NONE),
     (iTXt,  false,      true,       NONE),
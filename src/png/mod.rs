@@ -19,10 +19,12 @@ use crc::Crc;
 use crc::CRC_32_ISO_HDLC;
 use log::warn;
 use miniz_oxide::deflate::compress_to_vec_zlib;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
 use text::construct_similar_with_new_data;
 use text::get_data_from_text_chunk;
 
-use crate::general_file_io::io_error;
+use crate::error::Error;
+use crate::error::container_error;
 use crate::general_file_io::open_read_file;
 use crate::general_file_io::EXIF_HEADER;
 use crate::general_file_io::LITTLE_ENDIAN_INFO;
@@ -40,6 +42,7 @@ use crate::png::read::read_chunk_crc;
 use crate::png::text::get_keyword_from_text_chunk;
 
 use crate::xmp::remove_exif_from_xmp;
+use crate::util::copy_n_bytes;
 use crate::util::range_remove;
 
 pub(crate) const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
@@ -50,6 +53,10 @@ pub(crate) const RAW_PROFILE_TYPE_EXIF: [u8; 21] = [
 	0x65, 0x78, 0x69, 0x66,                             // exif
 ];
 
+/// Name of the ICC profile stored in newly written iCCP chunks. The name is
+/// only informative, but must not be empty
+const ICC_PROFILE_NAME: &[u8] = b"ICC Profile";
+
 pub(crate) const XML_COM_ADOBE_XMP: [u8; 17] = [
 	0x58, 0x4d, 0x4c, 0x3a,                 // XML:
 	0x63, 0x6f, 0x6d, 0x2e,                 // com.
@@ -74,22 +81,16 @@ fn encode_byte(byte: &u8) -> [u8; 2]
 
 
 
-fn
+pub(crate) fn
 check_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Cursor<&Vec<u8>>, std::io::Error>
+-> Result<Cursor<&[u8]>, Error>
 {	
-	// Check the signature
-	let signature_is_valid = file_buffer[0..8].iter()
-		.zip(PNG_SIGNATURE.iter())
-		.filter(|&(read, constant)| read == constant)
-		.count() == PNG_SIGNATURE.len();
-
-	if !signature_is_valid
+	if !starts_with_png_signature(file_buffer)
 	{
-		return io_error!(InvalidData, "Can't open PNG file - Wrong signature!");
+		return container_error!("Can't open PNG file - Wrong signature!");
 	}
 
 	// Signature is valid - can proceed using the data as PNG file
@@ -98,19 +99,41 @@ check_signature
 	return Ok(cursor);
 }
 
+/// Checks if the given data starts with the 8 bytes of the PNG signature
+pub(crate) fn
+starts_with_png_signature
+(
+	file_buffer: &[u8]
+)
+-> bool
+{
+	if file_buffer.len() < PNG_SIGNATURE.len()
+	{
+		return false;
+	}
+
+	// Check the signature
+	let signature_is_valid = file_buffer[0..8].iter()
+		.zip(PNG_SIGNATURE.iter())
+		.filter(|&(read, constant)| read == constant)
+		.count() == PNG_SIGNATURE.len();
+
+	return signature_is_valid;
+}
+
 fn
 file_check_signature
 (
 	path: &Path
 )
--> Result<File, std::io::Error>
+-> Result<File, Error>
 {
 	let mut file = open_read_file(path)?;
 	
 	// Check the signature
 	let mut signature_buffer = [0u8; 8];
 	file.read(&mut signature_buffer)?;
-	check_signature(&signature_buffer.to_vec())?;
+	check_signature(&signature_buffer)?;
 
 	// Signature is valid - can proceed using the file as PNG file
 	return Ok(file);
@@ -125,9 +148,9 @@ file_check_signature
 pub(crate) fn
 vec_parse_png
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Vec<PngChunk>, std::io::Error>
+-> Result<Vec<PngChunk>, Error>
 {
 	let mut cursor = check_signature(file_buffer)?;
 	return generic_parse_png(&mut cursor);
@@ -141,7 +164,7 @@ file_parse_png
 (
 	path: &Path
 )
--> Result<Vec<PngChunk>, std::io::Error>
+-> Result<Vec<PngChunk>, Error>
 {
 	let mut file = file_check_signature(path)?;
 	return generic_parse_png(&mut file);
@@ -153,7 +176,7 @@ generic_parse_png
 (
 	cursor: &mut T
 )
--> Result<Vec<PngChunk>, std::io::Error>
+-> Result<Vec<PngChunk>, Error>
 {
 	let mut chunks = Vec::new();
 
@@ -182,7 +205,7 @@ get_next_chunk_descriptor
 (
 	cursor: &mut T
 )
--> Result<PngChunk, std::io::Error>
+-> Result<PngChunk, Error>
 {
 	// Read the start of the chunk, its data and CRC
 	let chunk_length = read_chunk_length(cursor)?;
@@ -190,21 +213,7 @@ get_next_chunk_descriptor
 	let chunk_data   = read_chunk_data(cursor, chunk_length as usize)?;
 	let chunk_crc    = read_chunk_crc(cursor)?;
 
-	// Compute CRC on chunk
-	let mut crc_input = Vec::new();
-	crc_input.extend(chunk_name.bytes().into_iter());
-	crc_input.extend(chunk_data.iter());
-
-	let crc_struct = Crc::<u32>::new(&CRC_32_ISO_HDLC);
-	let checksum = crc_struct.checksum(&crc_input) as u32;
-
-	for i in 0..4
-	{
-		if ((checksum >> (8 * (3-i))) as u8) != chunk_crc[i]
-		{
-			return io_error!(InvalidData, "Checksum check failed while reading PNG!");
-		}
-	}
+	check_chunk_crc(&chunk_name, &chunk_data, &chunk_crc)?;
 
 	// If validating the chunk using the CRC was successful, return its descriptor
 	// Note: chunk_length does NOT include the +4 for the CRC area!
@@ -226,12 +235,53 @@ get_next_chunk_descriptor
 
 
 
+/// Computes the CRC of the chunk with the given name and data and compares
+/// it with the CRC that was read
+fn
+check_chunk_crc
+(
+	chunk_name: &str,
+	chunk_data: &[u8],
+	chunk_crc:  &[u8; 4]
+)
+-> Result<(), Error>
+{
+	let mut crc_input = Vec::new();
+	crc_input.extend(chunk_name.bytes());
+	crc_input.extend(chunk_data.iter());
+
+	let crc_struct = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+	let checksum   = crc_struct.checksum(&crc_input);
+
+	if checksum.to_be_bytes() != *chunk_crc
+	{
+		return container_error!("Checksum check failed while reading PNG!");
+	}
+
+	return Ok(());
+}
+
+/// Checks if the keyword of the given text chunk data matches the given one
+fn
+has_keyword
+(
+	chunk_data: &[u8],
+	keyword:    &[u8]
+)
+-> bool
+{
+	return get_keyword_from_text_chunk(chunk_data).as_bytes() == keyword;
+}
+
+
+
+
 pub(crate) fn
 read_metadata
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// Parse the PNG - if this fails, the read fails as well
 	let parse_png_result = vec_parse_png(file_buffer)?;
@@ -242,12 +292,121 @@ read_metadata
 	return generic_read_metadata(&mut cursor, &parse_png_result);
 }
 
+/// Reads the raw EXIF data from the PNG data provided by the given reader.
+/// In contrast to `read_metadata`, the PNG does not get parsed completely
+/// first as this would require reading all of the data. Instead, only chunks
+/// that might contain the EXIF data are read (and their CRC checked), all 
+/// other chunks are skipped.
+#[allow(non_snake_case)]
+pub(crate) fn
+read_metadata_from_stream
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Vec<u8>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(PNG_SIGNATURE.len() as u64).read_to_end(&mut signature_buffer)?;
+	if !starts_with_png_signature(&signature_buffer)
+	{
+		return container_error!("Can't open PNG file - Wrong signature!");
+	}
+
+	loop
+	{
+		let chunk_length = read_chunk_length(input)?;
+		let chunk_name   = read_chunk_name(input)?;
+
+		match chunk_name.as_str()
+		{
+			"eXIf" | "tEXt" | "zTXt" | "iTXt" => {
+				let chunk_data = read_chunk_data(input, chunk_length as usize)?;
+				let chunk_crc  = read_chunk_crc(input)?;
+				check_chunk_crc(&chunk_name, &chunk_data, &chunk_crc)?;
+
+				if chunk_name == "eXIf"
+				{
+					return Ok(chunk_data);
+				}
+
+				if has_keyword(&chunk_data, &RAW_PROFILE_TYPE_EXIF)
+				{
+					let decompressed_data = get_data_from_text_chunk(
+						chunk_name.as_str(), 
+						&chunk_data
+					)?;
+
+					return decode_metadata_png(&decompressed_data);
+				}
+			},
+
+			"IEND" => {
+				return Err(Error::NoExifData);
+			},
+
+			_ => {
+				// Skip the chunk data and CRC
+				input.seek(SeekFrom::Current(chunk_length as i64 + 4))?;
+			}
+		}
+	}
+}
+
+/// Determines the name of the text chunk (e.g. `zTXt`) that holds the EXIF
+/// data of the PNG provided by the given reader. Returns `None` if the EXIF
+/// data is stored in an `eXIf` chunk or if there is none at all. Like with 
+/// `read_metadata_from_stream`, all other chunks are skipped.
+pub(crate) fn
+get_exif_text_chunk_name
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Option<String>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(PNG_SIGNATURE.len() as u64).read_to_end(&mut signature_buffer)?;
+	if !starts_with_png_signature(&signature_buffer)
+	{
+		return container_error!("Can't open PNG file - Wrong signature!");
+	}
+
+	loop
+	{
+		let chunk_length = read_chunk_length(input)?;
+		let chunk_name   = read_chunk_name(input)?;
+
+		match chunk_name.as_str()
+		{
+			"eXIf" | "IEND" => {
+				return Ok(None);
+			},
+
+			"tEXt" | "zTXt" | "iTXt" => {
+				let chunk_data = read_chunk_data(input, chunk_length as usize)?;
+				read_chunk_crc(input)?;
+
+				if has_keyword(&chunk_data, &RAW_PROFILE_TYPE_EXIF)
+				{
+					return Ok(Some(chunk_name));
+				}
+			},
+
+			_ => {
+				// Skip the chunk data and CRC
+				input.seek(SeekFrom::Current(chunk_length as i64 + 4))?;
+			}
+		}
+	}
+}
+
 pub(crate) fn
 file_read_metadata
 (
 	path: &Path
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// Parse the PNG - if this fails, the read fails as well
 	let parse_png_result = file_parse_png(path)?;
@@ -266,7 +425,7 @@ generic_read_metadata
 	cursor:     &mut T,
 	parsed_png: &Vec<PngChunk>
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	for chunk in parsed_png
 	{
@@ -328,7 +487,7 @@ generic_read_metadata
 					&chunk_data
 				)?;
 				
-				return Ok(decode_metadata_png(&decompressed_data).unwrap());
+				return decode_metadata_png(&decompressed_data);
 			}
 
 			_ => {
@@ -338,7 +497,7 @@ generic_read_metadata
 		};
 	}
 
-	return io_error!(Other, "No metadata found!");
+	return Err(Error::NoExifData);
 
 }
 
@@ -353,7 +512,7 @@ file_clear_metadata
 (
 	path: &Path
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Load the entire file into memory instead of reading one byte at a time
 	// to improve the overall speed
@@ -381,7 +540,7 @@ clear_metadata
 (
 	file_buffer: &mut Vec<u8>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Parse the PNG - if this fails, the clear operation fails as well
 	let parse_png_result = vec_parse_png(&file_buffer)?;
@@ -485,7 +644,7 @@ remove_chunk_at
 (
 	cursor: &mut Cursor<&mut Vec<u8>>,
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	let chunk_start_position = cursor.position() as usize;
 	let chunk_length         = read_chunk_length(cursor)?;
@@ -515,24 +674,17 @@ clear_exif_from_xmp_metadata
 	cursor:     &mut Cursor<&mut Vec<u8>>,
 	chunk_data: &[u8],
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Read the chunk name and seek back
 	let _          = read_chunk_length(cursor)?;
 	let chunk_name = read_chunk_name(cursor)?;
 	cursor.seek(SeekFrom::Current(-8))?;
 
-	// Clear the EXIF from the XMP data
-	let clean_xmp_data = remove_exif_from_xmp(
-		// &chunk_data[XML_COM_ADOBE_XMP.len()..]
-		&get_data_from_text_chunk(chunk_name.as_str(), &chunk_data)?
-	).unwrap();
-
-	// Construct new chunk data field
-	let new_chunk_data = construct_similar_with_new_data(
+	// Construct new chunk data field without EXIF in the XMP data
+	let new_chunk_data = construct_xmp_chunk_data_without_exif(
 		chunk_name.as_str(), 
-		chunk_data, 
-		&clean_xmp_data
+		chunk_data
 	)?;
 
 	// Replace chunk
@@ -540,6 +692,29 @@ clear_exif_from_xmp_metadata
 	return write_chunk(cursor, chunk_name.as_str(), &new_chunk_data);
 }
 
+/// Constructs the data field for a text chunk that replaces the given one
+/// holding XMP data, with any EXIF data removed from the XMP
+fn
+construct_xmp_chunk_data_without_exif
+(
+	chunk_name: &str,
+	chunk_data: &[u8],
+)
+-> Result<Vec<u8>, Error>
+{
+	// Clear the EXIF from the XMP data
+	let clean_xmp_data = remove_exif_from_xmp(
+		// &chunk_data[XML_COM_ADOBE_XMP.len()..]
+		&get_data_from_text_chunk(chunk_name, chunk_data)?
+	).map_err(|error| Error::MalformedXmp(error.to_string()))?;
+
+	return construct_similar_with_new_data(
+		chunk_name, 
+		chunk_data, 
+		&clean_xmp_data
+	);
+}
+
 
 
 pub(crate) fn
@@ -548,7 +723,7 @@ write_metadata
 	file_buffer: &mut Vec<u8>,
 	metadata:    &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// First clear the existing metadata
 	// This also parses the PNG and checks its validity, so it is safe to
@@ -568,7 +743,7 @@ file_write_metadata
 	path:     &Path,
 	metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// First clear the existing metadata
 	// This also parses the PNG and checks its validity, so it is safe to
@@ -590,6 +765,322 @@ file_write_metadata
 	return Ok(());
 }
 
+/// Reads the XMP packet stored in the (first) text chunk with the keyword
+/// "XML:com.adobe.xmp"
+pub(crate) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	let chunks = vec_parse_png(file_buffer)?;
+
+	for (chunk_start, chunk) in get_chunk_positions(&chunks)
+	{
+		if !is_text_chunk(chunk)
+		{
+			continue;
+		}
+
+		let data_start = chunk_start + 8;
+		let chunk_data = &file_buffer[data_start..data_start + chunk.length() as usize];
+
+		if has_keyword(chunk_data, &XML_COM_ADOBE_XMP)
+		{
+			return get_data_from_text_chunk(chunk.as_string().as_str(), chunk_data);
+		}
+	}
+
+	return Err(Error::NoXmpData);
+}
+
+/// Removes all text chunks holding XMP data
+pub(crate) fn
+clear_xmp
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	let chunks = vec_parse_png(file_buffer)?;
+
+	// Remove the chunks starting with the last one so that the positions of
+	// the other chunks stay valid
+	let xmp_chunk_starts = get_chunk_positions(&chunks)
+		.filter(|(chunk_start, chunk)| {
+			let data_start = chunk_start + 8;
+			is_text_chunk(chunk) && has_keyword(
+				&file_buffer[data_start..data_start + chunk.length() as usize],
+				&XML_COM_ADOBE_XMP
+			)
+		})
+		.map(|(chunk_start, _)| chunk_start)
+		.collect::<Vec<usize>>();
+
+	let mut cursor = Cursor::new(file_buffer);
+	for chunk_start in xmp_chunk_starts.into_iter().rev()
+	{
+		cursor.set_position(chunk_start as u64);
+		remove_chunk_at(&mut cursor)?;
+	}
+
+	return Ok(());
+}
+
+/// Replaces the XMP data with the given packet, which gets stored in an
+/// uncompressed iTXt chunk right after the IHDR chunk, as recommended by the
+/// XMP specification part 3, section 1.1.5
+#[allow(non_snake_case)]
+pub(crate) fn
+write_xmp
+(
+	file_buffer: &mut Vec<u8>,
+	xmp_packet:  &[u8]
+)
+-> Result<(), Error>
+{
+	clear_xmp(file_buffer)?;
+
+	let chunks      = vec_parse_png(file_buffer)?;
+	let IHDR_length = match chunks.first()
+	{
+		Some(chunk) if chunk.as_string() == "IHDR" => chunk.length(),
+		_ => return container_error!("PNG does not start with an IHDR chunk!"),
+	};
+
+	let mut iTXt_chunk_data = XML_COM_ADOBE_XMP.to_vec();
+	iTXt_chunk_data.push(0x00);                                                 // NUL separator
+	iTXt_chunk_data.push(0x00);                                                 // Compression flag
+	iTXt_chunk_data.push(0x00);                                                 // Compression method
+	iTXt_chunk_data.push(0x00);                                                 // Empty language tag
+	iTXt_chunk_data.push(0x00);                                                 // Empty translated keyword
+	iTXt_chunk_data.extend(xmp_packet);
+
+	let mut cursor = Cursor::new(file_buffer);
+	cursor.set_position(PNG_SIGNATURE.len() as u64 + IHDR_length as u64 + 12);
+	return write_chunk(&mut cursor, "iTXt", &iTXt_chunk_data);
+}
+
+/// Reads the ICC profile stored in the iCCP chunk, which consists of the
+/// profile name, the compression method and the zlib compressed profile
+pub(crate) fn
+read_icc_profile
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	let chunks = vec_parse_png(file_buffer)?;
+
+	for (chunk_start, chunk) in get_chunk_positions(&chunks)
+	{
+		if chunk.as_string() != "iCCP"
+		{
+			continue;
+		}
+
+		let data_start = chunk_start + 8;
+		let chunk_data = &file_buffer[data_start..data_start + chunk.length() as usize];
+
+		// Skip the name, its NUL separator and the compression method
+		let compressed_profile = match chunk_data.iter().position(|byte| *byte == 0x00)
+		{
+			Some(separator) if chunk_data.get(separator + 1) == Some(&0x00)
+				=> &chunk_data[separator + 2..],
+			_
+				=> return container_error!("Invalid iCCP chunk!"),
+		};
+
+		return match decompress_to_vec_zlib(compressed_profile)
+		{
+			Ok(icc_profile) => Ok(icc_profile),
+			Err(_)          => container_error!("Could not inflate ICC profile!"),
+		};
+	}
+
+	return Err(Error::NoIccProfile);
+}
+
+/// Removes the iCCP chunk
+pub(crate) fn
+clear_icc_profile
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return remove_chunks(file_buffer, &["iCCP"]);
+}
+
+/// Replaces the ICC profile with the given one, which gets stored in an
+/// iCCP chunk right after the IHDR chunk. As the sRGB chunk would take 
+/// precedence over the profile, it gets removed as well
+#[allow(non_snake_case)]
+pub(crate) fn
+write_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+	remove_chunks(file_buffer, &["iCCP", "sRGB"])?;
+
+	let chunks      = vec_parse_png(file_buffer)?;
+	let IHDR_length = match chunks.first()
+	{
+		Some(chunk) if chunk.as_string() == "IHDR" => chunk.length(),
+		_ => return container_error!("PNG does not start with an IHDR chunk!"),
+	};
+
+	let mut iCCP_chunk_data = ICC_PROFILE_NAME.to_vec();
+	iCCP_chunk_data.push(0x00);                                                 // NUL separator
+	iCCP_chunk_data.push(0x00);                                                 // Compression method
+	iCCP_chunk_data.extend(compress_to_vec_zlib(icc_profile, 8));
+
+	let mut cursor = Cursor::new(file_buffer);
+	cursor.set_position(PNG_SIGNATURE.len() as u64 + IHDR_length as u64 + 12);
+	return write_chunk(&mut cursor, "iCCP", &iCCP_chunk_data);
+}
+
+/// Reads the C2PA manifest store from the caBX chunk by only reading the
+/// chunk headers and the data of that chunk
+pub(crate) fn
+read_c2pa_jumbf
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let mut signature_buffer = Vec::new();
+	input.take(PNG_SIGNATURE.len() as u64).read_to_end(&mut signature_buffer)?;
+	if !starts_with_png_signature(&signature_buffer)
+	{
+		return container_error!("Can't open PNG file - Wrong signature!");
+	}
+
+	loop
+	{
+		let chunk_length = read_chunk_length(input)?;
+		let chunk_name   = read_chunk_name(input)?;
+
+		match chunk_name.as_str()
+		{
+			"caBX" => {
+				let chunk_data = read_chunk_data(input, chunk_length as usize)?;
+				let chunk_crc  = read_chunk_crc(input)?;
+				check_chunk_crc(&chunk_name, &chunk_data, &chunk_crc)?;
+
+				return Ok(Some(chunk_data));
+			},
+
+			"IEND" => {
+				return Ok(None);
+			},
+
+			_ => {
+				// Skip the chunk data and CRC
+				input.seek(SeekFrom::Current(chunk_length as i64 + 4))?;
+			}
+		}
+	}
+}
+
+/// Removes the caBX chunk holding the C2PA manifest store
+pub(crate) fn
+clear_c2pa_jumbf
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return remove_chunks(file_buffer, &["caBX"]);
+}
+
+/// Removes all chunks with one of the given names
+fn
+remove_chunks
+(
+	file_buffer: &mut Vec<u8>,
+	chunk_names: &[&str]
+)
+-> Result<(), Error>
+{
+	let chunks = vec_parse_png(file_buffer)?;
+
+	// Remove the chunks starting with the last one so that the positions of
+	// the other chunks stay valid
+	let chunk_starts = get_chunk_positions(&chunks)
+		.filter(|(_, chunk)| chunk_names.contains(&chunk.as_string().as_str()))
+		.map(|(chunk_start, _)| chunk_start)
+		.collect::<Vec<usize>>();
+
+	let mut cursor = Cursor::new(file_buffer);
+	for chunk_start in chunk_starts.into_iter().rev()
+	{
+		cursor.set_position(chunk_start as u64);
+		remove_chunk_at(&mut cursor)?;
+	}
+
+	return Ok(());
+}
+
+fn
+is_text_chunk
+(
+	chunk: &PngChunk
+)
+-> bool
+{
+	return matches!(chunk.as_string().as_str(), "iTXt" | "zTXt" | "tEXt");
+}
+
+/// Pairs the parsed chunks with their start positions, i.e. the position of
+/// their length field
+fn
+get_chunk_positions
+(
+	chunks: &[PngChunk]
+)
+-> impl Iterator<Item = (usize, &PngChunk)>
+{
+	return chunks.iter()
+		.scan(PNG_SIGNATURE.len(), |chunk_start, chunk| {
+			let current_start = *chunk_start;
+			*chunk_start += 12 + chunk.length() as usize;
+			return Some((current_start, chunk));
+		});
+}
+
+/// Encodes a chunk with the given name and data, i.e. adds the length field
+/// at the start and the CRC at the end
+fn
+encode_chunk
+(
+	chunk_name: &str,
+	chunk_data: &[u8],
+)
+-> Vec<u8>
+{
+	// Start with the length of the new chunk, which does not include the
+	// length field, name and CRC
+	let chunk_data_len = chunk_data.len() as u32;
+	let mut chunk      = chunk_data_len.to_be_bytes().to_vec();
+
+	// Name and data are used for computing the CRC
+	chunk.extend(chunk_name.as_bytes());
+	chunk.extend(chunk_data);
+
+	// Compute CRC and append it to the chunk
+	let crc_struct = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+	let checksum   = crc_struct.checksum(&chunk[4..]);
+	chunk.extend(checksum.to_be_bytes());
+
+	return chunk;
+}
+
 /// Assumes the cursor to be positioned at the insert position
 #[allow(non_snake_case)]
 fn
@@ -600,20 +1091,8 @@ write_chunk
 	chunk_name: &str,
 	chunk_data: &[u8],
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
-	// Create a new vec for computing the CRC
-	let mut data = chunk_name.as_bytes().to_vec();
-	data.extend(chunk_data);
-
-	// Compute CRC and append it to the data vector
-	let crc_struct = Crc::<u32>::new(&CRC_32_ISO_HDLC);
-	let checksum = crc_struct.checksum(&data) as u32;
-	for i in 0..4
-	{
-		data.push( (checksum >> (8 * (3-i))) as u8);		
-	}
-
 	// Prepare writing: 
 	// - Backup cursor position 
 	// - Read everything from there onwards into a buffer
@@ -623,17 +1102,10 @@ write_chunk
 	cursor.read_to_end(&mut buffer)?;
 	cursor.seek(SeekFrom::Start(backup_cursor_position))?;
 
-	// Write length of the new chunk (which is 8 bytes shorter than `data`)
-	let chunk_data_len = chunk_data.len() as u32;
-	for i in 0..4
-	{
-		cursor.write(&[(chunk_data_len >> (8 * (3-i))) as u8])?;
-	}
-
-	// Write data of new chunk, remember that position, write remaining PNG
+	// Write the new chunk, remember that position, write remaining PNG
 	// data and revert position so that cursor now points to the chunk right
 	// after the one that has been written
-	cursor.write_all(&data)?;
+	cursor.write_all(&encode_chunk(chunk_name, chunk_data))?;
 	let end_of_written_chunk_cursor_position = cursor.stream_position()?;
 	cursor.write_all(&buffer)?;
 	cursor.seek(SeekFrom::Start(end_of_written_chunk_cursor_position))?;
@@ -649,16 +1121,18 @@ generic_write_metadata
 	cursor:     &mut T,
 	metadata:   &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	cursor.seek(SeekFrom::Start(8))?;
 
-	let mut IHDR_length = 0u32;
-
-	if let Ok(chunks) = generic_parse_png(cursor)
+	// The new chunk gets inserted right after the IHDR chunk, which is
+	// required to be the first one
+	let chunks      = generic_parse_png(cursor)?;
+	let IHDR_length = match chunks.first()
 	{
-		IHDR_length = chunks[0].length();
-	}
+		Some(chunk) if chunk.as_string() == "IHDR" => chunk.length(),
+		_ => return container_error!("PNG does not start with an IHDR chunk!"),
+	};
 
 	// Encode the data specifically for PNG and open the image file
 	let encoded_metadata = encode_metadata_png(&metadata.encode()?);
@@ -680,6 +1154,105 @@ generic_write_metadata
 
 
 
+/// Writes the PNG read from the input to the output while replacing the 
+/// metadata, see `write_metadata`. 
+/// The PNG gets parsed (and thereby validated) in a first pass over the input.
+/// The second pass copies the chunks one by one - only text chunks are loaded
+/// into memory as these might contain EXIF data that needs to be removed.
+#[allow(non_snake_case)]
+pub(crate) fn
+write_metadata_to_stream
+<R: Read + Seek, W: Write>
+(
+	input:    &mut R,
+	output:   &mut W,
+	metadata: &Metadata
+)
+-> Result<(), Error>
+{
+	let mut signature_buffer = [0u8; 8];
+	input.read_exact(&mut signature_buffer)?;
+	if !starts_with_png_signature(&signature_buffer)
+	{
+		return container_error!("Can't open PNG file - Wrong signature!");
+	}
+
+	// The new chunk gets inserted right after the IHDR chunk, which is
+	// required to be the first one
+	let chunks = generic_parse_png(input)?;
+	match chunks.first()
+	{
+		Some(chunk) if chunk.as_string() == "IHDR" => (),
+		_ => return container_error!("PNG does not start with an IHDR chunk!"),
+	};
+
+	// Build data of new chunk using zlib compression (level=8 -> default)
+	let zTXt_chunk_data: Vec<u8> = construct_zTXt_chunk_data(
+		Vec::new(),
+		&encode_metadata_png(&metadata.encode()?)
+	);
+
+	input.seek(SeekFrom::Start(8))?;
+	output.write_all(&PNG_SIGNATURE)?;
+
+	for (index, chunk) in chunks.iter().enumerate()
+	{
+		match chunk.as_string().as_str()
+		{
+			"eXIf" => {
+				// Drop the old metadata by not copying the chunk
+				input.seek(SeekFrom::Current(12 + chunk.length() as i64))?;
+			},
+
+			"iTXt" | "zTXt" | "tEXt" => {
+
+				// Read the chunk - no need to verify the CRC as this is
+				// already done by parsing the PNG
+				let _          = read_chunk_length(input)?;
+				let chunk_name = read_chunk_name(input)?;
+				let chunk_data = read_chunk_data(input, chunk.length() as usize)?;
+				let _          = read_chunk_crc(input)?;
+
+				let keyword = get_keyword_from_text_chunk(&chunk_data);
+
+				if keyword.as_bytes() == RAW_PROFILE_TYPE_EXIF
+				{
+					// Drop the old metadata
+				}
+				else if keyword.as_bytes() == XML_COM_ADOBE_XMP
+				{
+					// Keep the XMP data, but without EXIF
+					let new_chunk_data = construct_xmp_chunk_data_without_exif(
+						chunk_name.as_str(), 
+						&chunk_data
+					)?;
+					output.write_all(&encode_chunk(chunk_name.as_str(), &new_chunk_data))?;
+				}
+				else
+				{
+					output.write_all(&encode_chunk(chunk_name.as_str(), &chunk_data))?;
+				}
+			},
+
+			_ => {
+				// Chunk length and type as well as the CRC (4+4+4 Bytes)
+				copy_n_bytes(input, output, 12 + chunk.length() as u64)?;
+			}
+		}
+
+		if index == 0
+		{
+			output.write_all(&encode_chunk("zTXt", &zTXt_chunk_data))?;
+		}
+	}
+
+	// Copy anything that might follow after the IEND chunk
+	std::io::copy(input, output)?;
+
+	return Ok(());
+}
+
+
 fn
 encode_metadata_png
 (
@@ -733,7 +1306,7 @@ decode_metadata_png
 (
 	encoded_data: &Vec<u8>
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 
 	let mut exif_all: VecDeque<u8> = VecDeque::new();
@@ -772,67 +1345,23 @@ decode_metadata_png
 	// Now remove the first element until the exif header or endian information 
 	// is found.
 	// Store the popped elements to get the size information
-	let mut exif_header_found = false;
-	let mut endian_info_found = false;
+	// But what if the EXIF_HEADER is missing and we are directly starting
+	// with the endian information? See issue #54
 	let mut pop_storage: Vec<u8> = Vec::new();
 
-	while !exif_header_found && !endian_info_found
+	while 
+		!deque_starts_with(&exif_all, &EXIF_HEADER)        &&
+		!deque_starts_with(&exif_all, &LITTLE_ENDIAN_INFO) &&
+		!deque_starts_with(&exif_all, &BIG_ENDIAN_INFO)
 	{
-		let mut counter = 0;
-		for header_value in &EXIF_HEADER
-		{
-			if *header_value != exif_all[counter]
-			{
-				break;
-			}
-			counter += 1;
-		}
-
-		exif_header_found = counter == EXIF_HEADER.len();
-
-		if exif_header_found
-		{
-			break;
-		}
-
-		counter = 0;
-
-		// But what if the EXIF_HEADER is missing and we are directly starting
-		// with the endian information? See issue #54
-		for endian_info in &LITTLE_ENDIAN_INFO
-		{
-			if *endian_info != exif_all[counter]
-			{
-				break;
-			}
-			counter += 1;
-		}
-
-		endian_info_found = counter == LITTLE_ENDIAN_INFO.len();
-
-		if endian_info_found
+		if let Some(popped_element) = exif_all.pop_front()
 		{
-			break;
-		}
-
-		// And the same check for big endian
-		for endian_info in &BIG_ENDIAN_INFO
-		{
-			if *endian_info != exif_all[counter]
-			{
-				break;
-			}
-			counter += 1;
+			pop_storage.push(popped_element);
 		}
-
-		endian_info_found = counter == BIG_ENDIAN_INFO.len();
-
-		if endian_info_found
+		else
 		{
-			break;
+			return container_error!("Could not find EXIF header or endian information in PNG text chunk!");
 		}
-
-		pop_storage.push(exif_all.pop_front().unwrap());
 	}
 
 	// The exif header has been found
@@ -841,7 +1370,10 @@ decode_metadata_png
 	//    that will now get extracted
 	// Consider this part optional as it might be removed in the future and
 	// isn't strictly necessary and just for validating the data we get
-	assert!(pop_storage.len() > 0);
+	if pop_storage.is_empty()
+	{
+		return container_error!("Missing size information in PNG text chunk!");
+	}
 
 	// Using the encode_byte function re-encode the bytes regarding the size
 	// information and construct its value using decimal based shifting
@@ -852,18 +1384,45 @@ decode_metadata_png
 	for i in 0..std::cmp::min(4, pop_storage.len())
 	{
 		let re_encoded_byte = encode_byte(&pop_storage[pop_storage.len() -1 -i]);
-		let tens_place = u64::from_str_radix(&(re_encoded_byte[0] as char).to_string(), 10).unwrap();
-		let ones_place = u64::from_str_radix(&(re_encoded_byte[1] as char).to_string(), 10).unwrap();
-		given_exif_len = given_exif_len + tens_place * 10 * 10_u64.pow((2 * i).try_into().unwrap());
-		given_exif_len = given_exif_len + ones_place *  1 * 10_u64.pow((2 * i).try_into().unwrap());
+		let tens_place = (re_encoded_byte[0] as char).to_digit(10);
+		let ones_place = (re_encoded_byte[1] as char).to_digit(10);
+
+		if let (Some(tens_place), Some(ones_place)) = (tens_place, ones_place)
+		{
+			given_exif_len += tens_place as u64 * 10 * 10_u64.pow(2 * i as u32);
+			given_exif_len += ones_place as u64      * 10_u64.pow(2 * i as u32);
+		}
+		else
+		{
+			return container_error!("Invalid size information in PNG text chunk!");
+		}
 	}
 
-	assert!(given_exif_len == exif_all.len().try_into().unwrap());
+	if given_exif_len != exif_all.len() as u64
+	{
+		return container_error!(format!(
+			"Size information in PNG text chunk ({}) does not match EXIF data length ({})!", 
+			given_exif_len, 
+			exif_all.len()
+		));
+	}
 	// End optional part
 
 	return Ok(Vec::from(exif_all));
 }
 
+fn
+deque_starts_with
+(
+	deque:   &VecDeque<u8>,
+	pattern: &[u8]
+)
+-> bool
+{
+	return deque.len() >= pattern.len() 
+		&& deque.iter().zip(pattern.iter()).all(|(a, b)| a == b);
+}
+
 /// Provides the PNG specific encoding result as vector of bytes to be used
 /// by the user (e.g. in combination with another library)
 #[allow(non_snake_case)]
@@ -50,12 +50,15 @@ read_chunk_data
 )
 -> Result<Vec<u8>, std::io::Error>
 {
-	let mut chunk_data_buffer = vec![0u8; chunk_length];
-	let     bytes_read        = cursor.read(&mut chunk_data_buffer)?;
+	// Don't allocate the entire (possibly corrupt) length upfront
+	let mut chunk_data_buffer = Vec::new();
+	let     bytes_read        = cursor
+		.take(chunk_length as u64)
+		.read_to_end(&mut chunk_data_buffer)?;
 	
 	if bytes_read != chunk_length
 	{
-		return io_error!(Other, "Could not read chunk data");
+		return io_error!(UnexpectedEof, "Could not read chunk data");
 	}
 
 	return Ok(chunk_data_buffer);
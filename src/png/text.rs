@@ -4,7 +4,8 @@
 use miniz_oxide::inflate::decompress_to_vec_zlib;
 use miniz_oxide::deflate::compress_to_vec_zlib;
 
-use crate::general_file_io::io_error;
+use crate::error::Error;
+use crate::error::container_error;
 
 /// This gets the keyword of a $TEXT chunk.
 /// Fortunately, this is the same for tEXt, zTXt and iTXt, as they all
@@ -16,13 +17,38 @@ get_keyword_from_text_chunk
 )
 -> String
 {
-	let mut keyword_buffer = Vec::new();
-	for character in chunk_data
+	let keyword_length = get_keyword_length(chunk_data);
+	return String::from_utf8_lossy(&chunk_data[..keyword_length]).into_owned();
+}
+
+/// Number of bytes before the NUL separator that terminates the keyword
+/// (or the length of the entire chunk data if there is no separator)
+fn
+get_keyword_length
+(
+	chunk_data: &[u8]
+)
+-> usize
+{
+	return chunk_data
+		.iter()
+		.position(|byte| *byte == 0x00)
+		.unwrap_or(chunk_data.len());
+}
+
+fn
+get_chunk_data_from
+(
+	chunk_data: &[u8],
+	data_start: usize,
+)
+-> Result<&[u8], Error>
+{
+	if let Some(data) = chunk_data.get(data_start..)
 	{
-		if *character == 0x00 { break; }
-		keyword_buffer.push(*character);
+		return Ok(data);
 	}
-	return String::from_utf8(keyword_buffer).unwrap();
+	return container_error!("Text chunk ends before its data starts!");
 }
 
 pub(crate) fn
@@ -31,11 +57,11 @@ get_data_from_text_chunk
 	chunk_name: &str,
 	chunk_data: &[u8],
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// The keyword length is required in all cases for determining the start
 	// of the actual data
-	let keyword_length = get_keyword_from_text_chunk(chunk_data).len();
+	let keyword_length = get_keyword_length(chunk_data);
 
 	match chunk_name
 	{
@@ -44,7 +70,7 @@ get_data_from_text_chunk
 			// For this we take the keyword length and add 1 for the NUL byte
 			let data_start = keyword_length + 1;
 			
-			return Ok(chunk_data[data_start..].to_vec());
+			return Ok(get_chunk_data_from(chunk_data, data_start)?.to_vec());
 		},
 
 		"zTXt" => {
@@ -54,25 +80,23 @@ get_data_from_text_chunk
 			let data_start = keyword_length + 2;
 
 			// Check compression method
-			if chunk_data[keyword_length + 1] != 0x00
+			if chunk_data.get(keyword_length + 1) != Some(&0x00)
 			{
-				return io_error!(
-					Other,
+				return container_error!(
 					"Unknown compression method for zTXt!"
 				);
 			}
 
 			// Decode zlib data
 			if let Ok(decompressed_data) = decompress_to_vec_zlib(
-				&chunk_data[data_start..]
+				get_chunk_data_from(chunk_data, data_start)?
 			)
 			{
 				return Ok(decompressed_data);
 			}
 			else
 			{
-				return io_error!(
-					Other, 
+				return container_error!(
 					"Could not inflate compressed chunk data!"
 				);
 			}
@@ -86,7 +110,7 @@ get_data_from_text_chunk
 				_keyword,
 				language_tag,
 				translated_keyword,
-			) = get_info_about_iTXt_chunk(chunk_data);
+			) = get_info_about_iTXt_chunk(chunk_data)?;
 
 			let data_start = keyword_length // keyword
 				+ 3                         // NUL, compression flag & method
@@ -98,35 +122,33 @@ get_data_from_text_chunk
 			if compression_flag == 0x00
 			{
 				// No compression, simply return the data
-				return Ok(chunk_data[data_start..].to_vec());
+				return Ok(get_chunk_data_from(chunk_data, data_start)?.to_vec());
 			}
 			
 			if compression_method != 0x00
 			{
-				return io_error!(
-					Other,
+				return container_error!(
 					"Unknown compression method for iTXt!"
 				);
 			}
 
 			// Decode zlib data
 			if let Ok(decompressed_data) = decompress_to_vec_zlib(
-				&chunk_data[data_start..]
+				get_chunk_data_from(chunk_data, data_start)?
 			)
 			{
 				return Ok(decompressed_data);
 			}
 			else
 			{
-				return io_error!(
-					Other, 
+				return container_error!(
 					"Could not inflate compressed chunk data!"
 				);
 			}
 		},
 
 		_ => {
-			return io_error!(Other, "Unknown text chunk!");
+			return container_error!("Unknown text chunk!");
 		}
 	}
 }
@@ -138,7 +160,7 @@ construct_similar_with_new_data
 	old_chunk_data: &[u8],
 	new_data:       &[u8],
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// Note: data is just the text after the keyword an so on, while *chunk*
 	// data describes the entire data field that includes the keyword, the
@@ -160,10 +182,9 @@ construct_similar_with_new_data
 
 		"zTXt" => {
 			// Check compression method
-			if old_chunk_data[keyword.len() + 1] != 0x00
+			if old_chunk_data.get(get_keyword_length(old_chunk_data) + 1) != Some(&0x00)
 			{
-				return io_error!(
-					Other,
+				return container_error!(
 					"Unknown compression method for zTXt!"
 				);
 			}
@@ -179,7 +200,7 @@ construct_similar_with_new_data
 				_keyword,
 				language_tag,
 				translated_keyword,
-			) = get_info_about_iTXt_chunk(old_chunk_data);
+			) = get_info_about_iTXt_chunk(old_chunk_data)?;
 
 			// Push compression information
 			new_chunk_data.push(compression_flag);
@@ -205,8 +226,7 @@ construct_similar_with_new_data
 			{
 				if compression_method != 0x00
 				{
-					return io_error!(
-						Other,
+					return container_error!(
 						"Unknown compression method for iTXt!"
 					);
 				}
@@ -217,7 +237,7 @@ construct_similar_with_new_data
 		},
 
 		_ => {
-			return io_error!(Other, "Unknown text chunk!");
+			return container_error!("Unknown text chunk!");
 		}
 	}
 
@@ -242,74 +262,80 @@ get_info_about_iTXt_chunk
 (
 	chunk_data:   &[u8]
 )
--> (
+-> Result<(
 	u8,     // compression flag
 	u8,     // compression method
 	String, // keyword
 	String, // language tag
 	String, // translated keyword
-)
+), Error>
 {
-	// Tells us where we currently are in the chunk data
-	let mut chunk_counter = 0;
-
-	// Buffers for the different attributes
-	let mut keyword_buffer = Vec::new();
-
-	loop
+	// Splits off the next NUL terminated field, starting at the given position
+	// and returns it together with the position after its NUL separator
+	let read_field = |start: usize| -> Result<(String, usize), Error>
 	{
-		if chunk_data[chunk_counter] != 0x00
+		let remaining = get_chunk_data_from(chunk_data, start)?;
+		if let Some(length) = remaining.iter().position(|byte| *byte == 0x00)
 		{
-			keyword_buffer.push(chunk_data[chunk_counter]);
-			chunk_counter += 1;
+			return Ok((
+				String::from_utf8_lossy(&remaining[..length]).into_owned(),
+				start + length + 1
+			));
 		}
-		else
-		{
-			break;
-		}
-	}
+		return container_error!("Missing NUL separator in iTXt chunk!");
+	};
 
-	let _null_separator_1  = chunk_data[chunk_counter + 0];
-	let compression_flag   = chunk_data[chunk_counter + 1];
-	let compression_method = chunk_data[chunk_counter + 2];
+	let (keyword, chunk_counter) = read_field(0)?;
 
-	chunk_counter += 3;
-	let mut language_tag_buffer = Vec::new();
-
-	loop 
+	let compression_info = get_chunk_data_from(chunk_data, chunk_counter)?;
+	if compression_info.len() < 2
 	{
-		if chunk_data[chunk_counter] != 0x00
-		{
-			language_tag_buffer.push(chunk_data[chunk_counter]);
-			chunk_counter += 1;
-		}
-		else
-		{
-			break;
-		}
+		return container_error!("iTXt chunk lacks compression information!");
 	}
+	let compression_flag   = compression_info[0];
+	let compression_method = compression_info[1];
 
-	chunk_counter += 1;
-	let mut translated_keyword_buffer = Vec::new();
+	let (language_tag,       chunk_counter) = read_field(chunk_counter + 2)?;
+	let (translated_keyword, _            ) = read_field(chunk_counter    )?;
 
-	loop 
+	return Ok((
+		compression_flag,
+		compression_method,
+		keyword,
+		language_tag,
+		translated_keyword,
+	));
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn
+	malformed_text_chunks()
 	{
-		if chunk_data[chunk_counter] != 0x00
-		{
-			translated_keyword_buffer.push(chunk_data[chunk_counter]);
-			chunk_counter += 1;
-		}
-		else
+		let table: Vec<(&str, &[u8])> = vec![
+			("tEXt", b"Raw profile type exif"),
+			("zTXt", b"Raw profile type exif\0"),
+			("iTXt", b"Raw profile type exif"),
+			("iTXt", b"Raw profile type exif\0\x01"),
+			("iTXt", b"Raw profile type exif\0\x00\x00en"),
+			("iTXt", b"Raw profile type exif\0\x00\x00en\0"),
+		];
+
+		for (chunk_name, chunk_data) in table
 		{
-			break;
+			assert!(
+				get_data_from_text_chunk(chunk_name, chunk_data).is_err(),
+				"Expected error for {} chunk {:?}", chunk_name, chunk_data
+			);
 		}
-	}
 
-	return (
-		compression_flag,
-		compression_method,
-		String::from_utf8(keyword_buffer           ).unwrap_or_default(),
-		String::from_utf8(language_tag_buffer      ).unwrap_or_default(),
-		String::from_utf8(translated_keyword_buffer).unwrap_or_default()
-	);
-}
\ No newline at end of file
+		assert_eq!(
+			get_data_from_text_chunk("iTXt", b"key\0\x00\x00en\0\0data").unwrap(),
+			b"data".to_vec()
+		);
+	}
+}
@@ -5,6 +5,7 @@ use std::io::BufReader;
 use std::io::BufWriter;
 use std::path::Path;
 
+use crate::error::Error;
 use crate::general_file_io::open_read_file;
 use crate::general_file_io::open_write_file;
 use crate::metadata::Metadata;
@@ -17,7 +18,7 @@ read_metadata
 (
 	path: &Path
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	let mut buffered_file = BufReader::new(open_read_file(path)?);
 	return generic_read_metadata(&mut buffered_file);
@@ -28,7 +29,7 @@ clear_metadata
 (
 	path: &Path
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Read in the data
 	let     raw_data = generic_read_metadata(&mut BufReader::new(open_read_file(path)?));
@@ -49,7 +50,7 @@ write_metadata
 	path:     &Path,
 	metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	let mut buffered_file = BufWriter::new(open_write_file(path)?);
 	return generic_write_metadata(&mut buffered_file, metadata);
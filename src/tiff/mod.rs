@@ -2,15 +2,32 @@
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Read;
 use std::io::Write;
 
+use crate::error::Error;
 use crate::general_file_io::EXIF_HEADER;
+use crate::general_file_io::LITTLE_ENDIAN_INFO;
+use crate::general_file_io::BIG_ENDIAN_INFO;
 use crate::metadata::Metadata;
 
 pub mod file;
 pub mod vec;
 
+/// Checks if the given data starts with one of the two byte order headers of
+/// a TIFF file ("II*\0" or "MM\0*")
+pub(crate) fn
+starts_with_tiff_signature
+(
+	file_buffer: &[u8]
+)
+-> bool
+{
+	return file_buffer.starts_with(&LITTLE_ENDIAN_INFO) 
+		|| file_buffer.starts_with(&BIG_ENDIAN_INFO);
+}
+
 pub(crate) fn
 generic_write_metadata
 <T: Seek + Write>
@@ -18,7 +35,7 @@ generic_write_metadata
 	cursor:   &mut T,
 	metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Does *not* call generic_clear_metadata, as the entire tiff data gets
 	// overwritten anyways
@@ -27,13 +44,40 @@ generic_write_metadata
 	return Ok(());
 }
 
-fn
+/// Writes the TIFF data read from the input to the output, with the start
+/// being overwritten by the metadata - just like `generic_write_metadata`
+/// does when writing to the TIFF data directly
+pub(crate) fn
+generic_write_metadata_to_stream
+<R: Read + Seek, W: Write>
+(
+	input:    &mut R,
+	output:   &mut W,
+	metadata: &Metadata
+)
+-> Result<(), Error>
+{
+	let encoded_metadata = metadata.encode()?;
+	output.write_all(&encoded_metadata)?;
+
+	// Copy whatever is not overwritten by the metadata
+	let input_length = input.seek(SeekFrom::End(0))?;
+	if input_length > encoded_metadata.len() as u64
+	{
+		input.seek(SeekFrom::Start(encoded_metadata.len() as u64))?;
+		std::io::copy(input, output)?;
+	}
+
+	return Ok(());
+}
+
+pub(crate) fn
 generic_read_metadata
 <T: Seek + Read>
 (
 	cursor: &mut T
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	let mut tiff_with_exif_header = Vec::new();
 	tiff_with_exif_header.extend(EXIF_HEADER);
@@ -3,28 +3,21 @@
 
 use std::io::Cursor;
 
+use crate::error::Error;
+use crate::exif_tag::ExifTag;
+use crate::ifd::ExifTagGroup;
+use crate::ifd::ImageFileDirectory;
 use crate::metadata::Metadata;
 
 use super::generic_read_metadata;
 use super::generic_write_metadata;
 
-pub(crate) fn
-read_metadata
-(
-	file_buffer: &Vec<u8>
-)
--> Result<Vec<u8>, std::io::Error>
-{
-	let mut cursor = Cursor::new(file_buffer);
-	return generic_read_metadata(&mut cursor);
-}
-
 pub(crate) fn
 clear_metadata
 (
 	file_buffer: &mut Vec<u8>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Create cursor
 	let mut cursor           = Cursor::new(file_buffer);
@@ -48,8 +41,251 @@ write_metadata
 	file_buffer: &mut Vec<u8>,
 	metadata:    &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	let mut cursor = Cursor::new(file_buffer);
 	return generic_write_metadata(&mut cursor, metadata);
-}
\ No newline at end of file
+}
+
+/// The XMP packet is stored as an array of bytes in the XMP tag of IFD0
+const XMP_TAG_HEX:         u16 = 0x02bc;
+
+/// The IPTC-IIM data is stored in the IPTC-NAA tag of IFD0. Some writers use
+/// the type LONG for this tag, which is why the data may be padded with zeros
+const IPTC_TAG_HEX:        u16 = 0x83bb;
+
+/// The Photoshop image resources are stored in the Photoshop tag of IFD0
+const PHOTOSHOP_TAG_HEX:   u16 = 0x8649;
+
+/// The ICC profile is stored in the InterColorProfile tag of IFD0
+const ICC_PROFILE_TAG_HEX: u16 = 0x8773;
+
+/// The C2PA manifest store is stored in the tag 0xcd41 of IFD0
+const C2PA_TAG_HEX:        u16 = 0xcd41;
+
+pub(crate) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return read_ifd0_data(file_buffer, XMP_TAG_HEX)?.ok_or(Error::NoXmpData);
+}
+
+pub(crate) fn
+clear_xmp
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.remove_tag(&xmp_tag(Vec::new())));
+}
+
+pub(crate) fn
+write_xmp
+(
+	file_buffer: &mut Vec<u8>,
+	xmp_packet:  &[u8]
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.set_tag(xmp_tag(xmp_packet.to_vec())));
+}
+
+pub(crate) fn
+read_iptc
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return read_ifd0_data(file_buffer, IPTC_TAG_HEX)?.ok_or(Error::NoIptcData);
+}
+
+pub(crate) fn
+clear_iptc
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.remove_tag(&iptc_tag(Vec::new())));
+}
+
+pub(crate) fn
+write_iptc
+(
+	file_buffer: &mut Vec<u8>,
+	iptc_data:   &[u8]
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.set_tag(iptc_tag(iptc_data.to_vec())));
+}
+
+/// Reads the Photoshop image resources, returns no data if there are none
+pub(crate) fn
+read_photoshop_resources
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return Ok(read_ifd0_data(file_buffer, PHOTOSHOP_TAG_HEX)?.unwrap_or_default());
+}
+
+/// Replaces the Photoshop image resources, removes the tag if there are none
+pub(crate) fn
+write_photoshop_resources
+(
+	file_buffer:   &mut Vec<u8>,
+	resource_data: &[u8]
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| {
+		if resource_data.is_empty()
+		{
+			ifd0.remove_tag(&photoshop_tag(Vec::new()));
+		}
+		else
+		{
+			ifd0.set_tag(photoshop_tag(resource_data.to_vec()));
+		}
+	});
+}
+
+pub(crate) fn
+read_icc_profile
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return read_ifd0_data(file_buffer, ICC_PROFILE_TAG_HEX)?.ok_or(Error::NoIccProfile);
+}
+
+pub(crate) fn
+clear_icc_profile
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.remove_tag(&icc_profile_tag(Vec::new())));
+}
+
+pub(crate) fn
+write_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.set_tag(icc_profile_tag(icc_profile.to_vec())));
+}
+
+pub(crate) fn
+read_c2pa_jumbf
+(
+	file_buffer: &[u8]
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	return read_ifd0_data(file_buffer, C2PA_TAG_HEX);
+}
+
+pub(crate) fn
+clear_c2pa_jumbf
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return modify_ifd0(file_buffer, |ifd0| ifd0.remove_tag(&c2pa_tag(Vec::new())));
+}
+
+/// Gets the data of the tag with the given hex value in IFD0 as bytes
+fn
+read_ifd0_data
+(
+	file_buffer: &[u8],
+	tag_hex:     u16
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let metadata = Metadata::general_decoding_wrapper(Ok(file_buffer))?;
+
+	return Ok(metadata.get_ifd(ExifTagGroup::GENERIC, 0)
+		.and_then(|ifd0| ifd0.get_tags().iter().find(|tag| tag.as_u16() == tag_hex))
+		.map(|tag| tag.value_as_u8_vec(&metadata.get_endian()))
+	);
+}
+
+/// Applies the given modification to IFD0 and writes the result back
+fn
+modify_ifd0
+<F: FnOnce(&mut ImageFileDirectory)>
+(
+	file_buffer: &mut Vec<u8>,
+	modify:      F
+)
+-> Result<(), Error>
+{
+	let mut metadata = Metadata::general_decoding_wrapper(Ok(file_buffer.as_slice()))?;
+	modify(metadata.get_ifd_mut(ExifTagGroup::GENERIC, 0));
+	return write_metadata(file_buffer, &metadata);
+}
+
+fn
+xmp_tag
+(
+	xmp_packet: Vec<u8>
+)
+-> ExifTag
+{
+	return ExifTag::UnknownINT8U(xmp_packet, XMP_TAG_HEX, ExifTagGroup::GENERIC);
+}
+
+fn
+iptc_tag
+(
+	iptc_data: Vec<u8>
+)
+-> ExifTag
+{
+	return ExifTag::UnknownUNDEF(iptc_data, IPTC_TAG_HEX, ExifTagGroup::GENERIC);
+}
+
+fn
+photoshop_tag
+(
+	resource_data: Vec<u8>
+)
+-> ExifTag
+{
+	return ExifTag::UnknownINT8U(resource_data, PHOTOSHOP_TAG_HEX, ExifTagGroup::GENERIC);
+}
+
+fn
+icc_profile_tag
+(
+	icc_profile: Vec<u8>
+)
+-> ExifTag
+{
+	return ExifTag::UnknownUNDEF(icc_profile, ICC_PROFILE_TAG_HEX, ExifTagGroup::GENERIC);
+}
+
+fn
+c2pa_tag
+(
+	jumbf: Vec<u8>
+)
+-> ExifTag
+{
+	return ExifTag::UnknownUNDEF(jumbf, C2PA_TAG_HEX, ExifTagGroup::GENERIC);
+}
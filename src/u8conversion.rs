@@ -6,6 +6,8 @@ use paste::paste;
 use crate::endian::Endian;
 use crate::rational::*;
 
+/// Conversion of the values of EXIF tags from and to their bytes, using the
+/// given byte order
 pub trait
 U8conversion<T>
 {
@@ -17,10 +19,16 @@ U8conversion<T>
 	)
 	-> Vec<u8>;
 
+	/// # Panics
+	/// If the number of bytes does not fit the type, e.g. anything but 4 
+	/// bytes for a `u32` or 8 bytes for a `uR64`. For a `Vec`, it has to be
+	/// a multiple of the size of its elements. A `String` can be decoded from 
+	/// any number of bytes. The decoding of the `Metadata` checks this before
+	/// converting, so this only concerns direct calls.
 	fn
 	from_u8_vec
 	(
-		u8_vec: &Vec<u8>,
+		u8_vec: &[u8],
 		endian: &Endian
 	)
 	-> T;
@@ -54,7 +62,7 @@ macro_rules! build_u8conversion
 			fn
 			from_u8_vec
 			(
-				u8_vec: &Vec<u8>,
+				u8_vec: &[u8],
 				endian: &Endian
 			)
 			-> $type
@@ -105,7 +113,7 @@ impl U8conversion<String> for String
 	fn
 	from_u8_vec
 	(
-		u8_vec: &Vec<u8>,
+		u8_vec: &[u8],
 		_endian: &Endian
 	)
 	-> String
@@ -148,7 +156,7 @@ impl U8conversion<uR64> for uR64
 	fn
 	from_u8_vec
 	(
-		u8_vec: &Vec<u8>,
+		u8_vec: &[u8],
 		endian: &Endian
 	)
 	-> uR64
@@ -158,8 +166,8 @@ impl U8conversion<uR64> for uR64
 			panic!("from_u8_vec (r64u): Mangled EXIF data encountered!")
 		}
 
-		let nominator   = from_u8_vec_macro!(u32, &u8_vec[0..4].to_vec(), endian);
-		let denominator = from_u8_vec_macro!(u32, &u8_vec[4..8].to_vec(), endian);
+		let nominator   = from_u8_vec_macro!(u32, &u8_vec[0..4], endian);
+		let denominator = from_u8_vec_macro!(u32, &u8_vec[4..8], endian);
 
 		return uR64 { nominator, denominator };
 	}
@@ -183,7 +191,7 @@ impl U8conversion<iR64> for iR64
 	fn
 	from_u8_vec
 	(
-		u8_vec: &Vec<u8>,
+		u8_vec: &[u8],
 		endian: &Endian
 	)
 	-> iR64
@@ -193,8 +201,8 @@ impl U8conversion<iR64> for iR64
 			panic!("from_u8_vec (r64u): Mangled EXIF data encountered!")
 		}
 
-		let nominator   = from_u8_vec_macro!(i32, &u8_vec[0..4].to_vec(), endian);
-		let denominator = from_u8_vec_macro!(i32, &u8_vec[4..8].to_vec(), endian);
+		let nominator   = from_u8_vec_macro!(i32, &u8_vec[0..4], endian);
+		let denominator = from_u8_vec_macro!(i32, &u8_vec[4..8], endian);
 
 		return iR64 { nominator, denominator };
 	}
@@ -231,7 +239,7 @@ macro_rules! build_vec_u8conversion
 			fn
 			from_u8_vec
 			(
-				u8_vec: &Vec<u8>,
+				u8_vec: &[u8],
 				endian: &Endian
 			)
 			-> Vec<$type>
@@ -247,7 +255,7 @@ macro_rules! build_vec_u8conversion
 				{
 					result.push(
 						<$type>::from_u8_vec(
-							&u8_vec[(0 + i*$number_of_bytes)..((i+1)*$number_of_bytes)].to_vec(), 
+							&u8_vec[(0 + i*$number_of_bytes)..((i+1)*$number_of_bytes)], 
 							endian
 					) as $type);
 				}
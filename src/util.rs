@@ -3,6 +3,7 @@
 
 use std::io::Read;
 use std::io::Seek;
+use std::io::Write;
 
 use crate::general_file_io::io_error;
 
@@ -23,7 +24,7 @@ read_1_bytes
     // Check that indeed 1 bytes were read
     if bytes_read != 1
     {
-        return io_error!(Other, "Could not read the next 1 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 1 bytes!");
     }
 
     return Ok(field);
@@ -46,7 +47,7 @@ read_2_bytes
     // Check that indeed 2 bytes were read
     if bytes_read != 2
     {
-        return io_error!(Other, "Could not read the next 2 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 2 bytes!");
     }
 
     return Ok(field);
@@ -69,7 +70,7 @@ read_3_bytes
     // Check that indeed 3 bytes were read
     if bytes_read != 3
     {
-        return io_error!(Other, "Could not read the next 3 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 3 bytes!");
     }
 
     return Ok(field);
@@ -92,7 +93,7 @@ read_4_bytes
     // Check that indeed 4 bytes were read
     if bytes_read != 4
     {
-        return io_error!(Other, "Could not read the next 4 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 4 bytes!");
     }
 
     return Ok(field);
@@ -115,7 +116,7 @@ read_8_bytes
     // Check that indeed 8 bytes were read
     if bytes_read != 8
     {
-        return io_error!(Other, "Could not read the next 8 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 8 bytes!");
     }
 
     return Ok(field);
@@ -138,7 +139,7 @@ read_16_bytes
     // Check that indeed 16 bytes were read
     if bytes_read != 16
     {
-        return io_error!(Other, "Could not read the next 16 bytes!");
+        return io_error!(UnexpectedEof, "Could not read the next 16 bytes!");
     }
 
     return Ok(field);
@@ -216,7 +217,58 @@ read_null_terminated_string
         character_buffer = read_1_bytes(cursor)?;
     }
 
-    return Ok(String::from_utf8(string_buffer).unwrap());
+    if let Ok(string) = String::from_utf8(string_buffer)
+    {
+        return Ok(string);
+    }
+    return io_error!(InvalidData, "Null terminated string is not valid UTF-8!");
+}
+
+/// Reads in the next n bytes, starting at the current position of the cursor.
+/// Other than allocating a buffer of size n upfront, this only grows the
+/// buffer as data is actually read, so a bogus size information does not
+/// lead to huge allocations.
+pub(crate) fn
+read_n_bytes
+<T: Seek + Read>
+(
+    cursor: &mut T,
+    n:      usize
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut buffer = Vec::new();
+    cursor.take(n as u64).read_to_end(&mut buffer)?;
+
+    if buffer.len() != n
+    {
+        return io_error!(UnexpectedEof, format!("Could not read the next {} bytes!", n));
+    }
+
+    return Ok(buffer);
+}
+
+/// Copies the next n bytes from the reader to the writer without loading all
+/// of them into memory at once.
+/// The function call advances the reader by n bytes.
+pub(crate) fn
+copy_n_bytes
+<R: Read, W: Write>
+(
+    reader: &mut R,
+    writer: &mut W,
+    n:      u64
+)
+-> Result<(), std::io::Error>
+{
+    let bytes_copied = std::io::copy(&mut reader.take(n), writer)?;
+
+    if bytes_copied != n
+    {
+        return io_error!(UnexpectedEof, format!("Could not copy the next {} bytes!", n));
+    }
+
+    return Ok(());
 }
 
 
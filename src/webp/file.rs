@@ -8,8 +8,9 @@ use std::io::Seek;
 use std::io::SeekFrom;
 use std::path::Path;
 
-use log::debug;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::endian::*;
 use crate::metadata::Metadata;
 use crate::u8conversion::*;
@@ -30,7 +31,7 @@ check_signature
 (
 	path: &Path
 )
--> Result<File, std::io::Error>
+-> Result<File, Error>
 {
 	let mut file = open_write_file(path)?;
 
@@ -57,11 +58,11 @@ get_next_chunk
 (
 	file: &mut File
 )
--> Result<RiffChunk, std::io::Error>
+-> Result<RiffChunk, Error>
 {
 	// Read the start of the chunk
 	let mut chunk_start = [0u8; 8];
-	let mut bytes_read = file.read(&mut chunk_start).unwrap();
+	let mut bytes_read = file.read(&mut chunk_start)?;
 
 	// Check that indeed 8 bytes were read
 	if bytes_read != 8
@@ -71,18 +72,27 @@ get_next_chunk
 
 	// Construct name of chunk and its length
 	let chunk_name = String::from_utf8(chunk_start[0..4].to_vec());
-	let mut chunk_length = from_u8_vec_macro!(u32, &chunk_start[4..8].to_vec(), &Endian::Little);
+	let mut chunk_length = from_u8_vec_macro!(u32, &chunk_start[4..8], &Endian::Little) as u64;
 
 	// Account for the possible padding byte
 	chunk_length += chunk_length % 2;
 
+	// Make sure that the chunk fits into the remaining data before allocating
+	// a buffer for it
+	let remaining_bytes = file.metadata()?.len().saturating_sub(file.stream_position()?);
+	if chunk_length > remaining_bytes
+	{
+		return container_error!(
+			format!("RIFF chunk length {chunk_length} exceeds remaining {remaining_bytes} bytes!")
+		);
+	}
+
 	// Read RIFF chunk data
 	let mut chunk_data_buffer = vec![0u8; chunk_length as usize];
-	bytes_read = file.read(&mut chunk_data_buffer).unwrap();
+	bytes_read = file.read(&mut chunk_data_buffer)?;
 	if bytes_read != chunk_length as usize
 	{
-		return io_error!(
-			Other, 
+		return container_error!(
 			format!("Could not read RIFF chunk data! Expected {chunk_length} bytes but read {bytes_read}")
 		);
 	}
@@ -97,7 +107,7 @@ get_next_chunk
 	}
 	else
 	{
-		return io_error!(Other, "Could not parse RIFF fourCC chunk name!");
+		return container_error!("Could not parse RIFF fourCC chunk name!");
 	}
 }
 
@@ -112,7 +122,7 @@ get_next_chunk_descriptor
 (
 	file: &mut File
 )
--> Result<RiffChunkDescriptor, std::io::Error>
+-> Result<RiffChunkDescriptor, Error>
 {
 	let next_chunk_result = get_next_chunk(file)?;
 	return Ok(next_chunk_result.descriptor());
@@ -128,7 +138,7 @@ parse_webp
 (
 	path: &Path
 )
--> Result<Vec<RiffChunkDescriptor>, std::io::Error>
+-> Result<Vec<RiffChunkDescriptor>, Error>
 {
 	let file_result = check_signature(path);
 	let mut chunks = Vec::new();
@@ -178,7 +188,7 @@ parse_webp
 			// in the file and we are done with parsing.
 			// If the subroutine fails due to other reasons, the error gets
 			// propagated further.
-			if next_chunk_descriptor_result.as_ref().err().unwrap().kind() == std::io::ErrorKind::UnexpectedEof
+			if next_chunk_descriptor_result.as_ref().err().unwrap().is_unexpected_eof()
 			{
 				break;
 			}
@@ -199,7 +209,7 @@ check_exif_in_file
 (
 	path: &Path
 )
--> Result<(File, Vec<RiffChunkDescriptor>), std::io::Error>
+-> Result<(File, Vec<RiffChunkDescriptor>), Error>
 {
 	// Parse the WebP file - if this fails, we surely can't read any metadata
 	let parsed_webp_result = parse_webp(path);
@@ -217,15 +227,20 @@ check_exif_in_file
 		// Compare the chunk descriptor header.
 		if first_chunk.header().to_lowercase() != VP8X_HEADER.to_lowercase()
 		{
-			return io_error!(
-				Other, 
+			// Simple File Format (lossy or lossless) can't store EXIF data
+			if first_chunk.header() == "VP8 " || first_chunk.header() == "VP8L"
+			{
+				return Err(Error::NoExifData);
+			}
+
+			return container_error!(
 				format!("Expected first chunk of WebP file to be of type 'VP8X' but instead got {}!", first_chunk.header())
 			);
 		}
 	}
 	else
 	{
-		return io_error!(Other, "Could not read first chunk descriptor of WebP file!");
+		return container_error!("Could not read first chunk descriptor of WebP file!");
 	}
 
 	// Finally, check the flag by opening up the file and reading the data of
@@ -237,9 +252,9 @@ check_exif_in_file
 	let mut file = check_signature(path).unwrap();
 	let mut flag_buffer = vec![0u8; 4usize];
 	perform_file_action!(file.seek(SeekFrom::Start(12u64 + 4u64 + 4u64)));
-	if file.read(&mut flag_buffer).unwrap() != 4
+	if file.read(&mut flag_buffer)? != 4
 	{
-		return io_error!(Other, "Could not read flags of VP8X chunk!");
+		return container_error!("Could not read flags of VP8X chunk!");
 	}
 
 	// Check the 5th bit of the 32 bit flag_buffer. 
@@ -247,7 +262,8 @@ check_exif_in_file
 	// https://developers.google.com/speed/webp/docs/riff_container#extended_file_format
 	if flag_buffer[0] & 0x08 != 0x08
 	{
-		return io_error!(Other, "No EXIF chunk according to VP8X flags!");
+		// No EXIF chunk according to VP8X flags
+		return Err(Error::NoExifData);
 	}
 
 	return Ok((file, parsed_webp_result.unwrap()));
@@ -262,11 +278,11 @@ read_metadata
 (
 	path: &Path
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// Check the file signature, parse it, check that it has a VP8X chunk and
 	// the EXIF flag is set there
-	let (mut file, parse_webp_result) = check_exif_in_file(path).unwrap();
+	let (mut file, parse_webp_result) = check_exif_in_file(path)?;
 
 	// At this point we have established that the file has to contain an EXIF
 	// chunk at some point. So, now we need to find & return it
@@ -279,27 +295,31 @@ read_metadata
 	loop
 	{
 		// Read the chunk type into the buffer
-		if file.read(&mut header_buffer).unwrap() != 4
+		if file.read(&mut header_buffer)? != 4
 		{
-			return io_error!(Other, "Could not read chunk type while traversing WebP file!");
+			return container_error!("Could not read chunk type while traversing WebP file!");
 		}
-		let chunk_type = String::from_u8_vec(&header_buffer.to_vec(), &Endian::Little);
+		let chunk_type = String::from_utf8_lossy(&header_buffer).to_string();
 
 		// Check that this is still the type that we expect from the previous
 		// parsing over the file
 		// TODO: Maybe remove this part?
-		let expected_chunk_type = parse_webp_result.iter().nth(chunk_index).unwrap().header();
+		let expected_chunk = match parse_webp_result.get(chunk_index)
+		{
+			Some(chunk) => chunk,
+			None        => return container_error!("Could not find EXIF chunk even though VP8X flag is set!"),
+		};
+		let expected_chunk_type = expected_chunk.header();
 		if chunk_type != expected_chunk_type
 		{
-			return io_error!(
-				Other, 
+			return container_error!(
 				format!("Got unexpected chunk type! Expected {} but got {}", expected_chunk_type, chunk_type)
 			);
 		}
 
 		// Get the size of this chunk from the previous parsing process and skip
 		// the 4 bytes regarding the size
-		let chunk_size = parse_webp_result.iter().nth(chunk_index).unwrap().len();
+		let chunk_size = expected_chunk.len();
 		file.seek(std::io::SeekFrom::Current(4))?;
 
 		if chunk_type.to_lowercase() == EXIF_CHUNK_HEADER.to_lowercase()
@@ -341,7 +361,7 @@ update_file_size_information
 	file:  &mut File,
 	delta: i32
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Note from the documentation:
 	// As the size of any chunk is even, the size given by the RIFF header is also even.
@@ -352,7 +372,7 @@ update_file_size_information
 
 	// ...converting it to u32 representation...
 	perform_file_action!(file.read(&mut file_size_buffer));
-	let old_file_size = from_u8_vec_macro!(u32, &file_size_buffer.to_vec(), &Endian::Little);
+	let old_file_size = from_u8_vec_macro!(u32, &file_size_buffer, &Endian::Little);
 
 	// ...adding the delta byte count (and performing some checks)...
 	if delta < 0
@@ -378,7 +398,7 @@ convert_to_extended_format
 (
 	file: &mut File
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Start by getting the first chunk of the WebP file
 	perform_file_action!(file.seek(SeekFrom::Start(12)));
@@ -392,29 +412,10 @@ convert_to_extended_format
 
 	let first_chunk = first_chunk_result.unwrap();
 
-	// Find out what simple type of WebP file we are dealing with
-	let (width, height) = match first_chunk.descriptor().header().as_str()
-	{
-		"VP8" 
-			=> {debug!("VP8 !"); todo!()},
-		"VP8L"
-			=> get_dimension_info_from_vp8l_chunk(first_chunk.payload()),
-		_ 
-			=> io_error!(Other, "Expected either 'VP8 ' or 'VP8L' chunk for conversion!")
-	}?;
-
-	let width_vec  = to_u8_vec_macro!(u32, &width,  &Endian::Little);
-	let height_vec = to_u8_vec_macro!(u32, &height, &Endian::Little);
-
-	let mut vp8x_chunk = vec![
-		0x56, 0x50, 0x38, 0x58, // ASCII chars "V", "P", "8", "X"                  -> 4 byte
-		0x0A, 0x00, 0x00, 0x00, // size of this chunk (32 + 24 + 24 bit = 10 byte) -> 4 byte
-		0x00, 0x00, 0x00, 0x00, // Flags and reserved area                         -> 4 byte
-	];
-
-	// Add the two 24 bits for width and height information
-	for i in 0..3 { vp8x_chunk.push(width_vec[i]); }
-	for i in 0..3 { vp8x_chunk.push(height_vec[i]); }
+	let vp8x_chunk = construct_vp8x_chunk(
+		first_chunk.descriptor().header().as_str(), 
+		first_chunk.payload()
+	)?;
 
 	// Write the VP8X chunk, first by reading the file (except for the header)
 	// into a buffer...
@@ -437,49 +438,13 @@ convert_to_extended_format
 
 
 
-fn
-get_dimension_info_from_vp8l_chunk
-(
-	payload: &Vec<u8>
-)
--> Result<(u32, u32), std::io::Error>
-{
-	// Get the 4 bytes containing the dimension information
-	// (although we only need 28 bits)
-	// Starting at byte 1 instead of 0 due to the 0x2F byte
-	// See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
-	let width_height_info_buffer = payload[1..5].to_vec();
-	
-	// Convert to a single u32 number for bit-mask operations
-	let width_height_info = from_u8_vec_macro!(u32, &width_height_info_buffer, &Endian::Little);
-	
-	let mut width  = 0;
-	let mut height = 0;
-
-	// Get the first 14 bit to construct the width
-	for bit_index in 0..14
-	{
-		width  |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-	}
-
-	// Get the next 14 bit to construct the height
-	for bit_index in 14..28
-	{
-		height |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-	}
-
-	return Ok((width, height));
-}
-
-
-
 fn
 set_exif_flag
 (
 	path:  &Path,
 	exif_flag_value: bool
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Parse the WebP file - if this fails, we surely can't read any metadata
 	let parsed_webp_result = parse_webp(path);
@@ -504,16 +469,16 @@ set_exif_flag
 	}
 	else
 	{
-		return io_error!(Other, "Could not read first chunk descriptor of WebP file!");
+		return container_error!("Could not read first chunk descriptor of WebP file!");
 	}	
 
 	// At this point we know that we have a VP8X chunk at the expected location
 	// So, read in the flags and set the EXIF flag according to the given bool
 	let mut flag_buffer = vec![0u8; 4usize];
 	perform_file_action!(file.seek(SeekFrom::Start(12u64 + 4u64 + 4u64)));
-	if file.read(&mut flag_buffer).unwrap() != 4
+	if file.read(&mut flag_buffer)? != 4
 	{
-		return io_error!(Other, "Could not read flags of VP8X chunk!");
+		return container_error!("Could not read flags of VP8X chunk!");
 	}
 
 	// Mask the old flag by either or-ing with 1 at the EXIF flag position for
@@ -542,31 +507,37 @@ clear_metadata
 (
 	path: &Path
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Check the file signature, parse it, check that it has a VP8X chunk and
 	// the EXIF flag is set there
-	let exif_check_result = check_exif_in_file(path);
-	if exif_check_result.is_err()
+	let (mut file, parse_webp_result) = match check_exif_in_file(path)
 	{
-		match exif_check_result.as_ref().err().unwrap().to_string().as_str()
-		{
-			"No EXIF chunk according to VP8X flags!"
-				=> return Ok(()),
-			"Expected first chunk of WebP file to be of type 'VP8X' but instead got VP8L!"
-				=> return Ok(()),
-			_
-				=> return Err(exif_check_result.err().unwrap())
-		}
-	}
-
-	let (mut file, parse_webp_result) = exif_check_result.unwrap();
+		Ok(exif_check_result)  => exif_check_result,
+		Err(Error::NoExifData) => {
+			// The EXIF flag may be unset even though there is an EXIF chunk
+			// in an Extended File Format WebP. Remove it anyway, otherwise it
+			// would shadow the EXIF chunk written afterwards.
+			let parse_webp_result = parse_webp(path)?;
+			let is_extended = parse_webp_result.first()
+				.map_or(false, |chunk| chunk.header().to_lowercase() == VP8X_HEADER.to_lowercase());
+			let has_exif    = parse_webp_result.iter()
+				.any(|chunk| chunk.header().to_lowercase() == EXIF_CHUNK_HEADER.to_lowercase());
+
+			if !is_extended || !has_exif
+			{
+				return Ok(());
+			}
+			(check_signature(path)?, parse_webp_result)
+		},
+		Err(error)             => return Err(error),
+	};
 
 	// Compute a delta of how much the file size information has to change
 	let mut delta = 0i32;
 
-	// Skip the WEBP signature
-	file.seek(std::io::SeekFrom::Current(4i64))?;
+	// Skip the RIFF header, file size and WEBP signature
+	file.seek(std::io::SeekFrom::Start(12))?;
 
 	for parsed_chunk in parse_webp_result
 	{
@@ -636,7 +607,7 @@ write_metadata
 	path:     &Path,
 	metadata: &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Clear the metadata from the file and return if this results in an error
 	clear_metadata(path)?;
@@ -650,18 +621,11 @@ write_metadata
 	// ...and find a location where to put the EXIF chunk
 	// This is done by requesting a chunk descriptor as long as we find a chunk
 	// that is both known and should be located *before* the EXIF chunk
-	let pre_exif_chunks = [
-		"VP8X",
-		"VP8",
-		"VP8L",
-		"ICCP",
-		"ANIM"
-	];
-
 	loop
 	{
 		// Request a chunk descriptor. If this fails, check the error 
 		// Depending on its type, either continue normally or return it
+		let chunk_start_position    = file.stream_position()?;
 		let chunk_descriptor_result = get_next_chunk_descriptor(&mut file);
 
 		if let Ok(chunk_descriptor) = chunk_descriptor_result
@@ -670,24 +634,27 @@ write_metadata
 
 			// Check header of chunk descriptor against any of the known chunks
 			// that should come before the EXIF chunk
-			for pre_exif_chunk in &pre_exif_chunks
+			for pre_exif_chunk in &PRE_EXIF_CHUNK_HEADERS
 			{
 				chunk_type_found_in_pre_exif_chunks |= pre_exif_chunk.to_lowercase() == chunk_descriptor.header().to_lowercase();
 			}
 
 			if !chunk_type_found_in_pre_exif_chunks
 			{
+				// Place the EXIF chunk in front of this chunk
+				file.seek(SeekFrom::Start(chunk_start_position))?;
 				break;
 			}
 		}
 		else
 		{
-			match chunk_descriptor_result.as_ref().err().unwrap().kind()
+			if chunk_descriptor_result.as_ref().err().unwrap().is_unexpected_eof()
 			{
-				std::io::ErrorKind::UnexpectedEof
-					=> break, // No further chunks, place EXIF chunk here
-				_
-					=> return Err(chunk_descriptor_result.err().unwrap())
+				break; // No further chunks, place EXIF chunk here
+			}
+			else
+			{
+				return Err(chunk_descriptor_result.err().unwrap());
 			}
 		}
 	}
@@ -729,10 +696,12 @@ mod tests
 	use std::fs::remove_file;
 	use std::path::Path;
 
+	use crate::error::Error;
+
 	#[test]
 	fn
 	clear_metadata()
-	-> Result<(), std::io::Error>
+	-> Result<(), Error>
 	{
 		// Remove file from previous run and replace it with fresh copy
 		if let Err(error) = remove_file("tests/read_sample_no_exif.webp")
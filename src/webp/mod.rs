@@ -1,4 +1,5 @@
 pub mod file;
+pub mod stream;
 pub mod vec;
 
 mod riff_chunk;
@@ -7,31 +8,75 @@ pub(crate) const RIFF_SIGNATURE:       [u8; 4] = [0x52, 0x49, 0x46, 0x46];
 pub(crate) const WEBP_SIGNATURE:       [u8; 4] = [0x57, 0x45, 0x42, 0x50];
 pub(crate) const VP8X_HEADER:          &str    = "VP8X";
 pub(crate) const EXIF_CHUNK_HEADER:    &str    = "EXIF";
+pub(crate) const XMP_CHUNK_HEADER:     &str    = "XMP ";
+pub(crate) const ICCP_CHUNK_HEADER:    &str    = "ICCP";
+pub(crate) const C2PA_CHUNK_HEADER:    &str    = "C2PA";
+
+/// Known chunks that should be located *before* the EXIF chunk
+const PRE_EXIF_CHUNK_HEADERS:          [&str; 7] = [
+	"VP8X",
+	"VP8 ",
+	"VP8L",
+	"ALPH",
+	"ICCP",
+	"ANIM",
+	"ANMF"
+];
+
+/// Start code of a VP8 key frame, following the 3 bytes of the frame tag
+const VP8_START_CODE:                  [u8; 3] = [0x9D, 0x01, 0x2A];
+
+/// Signature byte at the start of a VP8L bitstream
+const VP8L_SIGNATURE:                  u8      = 0x2F;
 
 use std::fs::File;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::endian::Endian;
 use crate::general_file_io::io_error;
 use crate::u8conversion::from_u8_vec_macro;
 use crate::u8conversion::to_u8_vec_macro;
 use crate::u8conversion::U8conversion;
 
+/// Checks if the given data starts with a RIFF header that announces WebP
+/// data, i.e. the RIFF signature, 4 bytes of size info and the WEBP signature
+pub(crate) fn
+starts_with_webp_signature
+(
+	file_buffer: &[u8]
+)
+-> bool
+{
+	if file_buffer.len() < 12
+	{
+		return false;
+	}
+
+	return file_buffer[0..4] == RIFF_SIGNATURE 
+		&& file_buffer[8..12] == WEBP_SIGNATURE;
+}
+
 fn
 check_riff_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
+	if file_buffer.len() < 12
+	{
+		return container_error!("Can't open WebP file - Not enough data for signature!");
+	}
+
 	// Check the RIFF signature
 	if !file_buffer[0..4].iter()
 		.zip(RIFF_SIGNATURE.iter())
 		.filter(|&(read, constant)| read == constant)
 		.count() == RIFF_SIGNATURE.len()
 	{
-		return io_error!(
-			InvalidData, 
-			format!("Can't open WebP file - Expected RIFF signature but found {}!", from_u8_vec_macro!(String, &file_buffer[0..4].to_vec(), &Endian::Big))
+		return container_error!(
+			format!("Can't open WebP file - Expected RIFF signature but found {}!", from_u8_vec_macro!(String, &file_buffer[0..4], &Endian::Big))
 		);
 	}
 
@@ -41,18 +86,17 @@ check_riff_signature
 fn
 check_webp_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	if !file_buffer[8..12].iter()
 		.zip(WEBP_SIGNATURE.iter())
 		.filter(|&(read, constant)| read == constant)
 		.count() == WEBP_SIGNATURE.len()
 	{
-		return io_error!(
-			InvalidData, 
-			format!("Can't open WebP file - Expected WEBP signature but found {}!", from_u8_vec_macro!(String, &file_buffer[8..12].to_vec(), &Endian::Big))
+		return container_error!(
+			format!("Can't open WebP file - Expected WEBP signature but found {}!", from_u8_vec_macro!(String, &file_buffer[8..12], &Endian::Big))
 		);
 	}
 
@@ -62,29 +106,29 @@ check_webp_signature
 fn
 check_byte_count
 (
-	file_buffer: &Vec<u8>,
+	file_buffer: &[u8],
 	opt_file: Option<&File>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	let byte_count = from_u8_vec_macro!(
 		u32, 
-		&file_buffer[4..8].to_vec(), 
+		&file_buffer[4..8], 
 		&Endian::Little
-	) + 8;
+	) as u64 + 8;
 
 	if let Some(file) = opt_file
 	{
-		if file.metadata().unwrap().len() != byte_count as u64
+		if file.metadata()?.len() != byte_count
 		{
-			return io_error!(InvalidData, "Can't open WebP file - Promised byte count does not correspond with file size!");
+			return container_error!("Can't open WebP file - Promised byte count does not correspond with file size!");
 		}	
 	}
 	else
 	{
-		if file_buffer.len() != byte_count as usize
+		if file_buffer.len() as u64 != byte_count
 		{
-			return io_error!(InvalidData, format!("Can't handle WebP file buffer - Promised byte count {} does not correspond with file buffer length {}!", byte_count, file_buffer.len()));
+			return container_error!(format!("Can't handle WebP file buffer - Promised byte count {} does not correspond with file buffer length {}!", byte_count, file_buffer.len()));
 		}
 	}
 
@@ -133,4 +177,116 @@ as_u8_vec
 -> Vec<u8>
 {
 	encode_metadata_webp(general_encoded_metadata)
-}
\ No newline at end of file
+}
+
+
+
+/// Gets the canvas width and height (each minus one, as they are stored in
+/// the VP8X chunk) from the payload of a lossy `VP8 ` chunk. 
+/// See: https://datatracker.ietf.org/doc/html/rfc6386#section-9.1
+fn
+get_dimension_info_from_vp8_chunk
+(
+	payload: &[u8]
+)
+-> Result<(u32, u32), Error>
+{
+	// 3 bytes frame tag, 3 bytes start code, 2 bytes each for width & height
+	if payload.len() < 10
+	{
+		return container_error!("VP8 chunk is too short for a frame header!");
+	}
+
+	// Only key frames have a start code and dimension information, which is
+	// indicated by the first bit of the frame tag being 0
+	if payload[0] & 0x01 != 0 || payload[3..6] != VP8_START_CODE
+	{
+		return container_error!("VP8 chunk does not start with a key frame!");
+	}
+
+	// The upper 2 bits of each 16 bit value are scaling information
+	let width  = u16::from_le_bytes([payload[6], payload[7]]) as u32 & 0x3FFF;
+	let height = u16::from_le_bytes([payload[8], payload[9]]) as u32 & 0x3FFF;
+
+	if width == 0 || height == 0
+	{
+		return container_error!("VP8 frame header has a width or height of 0!");
+	}
+
+	return Ok((width - 1, height - 1));
+}
+
+/// Gets the canvas width and height (each minus one, as they are stored in
+/// the VP8X chunk) from the payload of a lossless `VP8L` chunk as well as
+/// whether the alpha channel is used.
+/// See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
+fn
+get_dimension_info_from_vp8l_chunk
+(
+	payload: &[u8]
+)
+-> Result<(u32, u32, bool), Error>
+{
+	if payload.len() < 5 || payload[0] != VP8L_SIGNATURE
+	{
+		return container_error!("VP8L chunk does not start with a valid header!");
+	}
+
+	// The 4 bytes after the signature contain 14 bits of width, 14 bits of
+	// height (both minus one), the alpha_is_used bit and 3 bits of version
+	let width_height_info = u32::from_le_bytes([
+		payload[1], payload[2], payload[3], payload[4]
+	]);
+
+	let width  =  width_height_info        & 0x3FFF;
+	let height = (width_height_info >> 14) & 0x3FFF;
+	let alpha  = (width_height_info >> 28) & 0x01 == 0x01;
+
+	return Ok((width, height, alpha));
+}
+
+/// Constructs a VP8X chunk for converting a Simple File Format WebP file to
+/// the Extended File Format, based on the type and payload of the first chunk
+/// of the file which contains the image data. Only the start of the payload 
+/// is required for this. Apart from the alpha flag no flags are set.
+fn
+construct_vp8x_chunk
+(
+	first_chunk_type:    &str,
+	first_chunk_payload: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	// Find out what simple type of WebP file we are dealing with
+	let (width, height, alpha) = match first_chunk_type
+	{
+		"VP8 " 
+			=> get_dimension_info_from_vp8_chunk(first_chunk_payload)
+				.map(|(width, height)| (width, height, false)),
+		"VP8L"
+			=> get_dimension_info_from_vp8l_chunk(first_chunk_payload),
+		_ 
+			=> container_error!(format!("Expected either 'VP8 ' or 'VP8L' chunk for conversion but got {:?}!", first_chunk_type))
+	}?;
+
+	let width_vec  = to_u8_vec_macro!(u32, &width,  &Endian::Little);
+	let height_vec = to_u8_vec_macro!(u32, &height, &Endian::Little);
+
+	let mut vp8x_chunk = vec![
+		0x56, 0x50, 0x38, 0x58, // ASCII chars "V", "P", "8", "X"                  -> 4 byte
+		0x0A, 0x00, 0x00, 0x00, // size of this chunk (32 + 24 + 24 bit = 10 byte) -> 4 byte
+		0x00, 0x00, 0x00, 0x00, // Flags and reserved area                         -> 4 byte
+	];
+
+	// Set the alpha flag if the lossless image data uses it
+	if alpha
+	{
+		vp8x_chunk[8] |= 0x10;
+	}
+
+	// Add the two 24 bits for width and height information
+	vp8x_chunk.extend(&width_vec[..3]);
+	vp8x_chunk.extend(&height_vec[..3]);
+
+	return Ok(vp8x_chunk);
+}
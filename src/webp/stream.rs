@@ -0,0 +1,298 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+
+use crate::error::Error;
+use crate::error::container_error;
+use crate::general_file_io::EXIF_HEADER;
+use crate::metadata::Metadata;
+use crate::util::copy_n_bytes;
+
+use super::riff_chunk::RiffChunkDescriptor;
+use super::*;
+
+/// Checks the RIFF header of the input and goes through the chunks by only
+/// reading their fourCC and size information.
+/// Advances the input to the end of the data.
+fn
+parse_webp
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Vec<RiffChunkDescriptor>, Error>
+{
+	// Only the first 12 bytes are needed for checking the signatures
+	let mut header_buffer = vec![0u8; 12];
+	if input.read(&mut header_buffer)? != 12
+	{
+		return container_error!("Can't open WebP file - Not enough data for signature!");
+	}
+	check_riff_signature(&header_buffer)?;
+	check_webp_signature(&header_buffer)?;
+
+	let byte_count = from_u8_vec_macro!(
+		u32,
+		&header_buffer[4..8],
+		&Endian::Little
+	) as u64 + 8;
+
+	let input_length = input.seek(SeekFrom::End(0))?;
+	if input_length != byte_count
+	{
+		return container_error!("Can't open WebP file - Promised byte count does not correspond with file size!");
+	}
+
+	let mut chunks   = Vec::new();
+	let mut position = input.seek(SeekFrom::Start(12))?;
+
+	// Stop if there is not enough data left for another chunk header, just
+	// like the parser for WebP files in memory does
+	while position + 8 <= input_length
+	{
+		let mut chunk_start = [0u8; 8];
+		input.read_exact(&mut chunk_start)?;
+
+		let chunk_name = match String::from_utf8(chunk_start[0..4].to_vec())
+		{
+			Ok(chunk_name) => chunk_name,
+			Err(_)         => return container_error!("Could not parse RIFF fourCC chunk name!"),
+		};
+
+		// Account for the possible padding byte
+		let mut chunk_length = from_u8_vec_macro!(u32, &chunk_start[4..8], &Endian::Little) as u64;
+		chunk_length += chunk_length % 2;
+
+		let remaining_bytes = input_length - position - 8;
+		if chunk_length > remaining_bytes
+		{
+			return container_error!(
+				format!("RIFF chunk length {chunk_length} exceeds remaining {remaining_bytes} bytes!")
+			);
+		}
+
+		chunks.push(RiffChunkDescriptor::new(chunk_name, chunk_length as usize));
+		position = input.seek(SeekFrom::Current(chunk_length as i64))?;
+	}
+
+	return Ok(chunks);
+}
+
+
+
+/// Reads the raw EXIF data from the WebP data provided by the given reader.
+/// Only the chunk headers, the flags of the VP8X chunk and the EXIF chunk
+/// itself are read. Just like the other read functions, the first EXIF chunk
+/// is returned and any others get ignored.
+pub(crate) fn
+read_metadata
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Vec<u8>, Error>
+{
+	let chunks = parse_webp(input)?;
+
+	// Only Extended File Format WebP files can store EXIF data, in which case
+	// the first chunk has to be of type "VP8X"
+	match chunks.first()
+	{
+		Some(chunk) if chunk.header().to_lowercase() == VP8X_HEADER.to_lowercase() => (),
+		Some(chunk) if chunk.header() == "VP8 " || chunk.header() == "VP8L"         => return Err(Error::NoExifData),
+		Some(chunk) => return container_error!(
+			format!("Expected first chunk of WebP file to be of type 'VP8X' but instead got {}!", chunk.header())
+		),
+		None        => return container_error!("Could not read first chunk descriptor of WebP file!"),
+	};
+
+	// Check the EXIF flag of the VP8X chunk, located after the RIFF header 
+	// (12 bytes) and the VP8X chunk header (8 bytes)
+	let mut flag_buffer = Vec::new();
+	input.seek(SeekFrom::Start(12 + 8))?;
+	input.take(4).read_to_end(&mut flag_buffer)?;
+	if flag_buffer.len() != 4
+	{
+		return container_error!("Could not read flags of VP8X chunk!");
+	}
+	if flag_buffer[0] & 0x08 != 0x08
+	{
+		return Err(Error::NoExifData);
+	}
+
+	// Find the EXIF chunk
+	let mut position = 12u64;
+	for chunk in &chunks
+	{
+		if chunk.header().to_lowercase() == EXIF_CHUNK_HEADER.to_lowercase()
+		{
+			input.seek(SeekFrom::Start(position + 8))?;
+
+			// Add the 6 bytes of the EXIF_HEADER as prefix for the generic
+			// EXIF data parser, see `vec::read_metadata`
+			let mut raw_exif_data = EXIF_HEADER.to_vec();
+			copy_n_bytes(input, &mut raw_exif_data, chunk.len() as u64)?;
+
+			return Ok(raw_exif_data);
+		}
+
+		position += 8 + chunk.len() as u64;
+	}
+
+	return container_error!("Could not find EXIF chunk even though VP8X flag is set!");
+}
+
+
+
+/// Reads the C2PA manifest store from the (first) C2PA chunk by only reading
+/// the chunk headers and the data of that chunk
+pub(crate) fn
+read_c2pa_jumbf
+<R: Read + Seek>
+(
+	input: &mut R
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let chunks = parse_webp(input)?;
+
+	let mut position = 12u64;
+	for chunk in &chunks
+	{
+		if chunk.header() == C2PA_CHUNK_HEADER
+		{
+			// Use the size information of the chunk itself, as the parsed
+			// length includes the possible padding byte
+			let mut size_buffer = [0u8; 4];
+			input.seek(SeekFrom::Start(position + 4))?;
+			input.read_exact(&mut size_buffer)?;
+			let payload_size = from_u8_vec_macro!(u32, &size_buffer, &Endian::Little);
+
+			let mut jumbf = Vec::new();
+			copy_n_bytes(input, &mut jumbf, payload_size as u64)?;
+			return Ok(Some(jumbf));
+		}
+
+		position += 8 + chunk.len() as u64;
+	}
+
+	return Ok(None);
+}
+
+
+
+/// Writes the WebP image read from the input to the output while replacing
+/// the EXIF chunk(s) with the given metadata.
+/// Computing the new file size requires a first pass over the chunk headers,
+/// the second pass then copies the chunks without loading them into memory.
+/// Like the other write functions, this converts a Simple File Format WebP
+/// to the Extended File Format first.
+pub(crate) fn
+write_metadata
+<R: Read + Seek, W: Write>
+(
+	input:    &mut R,
+	output:   &mut W,
+	metadata: &Metadata
+)
+-> Result<(), Error>
+{
+	let chunks = parse_webp(input)?;
+
+	let is_extended = match chunks.first()
+	{
+		Some(chunk) => chunk.header().to_lowercase() == VP8X_HEADER.to_lowercase(),
+		None        => return container_error!("Could not read first chunk descriptor of WebP file!"),
+	};
+
+	// Construct the VP8X chunk if the file does not have one yet. For this,
+	// only the first few bytes of the image data are needed
+	let mut vp8x_chunk = Vec::new();
+	if !is_extended
+	{
+		let mut first_chunk_payload = Vec::new();
+		input.seek(SeekFrom::Start(12 + 8))?;
+		input.take(10).read_to_end(&mut first_chunk_payload)?;
+
+		vp8x_chunk = construct_vp8x_chunk(
+			chunks[0].header().as_str(),
+			&first_chunk_payload
+		)?;
+		vp8x_chunk[8] |= 0x08;
+	}
+
+	let encoded_metadata = encode_metadata_webp(&metadata.encode()?);
+
+	// The new file size: the WEBP signature, all chunks apart from the old
+	// EXIF ones, the new VP8X chunk and the new EXIF chunk
+	let mut file_size = 4u64 + vp8x_chunk.len() as u64 + encoded_metadata.len() as u64;
+	for chunk in &chunks
+	{
+		if chunk.header().to_lowercase() != EXIF_CHUNK_HEADER.to_lowercase()
+		{
+			file_size += 8 + chunk.len() as u64;
+		}
+	}
+
+	if file_size > u32::MAX as u64
+	{
+		return container_error!("WebP file would exceed the maximum size of a RIFF file!");
+	}
+
+	output.write_all(&RIFF_SIGNATURE)?;
+	output.write_all(&to_u8_vec_macro!(u32, &(file_size as u32), &Endian::Little))?;
+	output.write_all(&WEBP_SIGNATURE)?;
+	output.write_all(&vp8x_chunk)?;
+
+	input.seek(SeekFrom::Start(12))?;
+	let mut exif_chunk_written = false;
+
+	for chunk in &chunks
+	{
+		let chunk_type = chunk.header().to_lowercase();
+
+		// Drop the old metadata by not copying the chunk
+		if chunk_type == EXIF_CHUNK_HEADER.to_lowercase()
+		{
+			input.seek(SeekFrom::Current(8 + chunk.len() as i64))?;
+			continue;
+		}
+
+		// Place the EXIF chunk in front of the first chunk that should be
+		// located after it
+		if !exif_chunk_written && !PRE_EXIF_CHUNK_HEADERS
+			.iter()
+			.any(|pre_exif_chunk| pre_exif_chunk.to_lowercase() == chunk_type)
+		{
+			output.write_all(&encoded_metadata)?;
+			exif_chunk_written = true;
+		}
+
+		if chunk_type == VP8X_HEADER.to_lowercase()
+		{
+			// Copy the VP8X chunk with the EXIF flag being set
+			let mut existing_vp8x_chunk = vec![0u8; 8 + chunk.len()];
+			input.read_exact(&mut existing_vp8x_chunk)?;
+			if existing_vp8x_chunk.len() > 8
+			{
+				existing_vp8x_chunk[8] |= 0x08;
+			}
+			output.write_all(&existing_vp8x_chunk)?;
+		}
+		else
+		{
+			copy_n_bytes(input, output, 8 + chunk.len() as u64)?;
+		}
+	}
+
+	if !exif_chunk_written
+	{
+		output.write_all(&encoded_metadata)?;
+	}
+
+	return Ok(());
+}
@@ -6,8 +6,9 @@ use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
 
-use log::debug;
 
+use crate::error::Error;
+use crate::error::container_error;
 use crate::general_file_io::EXIF_HEADER;
 use crate::metadata::Metadata;
 use crate::util::insert_multiple_at;
@@ -25,9 +26,9 @@ use super::*;
 fn
 check_signature
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Cursor<&Vec<u8>>, std::io::Error>
+-> Result<Cursor<&[u8]>, Error>
 {
 	check_riff_signature(file_buffer      )?;
 	check_byte_count(    file_buffer, None)?;
@@ -46,15 +47,15 @@ check_signature
 fn
 get_next_chunk
 (
-	cursor: &mut Cursor<&Vec<u8>>
+	cursor: &mut Cursor<&[u8]>
 )
--> Result<RiffChunk, std::io::Error>
+-> Result<RiffChunk, Error>
 {
 	// Read the start of the chunk
 	let mut chunk_start = [0u8; 8];
 
 	
-	let mut bytes_read = cursor.read(&mut chunk_start).unwrap();
+	let mut bytes_read = cursor.read(&mut chunk_start)?;
 
 	// Check that indeed 8 bytes were read
 	if bytes_read != 8
@@ -64,18 +65,27 @@ get_next_chunk
 
 	// Construct name of chunk and its length
 	let chunk_name = String::from_utf8(chunk_start[0..4].to_vec());
-	let mut chunk_length = from_u8_vec_macro!(u32, &chunk_start[4..8].to_vec(), &Endian::Little);
+	let mut chunk_length = from_u8_vec_macro!(u32, &chunk_start[4..8], &Endian::Little) as u64;
 
 	// Account for the possible padding byte
 	chunk_length += chunk_length % 2;
 
+	// Make sure that the chunk fits into the remaining data before allocating
+	// a buffer for it
+	let remaining_bytes = (cursor.get_ref().len() as u64).saturating_sub(cursor.position());
+	if chunk_length > remaining_bytes
+	{
+		return container_error!(
+			format!("RIFF chunk length {chunk_length} exceeds remaining {remaining_bytes} bytes!")
+		);
+	}
+
 	// Read RIFF chunk data
 	let mut chunk_data_buffer = vec![0u8; chunk_length as usize];
-	bytes_read = cursor.read(&mut chunk_data_buffer).unwrap();
+	bytes_read = cursor.read(&mut chunk_data_buffer)?;
 	if bytes_read != chunk_length as usize
 	{
-		return io_error!(
-			Other, 
+		return container_error!(
 			format!("Could not read RIFF chunk data! Expected {chunk_length} bytes but read {bytes_read}")
 		);
 	}
@@ -90,7 +100,7 @@ get_next_chunk
 	}
 	else
 	{
-		return io_error!(Other, "Could not parse RIFF fourCC chunk name!");
+		return container_error!("Could not parse RIFF fourCC chunk name!");
 	}
 }
 
@@ -103,9 +113,9 @@ get_next_chunk
 fn
 get_next_chunk_descriptor
 (
-	cursor: &mut Cursor<&Vec<u8>>
+	cursor: &mut Cursor<&[u8]>
 )
--> Result<RiffChunkDescriptor, std::io::Error>
+-> Result<RiffChunkDescriptor, Error>
 {
 	let next_chunk_result = get_next_chunk(cursor)?;
 	return Ok(next_chunk_result.descriptor());
@@ -119,9 +129,9 @@ get_next_chunk_descriptor
 pub(crate) fn
 parse_webp
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Vec<RiffChunkDescriptor>, std::io::Error>
+-> Result<Vec<RiffChunkDescriptor>, Error>
 {
 	let mut cursor = check_signature(file_buffer)?;
 
@@ -165,7 +175,7 @@ parse_webp
 			// in the file and we are done with parsing.
 			// If the subroutine fails due to other reasons, the error gets
 			// propagated further.
-			if next_chunk_descriptor_result.as_ref().err().unwrap().kind() == std::io::ErrorKind::UnexpectedEof
+			if next_chunk_descriptor_result.as_ref().err().unwrap().is_unexpected_eof()
 			{
 				break;
 			}
@@ -184,9 +194,9 @@ parse_webp
 fn
 check_exif_in_file
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<(Cursor<&Vec<u8>>, Vec<RiffChunkDescriptor>), std::io::Error>
+-> Result<(Cursor<&[u8]>, Vec<RiffChunkDescriptor>), Error>
 {
 	// Parse the WebP file - if this fails, we surely can't read any metadata
 	let parsed_webp_result = parse_webp(file_buffer);
@@ -204,15 +214,20 @@ check_exif_in_file
 		// Compare the chunk descriptor header.
 		if first_chunk.header().to_lowercase() != VP8X_HEADER.to_lowercase()
 		{
-			return io_error!(
-				Other, 
+			// Simple File Format (lossy or lossless) can't store EXIF data
+			if first_chunk.header() == "VP8 " || first_chunk.header() == "VP8L"
+			{
+				return Err(Error::NoExifData);
+			}
+
+			return container_error!(
 				format!("Expected first chunk of WebP file to be of type 'VP8X' but instead got {}!", first_chunk.header())
 			);
 		}
 	}
 	else
 	{
-		return io_error!(Other, "Could not read first chunk descriptor of WebP file!");
+		return container_error!("Could not read first chunk descriptor of WebP file!");
 	}
 
 	// Finally, check the flag by opening up the file and reading the data of
@@ -224,9 +239,9 @@ check_exif_in_file
 	let mut cursor = check_signature(file_buffer).unwrap();
 	let mut flag_buffer = vec![0u8; 4usize];
 	cursor.set_position(12u64 + 4u64 + 4u64);
-	if cursor.read(&mut flag_buffer).unwrap() != 4
+	if cursor.read(&mut flag_buffer)? != 4
 	{
-		return io_error!(Other, "Could not read flags of VP8X chunk!");
+		return container_error!("Could not read flags of VP8X chunk!");
 	}
 
 	// Check the 5th bit of the 32 bit flag_buffer. 
@@ -234,7 +249,8 @@ check_exif_in_file
 	// https://developers.google.com/speed/webp/docs/riff_container#extended_file_format
 	if flag_buffer[0] & 0x08 != 0x08
 	{
-		return io_error!(Other, "No EXIF chunk according to VP8X flags!");
+		// No EXIF chunk according to VP8X flags
+		return Err(Error::NoExifData);
 	}
 
 	return Ok((cursor, parsed_webp_result.unwrap()));
@@ -247,13 +263,13 @@ check_exif_in_file
 pub(crate) fn
 read_metadata
 (
-	file_buffer: &Vec<u8>
+	file_buffer: &[u8]
 )
--> Result<Vec<u8>, std::io::Error>
+-> Result<Vec<u8>, Error>
 {
 	// Check the signature, parse it, check that it has a VP8X chunk and the
 	// EXIF flag is set there
-	let (mut cursor, parse_webp_result) = check_exif_in_file(file_buffer).unwrap();
+	let (mut cursor, parse_webp_result) = check_exif_in_file(file_buffer)?;
 
 	// At this point we have established that the file has to contain an EXIF
 	// chunk at some point. So, now we need to find & return it
@@ -266,27 +282,31 @@ read_metadata
 	loop
 	{
 		// Read the chunk type into the buffer
-		if cursor.read(&mut header_buffer).unwrap() != 4
+		if cursor.read(&mut header_buffer)? != 4
 		{
-			return io_error!(Other, "Could not read chunk type while traversing WebP file!");
+			return container_error!("Could not read chunk type while traversing WebP file!");
 		}
-		let chunk_type = String::from_u8_vec(&header_buffer.to_vec(), &Endian::Little);
+		let chunk_type = String::from_utf8_lossy(&header_buffer).to_string();
 
 		// Check that this is still the type that we expect from the previous
 		// parsing over the file
 		// TODO: Maybe remove this part?
-		let expected_chunk_type = parse_webp_result.iter().nth(chunk_index).unwrap().header();
+		let expected_chunk = match parse_webp_result.get(chunk_index)
+		{
+			Some(chunk) => chunk,
+			None        => return container_error!("Could not find EXIF chunk even though VP8X flag is set!"),
+		};
+		let expected_chunk_type = expected_chunk.header();
 		if chunk_type != expected_chunk_type
 		{
-			return io_error!(
-				Other, 
+			return container_error!(
 				format!("Got unexpected chunk type! Expected {} but got {}", expected_chunk_type, chunk_type)
 			);
 		}
 
 		// Get the size of this chunk from the previous parsing process and skip
 		// the 4 bytes regarding the size
-		let chunk_size = parse_webp_result.iter().nth(chunk_index).unwrap().len();
+		let chunk_size = expected_chunk.len();
 		cursor.seek(std::io::SeekFrom::Current(4))?;
 
 		if chunk_type.to_lowercase() == EXIF_CHUNK_HEADER.to_lowercase()
@@ -328,7 +348,7 @@ update_file_size_information
 	cursor: &mut Cursor<&mut Vec<u8>>,
 	delta:  i32
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Note from the documentation:
 	// As the size of any chunk is even, the size given by the RIFF header is also even.
@@ -363,7 +383,7 @@ convert_to_extended_format
 (
 	cursor: &mut Cursor<&mut Vec<u8>>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Start by getting the first chunk of the WebP file
 	let mut read_cursor = Cursor::new(cursor.get_ref().as_ref());
@@ -378,29 +398,10 @@ convert_to_extended_format
 
 	let first_chunk = first_chunk_result.unwrap();
 
-	// Find out what simple type of WebP file we are dealing with
-	let (width, height) = match first_chunk.descriptor().header().as_str()
-	{
-		"VP8" 
-			=> {debug!("VP8 !"); todo!()},
-		"VP8L"
-			=> get_dimension_info_from_vp8l_chunk(first_chunk.payload()),
-		_ 
-			=> io_error!(Other, format!("Expected either 'VP8 ' or 'VP8L' chunk for conversion but got {:?}!", first_chunk.descriptor().header().as_str()))
-	}?;
-
-	let width_vec  = to_u8_vec_macro!(u32, &width,  &Endian::Little);
-	let height_vec = to_u8_vec_macro!(u32, &height, &Endian::Little);
-
-	let mut vp8x_chunk = vec![
-		0x56, 0x50, 0x38, 0x58, // ASCII chars "V", "P", "8", "X"                  -> 4 byte
-		0x0A, 0x00, 0x00, 0x00, // size of this chunk (32 + 24 + 24 bit = 10 byte) -> 4 byte
-		0x00, 0x00, 0x00, 0x00, // Flags and reserved area                         -> 4 byte
-	];
-
-	// Add the two 24 bits for width and height information
-	for i in 0..3 { vp8x_chunk.push(width_vec[i]); }
-	for i in 0..3 { vp8x_chunk.push(height_vec[i]); }
+	let mut vp8x_chunk = construct_vp8x_chunk(
+		first_chunk.descriptor().header().as_str(), 
+		first_chunk.payload()
+	)?;
 
 	// Write the VP8X chunk
 	insert_multiple_at(cursor.get_mut(), 12, &mut vp8x_chunk);
@@ -414,48 +415,29 @@ convert_to_extended_format
 
 
 fn
-get_dimension_info_from_vp8l_chunk
+set_exif_flag
 (
-	payload: &Vec<u8>
+	cursor: &mut Cursor<&mut Vec<u8>>,
+	exif_flag_value: bool
 )
--> Result<(u32, u32), std::io::Error>
+-> Result<(), Error>
 {
-	// Get the 4 bytes containing the dimension information
-	// (although we only need 28 bits)
-	// Starting at byte 1 instead of 0 due to the 0x2F byte
-	// See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
-	let width_height_info_buffer = payload[1..5].to_vec();
-	
-	// Convert to a single u32 number for bit-mask operations
-	let width_height_info = from_u8_vec_macro!(u32, &width_height_info_buffer, &Endian::Little);
-	
-	let mut width  = 0;
-	let mut height = 0;
-
-	// Get the first 14 bit to construct the width
-	for bit_index in 0..14
-	{
-		width  |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-	}
-
-	// Get the next 14 bit to construct the height
-	for bit_index in 14..28
-	{
-		height |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-	}
-
-	return Ok((width, height));
+	return set_vp8x_flag(cursor, 0x08, exif_flag_value);
 }
 
 
 
+/// Sets or unsets the flag(s) given by the mask in the VP8X chunk. If the
+/// file does not have a VP8X chunk, it gets converted to the Extended File
+/// Format first
 fn
-set_exif_flag
+set_vp8x_flag
 (
-	cursor: &mut Cursor<&mut Vec<u8>>,
-	exif_flag_value: bool
+	cursor:     &mut Cursor<&mut Vec<u8>>,
+	flag_mask:  u8,
+	flag_value: bool
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Parse the WebP file - if this fails, we surely can't read any metadata
 	let parsed_webp_result = parse_webp(cursor.get_ref())?;
@@ -473,20 +455,20 @@ set_exif_flag
 	}
 	else
 	{
-		return io_error!(Other, "Could not read first chunk descriptor of WebP file!");
+		return container_error!("Could not read first chunk descriptor of WebP file!");
 	}	
 
 	// At this point we know that we have a VP8X chunk at the expected location
-	// Mask the old flag by either or-ing with 1 at the EXIF flag position for
-	// setting it to true, or and-ing with 1 everywhere but the EXIF flag pos
+	// Mask the old flag by either or-ing with 1 at the flag position for
+	// setting it to true, or and-ing with 1 everywhere but the flag pos
 	// to set it to false
-	cursor.get_mut()[20] = if exif_flag_value
+	cursor.get_mut()[20] = if flag_value
 	{
-		cursor.get_ref()[20] | 0x08
+		cursor.get_ref()[20] | flag_mask
 	}
 	else
 	{
-		cursor.get_ref()[20] & 0b11110111
+		cursor.get_ref()[20] & !flag_mask
 	};
 
 	Ok(())
@@ -499,32 +481,38 @@ clear_metadata
 (
 	file_buffer: &mut Vec<u8>
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Check the file signature, parse it, check that it has a VP8X chunk and
 	// the EXIF flag is set there
-	let exif_check_result = check_exif_in_file(file_buffer);
-	if exif_check_result.is_err()
+	let parse_webp_result = match check_exif_in_file(file_buffer)
 	{
-		match exif_check_result.as_ref().err().unwrap().to_string().as_str()
-		{
-			"No EXIF chunk according to VP8X flags!"
-				=> return Ok(()),
-			"Expected first chunk of WebP file to be of type 'VP8X' but instead got VP8L!"
-				=> return Ok(()),
-			_
-				=> return Err(exif_check_result.err().unwrap())
-		}
-	}
-
-	let (_, parse_webp_result) = exif_check_result.unwrap();
+		Ok((_, parse_webp_result)) => parse_webp_result,
+		Err(Error::NoExifData)     => {
+			// The EXIF flag may be unset even though there is an EXIF chunk
+			// in an Extended File Format WebP. Remove it anyway, otherwise it
+			// would shadow the EXIF chunk written afterwards.
+			let parse_webp_result = parse_webp(file_buffer)?;
+			let is_extended = parse_webp_result.first()
+				.map_or(false, |chunk| chunk.header().to_lowercase() == VP8X_HEADER.to_lowercase());
+			let has_exif    = parse_webp_result.iter()
+				.any(|chunk| chunk.header().to_lowercase() == EXIF_CHUNK_HEADER.to_lowercase());
+
+			if !is_extended || !has_exif
+			{
+				return Ok(());
+			}
+			parse_webp_result
+		},
+		Err(error)                 => return Err(error),
+	};
 	let mut cursor = Cursor::new(file_buffer);
 
 	// Compute a delta of how much the file size information has to change
 	let mut delta = 0i32;
 
-	// Skip the WEBP signature
-	cursor.set_position(4);
+	// Skip the RIFF header, file size and WEBP signature
+	cursor.set_position(12);
 
 	for parsed_chunk in parse_webp_result
 	{
@@ -577,7 +565,7 @@ write_metadata
 	file_buffer: &mut Vec<u8>,
 	metadata:    &Metadata
 )
--> Result<(), std::io::Error>
+-> Result<(), Error>
 {
 	// Clear the metadata from the file and return if this results in an error
 	clear_metadata(file_buffer)?;
@@ -589,20 +577,16 @@ write_metadata
 	// Find a location where to put the EXIF chunk
 	// This is done by requesting a chunk descriptor as long as we find a chunk
 	// that is both known and should be located *before* the EXIF chunk
-	let pre_exif_chunks = [
-		"VP8X",
-		"VP8",
-		"VP8L",
-		"ICCP",
-		"ANIM"
-	];
 
+	// Skip the RIFF header, file size and WEBP signature
 	let mut read_cursor = Cursor::new(file_buffer.as_ref());
+	read_cursor.set_position(12);
 
 	loop
 	{
 		// Request a chunk descriptor. If this fails, check the error 
 		// Depending on its type, either continue normally or return it
+		let chunk_start_position    = read_cursor.position();
 		let chunk_descriptor_result = get_next_chunk_descriptor(&mut read_cursor);
 
 		if let Ok(chunk_descriptor) = chunk_descriptor_result
@@ -611,24 +595,27 @@ write_metadata
 
 			// Check header of chunk descriptor against any of the known chunks
 			// that should come before the EXIF chunk
-			for pre_exif_chunk in &pre_exif_chunks
+			for pre_exif_chunk in &PRE_EXIF_CHUNK_HEADERS
 			{
 				chunk_type_found_in_pre_exif_chunks |= pre_exif_chunk.to_lowercase() == chunk_descriptor.header().to_lowercase();
 			}
 
 			if !chunk_type_found_in_pre_exif_chunks
 			{
+				// Place the EXIF chunk in front of this chunk
+				read_cursor.set_position(chunk_start_position);
 				break;
 			}
 		}
 		else
 		{
-			match chunk_descriptor_result.as_ref().err().unwrap().kind()
+			if chunk_descriptor_result.as_ref().err().unwrap().is_unexpected_eof()
+			{
+				break; // No further chunks, place EXIF chunk here
+			}
+			else
 			{
-				std::io::ErrorKind::UnexpectedEof
-					=> break, // No further chunks, place EXIF chunk here
-				_
-					=> return Err(chunk_descriptor_result.err().unwrap())
+				return Err(chunk_descriptor_result.err().unwrap());
 			}
 		}
 	}
@@ -650,3 +637,233 @@ write_metadata
 
 	return Ok(());
 }
+
+
+
+/// Reads the payload of the (first) XMP chunk. The XMP flag of the VP8X
+/// chunk is not checked, as some writers don't set it
+pub(crate) fn
+read_xmp
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return read_chunk_payload(file_buffer, XMP_CHUNK_HEADER)?.ok_or(Error::NoXmpData);
+}
+
+
+
+/// Removes all XMP chunks and unsets the XMP flag in the VP8X chunk
+pub(crate) fn
+clear_xmp
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return clear_chunks(file_buffer, XMP_CHUNK_HEADER, 0x04);
+}
+
+
+
+/// Replaces the XMP data with the given packet. The XMP chunk is placed at
+/// the end of the file, after the image data and the EXIF chunk
+pub(crate) fn
+write_xmp
+(
+	file_buffer: &mut Vec<u8>,
+	xmp_packet:  &[u8]
+)
+-> Result<(), Error>
+{
+	clear_xmp(file_buffer)?;
+
+	let mut xmp_chunk = XMP_CHUNK_HEADER.as_bytes().to_vec();
+	xmp_chunk.extend(to_u8_vec_macro!(u32, &(xmp_packet.len() as u32), &Endian::Little));
+	xmp_chunk.extend(xmp_packet);
+
+	// Chunks need to have an even size
+	if xmp_chunk.len() % 2 == 1
+	{
+		xmp_chunk.push(0x00);
+	}
+
+	// Put the chunk at the end of the RIFF data, which is usually the end of
+	// the file buffer
+	let riff_end = 8 + from_u8_vec_macro!(u32, &file_buffer[4..8], &Endian::Little) as usize;
+	let xmp_chunk_len = xmp_chunk.len() as i32;
+	insert_multiple_at(file_buffer, riff_end.min(file_buffer.len()), &mut xmp_chunk);
+
+	let mut cursor = Cursor::new(file_buffer);
+	update_file_size_information(&mut cursor, xmp_chunk_len)?;
+	set_vp8x_flag(&mut cursor, 0x04, true)?;
+
+	return Ok(());
+}
+
+
+
+/// Reads the ICC profile stored in the (first) ICCP chunk
+pub(crate) fn
+read_icc_profile
+(
+	file_buffer: &[u8]
+)
+-> Result<Vec<u8>, Error>
+{
+	return read_chunk_payload(file_buffer, ICCP_CHUNK_HEADER)?.ok_or(Error::NoIccProfile);
+}
+
+
+
+/// Removes all ICCP chunks and unsets the ICC profile flag in the VP8X chunk
+pub(crate) fn
+clear_icc_profile
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return clear_chunks(file_buffer, ICCP_CHUNK_HEADER, 0x20);
+}
+
+
+
+/// Replaces the ICC profile with the given one. As demanded by the WebP
+/// container specification, the ICCP chunk is placed right after the VP8X
+/// chunk, which gets created first if the file does not have one yet
+pub(crate) fn
+write_icc_profile
+(
+	file_buffer: &mut Vec<u8>,
+	icc_profile: &[u8]
+)
+-> Result<(), Error>
+{
+	clear_icc_profile(file_buffer)?;
+
+	let mut iccp_chunk = ICCP_CHUNK_HEADER.as_bytes().to_vec();
+	iccp_chunk.extend(to_u8_vec_macro!(u32, &(icc_profile.len() as u32), &Endian::Little));
+	iccp_chunk.extend(icc_profile);
+
+	// Chunks need to have an even size
+	if iccp_chunk.len() % 2 == 1
+	{
+		iccp_chunk.push(0x00);
+	}
+
+	let mut cursor = Cursor::new(file_buffer);
+	set_vp8x_flag(&mut cursor, 0x20, true)?;
+
+	// Skip the RIFF header, file size, WEBP signature and the VP8X chunk
+	let iccp_chunk_len = iccp_chunk.len() as i32;
+	insert_multiple_at(cursor.get_mut(), 12 + 18, &mut iccp_chunk);
+	update_file_size_information(&mut cursor, iccp_chunk_len)?;
+
+	return Ok(());
+}
+
+
+
+/// Removes all C2PA chunks. These are not announced by a flag in the VP8X
+/// chunk, so no flag needs to be unset.
+pub(crate) fn
+clear_c2pa_jumbf
+(
+	file_buffer: &mut Vec<u8>
+)
+-> Result<(), Error>
+{
+	return clear_chunks(file_buffer, C2PA_CHUNK_HEADER, 0x00);
+}
+
+
+
+/// Reads the payload of the first chunk with the given header, if there is
+/// such a chunk
+fn
+read_chunk_payload
+(
+	file_buffer:  &[u8],
+	chunk_header: &str
+)
+-> Result<Option<Vec<u8>>, Error>
+{
+	let parse_webp_result = parse_webp(file_buffer)?;
+
+	// Skip the RIFF header, file size and WEBP signature
+	let mut chunk_start = 12usize;
+
+	for parsed_chunk in parse_webp_result
+	{
+		if parsed_chunk.header() == chunk_header
+		{
+			// Use the size information of the chunk itself, as the parsed
+			// length includes the possible padding byte
+			let payload_size = from_u8_vec_macro!(
+				u32, 
+				&file_buffer[chunk_start+4..chunk_start+8], 
+				&Endian::Little
+			) as usize;
+			let payload_start = chunk_start + 8;
+
+			return Ok(Some(file_buffer[payload_start..payload_start + payload_size].to_vec()));
+		}
+
+		chunk_start += 8 + parsed_chunk.len();
+	}
+
+	return Ok(None);
+}
+
+
+
+/// Removes all chunks with the given header and unsets the flag(s) given by
+/// the mask in the VP8X chunk
+fn
+clear_chunks
+(
+	file_buffer:  &mut Vec<u8>,
+	chunk_header: &str,
+	flag_mask:    u8
+)
+-> Result<(), Error>
+{
+	let parse_webp_result = parse_webp(file_buffer)?;
+
+	let is_extended = parse_webp_result.first()
+		.map_or(false, |chunk| chunk.header().to_lowercase() == VP8X_HEADER.to_lowercase());
+
+	let mut cursor = Cursor::new(file_buffer);
+	let mut delta  = 0i32;
+
+	// Skip the RIFF header, file size and WEBP signature
+	cursor.set_position(12);
+
+	for parsed_chunk in parse_webp_result
+	{
+		let parsed_chunk_byte_count = 8 + parsed_chunk.len();
+
+		if parsed_chunk.header() != chunk_header
+		{
+			cursor.seek(std::io::SeekFrom::Current(parsed_chunk_byte_count as i64))?;
+			continue;
+		}
+
+		let remove_start = cursor.position() as usize;
+		range_remove(cursor.get_mut(), remove_start, remove_start + parsed_chunk_byte_count);
+		delta -= parsed_chunk_byte_count as i32;
+	}
+
+	update_file_size_information(&mut cursor, delta)?;
+
+	// A Simple File Format WebP can't have any data that is announced by a
+	// flag, so there is no need to convert it
+	if is_extended
+	{
+		set_vp8x_flag(&mut cursor, flag_mask, false)?;
+	}
+
+	return Ok(());
+}
@@ -0,0 +1,137 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use crate::error::Error;
+use crate::jpg;
+
+use super::namespace;
+use super::serialize;
+use super::Xmp;
+use super::XmpValue;
+
+/// The property of the standard XMP packet that holds the GUID of the
+/// extended XMP packet, see the XMP specification part 3, section 1.1.3.1
+const HAS_EXTENDED_XMP: &str = "HasExtendedXMP";
+
+impl
+Xmp
+{
+    /// Merges the properties of the extended XMP packet of a JPEG file into
+    /// this (standard) XMP data, if it refers to one. Properties that are
+    /// present in both packets are taken from the standard one
+    pub(super) fn
+    merge_jpg_extended_xmp
+    (
+        &mut self,
+        file_buffer: &[u8]
+    )
+    -> Result<(), Error>
+    {
+        let guid = match self.remove_property(namespace::XMP_NOTE, HAS_EXTENDED_XMP)
+        {
+            Some(XmpValue::Simple(guid)) => guid,
+            _                            => return Ok(()),
+        };
+
+        let extended_xmp = Xmp::new_from_packet(&jpg::read_extended_xmp(file_buffer, &guid)?)?;
+
+        for (prefix, uri) in extended_xmp.namespaces
+        {
+            if self.get_prefix(&uri).is_none()
+            {
+                self.namespaces.push((prefix, uri));
+            }
+        }
+
+        for property in extended_xmp.properties
+        {
+            if self.get_property(&property.namespace, &property.name).is_none()
+            {
+                self.properties.push(property);
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Encodes the data for storing it in a JPEG file. If the packet does
+    /// not fit into a single APP1 segment, the largest properties are moved
+    /// to an extended packet until the standard packet fits. The standard
+    /// packet then refers to the extended one via `xmpNote:HasExtendedXMP`
+    pub(super) fn
+    encode_for_jpg
+    (
+        &self
+    )
+    -> (Vec<u8>, Option<Vec<u8>>)
+    {
+        let packet = self.encode();
+        if packet.len() <= jpg::MAX_XMP_PACKET_LENGTH
+        {
+            return (packet, None);
+        }
+
+        let mut standard_xmp = self.clone();
+        let mut extended_xmp = Xmp {
+            namespaces: self.namespaces.clone(),
+            properties: Vec::new(),
+        };
+
+        // Use a placeholder with the same length as the actual GUID, so that
+        // the size of the standard packet is known before computing the GUID
+        standard_xmp.set_property(
+            namespace::XMP_NOTE,
+            HAS_EXTENDED_XMP,
+            XmpValue::Simple("0".repeat(32))
+        );
+
+        // Move the largest properties first, so that as many properties as
+        // possible remain in the standard packet. The packet only gets 
+        // serialized once, after that the size of the moved properties is 
+        // subtracted. This overestimates the remaining size a bit, as e.g.
+        // namespaces that are no longer used are not taken into account.
+        let mut standard_length = standard_xmp.encode_with_padding(0).len();
+        let mut candidates = serialize::get_property_lengths(&standard_xmp)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)|
+                standard_xmp.properties[*index].namespace != namespace::XMP_NOTE
+                || standard_xmp.properties[*index].name   != HAS_EXTENDED_XMP
+            )
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, serialized_length)| std::cmp::Reverse(*serialized_length));
+
+        let mut moved = vec![false; standard_xmp.properties.len()];
+        for (index, serialized_length) in candidates
+        {
+            if standard_length <= jpg::MAX_XMP_PACKET_LENGTH
+            {
+                break;
+            }
+            standard_length -= serialized_length;
+            moved[index] = true;
+        }
+
+        let (moved_properties, remaining_properties) = standard_xmp.properties
+            .drain(..)
+            .zip(moved)
+            .partition::<Vec<_>, _>(|(_, moved)| *moved);
+        standard_xmp.properties = remaining_properties.into_iter().map(|(property, _)| property).collect();
+        extended_xmp.properties = moved_properties.into_iter().map(|(property, _)| property).collect();
+
+        let extended_packet = serialize::serialize_xmpmeta(&extended_xmp).into_bytes();
+        standard_xmp.set_property(
+            namespace::XMP_NOTE,
+            HAS_EXTENDED_XMP,
+            XmpValue::Simple(jpg::get_extended_xmp_guid(&extended_packet))
+        );
+
+        // Add as much of the default padding as still fits into the segment
+        let unpadded_length = standard_xmp.encode_with_padding(0).len();
+        let padding = jpg::MAX_XMP_PACKET_LENGTH
+            .saturating_sub(unpadded_length)
+            .min(serialize::DEFAULT_PADDING);
+
+        return (standard_xmp.encode_with_padding(padding), Some(extended_packet));
+    }
+}
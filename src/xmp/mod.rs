@@ -0,0 +1,400 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+pub mod namespace;
+mod extended;
+mod parse;
+mod serialize;
+mod xmp_io;
+
+use log::error;
+use quick_xml::events::BytesStart;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use quick_xml::Writer;
+use std::io::Cursor;
+
+use crate::error::Error;
+
+/// The kinds of arrays XMP knows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+XmpArrayType
+{
+    /// Unordered array, e.g. `dc:subject`
+    Bag,
+    /// Ordered array, e.g. `dc:creator`
+    Seq,
+    /// Array of alternatives, of which one gets chosen
+    Alt,
+}
+
+/// The value of an XMP property
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum
+XmpValue
+{
+    /// A simple value, e.g. a text, a date or a URI
+    Simple(String),
+    /// A structure, consisting of (named) fields
+    Struct(Vec<XmpProperty>),
+    /// An array of values
+    Array(XmpArrayType, Vec<XmpValue>),
+    /// An `Alt` array of texts, each of them for a different language given
+    /// by its `xml:lang` qualifier (e.g. "x-default" or "en-US"), as used by
+    /// properties like `dc:title` or `dc:description`
+    LangAlt(Vec<(String, String)>),
+}
+
+/// A property of an XMP packet (or a field of a struct), identified by the
+/// URI of its namespace and its name within that namespace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+XmpProperty
+{
+    pub namespace: String,
+    pub name:      String,
+    pub value:     XmpValue,
+}
+
+/// The XMP data of an image.
+/// This is the parsed representation of an XMP packet, consisting of the
+/// top level properties of its `rdf:Description` element(s) and the prefixes
+/// used for their namespaces.
+///
+/// # Examples
+/// ```no_run
+/// use little_exif::filetype::FileExtension;
+/// use little_exif::xmp::Xmp;
+/// use little_exif::xmp::XmpValue;
+/// use little_exif::xmp::namespace;
+///
+/// let mut file_data = std::fs::read("image.jpg").unwrap();
+/// let mut xmp = Xmp::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+///
+/// xmp.set_property(
+///     namespace::XMP,
+///     "Rating",
+///     XmpValue::Simple("5".to_string())
+/// );
+/// xmp.write_to_vec(&mut file_data, FileExtension::JPEG).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+Xmp
+{
+    namespaces: Vec<(String, String)>,
+    properties: Vec<XmpProperty>,
+}
+
+impl
+Xmp
+{
+    /// Constructs a new, empty `Xmp` object
+    pub fn
+    new
+    ()
+    -> Xmp
+    {
+        return Xmp::default();
+    }
+
+    /// Parses the given XMP packet, e.g. the payload of the XMP APP1 segment
+    /// of a JPEG file
+    pub fn
+    new_from_packet
+    (
+        packet: &[u8]
+    )
+    -> Result<Xmp, Error>
+    {
+        return parse::parse_packet(packet);
+    }
+
+    /// Serializes the data into an XMP packet, including 2 KB of padding
+    /// that allows other applications to edit the packet in place
+    pub fn
+    encode
+    (
+        &self
+    )
+    -> Vec<u8>
+    {
+        return self.encode_with_padding(serialize::DEFAULT_PADDING);
+    }
+
+    /// Same as `encode`, but with the given number of padding bytes
+    pub fn
+    encode_with_padding
+    (
+        &self,
+        padding: usize
+    )
+    -> Vec<u8>
+    {
+        return serialize::serialize_packet(self, padding);
+    }
+
+    /// Gets the properties in the order they are stored
+    pub fn
+    get_properties
+    (
+        &self
+    )
+    -> &[XmpProperty]
+    {
+        return &self.properties;
+    }
+
+    /// Gets the value of the property with the given namespace URI and name
+    pub fn
+    get_property
+    (
+        &self,
+        namespace: &str,
+        name:      &str
+    )
+    -> Option<&XmpValue>
+    {
+        return self.properties.iter()
+            .find(|property| property.namespace == namespace && property.name == name)
+            .map(|property| &property.value);
+    }
+
+    /// Gets the text of a property, which is either the value of a simple
+    /// property or the default ("x-default", otherwise the first) entry of a
+    /// language alternative
+    pub fn
+    get_text
+    (
+        &self,
+        namespace: &str,
+        name:      &str
+    )
+    -> Option<&str>
+    {
+        return match self.get_property(namespace, name)?
+        {
+            XmpValue::Simple(text) => Some(text),
+            XmpValue::LangAlt(alternatives) => alternatives.iter()
+                .find(|(language, _)| language == "x-default")
+                .or(alternatives.first())
+                .map(|(_, text)| text.as_str()),
+            _ => None,
+        };
+    }
+
+    /// Sets the value of a property, replacing the previous value if the
+    /// property already exists
+    pub fn
+    set_property
+    (
+        &mut self,
+        namespace: &str,
+        name:      &str,
+        value:     XmpValue
+    )
+    {
+        if let Some(property) = self.properties.iter_mut()
+            .find(|property| property.namespace == namespace && property.name == name)
+        {
+            property.value = value;
+            return;
+        }
+
+        self.properties.push(XmpProperty {
+            namespace: namespace.to_string(),
+            name:      name.to_string(),
+            value,
+        });
+    }
+
+    /// Removes the property with the given namespace URI and name and
+    /// returns its value
+    pub fn
+    remove_property
+    (
+        &mut self,
+        namespace: &str,
+        name:      &str
+    )
+    -> Option<XmpValue>
+    {
+        let index = self.properties.iter()
+            .position(|property| property.namespace == namespace && property.name == name)?;
+        return Some(self.properties.remove(index).value);
+    }
+
+    /// Registers the prefix that is used for the given namespace URI when
+    /// encoding the data. Prefixes of commonly used namespaces and the ones
+    /// of a parsed packet are known already
+    pub fn
+    register_namespace
+    (
+        &mut self,
+        prefix:    &str,
+        namespace: &str
+    )
+    {
+        self.namespaces.retain(|(_, uri)| uri != namespace);
+        self.namespaces.push((prefix.to_string(), namespace.to_string()));
+    }
+
+    /// Gets the prefix registered for the given namespace URI
+    pub fn
+    get_prefix
+    (
+        &self,
+        namespace: &str
+    )
+    -> Option<&str>
+    {
+        return self.namespaces.iter()
+            .find(|(_, uri)| uri == namespace)
+            .map(|(prefix, _)| prefix.as_str());
+    }
+}
+
+/// Some images also contain XMP metadata, which in turn may include EXIF data
+/// that is simply a duplicate from e.g. the eXIf chunk in a PNG.
+/// This function takes in the raw XMP information and removes EXIF attributes,
+/// while maintaining other XMP information so that the result can be 
+/// written back to the image data.
+pub(crate) fn
+remove_exif_from_xmp
+(
+    data: &[u8]
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let mut reader = Reader::from_reader(data);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+    // Needed by the reader
+    let mut read_buffer = Vec::new();
+
+    // Needed for skipping stuff like 
+    // <exif:Description>Hi</exif:Description>\n
+    let mut skip_depth   = 0u32;
+    let mut skip_next_nl = false;
+
+    loop 
+    {
+        // Read in the event
+        let read_event = reader.read_event_into(&mut read_buffer);
+
+        match read_event
+        {
+            Ok(Event::Start(ref event)) => {
+                let event_name = String::from_utf8(event.name().0.to_vec())?;
+
+                if event_name.starts_with("exif:")
+                {
+                    skip_depth += 1;
+                }
+                else if skip_depth == 0
+                {
+                    writer.write_event(Event::Start(get_exif_filtered_event(event)?))?;
+                }
+            }
+
+            Ok(Event::Empty(ref event)) => {
+                let event_name = String::from_utf8(event.name().0.to_vec())?;
+
+                if event_name.starts_with("exif:")
+                {
+                    // do nothing
+                }
+                else if skip_depth == 0
+                {
+                    writer.write_event(Event::Empty(get_exif_filtered_event(event)?))?;
+                }
+            }
+
+            Ok(Event::End(ref event)) => {
+                if skip_depth > 0 
+                {
+                    skip_depth  -= 1;
+                    skip_next_nl = true;
+                } 
+                else 
+                {
+                    writer.write_event(Event::End(event.clone()))?;
+                }
+            }
+
+            Ok(Event::Eof) => {
+                if skip_depth != 0
+                {
+                    return Err("XMP data ends within an EXIF element".into());
+                }
+                break;
+            }
+
+            Ok(Event::Text(ref event)) => {
+                let event_string = String::from_utf8(event.to_vec())?;
+
+                let characters = event_string.chars()
+                    .filter(|c| *c == '\n' || !c.is_whitespace())
+                    .collect::<Vec<char>>();
+
+                if characters == vec!['\n'] && skip_next_nl
+                {
+                    skip_next_nl = false;
+                }
+                else if skip_depth == 0 
+                {
+                    writer.write_event(Event::Text(event.clone()))?; 
+                }
+            }
+
+            Ok(other_event) => {
+                if skip_depth == 0 { writer.write_event(other_event)?; }
+            }
+
+            Err(error_message) => {
+                error!(
+                    "Error at position {}: {:?}", 
+                    reader.buffer_position(), 
+                    error_message
+                );
+                break;
+            }
+        };
+
+        read_buffer.clear();
+    }
+
+    return Ok(writer.into_inner().into_inner());
+}
+
+fn
+get_exif_filtered_event<'a>
+(
+    event: &'a BytesStart<'a>
+)
+-> Result<BytesStart<'a>, Box<dyn std::error::Error>>
+{
+    let mut new_event = BytesStart::new(
+        std::str::from_utf8(event.name().0)?
+    );
+
+    new_event.extend_attributes(
+        event.attributes()
+            .filter_map(Result::ok)
+            .filter(|attribute| 
+                {
+                    if let Ok(key) = std::str::from_utf8(
+                        attribute.key.as_ref()
+                    ) 
+                    {
+                        !key.starts_with("exif:")
+                    } else {
+                        true
+                    }
+                }
+            ),
+    );
+
+    return Ok(new_event);
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! URIs of commonly used XMP namespaces, to be used e.g. with
+//! `Xmp::get_property` and `Xmp::set_property`
+
+pub const RDF:        &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub const XML:        &str = "http://www.w3.org/XML/1998/namespace";
+pub const X:          &str = "adobe:ns:meta/";
+
+pub const DC:         &str = "http://purl.org/dc/elements/1.1/";
+pub const XMP:        &str = "http://ns.adobe.com/xap/1.0/";
+pub const XMP_RIGHTS: &str = "http://ns.adobe.com/xap/1.0/rights/";
+pub const XMP_MM:     &str = "http://ns.adobe.com/xap/1.0/mm/";
+pub const XMP_NOTE:   &str = "http://ns.adobe.com/xmp/note/";
+pub const PHOTOSHOP:  &str = "http://ns.adobe.com/photoshop/1.0/";
+pub const EXIF:       &str = "http://ns.adobe.com/exif/1.0/";
+pub const EXIF_EX:    &str = "http://cipa.jp/exif/1.0/";
+pub const TIFF:       &str = "http://ns.adobe.com/tiff/1.0/";
+pub const CRS:        &str = "http://ns.adobe.com/camera-raw-settings/1.0/";
+pub const IPTC_CORE:  &str = "http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/";
+
+pub const GCAMERA:        &str = "http://ns.google.com/photos/1.0/camera/";
+pub const CONTAINER:      &str = "http://ns.google.com/photos/1.0/container/";
+pub const CONTAINER_ITEM: &str = "http://ns.google.com/photos/1.0/container/item/";
+pub const HDRGM:          &str = "http://ns.adobe.com/hdr-gain-map/1.0/";
+
+/// The prefixes that are commonly used for the above namespaces. These are
+/// used when serializing a namespace that has no prefix registered
+const DEFAULT_PREFIXES: [(&str, &str); 17] = [
+    (DC,         "dc"),
+    (XMP,        "xmp"),
+    (XMP_RIGHTS, "xmpRights"),
+    (XMP_MM,     "xmpMM"),
+    (XMP_NOTE,   "xmpNote"),
+    (PHOTOSHOP,  "photoshop"),
+    (EXIF,       "exif"),
+    (EXIF_EX,    "exifEX"),
+    (TIFF,       "tiff"),
+    (CRS,        "crs"),
+    (IPTC_CORE,  "Iptc4xmpCore"),
+    (GCAMERA,        "GCamera"),
+    (CONTAINER,      "Container"),
+    (CONTAINER_ITEM, "Item"),
+    (HDRGM,          "hdrgm"),
+    (RDF,        "rdf"),
+    (X,          "x"),
+];
+
+pub(crate) fn
+get_default_prefix
+(
+    namespace: &str
+)
+-> Option<&'static str>
+{
+    return DEFAULT_PREFIXES.iter()
+        .find(|(uri, _)| *uri == namespace)
+        .map(|(_, prefix)| *prefix);
+}
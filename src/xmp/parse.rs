@@ -0,0 +1,430 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use quick_xml::events::BytesStart;
+use quick_xml::events::Event;
+use quick_xml::name::PrefixDeclaration;
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+
+use crate::error::Error;
+
+use super::namespace;
+use super::Xmp;
+use super::XmpArrayType;
+use super::XmpProperty;
+use super::XmpValue;
+
+/// Limits the nesting of elements, so that malicious packets can't cause a
+/// stack overflow while interpreting the RDF structure
+const MAX_DEPTH: usize = 64;
+
+/// A (very) simple representation of an XML element with resolved namespaces
+struct
+XmlElement
+{
+    namespace:  String,
+    name:       String,
+    attributes: Vec<XmlAttribute>,
+    children:   Vec<XmlElement>,
+    text:       String,
+}
+
+struct
+XmlAttribute
+{
+    namespace: String,
+    name:      String,
+    value:     String,
+}
+
+impl
+XmlElement
+{
+    fn
+    is
+    (
+        &self,
+        namespace: &str,
+        name:      &str
+    )
+    -> bool
+    {
+        return self.namespace == namespace && self.name == name;
+    }
+
+    fn
+    get_attribute
+    (
+        &self,
+        namespace: &str,
+        name:      &str
+    )
+    -> Option<&str>
+    {
+        return self.attributes.iter()
+            .find(|attribute| attribute.namespace == namespace && attribute.name == name)
+            .map(|attribute| attribute.value.as_str());
+    }
+
+    /// Attributes that represent properties (or struct fields) and not e.g.
+    /// RDF syntax like `rdf:about` or qualifiers like `xml:lang`
+    fn
+    get_property_attributes
+    (
+        &self
+    )
+    -> impl Iterator<Item = &XmlAttribute>
+    {
+        return self.attributes.iter()
+            .filter(|attribute|
+                !attribute.namespace.is_empty()
+                && attribute.namespace != namespace::RDF
+                && attribute.namespace != namespace::XML
+            );
+    }
+}
+
+fn
+malformed_xmp
+<T>
+(
+    reason: impl ToString
+)
+-> Result<T, Error>
+{
+    return Err(Error::MalformedXmp(reason.to_string()));
+}
+
+/// Parses the XMP packet into an `Xmp` object by first reading the XML into
+/// a tree of `XmlElement`s and then interpreting the RDF structure
+pub(super) fn
+parse_packet
+(
+    packet: &[u8]
+)
+-> Result<Xmp, Error>
+{
+    let mut xmp  = Xmp::new();
+    let     root = read_xml_tree(packet, &mut xmp.namespaces)?;
+
+    let rdf = match find_rdf_element(&root, 0)
+    {
+        Some(rdf) => rdf,
+        None      => return malformed_xmp("Could not find rdf:RDF element!"),
+    };
+
+    for description in rdf.children.iter()
+        .filter(|child| child.is(namespace::RDF, "Description"))
+    {
+        xmp.properties.extend(parse_fields(description, 0)?);
+    }
+
+    return Ok(xmp);
+}
+
+fn
+read_xml_tree
+(
+    packet:     &[u8],
+    namespaces: &mut Vec<(String, String)>
+)
+-> Result<XmlElement, Error>
+{
+    let mut reader = NsReader::from_reader(packet);
+    let mut stack  = Vec::<XmlElement>::new();
+
+    loop
+    {
+        let event = match reader.read_resolved_event()
+        {
+            Ok((_, event)) => event,
+            Err(error)     => return malformed_xmp(format!(
+                "Error at position {}: {}",
+                reader.buffer_position(),
+                error
+            )),
+        };
+
+        match event
+        {
+            Event::Start(ref start) => {
+                if stack.len() >= MAX_DEPTH
+                {
+                    return malformed_xmp("Elements are nested too deeply!");
+                }
+                stack.push(read_element(&reader, start, namespaces)?);
+            },
+
+            Event::Empty(ref start) => {
+                let element = read_element(&reader, start, namespaces)?;
+                match stack.last_mut()
+                {
+                    Some(parent) => parent.children.push(element),
+                    None         => return Ok(element),
+                }
+            },
+
+            Event::End(_) => {
+                let element = match stack.pop()
+                {
+                    Some(element) => element,
+                    None          => return malformed_xmp("Unexpected end tag!"),
+                };
+                match stack.last_mut()
+                {
+                    Some(parent) => parent.children.push(element),
+                    None         => return Ok(element),
+                }
+            },
+
+            Event::Text(ref text) => {
+                if let Some(element) = stack.last_mut()
+                {
+                    match text.unescape()
+                    {
+                        Ok(text)   => element.text.push_str(&text),
+                        Err(error) => return malformed_xmp(error),
+                    }
+                }
+            },
+
+            Event::CData(ref data) => {
+                if let Some(element) = stack.last_mut()
+                {
+                    element.text.push_str(&String::from_utf8_lossy(data));
+                }
+            },
+
+            Event::Eof => {
+                return malformed_xmp("Unexpected end of XMP packet!");
+            },
+
+            // Processing instructions (like the xpacket wrapper), comments,
+            // declarations and so on are not needed
+            _ => (),
+        }
+    }
+}
+
+fn
+read_element
+(
+    reader:     &NsReader<&[u8]>,
+    start:      &BytesStart,
+    namespaces: &mut Vec<(String, String)>
+)
+-> Result<XmlElement, Error>
+{
+    let (element_namespace, element_name) = reader.resolve_element(start.name());
+
+    let mut attributes = Vec::new();
+    for attribute in start.attributes()
+    {
+        let attribute = match attribute
+        {
+            Ok(attribute) => attribute,
+            Err(error)    => return malformed_xmp(error),
+        };
+        let value = match attribute.unescape_value()
+        {
+            Ok(value)  => value.to_string(),
+            Err(error) => return malformed_xmp(error),
+        };
+
+        // Remember the prefixes of the namespaces
+        if let Some(PrefixDeclaration::Named(prefix)) = attribute.key.as_namespace_binding()
+        {
+            if !namespaces.iter().any(|(_, uri)| *uri == value)
+            {
+                namespaces.push((String::from_utf8_lossy(prefix).to_string(), value));
+            }
+            continue;
+        }
+        if attribute.key.as_namespace_binding().is_some()
+        {
+            continue;
+        }
+
+        let (attribute_namespace, attribute_name) = reader.resolve_attribute(attribute.key);
+        attributes.push(XmlAttribute {
+            namespace: resolved_namespace_to_string(attribute_namespace),
+            name:      String::from_utf8_lossy(attribute_name.as_ref()).to_string(),
+            value,
+        });
+    }
+
+    return Ok(XmlElement {
+        namespace: resolved_namespace_to_string(element_namespace),
+        name:      String::from_utf8_lossy(element_name.as_ref()).to_string(),
+        attributes,
+        children:  Vec::new(),
+        text:      String::new(),
+    });
+}
+
+fn
+resolved_namespace_to_string
+(
+    resolve_result: ResolveResult
+)
+-> String
+{
+    return match resolve_result
+    {
+        ResolveResult::Bound(namespace)                   => String::from_utf8_lossy(namespace.as_ref()).to_string(),
+        ResolveResult::Unknown(prefix) if prefix == b"xml" => namespace::XML.to_string(),
+        _                                                 => String::new(),
+    };
+}
+
+/// The rdf:RDF element is usually wrapped in an x:xmpmeta element, but may
+/// also be the root itself
+fn
+find_rdf_element
+(
+    element: &XmlElement,
+    depth:   usize,
+)
+-> Option<&XmlElement>
+{
+    if element.is(namespace::RDF, "RDF")
+    {
+        return Some(element);
+    }
+    if depth >= MAX_DEPTH
+    {
+        return None;
+    }
+    return element.children.iter()
+        .find_map(|child| find_rdf_element(child, depth + 1));
+}
+
+/// Gets the properties of an rdf:Description element or the fields of a
+/// struct, which may be given as attributes or child elements
+fn
+parse_fields
+(
+    element: &XmlElement,
+    depth:   usize,
+)
+-> Result<Vec<XmpProperty>, Error>
+{
+    let mut fields = element.get_property_attributes()
+        .map(|attribute| XmpProperty {
+            namespace: attribute.namespace.clone(),
+            name:      attribute.name.clone(),
+            value:     XmpValue::Simple(attribute.value.clone()),
+        })
+        .collect::<Vec<XmpProperty>>();
+
+    for child in &element.children
+    {
+        fields.push(XmpProperty {
+            namespace: child.namespace.clone(),
+            name:      child.name.clone(),
+            value:     parse_value(child, depth + 1)?,
+        });
+    }
+
+    return Ok(fields);
+}
+
+/// Interprets the content of a property element (or an rdf:li item), see
+/// the XMP specification part 1, section 7.9 (RDF/XML syntax)
+fn
+parse_value
+(
+    element: &XmlElement,
+    depth:   usize,
+)
+-> Result<XmpValue, Error>
+{
+    if depth >= MAX_DEPTH
+    {
+        return malformed_xmp("Values are nested too deeply!");
+    }
+
+    if let Some(resource) = element.get_attribute(namespace::RDF, "resource")
+    {
+        return Ok(XmpValue::Simple(resource.to_string()));
+    }
+
+    if element.get_attribute(namespace::RDF, "parseType") == Some("Resource")
+    {
+        return Ok(XmpValue::Struct(parse_fields(element, depth)?));
+    }
+
+    if let Some(first_child) = element.children.first()
+    {
+        for (array_name, array_type) in [
+            ("Bag", XmpArrayType::Bag),
+            ("Seq", XmpArrayType::Seq),
+            ("Alt", XmpArrayType::Alt),
+        ]
+        {
+            if first_child.is(namespace::RDF, array_name)
+            {
+                return parse_array(first_child, array_type, depth);
+            }
+        }
+
+        if first_child.is(namespace::RDF, "Description")
+        {
+            return Ok(XmpValue::Struct(parse_fields(first_child, depth)?));
+        }
+
+        return Ok(XmpValue::Struct(parse_fields(element, depth)?));
+    }
+
+    // Struct in the shorthand form, with its fields given as attributes
+    if element.get_property_attributes().next().is_some()
+    {
+        return Ok(XmpValue::Struct(parse_fields(element, depth)?));
+    }
+
+    return Ok(XmpValue::Simple(element.text.clone()));
+}
+
+fn
+parse_array
+(
+    array:      &XmlElement,
+    array_type: XmpArrayType,
+    depth:      usize,
+)
+-> Result<XmpValue, Error>
+{
+    let items = array.children.iter()
+        .filter(|child| child.is(namespace::RDF, "li"))
+        .collect::<Vec<&XmlElement>>();
+
+    // An Alt array where every item is a text with a language qualifier is
+    // a language alternative
+    let is_language_alternative = array_type == XmpArrayType::Alt
+        && !items.is_empty()
+        && items.iter().all(|item|
+            item.get_attribute(namespace::XML, "lang").is_some()
+            && item.children.is_empty()
+        );
+
+    if is_language_alternative
+    {
+        return Ok(XmpValue::LangAlt(
+            items.iter()
+                .map(|item| (
+                    item.get_attribute(namespace::XML, "lang").unwrap_or_default().to_string(),
+                    item.text.clone()
+                ))
+                .collect()
+        ));
+    }
+
+    let mut values = Vec::new();
+    for item in items
+    {
+        values.push(parse_value(item, depth + 1)?);
+    }
+
+    return Ok(XmpValue::Array(array_type, values));
+}
@@ -0,0 +1,274 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use quick_xml::escape::escape;
+
+use super::namespace;
+use super::Xmp;
+use super::XmpArrayType;
+use super::XmpProperty;
+use super::XmpValue;
+
+/// Amount of padding that gets added by default. The XMP specification
+/// recommends 2 KB so that the packet can be modified in place
+pub(super) const DEFAULT_PADDING: usize = 2048;
+
+const PACKET_HEADER:  &str = "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n";
+const PACKET_TRAILER: &str = "<?xpacket end=\"w\"?>";
+
+/// Serializes the given XMP data into a packet, using the RDF/XML syntax as
+/// described by the XMP specification part 1, section 7
+pub(super) fn
+serialize_packet
+(
+    xmp:     &Xmp,
+    padding: usize
+)
+-> Vec<u8>
+{
+    let mut packet = String::new();
+    packet.push_str(PACKET_HEADER);
+    packet.push_str(&serialize_xmpmeta(xmp));
+
+    // The padding consists of whitespace, with a newline every 100 bytes
+    for padding_line_start in (0..padding).step_by(100)
+    {
+        let line_length = (padding - padding_line_start).min(100);
+        packet.push_str(&" ".repeat(line_length - 1));
+        packet.push('\n');
+    }
+
+    packet.push_str(PACKET_TRAILER);
+
+    return packet.into_bytes();
+}
+
+/// Serializes the given XMP data into an x:xmpmeta element, i.e. without the
+/// xpacket wrapper and padding. This is e.g. used for the extended XMP data
+/// of JPEG files
+pub(super) fn
+serialize_xmpmeta
+(
+    xmp: &Xmp
+)
+-> String
+{
+    let prefixes = collect_prefixes(xmp);
+
+    let mut xmpmeta = String::new();
+    xmpmeta.push_str(&format!("<x:xmpmeta xmlns:x=\"{}\">\n", namespace::X));
+    xmpmeta.push_str(&format!(" <rdf:RDF xmlns:rdf=\"{}\">\n", namespace::RDF));
+    xmpmeta.push_str("  <rdf:Description rdf:about=\"\"");
+    for (prefix, uri) in &prefixes
+    {
+        xmpmeta.push_str(&format!("\n    xmlns:{}=\"{}\"", prefix, escape(uri.as_str())));
+    }
+    xmpmeta.push_str(">\n");
+
+    for property in &xmp.properties
+    {
+        write_property(&mut xmpmeta, &prefixes, property, 3);
+    }
+
+    xmpmeta.push_str("  </rdf:Description>\n");
+    xmpmeta.push_str(" </rdf:RDF>\n");
+    xmpmeta.push_str("</x:xmpmeta>\n");
+
+    return xmpmeta;
+}
+
+/// Determines the number of bytes each of the properties takes up when
+/// serializing the given XMP data, in the order of the properties
+pub(super) fn
+get_property_lengths
+(
+    xmp: &Xmp
+)
+-> Vec<usize>
+{
+    let prefixes = collect_prefixes(xmp);
+
+    let mut serialized_property = String::new();
+    return xmp.properties.iter()
+        .map(|property| {
+            serialized_property.clear();
+            write_property(&mut serialized_property, &prefixes, property, 3);
+            serialized_property.len()
+        })
+        .collect();
+}
+
+/// Determines the prefixes for all namespaces used by the properties and
+/// struct fields. Prefers the registered prefixes, followed by the default
+/// prefix of well known namespaces and a generated one otherwise
+fn
+collect_prefixes
+(
+    xmp: &Xmp
+)
+-> Vec<(String, String)>
+{
+    let mut used_namespaces = Vec::new();
+    for property in &xmp.properties
+    {
+        collect_namespaces(property, &mut used_namespaces);
+    }
+
+    let mut prefixes: Vec<(String, String)> = Vec::new();
+    for uri in used_namespaces
+    {
+        let mut prefix = xmp.get_prefix(&uri)
+            .or(namespace::get_default_prefix(&uri))
+            .map(|prefix| prefix.to_string())
+            .unwrap_or_default();
+
+        // Prefixes have to be unique and can't be the ones used for the
+        // namespaces of the packet structure
+        let mut counter = 1;
+        while prefix.is_empty()
+            || prefix == "x"
+            || prefix == "rdf"
+            || prefixes.iter().any(|(existing, _)| *existing == prefix)
+        {
+            prefix = format!("ns{}", counter);
+            counter += 1;
+        }
+
+        prefixes.push((prefix, uri));
+    }
+
+    return prefixes;
+}
+
+fn
+collect_namespaces
+(
+    property:        &XmpProperty,
+    used_namespaces: &mut Vec<String>
+)
+{
+    if !used_namespaces.contains(&property.namespace)
+    {
+        used_namespaces.push(property.namespace.clone());
+    }
+    collect_value_namespaces(&property.value, used_namespaces);
+}
+
+fn
+collect_value_namespaces
+(
+    value:           &XmpValue,
+    used_namespaces: &mut Vec<String>
+)
+{
+    match value
+    {
+        XmpValue::Struct(fields) => {
+            for field in fields
+            {
+                collect_namespaces(field, used_namespaces);
+            }
+        },
+        XmpValue::Array(_, items) => {
+            for item in items
+            {
+                collect_value_namespaces(item, used_namespaces);
+            }
+        },
+        _ => (),
+    }
+}
+
+fn
+get_qualified_name
+(
+    prefixes: &[(String, String)],
+    property: &XmpProperty
+)
+-> String
+{
+    // Every namespace got a prefix assigned by `collect_prefixes`
+    let prefix = prefixes.iter()
+        .find(|(_, uri)| *uri == property.namespace)
+        .map(|(prefix, _)| prefix.as_str())
+        .unwrap_or_default();
+
+    return format!("{}:{}", prefix, property.name);
+}
+
+fn
+write_property
+(
+    packet:   &mut String,
+    prefixes: &[(String, String)],
+    property: &XmpProperty,
+    indent:   usize
+)
+{
+    let qualified_name = get_qualified_name(prefixes, property);
+    write_value(packet, prefixes, &qualified_name, &property.value, indent);
+}
+
+fn
+write_value
+(
+    packet:         &mut String,
+    prefixes:       &[(String, String)],
+    qualified_name: &str,
+    value:          &XmpValue,
+    indent:         usize
+)
+{
+    let indentation = " ".repeat(indent);
+
+    match value
+    {
+        XmpValue::Simple(text) => {
+            packet.push_str(&format!(
+                "{}<{}>{}</{}>\n",
+                indentation, qualified_name, escape(text.as_str()), qualified_name
+            ));
+        },
+
+        XmpValue::Struct(fields) => {
+            packet.push_str(&format!("{}<{} rdf:parseType=\"Resource\">\n", indentation, qualified_name));
+            for field in fields
+            {
+                write_property(packet, prefixes, field, indent + 1);
+            }
+            packet.push_str(&format!("{}</{}>\n", indentation, qualified_name));
+        },
+
+        XmpValue::Array(array_type, items) => {
+            let array_name = match array_type
+            {
+                XmpArrayType::Bag => "rdf:Bag",
+                XmpArrayType::Seq => "rdf:Seq",
+                XmpArrayType::Alt => "rdf:Alt",
+            };
+
+            packet.push_str(&format!("{}<{}>\n", indentation, qualified_name));
+            packet.push_str(&format!("{} <{}>\n", indentation, array_name));
+            for item in items
+            {
+                write_value(packet, prefixes, "rdf:li", item, indent + 2);
+            }
+            packet.push_str(&format!("{} </{}>\n", indentation, array_name));
+            packet.push_str(&format!("{}</{}>\n", indentation, qualified_name));
+        },
+
+        XmpValue::LangAlt(alternatives) => {
+            packet.push_str(&format!("{}<{}>\n", indentation, qualified_name));
+            packet.push_str(&format!("{} <rdf:Alt>\n", indentation));
+            for (language, text) in alternatives
+            {
+                packet.push_str(&format!(
+                    "{}  <rdf:li xml:lang=\"{}\">{}</rdf:li>\n",
+                    indentation, escape(language.as_str()), escape(text.as_str())
+                ));
+            }
+            packet.push_str(&format!("{} </rdf:Alt>\n", indentation));
+            packet.push_str(&format!("{}</{}>\n", indentation, qualified_name));
+        },
+    }
+}
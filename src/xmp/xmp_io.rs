@@ -0,0 +1,324 @@
+// Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::c2pa;
+use crate::c2pa::C2paPolicy;
+use crate::error::Error;
+use crate::error::unsupported_format;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::modify_file;
+use crate::general_file_io::modify_file_atomically;
+use crate::metadata::WriteOptions;
+
+use crate::heif;
+use crate::jpg;
+use crate::jxl;
+use crate::png;
+use crate::tiff;
+use crate::webp;
+
+use super::Xmp;
+
+impl
+Xmp
+{
+    /// Gets the raw XMP packet of an image that is stored in memory, without
+    /// parsing it. Returns `Error::NoXmpData` if the image has no XMP packet.
+    /// For JPEG images, this is the standard XMP packet, without the data
+    /// of an extended XMP packet it may refer to.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::xmp::Xmp;
+    ///
+    /// let file_data = std::fs::read("image.webp").unwrap();
+    /// let packet = Xmp::read_packet(&file_data, FileExtension::WEBP).unwrap();
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    read_packet
+    <T: AsRef<[u8]> + ?Sized>
+    (
+        file_buffer: &T,
+        file_type:   FileExtension
+    )
+    -> Result<Vec<u8>, Error>
+    {
+        let file_buffer = file_buffer.as_ref();
+
+        return match file_type
+        {
+            FileExtension::HEIF
+                => heif::read_xmp(file_buffer),
+            FileExtension::JPEG
+                =>  jpg::read_xmp(file_buffer),
+            FileExtension::JXL
+                =>  jxl::read_xmp(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::read_xmp(file_buffer),
+            FileExtension::TIFF
+                => tiff::vec::read_xmp(file_buffer),
+            FileExtension::WEBP
+                => webp::vec::read_xmp(file_buffer),
+            _
+                => unsupported_format!(
+                    format!(
+                        "Function 'read_packet' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        };
+    }
+
+    /// Constructs a new `Xmp` object with the XMP data of an image that is
+    /// stored in memory. Returns `Error::NoXmpData` if the image has no XMP
+    /// packet and `Error::MalformedXmp` if the packet can't be parsed.
+    /// For JPEG images, the properties of an extended XMP packet are 
+    /// included as well.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::xmp::Xmp;
+    /// use little_exif::xmp::namespace;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// let xmp = Xmp::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+    /// println!("{:?}", xmp.get_text(namespace::DC, "title"));
+    /// ```
+    pub fn
+    new_from_vec
+    <T: AsRef<[u8]> + ?Sized>
+    (
+        file_buffer: &T,
+        file_type:   FileExtension
+    )
+    -> Result<Xmp, Error>
+    {
+        let mut xmp = Self::new_from_packet(&Self::read_packet(file_buffer, file_type)?)?;
+
+        if file_type == FileExtension::JPEG
+        {
+            xmp.merge_jpg_extended_xmp(file_buffer.as_ref())?;
+        }
+
+        return Ok(xmp);
+    }
+
+    /// Constructs a new `Xmp` object with the XMP data of the image at the
+    /// specified path
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::xmp::Xmp;
+    ///
+    /// let xmp = Xmp::new_from_path(std::path::Path::new("image.png")).unwrap();
+    /// ```
+    pub fn
+    new_from_path
+    (
+        path: &Path
+    )
+    -> Result<Xmp, Error>
+    {
+        let file_type   = get_file_type(path)?;
+        let file_buffer = std::fs::read(path)?;
+        return Self::new_from_vec(&file_buffer, file_type);
+    }
+
+    /// Writes the XMP data to an image stored as a `Vec<u8>`, replacing any
+    /// XMP packet that is already present. For HEIF images, this is only
+    /// possible if the image already contains an XMP item.
+    /// For JPEG images, the data gets split into a standard and an extended
+    /// XMP packet if it does not fit into a single APP1 segment.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::xmp::Xmp;
+    /// use little_exif::xmp::XmpValue;
+    /// use little_exif::xmp::namespace;
+    ///
+    /// let mut file_data = std::fs::read("image.jxl").unwrap();
+    /// let mut xmp = Xmp::new();
+    /// xmp.set_property(namespace::XMP, "Rating", XmpValue::Simple("3".to_string()));
+    /// xmp.write_to_vec(&mut file_data, FileExtension::JXL).unwrap();
+    /// ```
+    pub fn
+    write_to_vec
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), Error>
+    {
+        return self.write_to_vec_with_options(file_buffer, file_type, &WriteOptions::default());
+    }
+
+    /// Same as `write_to_vec`, with the C2PA policy of the given options
+    /// deciding what happens to a C2PA manifest of the image. The other 
+    /// options have no effect here.
+    pub fn
+    write_to_vec_with_options
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension,
+        options:     &WriteOptions
+    )
+    -> Result<(), Error>
+    {
+        c2pa::apply_write_policy_to_vec(file_buffer, file_type, options.c2pa)?;
+
+        if file_type == FileExtension::JPEG
+        {
+            let (packet, extended_packet) = self.encode_for_jpg();
+            return jpg::write_xmp(file_buffer, &packet, extended_packet.as_deref());
+        }
+
+        return Self::generic_write_packet_to_vec(file_buffer, &self.encode(), file_type);
+    }
+
+    /// Writes the given raw XMP packet to an image stored as a `Vec<u8>`,
+    /// replacing any XMP packet that is already present. The packet is not
+    /// checked for validity.
+    /// For JPEG images, the packet has to fit into a single APP1 segment and
+    /// any extended XMP packet gets removed.
+    /// A C2PA manifest of the image is preserved, see `WriteOptions`
+    pub fn
+    write_packet_to_vec
+    (
+        file_buffer: &mut Vec<u8>,
+        packet:      &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<(), Error>
+    {
+        c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
+        return Self::generic_write_packet_to_vec(file_buffer, packet, file_type);
+    }
+
+    #[allow(unreachable_patterns)]
+    fn
+    generic_write_packet_to_vec
+    (
+        file_buffer: &mut Vec<u8>,
+        packet:      &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<(), Error>
+    {
+        return match file_type
+        {
+            FileExtension::HEIF
+                => heif::write_xmp(file_buffer, packet),
+            FileExtension::JPEG
+                =>  jpg::write_xmp(file_buffer, packet, None),
+            FileExtension::JXL
+                =>  jxl::write_xmp(file_buffer, packet),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::write_xmp(file_buffer, packet),
+            FileExtension::TIFF
+                => tiff::vec::write_xmp(file_buffer, packet),
+            FileExtension::WEBP
+                => webp::vec::write_xmp(file_buffer, packet),
+            _
+                => unsupported_format!(
+                    format!(
+                        "Function 'write_packet_to_vec' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        };
+    }
+
+    /// Writes the XMP data to the image at the specified path, which gets
+    /// replaced atomically
+    pub fn
+    write_to_file
+    (
+        &self,
+        path: &Path
+    )
+    -> Result<(), Error>
+    {
+        return self.write_to_file_with_options(path, &WriteOptions::default());
+    }
+
+    /// Writes the XMP data to the image at the specified path, with the given
+    /// options deciding whether the file gets replaced atomically or modified
+    /// in place and what happens to a C2PA manifest of the image
+    pub fn
+    write_to_file_with_options
+    (
+        &self,
+        path:    &Path,
+        options: &WriteOptions
+    )
+    -> Result<(), Error>
+    {
+        let file_type = get_file_type(path)?;
+        return modify_file(
+            path,
+            options.atomic,
+            |file_buffer| self.write_to_vec_with_options(file_buffer, file_type, options)
+        );
+    }
+
+    /// Removes the XMP packet from an image stored as a `Vec<u8>`. Images
+    /// without an XMP packet are left untouched.
+    /// Not (yet) supported for HEIF images.
+    /// A C2PA manifest of the image is preserved, see `WriteOptions`
+    #[allow(unreachable_patterns)]
+    pub fn
+    clear_xmp
+    (
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), Error>
+    {
+        c2pa::apply_write_policy_to_vec(file_buffer, file_type, C2paPolicy::default())?;
+
+        return match file_type
+        {
+            FileExtension::HEIF
+                => heif::clear_xmp(file_buffer),
+            FileExtension::JPEG
+                =>  jpg::clear_xmp(file_buffer),
+            FileExtension::JXL
+                =>  jxl::clear_xmp(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::clear_xmp(file_buffer),
+            FileExtension::TIFF
+                => tiff::vec::clear_xmp(file_buffer),
+            FileExtension::WEBP
+                => webp::vec::clear_xmp(file_buffer),
+            _
+                => unsupported_format!(
+                    format!(
+                        "Function 'clear_xmp' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        };
+    }
+
+    /// Removes the XMP packet from the image at the specified path, which
+    /// gets replaced atomically
+    pub fn
+    file_clear_xmp
+    (
+        path: &Path
+    )
+    -> Result<(), Error>
+    {
+        let file_type = get_file_type(path)?;
+        return modify_file_atomically(
+            path,
+            |file_buffer| Self::clear_xmp(file_buffer, file_type)
+        );
+    }
+}
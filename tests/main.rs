@@ -7,8 +7,30 @@ use std::fs::remove_file;
 use std::path::Path;
 
 extern crate little_exif;
+use little_exif::c2pa;
+use little_exif::c2pa::C2paPolicy;
 use little_exif::metadata::Metadata;
+use little_exif::metadata::DecodeOptions;
+use little_exif::metadata::WriteOptions;
+use little_exif::metadata::ExifSizeReduction;
+use little_exif::metadata::RemovedExifData;
 use little_exif::exif_tag::ExifTag;
+use little_exif::filetype::FileExtension;
+use little_exif::icc;
+use little_exif::ifd::ExifTagGroup;
+use little_exif::iptc::Iptc;
+use little_exif::iptc::IptcTag;
+use little_exif::jpeg;
+use little_exif::jpeg::App1Segment;
+use little_exif::jpeg::read_mpf_images;
+use little_exif::photoshop;
+use little_exif::photoshop::ImageResource;
+use little_exif::photoshop::PhotoshopResources;
+use little_exif::xmp::namespace;
+use little_exif::xmp::Xmp;
+use little_exif::xmp::XmpArrayType;
+use little_exif::xmp::XmpProperty;
+use little_exif::xmp::XmpValue;
 
 #[test]
 fn
@@ -40,6 +62,41 @@ new_from_path_no_data_jpg()
 	assert!(data.is_err());
 }
 
+#[test]
+fn
+new_from_path_error_variants()
+{
+	assert!(matches!(
+		Metadata::new_from_path(Path::new("tests/no_exif.jpeg")), 
+		Err(little_exif::Error::NoExifData)
+	));
+	assert!(matches!(
+		Metadata::new_from_path(Path::new("tests/no_exif.jxl")), 
+		Err(little_exif::Error::NoExifData)
+	));
+	assert!(matches!(
+		Metadata::new_from_path(Path::new("tests/sample2_simple_lossless.webp")), 
+		Err(little_exif::Error::NoExifData)
+	));
+	assert!(matches!(
+		Metadata::new_from_path(Path::new("tests/sample1.txt")), 
+		Err(little_exif::Error::UnsupportedFormat(_))
+	));
+	assert!(matches!(
+		Metadata::new_from_path(Path::new("tests/does_not_exist.jpg")), 
+		Err(little_exif::Error::Io(_))
+	));
+
+	// Corrupt the endian information of the EXIF data
+	let mut image_data = read("tests/read_sample.tif").unwrap();
+	image_data[0] = 0x00;
+	image_data[1] = 0x00;
+	assert!(matches!(
+		Metadata::new_from_vec(&image_data, little_exif::filetype::FileExtension::TIFF), 
+		Err(little_exif::Error::MalformedIfd { .. })
+	));
+}
+
 #[test]
 #[should_panic(expected = "File does not exist!")]
 fn
@@ -49,7 +106,7 @@ new_from_path_panic_not_existent()
 }
 
 #[test]
-#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: Custom { kind: Other, error: \"Can't get file extension!\" }")]
+#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: UnsupportedFormat(\"Can't get file extension!\")")]
 fn
 new_from_path_panic_no_extension()
 {
@@ -57,7 +114,7 @@ new_from_path_panic_no_extension()
 }
 
 #[test]
-#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: Custom { kind: Unsupported, error: \"Unsupported file type: txt - Unknown file type: txt\" }")]
+#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: UnsupportedFormat(\"Unknown file type: txt\")")]
 fn
 new_from_path_panic_not_supported()
 {
@@ -73,6 +130,33 @@ new_from_vec()
 	let _ = Metadata::new_from_vec(&image_data, little_exif::filetype::FileExtension::JPEG).unwrap();
 }
 
+#[test]
+fn
+new_from_path_misleading_extension()
+-> Result<(), std::io::Error>
+{
+	// A JPEG file that pretends to be a PNG file
+	copy("tests/read_sample.jpg", "tests/read_sample_misleading_extension_copy.png")?;
+	let path = Path::new("tests/read_sample_misleading_extension_copy.png");
+
+	let mut metadata = Metadata::new_from_path(path)?;
+	metadata.set_tag(ExifTag::ImageDescription("Detected by content".to_string()));
+	metadata.write_to_file(path)?;
+
+	let reread = Metadata::new_from_path(path)?;
+	let image_description_tag = reread.get_tag(&ExifTag::ImageDescription(String::new())).next().unwrap();
+	assert_eq!(String::from_utf8(image_description_tag.value_as_u8_vec(&little_exif::endian::Endian::Little)).unwrap(), "Detected by content\0".to_string());
+
+	assert_eq!(
+		little_exif::filetype::get_file_type(path)?,
+		little_exif::filetype::FileExtension::JPEG
+	);
+
+	remove_file(path)?;
+
+	Ok(())
+}
+
 
 
 fn
@@ -241,6 +325,14 @@ read_from_vec_jxl()
 	return read_from_vec_generic(&read("tests/with_exif.jxl").unwrap(), little_exif::filetype::FileExtension::JXL);
 }
 
+#[test]
+fn
+read_from_vec_heif()
+-> Result<(), std::io::Error>
+{
+	return read_from_vec_generic(&read("tests/read_sample.heic").unwrap(), little_exif::filetype::FileExtension::HEIF);
+}
+
 
 
 
@@ -472,14 +564,11 @@ write_to_file_png()
 	Ok(())
 }
 
-#[ignore]
 #[test]
 fn 
 write_to_file_webp_simple_lossy() 
 -> Result<(), std::io::Error>
 {
-	// Currently not active as the "VP8 " converter does not exist yet!
-
 	// Remove file from previous run and replace it with fresh copy
 	if let Err(error) = remove_file("tests/sample2_simple_lossy_copy.webp")
 	{
@@ -663,6 +752,19 @@ compare_write_to_webp_lossless()
 	);
 }
 
+#[test]
+fn 
+compare_write_to_webp_lossy()
+-> Result<(), std::io::Error>
+{
+	return compare_write_to_generic(
+		"tests/sample2_simple_lossy.webp",
+		"tests/sample2_simple_lossy_copy1.webp",
+		"tests/sample2_simple_lossy_copy2.webp",
+		little_exif::filetype::FileExtension::WEBP
+	);
+}
+
 #[test]
 fn 
 compare_write_to_webp_extended()
@@ -674,4 +776,2809 @@ compare_write_to_webp_extended()
 		"tests/sample2_extended_copy2.webp",
 		little_exif::filetype::FileExtension::WEBP
 	);
-}
\ No newline at end of file
+}
+
+/// Writes the test metadata via `write_to_vec`, `write_to_stream` and to a
+/// copy of the file via `write_to_file` and checks that the results are
+/// identical
+fn
+compare_write_to_stream_generic
+(
+	original_file:  &str,
+	file_extension: FileExtension
+)
+-> Result<(), little_exif::Error>
+{
+	let metadata = get_test_metadata()?;
+
+	let mut vec_result = read(original_file)?;
+	metadata.write_to_vec(&mut vec_result, file_extension)?;
+
+	let mut input         = std::fs::File::open(original_file)?;
+	let mut stream_result = Vec::new();
+	metadata.write_to_stream(&mut input, &mut stream_result, file_extension)?;
+
+	assert!(stream_result == vec_result, "Stream and vec results differ for {}!", original_file);
+
+	// The atomic write streams the file into the temporary one
+	let original_path = Path::new(original_file);
+	let copy_path     = original_path.with_file_name(format!(
+		"{}_atomic_copy.{}",
+		original_path.file_stem().unwrap().to_string_lossy(),
+		original_path.extension().unwrap().to_string_lossy()
+	));
+	copy(original_path, &copy_path)?;
+	metadata.write_to_file(&copy_path)?;
+	assert!(read(&copy_path)? == vec_result, "File and vec results differ for {}!", original_file);
+	remove_file(&copy_path)?;
+
+	// The written metadata has to be readable again
+	let written_metadata = Metadata::new_from_vec(&stream_result, file_extension)?;
+	assert!(written_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	Ok(())
+}
+
+#[test]
+fn
+compare_write_to_stream()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.heic",              FileExtension::HEIF),
+		("tests/sample2.jpg",                   FileExtension::JPEG),
+		("tests/read_sample.jpg",               FileExtension::JPEG),
+		("tests/no_exif.jpeg",                  FileExtension::JPEG),
+		("tests/no_exif.jxl",                   FileExtension::JXL),
+		("tests/iso_no_exif.jxl",               FileExtension::JXL),
+		("tests/with_exif.jxl",                 FileExtension::JXL),
+		("tests/sample2.png",                   FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/png_parse_test_image.png",      FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/read_sample.tif",               FileExtension::TIFF),
+		("tests/read_sample.webp",              FileExtension::WEBP),
+		("tests/sample2_extended.webp",         FileExtension::WEBP),
+		("tests/sample2_simple_lossless.webp",  FileExtension::WEBP),
+		("tests/sample2_simple_lossy.webp",     FileExtension::WEBP),
+	];
+
+	for (sample, file_extension) in samples
+	{
+		compare_write_to_stream_generic(sample, file_extension)?;
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_stream_rejects_wrong_file_type()
+{
+	let metadata   = get_test_metadata().unwrap();
+	let mut input  = std::fs::File::open("tests/sample2.png").unwrap();
+	let mut output = Vec::new();
+
+	assert!(metadata.write_to_stream(&mut input, &mut output, FileExtension::JPEG).is_err());
+}
+
+#[test]
+fn
+new_from_vec_accepts_borrowed_slices()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.heic",          FileExtension::HEIF),
+		("tests/read_sample.jpg",           FileExtension::JPEG),
+		("tests/with_exif.jxl",             FileExtension::JXL),
+		("tests/read_sample.tif",           FileExtension::TIFF),
+		("tests/read_sample.webp",          FileExtension::WEBP),
+	];
+
+	for (sample, file_extension) in samples
+	{
+		// Embed the image in a larger buffer, like e.g. a memory mapped 
+		// archive, and read it without copying it into its own `Vec`
+		let file_buffer = read(sample)?;
+		let mut archive = vec![0xffu8; 16];
+		archive.extend(&file_buffer);
+		archive.extend([0xffu8; 16]);
+
+		let image: &[u8] = &archive[16..16 + file_buffer.len()];
+		let slice_metadata = Metadata::new_from_vec(image, file_extension)?;
+		let vec_metadata   = Metadata::new_from_vec(&file_buffer, file_extension)?;
+
+		assert!(slice_metadata.encode()? == vec_metadata.encode()?, "Slice and vec results differ for {}!", sample);
+	}
+
+	Ok(())
+}
+
+/// Wraps a reader and counts how many bytes actually get read from it
+struct
+CountingReader<R>
+{
+	inner:      R,
+	bytes_read: u64,
+}
+
+impl<R: std::io::Read> std::io::Read for CountingReader<R>
+{
+	fn
+	read
+	(
+		&mut self,
+		buf: &mut [u8]
+	)
+	-> std::io::Result<usize>
+	{
+		let read_count = self.inner.read(buf)?;
+		self.bytes_read += read_count as u64;
+		Ok(read_count)
+	}
+}
+
+impl<R: std::io::Seek> std::io::Seek for CountingReader<R>
+{
+	fn
+	seek
+	(
+		&mut self,
+		pos: std::io::SeekFrom
+	)
+	-> std::io::Result<u64>
+	{
+		self.inner.seek(pos)
+	}
+}
+
+#[test]
+fn
+compare_new_from_reader()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.heic",              FileExtension::HEIF),
+		("tests/sample2.jpg",                   FileExtension::JPEG),
+		("tests/read_sample.jpg",               FileExtension::JPEG),
+		("tests/no_exif.jpeg",                  FileExtension::JPEG),
+		("tests/no_exif.jxl",                   FileExtension::JXL),
+		("tests/iso_no_exif.jxl",               FileExtension::JXL),
+		("tests/with_exif.jxl",                 FileExtension::JXL),
+		("tests/sample2.png",                   FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/png_parse_test_image.png",      FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/read_sample.tif",               FileExtension::TIFF),
+		("tests/read_sample.webp",              FileExtension::WEBP),
+		("tests/sample2_extended.webp",         FileExtension::WEBP),
+		("tests/sample2_simple_lossless.webp",  FileExtension::WEBP),
+		("tests/sample2_simple_lossy.webp",     FileExtension::WEBP),
+	];
+
+	for (sample, file_extension) in samples
+	{
+		let file_buffer = read(sample)?;
+		let mut input   = std::fs::File::open(sample)?;
+
+		match (
+			Metadata::new_from_vec(&file_buffer, file_extension),
+			Metadata::new_from_reader(&mut input, file_extension)
+		)
+		{
+			(Ok(vec_metadata), Ok(reader_metadata)) => assert!(
+				vec_metadata.encode()? == reader_metadata.encode()?,
+				"Reader and vec results differ for {}!", sample
+			),
+			(Err(little_exif::Error::NoExifData), Err(little_exif::Error::NoExifData)) => (),
+			(vec_result, reader_result) => panic!(
+				"Reader and vec results differ for {}: {:?} vs. {:?}", 
+				sample, vec_result.err(), reader_result.err()
+			),
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+new_from_reader_at_stream_position()
+-> Result<(), little_exif::Error>
+{
+	// The PNG samples come without EXIF data
+	let mut png_buffer = read("tests/sample2.png")?;
+	get_test_metadata()?.write_to_vec(&mut png_buffer, FileExtension::PNG { as_zTXt_chunk: true })?;
+
+	let samples = [
+		(read("tests/read_sample.heic")?,      FileExtension::HEIF),
+		(read("tests/read_sample.jpg")?,       FileExtension::JPEG),
+		(read("tests/with_exif.jxl")?,         FileExtension::JXL),
+		(png_buffer,                           FileExtension::PNG { as_zTXt_chunk: true }),
+		(read("tests/read_sample.tif")?,       FileExtension::TIFF),
+		(read("tests/read_sample.webp")?,      FileExtension::WEBP),
+	];
+
+	for (file_buffer, file_extension) in samples
+	{
+		// The image is embedded in a larger stream and starts at its current
+		// position instead of its beginning
+		let mut stream = b"Some other data".to_vec();
+		stream.extend(&file_buffer);
+		let mut input = std::io::Cursor::new(&stream);
+		input.set_position(15);
+
+		assert!(
+			Metadata::new_from_vec(&file_buffer, file_extension)?.encode()? ==
+			Metadata::new_from_reader(&mut input, file_extension)?.encode()?,
+			"Reader and vec results differ for {:?}!", file_extension
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+new_from_reader_reads_only_needed_bytes()
+-> Result<(), little_exif::Error>
+{
+	let metadata  = get_test_metadata()?;
+	let padding   = vec![0u8; 1 << 20];
+
+	// Pad the images with a large amount of data after the metadata, which 
+	// the reader should never get to
+	let mut jpg_buffer = read("tests/sample2.jpg")?;
+	metadata.write_to_vec(&mut jpg_buffer, FileExtension::JPEG)?;
+	jpg_buffer.extend(&padding);
+
+	let mut png_buffer = read("tests/sample2.png")?;
+	metadata.write_to_vec(&mut png_buffer, FileExtension::PNG { as_zTXt_chunk: true })?;
+	png_buffer.extend(&padding);
+
+	let mut webp_buffer = read("tests/sample2_extended.webp")?;
+	metadata.write_to_vec(&mut webp_buffer, FileExtension::WEBP)?;
+	webp_buffer.extend(b"PADD");
+	webp_buffer.extend(&(padding.len() as u32).to_le_bytes());
+	webp_buffer.extend(&padding);
+	let riff_size = (webp_buffer.len() as u32 - 8).to_le_bytes();
+	webp_buffer[4..8].copy_from_slice(&riff_size);
+
+	let samples = [
+		(jpg_buffer,  FileExtension::JPEG),
+		(png_buffer,  FileExtension::PNG { as_zTXt_chunk: true }),
+		(webp_buffer, FileExtension::WEBP),
+	];
+
+	for (file_buffer, file_extension) in samples
+	{
+		let mut input = CountingReader {
+			inner:      std::io::Cursor::new(&file_buffer),
+			bytes_read: 0,
+		};
+		let read_metadata = Metadata::new_from_reader(&mut input, file_extension)?;
+
+		assert!(read_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+		assert!(
+			input.bytes_read < padding.len() as u64,
+			"Read {} of {} bytes for {:?}!", input.bytes_read, file_buffer.len(), file_extension
+		);
+	}
+
+	Ok(())
+}
+
+/// Checks that no temporary file of an atomic write is left behind next to
+/// the file with the given name in the tests directory
+fn
+assert_no_temp_file_left
+(
+	file_name: &str
+)
+{
+	let temp_file_prefix = format!(".{}.", file_name);
+
+	for entry in std::fs::read_dir("tests").unwrap()
+	{
+		let entry_name = entry.unwrap().file_name().to_string_lossy().to_string();
+		assert!(!entry_name.starts_with(&temp_file_prefix), "Temporary file {} was left behind!", entry_name);
+	}
+}
+
+#[cfg(unix)]
+#[test]
+fn
+write_to_file_atomic_keeps_permissions()
+-> Result<(), little_exif::Error>
+{
+	use std::os::unix::fs::PermissionsExt;
+
+	let path = Path::new("tests/sample2_atomic_copy.jpg");
+	copy("tests/sample2.jpg", path)?;
+	std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o640))?;
+
+	get_test_metadata()?.write_to_file(path)?;
+	assert_eq!(std::fs::metadata(path)?.permissions().mode() & 0o777, 0o640);
+	assert_no_temp_file_left("sample2_atomic_copy.jpg");
+
+	Metadata::file_clear_metadata(path)?;
+	assert_eq!(std::fs::metadata(path)?.permissions().mode() & 0o777, 0o640);
+	assert_no_temp_file_left("sample2_atomic_copy.jpg");
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+write_to_file_atomic_keeps_original_on_error()
+-> Result<(), little_exif::Error>
+{
+	// This PNG does not start with an IHDR chunk, so writing fails
+	let path     = Path::new("tests/no_ihdr_atomic_copy.png");
+	let original = read("tests/fuzz_regressions/round_trip_png/no_ihdr")?;
+	std::fs::write(path, &original)?;
+
+	assert!(get_test_metadata()?.write_to_file(path).is_err());
+	assert_eq!(read(path)?, original);
+	assert_no_temp_file_left("no_ihdr_atomic_copy.png");
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+write_to_file_in_place()
+-> Result<(), little_exif::Error>
+{
+	let path    = Path::new("tests/sample2_in_place_copy.png");
+	let options = WriteOptions { atomic: false, ..WriteOptions::default() };
+	copy("tests/sample2.png", path)?;
+
+	get_test_metadata()?.write_to_file_with_options(path, &options)?;
+	let metadata = Metadata::new_from_path(path)?;
+	assert!(metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	Metadata::file_clear_metadata_with_options(path, &options)?;
+	assert!(matches!(Metadata::new_from_path(path), Err(little_exif::Error::NoExifData)));
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+clear_metadata_webp_keeps_image_data()
+-> Result<(), little_exif::Error>
+{
+	// The EXIF chunk is the last one of this file and starts at 11124
+	let original_buffer = read("tests/read_sample.webp").unwrap();
+	let mut cleared_buffer = original_buffer.clone();
+
+	Metadata::clear_metadata(&mut cleared_buffer, FileExtension::WEBP)?;
+
+	// Apart from the RIFF header and VP8X flags the data must be unchanged
+	assert_eq!(cleared_buffer.len(), 11124);
+	assert_eq!(cleared_buffer[24..], original_buffer[24..11124]);
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_vec_webp_simple_lossy()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/sample2_simple_lossy.webp").unwrap();
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Lossy".to_string()));
+	metadata.write_to_vec(&mut file_buffer, FileExtension::WEBP)?;
+
+	// The file gets converted to the Extended File Format, using the canvas
+	// size of the 1000x1000 pixel VP8 frame and the EXIF flag
+	assert_eq!(file_buffer[12..30], [
+		0x56, 0x50, 0x38, 0x58, 0x0A, 0x00, 0x00, 0x00,
+		0x08, 0x00, 0x00, 0x00, 0xE7, 0x03, 0x00, 0xE7, 0x03, 0x00
+	]);
+
+	let reread = Metadata::new_from_vec(&file_buffer, FileExtension::WEBP)?;
+	assert!(reread.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	Metadata::clear_metadata(&mut file_buffer, FileExtension::WEBP)?;
+	assert!(matches!(
+		Metadata::new_from_vec(&file_buffer, FileExtension::WEBP),
+		Err(little_exif::Error::NoExifData)
+	));
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_vec_webp_exif_before_xmp()
+-> Result<(), little_exif::Error>
+{
+	// Replace the EXIF chunk of the sample with an XMP chunk
+	let mut file_buffer = read("tests/read_sample.webp").unwrap();
+	Metadata::clear_metadata(&mut file_buffer, FileExtension::WEBP)?;
+	file_buffer.extend(b"XMP \x04\x00\x00\x00test");
+	let riff_size = (file_buffer.len() - 8) as u32;
+	file_buffer[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("EXIF before XMP".to_string()));
+	metadata.write_to_vec(&mut file_buffer, FileExtension::WEBP)?;
+
+	// Collect the chunk types
+	let mut chunk_types = Vec::new();
+	let mut position    = 12;
+	while position + 8 <= file_buffer.len()
+	{
+		let chunk_size = u32::from_le_bytes(file_buffer[position+4..position+8].try_into().unwrap()) as usize;
+		chunk_types.push(String::from_utf8_lossy(&file_buffer[position..position+4]).to_string());
+		position += 8 + chunk_size + chunk_size % 2;
+	}
+	assert_eq!(chunk_types, vec!["VP8X", "VP8 ", "EXIF", "XMP "]);
+
+	Ok(())
+}
+
+
+/// Places an XMP APP1 segment right after the signature, i.e. in front of 
+/// the EXIF APP1 segment
+fn
+insert_xmp_app1_segment
+(
+	file_buffer: &mut Vec<u8>,
+	xmp_packet:  &[u8]
+)
+{
+	let mut segment = vec![0xff, 0xe1];
+	let payload_length = (2 + 29 + xmp_packet.len()) as u16;
+	segment.extend(payload_length.to_be_bytes());
+	segment.extend(b"http://ns.adobe.com/xap/1.0/\0");
+	segment.extend(xmp_packet);
+	file_buffer.splice(2..2, segment);
+}
+
+#[test]
+fn
+jpg_xmp_app1_before_exif()
+-> Result<(), little_exif::Error>
+{
+	let xmp_packet      = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>";
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+	let original        = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	insert_xmp_app1_segment(&mut file_buffer, xmp_packet);
+
+	// Reading skips the XMP segment instead of trying to decode it as EXIF
+	let metadata = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	assert!(metadata.encode()? == original.encode()?);
+
+	let mut input = std::io::Cursor::new(&file_buffer);
+	assert!(Metadata::new_from_reader(&mut input, FileExtension::JPEG)?.encode()? == original.encode()?);
+
+	// Both segments are available separately
+	let segments = jpeg::read_app1_segments(&file_buffer)?;
+	assert_eq!(segments.len(), 2);
+	assert_eq!(segments[0], App1Segment::Xmp(xmp_packet.to_vec()));
+	assert!(matches!(segments[1], App1Segment::Exif(_)));
+
+	// Writing and clearing only replace the EXIF segment
+	let mut written_buffer = file_buffer.clone();
+	get_test_metadata()?.write_to_vec(&mut written_buffer, FileExtension::JPEG)?;
+	let segments = jpeg::read_app1_segments(&written_buffer)?;
+	assert_eq!(segments.len(), 2);
+	assert!(matches!(segments[0], App1Segment::Exif(_)));
+	assert_eq!(segments[1], App1Segment::Xmp(xmp_packet.to_vec()));
+
+	let mut streamed_buffer = Vec::new();
+	get_test_metadata()?.write_to_stream(&mut std::io::Cursor::new(&file_buffer), &mut streamed_buffer, FileExtension::JPEG)?;
+	assert!(streamed_buffer == written_buffer);
+
+	Metadata::clear_metadata(&mut written_buffer, FileExtension::JPEG)?;
+	assert_eq!(
+		jpeg::read_app1_segments(&written_buffer)?, 
+		vec![App1Segment::Xmp(xmp_packet.to_vec())]
+	);
+	assert!(matches!(
+		Metadata::new_from_vec(&written_buffer, FileExtension::JPEG), 
+		Err(little_exif::Error::NoExifData)
+	));
+
+	Ok(())
+}
+
+const XMP_TEST_PACKET: &str = r#"<?xpacket begin="﻿" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:Iptc4xmpCore="http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/"
+    xmlns:my="http://example.com/my/"
+    xmp:Rating="4">
+   <dc:title>
+    <rdf:Alt>
+     <rdf:li xml:lang="x-default">Hello &amp; World</rdf:li>
+     <rdf:li xml:lang="de-AT">Servus</rdf:li>
+    </rdf:Alt>
+   </dc:title>
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>little</rdf:li>
+     <rdf:li>exif</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+   <Iptc4xmpCore:CreatorContactInfo rdf:parseType="Resource">
+    <Iptc4xmpCore:CiAdrCity>Graz</Iptc4xmpCore:CiAdrCity>
+   </Iptc4xmpCore:CreatorContactInfo>
+   <my:Link rdf:resource="https://example.com"/>
+   <my:Shorthand my:Field="value"/>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+#[test]
+fn
+xmp_parse_and_encode()
+-> Result<(), little_exif::Error>
+{
+	let xmp = Xmp::new_from_packet(XMP_TEST_PACKET.as_bytes())?;
+
+	assert_eq!(xmp.get_text(namespace::XMP, "Rating"), Some("4"));
+	assert_eq!(xmp.get_text(namespace::DC,  "title"),  Some("Hello & World"));
+	assert_eq!(
+		xmp.get_property(namespace::DC, "title"),
+		Some(&XmpValue::LangAlt(vec![
+			("x-default".to_string(), "Hello & World".to_string()),
+			("de-AT".to_string(),     "Servus".to_string()),
+		]))
+	);
+	assert_eq!(
+		xmp.get_property(namespace::DC, "subject"),
+		Some(&XmpValue::Array(XmpArrayType::Bag, vec![
+			XmpValue::Simple("little".to_string()),
+			XmpValue::Simple("exif".to_string()),
+		]))
+	);
+	assert_eq!(
+		xmp.get_property(namespace::IPTC_CORE, "CreatorContactInfo"),
+		Some(&XmpValue::Struct(vec![XmpProperty {
+			namespace: namespace::IPTC_CORE.to_string(),
+			name:      "CiAdrCity".to_string(),
+			value:     XmpValue::Simple("Graz".to_string()),
+		}]))
+	);
+	assert_eq!(xmp.get_text("http://example.com/my/", "Link"), Some("https://example.com"));
+	assert!(matches!(
+		xmp.get_property("http://example.com/my/", "Shorthand"), 
+		Some(XmpValue::Struct(fields)) if fields.len() == 1
+	));
+	assert_eq!(xmp.get_prefix("http://example.com/my/"), Some("my"));
+
+	// Encoding and parsing again results in the same data
+	let encoded = xmp.encode();
+	assert!(String::from_utf8_lossy(&encoded).contains("xmlns:my=\"http://example.com/my/\""));
+	assert_eq!(Xmp::new_from_packet(&encoded)?, xmp);
+
+	// Namespaces without a known prefix get one generated
+	let mut new_xmp = Xmp::new();
+	new_xmp.set_property("http://example.com/other/", "Value", XmpValue::Simple("<1>".to_string()));
+	let reparsed = Xmp::new_from_packet(&new_xmp.encode_with_padding(0))?;
+	assert_eq!(reparsed.get_text("http://example.com/other/", "Value"), Some("<1>"));
+
+	assert!(matches!(
+		Xmp::new_from_packet(b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">"),
+		Err(little_exif::Error::MalformedXmp(_))
+	));
+
+	Ok(())
+}
+
+#[test]
+fn
+xmp_read_tiff()
+-> Result<(), little_exif::Error>
+{
+	let xmp = Xmp::new_from_path(Path::new("tests/read_sample.tif"))?;
+	assert!(!xmp.get_properties().is_empty());
+	Ok(())
+}
+
+#[test]
+fn
+xmp_write_and_clear()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.jpg",              FileExtension::JPEG),
+		("tests/no_exif.jpeg",                 FileExtension::JPEG),
+		("tests/sample2.png",                  FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/read_sample.webp",             FileExtension::WEBP),
+		("tests/sample2_simple_lossy.webp",    FileExtension::WEBP),
+		("tests/sample2_simple_lossless.webp", FileExtension::WEBP),
+		("tests/with_exif.jxl",                FileExtension::JXL),
+		("tests/no_exif.jxl",                  FileExtension::JXL),
+		("tests/read_sample.tif",              FileExtension::TIFF),
+	];
+
+	let mut xmp = Xmp::new_from_packet(XMP_TEST_PACKET.as_bytes())?;
+
+	for (path, file_type) in samples
+	{
+		let mut file_buffer = read(path)?;
+		let     exif_before = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+
+		xmp.set_property(namespace::XMP, "Label", XmpValue::Simple(path.to_string()));
+		xmp.write_to_vec(&mut file_buffer, file_type)?;
+		assert_eq!(Xmp::new_from_vec(&file_buffer, file_type)?, xmp, "{}", path);
+
+		// Writing again replaces the packet
+		xmp.set_property(namespace::XMP, "Rating", XmpValue::Simple("5".to_string()));
+		xmp.write_to_vec(&mut file_buffer, file_type)?;
+		assert_eq!(Xmp::new_from_vec(&file_buffer, file_type)?, xmp, "{}", path);
+
+		// The EXIF data is not affected
+		if file_type != FileExtension::TIFF
+		{
+			let exif_after = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+			assert_eq!(exif_before.ok(), exif_after.ok(), "{}", path);
+		}
+
+		Xmp::clear_xmp(&mut file_buffer, file_type)?;
+		assert!(
+			matches!(Xmp::new_from_vec(&file_buffer, file_type), Err(little_exif::Error::NoXmpData)), 
+			"{}", path
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+xmp_no_data()
+{
+	let samples = [
+		("tests/read_sample.jpg",          FileExtension::JPEG),
+		("tests/sample2.png",              FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/sample2_extended.webp",    FileExtension::WEBP),
+		("tests/no_exif.jxl",              FileExtension::JXL),
+		("tests/with_exif.jxl",            FileExtension::JXL),
+		("tests/read_sample.heic",         FileExtension::HEIF),
+	];
+
+	for (path, file_type) in samples
+	{
+		let file_buffer = read(path).unwrap();
+		assert!(
+			matches!(Xmp::new_from_vec(&file_buffer, file_type), Err(little_exif::Error::NoXmpData)), 
+			"{}", path
+		);
+	}
+
+	// Adding an XMP item to a HEIF is not supported
+	let mut file_buffer = read("tests/read_sample.heic").unwrap();
+	assert!(matches!(
+		Xmp::new().write_to_vec(&mut file_buffer, FileExtension::HEIF),
+		Err(little_exif::Error::UnsupportedFormat(_))
+	));
+}
+
+#[test]
+fn
+xmp_write_extended_jpg_many_properties()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+
+	// Lots of small properties, so that many of them need to be moved
+	let mut xmp = Xmp::new();
+	for index in 0..5000
+	{
+		xmp.set_property(
+			"http://example.com/many/",
+			&format!("Property{}", index),
+			XmpValue::Simple(format!("Value number {}", index))
+		);
+	}
+	xmp.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+
+	// The standard packet fits into its APP1 segment, the rest is moved
+	let standard_packet = Xmp::read_packet(&file_buffer, FileExtension::JPEG)?;
+	assert!(b"http://ns.adobe.com/xap/1.0/\0".len() + standard_packet.len() <= u16::MAX as usize - 2);
+
+	let read_xmp = Xmp::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	assert_eq!(read_xmp.get_properties().len(), xmp.get_properties().len());
+	for property in xmp.get_properties()
+	{
+		assert_eq!(read_xmp.get_property(&property.namespace, &property.name), Some(&property.value));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+xmp_write_extended_jpg()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+	let     exif_before = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?.encode()?;
+
+	// Three large properties that don't fit into a single APP1 segment
+	let mut xmp = Xmp::new();
+	xmp.set_property(namespace::XMP, "Rating", XmpValue::Simple("3".to_string()));
+	for (index, name) in ["First", "Second", "Third"].iter().enumerate()
+	{
+		xmp.set_property(
+			"http://example.com/large/",
+			name,
+			XmpValue::Simple(index.to_string().repeat(40000))
+		);
+	}
+	xmp.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+
+	let segments = jpeg::read_app1_segments(&file_buffer)?;
+	let extended_segment_count = segments.iter()
+		.filter(|segment| matches!(segment, App1Segment::ExtendedXmp(_)))
+		.count();
+	assert!(extended_segment_count >= 2);
+
+	// The standard packet refers to the extended one and keeps the smaller
+	// properties
+	let standard_xmp = Xmp::new_from_packet(&Xmp::read_packet(&file_buffer, FileExtension::JPEG)?)?;
+	assert_eq!(standard_xmp.get_text(namespace::XMP_NOTE, "HasExtendedXMP").map(|guid| guid.len()), Some(32));
+	assert_eq!(standard_xmp.get_text(namespace::XMP, "Rating"), Some("3"));
+
+	// Reading reassembles the data of both packets
+	let read_xmp = Xmp::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	assert_eq!(read_xmp.get_properties().len(), xmp.get_properties().len());
+	for property in xmp.get_properties()
+	{
+		assert_eq!(read_xmp.get_property(&property.namespace, &property.name), Some(&property.value));
+	}
+
+	assert_eq!(Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?.encode()?, exif_before);
+
+	// A modified extended packet no longer matches its GUID
+	let mut corrupted_buffer = file_buffer.clone();
+	let     last_byte_index  = corrupted_buffer.windows(5)
+		.rposition(|window| window == b"00000")
+		.unwrap();
+	corrupted_buffer[last_byte_index] = b'3';
+	assert!(Xmp::new_from_vec(&corrupted_buffer, FileExtension::JPEG).is_err());
+
+	// Writing a small packet removes the extended one
+	Xmp::new().write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert!(!jpeg::read_app1_segments(&file_buffer)?.iter()
+		.any(|segment| matches!(segment, App1Segment::ExtendedXmp(_)))
+	);
+
+	Ok(())
+}
+
+#[test]
+fn
+iptc_read_jpg()
+-> Result<(), little_exif::Error>
+{
+	let iptc = Iptc::new_from_path(Path::new("tests/read_sample.jpg"))?;
+	assert!(iptc.is_utf8());
+	assert_eq!(iptc.get_string(IptcTag::CaptionAbstract), Some("Hello World!".to_string()));
+	assert_eq!(iptc.get_data(IptcTag::RecordVersion), vec![[0x00, 0x02].as_slice()]);
+	Ok(())
+}
+
+#[test]
+fn
+iptc_write_and_clear()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.jpg", FileExtension::JPEG),
+		("tests/no_exif.jpeg",    FileExtension::JPEG),
+		("tests/read_sample.tif", FileExtension::TIFF),
+	];
+
+	for (path, file_type) in samples
+	{
+		let mut file_buffer = read(path)?;
+		let     exif_before = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+
+		let mut iptc = Iptc::new_from_vec(&file_buffer, file_type).unwrap_or_default();
+		iptc.set_strings(IptcTag::Keywords, &["Steiermark", "Schöckl"]);
+		iptc.set_string(IptcTag::Byline, "little_exif");
+		iptc.write_to_vec(&mut file_buffer, file_type)?;
+
+		let read_iptc = Iptc::new_from_vec(&file_buffer, file_type)?;
+		assert_eq!(read_iptc, iptc, "{}", path);
+		assert_eq!(read_iptc.get_strings(IptcTag::Keywords), vec!["Steiermark", "Schöckl"]);
+
+		if file_type == FileExtension::JPEG
+		{
+			let exif_after = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+			assert_eq!(exif_before.ok(), exif_after.ok(), "{}", path);
+		}
+
+		Iptc::clear_iptc(&mut file_buffer, file_type)?;
+		assert!(
+			matches!(Iptc::new_from_vec(&file_buffer, file_type), Err(little_exif::Error::NoIptcData)),
+			"{}", path
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+iptc_write_jpg_updates_digest()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+
+	let mut iptc = Iptc::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	iptc.set_string(IptcTag::CaptionAbstract, "Servus!");
+	iptc.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+
+	// The digest of the IPTC data (resource 0x0425) matches the new data
+	let digest = md5::compute(iptc.encode()).0;
+	assert!(file_buffer.windows(digest.len()).any(|window| window == digest));
+
+	// Only the Photoshop segment gets removed once it holds no more data
+	Iptc::clear_iptc(&mut file_buffer, FileExtension::JPEG)?;
+	assert!(!file_buffer.windows(13).any(|window| window == b"Photoshop 3.0"));
+
+	Ok(())
+}
+
+#[test]
+fn
+photoshop_resources_jpg()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+
+	let mut resources = PhotoshopResources::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	let ids = resources.get_resources().iter().map(|resource| resource.id).collect::<Vec<u16>>();
+	assert_eq!(ids, vec![photoshop::IPTC_RESOURCE_ID, photoshop::IPTC_DIGEST_RESOURCE_ID]);
+
+	// A resource that does not fit into a single APP13 segment
+	resources.set_resource(ImageResource::new(photoshop::THUMBNAIL_RESOURCE_ID, vec![0xab; 100_001]));
+	resources.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(PhotoshopResources::new_from_vec(&file_buffer, FileExtension::JPEG)?, resources);
+	assert_eq!(
+		Iptc::new_from_vec(&file_buffer, FileExtension::JPEG)?.get_string(IptcTag::CaptionAbstract),
+		Some("Hello World!".to_string())
+	);
+
+	// Removing a single resource keeps the others
+	resources.remove_resource(photoshop::IPTC_RESOURCE_ID);
+	resources.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert!(matches!(Iptc::new_from_vec(&file_buffer, FileExtension::JPEG), Err(little_exif::Error::NoIptcData)));
+	assert_eq!(
+		PhotoshopResources::new_from_vec(&file_buffer, FileExtension::JPEG)?
+			.get_resource(photoshop::THUMBNAIL_RESOURCE_ID)
+			.map(|resource| resource.data.len()),
+		Some(100_001)
+	);
+
+	// Writing no resources at all removes the segments
+	PhotoshopResources::new().write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert!(!file_buffer.windows(13).any(|window| window == b"Photoshop 3.0"));
+
+	Ok(())
+}
+
+#[test]
+fn
+photoshop_resources_tiff()
+-> Result<(), little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.tif")?;
+	assert!(PhotoshopResources::new_from_vec(&file_buffer, FileExtension::TIFF)?.get_resources().is_empty());
+
+	let mut resources = PhotoshopResources::new();
+	resources.set_resource(ImageResource::new(photoshop::URL_LIST_RESOURCE_ID, vec![0, 0, 0, 0]));
+	resources.write_to_vec(&mut file_buffer, FileExtension::TIFF)?;
+	assert_eq!(PhotoshopResources::new_from_vec(&file_buffer, FileExtension::TIFF)?, resources);
+
+	PhotoshopResources::new().write_to_vec(&mut file_buffer, FileExtension::TIFF)?;
+	assert!(PhotoshopResources::new_from_vec(&file_buffer, FileExtension::TIFF)?.get_resources().is_empty());
+
+	Ok(())
+}
+
+#[test]
+fn
+iptc_unsupported_format()
+{
+	let mut file_buffer = read("tests/sample2.png").unwrap();
+	assert!(matches!(
+		Iptc::new().write_to_vec(&mut file_buffer, FileExtension::PNG { as_zTXt_chunk: true }),
+		Err(little_exif::Error::UnsupportedFormat(_))
+	));
+}
+
+/// Constructs some data that looks like an ICC profile of the given length
+fn
+fake_icc_profile
+(
+	length: usize
+)
+-> Vec<u8>
+{
+	let mut icc_profile = (0..length).map(|index| (index % 251) as u8).collect::<Vec<u8>>();
+	icc_profile[0..4].copy_from_slice(&(length as u32).to_be_bytes());
+	icc_profile[36..40].copy_from_slice(b"acsp");
+	return icc_profile;
+}
+
+#[test]
+fn
+icc_profile_read_tiff()
+-> Result<(), little_exif::Error>
+{
+	let icc_profile = icc::file_read_icc_profile(Path::new("tests/read_sample.tif"))?;
+	assert_eq!(&icc_profile[36..40], b"acsp");
+	assert_eq!(u32::from_be_bytes([icc_profile[0], icc_profile[1], icc_profile[2], icc_profile[3]]) as usize, icc_profile.len());
+
+	Ok(())
+}
+
+#[test]
+fn
+icc_profile_write_and_clear()
+-> Result<(), little_exif::Error>
+{
+	let samples = [
+		("tests/read_sample.jpg",              FileExtension::JPEG),
+		("tests/no_exif.jpeg",                 FileExtension::JPEG),
+		("tests/sample2.png",                  FileExtension::PNG { as_zTXt_chunk: true }),
+		("tests/read_sample.tif",              FileExtension::TIFF),
+		("tests/read_sample.webp",             FileExtension::WEBP),
+		("tests/sample2_simple_lossy.webp",    FileExtension::WEBP),
+		("tests/sample2_simple_lossless.webp", FileExtension::WEBP),
+		("tests/with_properties.heic",         FileExtension::HEIF),
+	];
+
+	// The large profile needs to be split across several JPEG APP2 segments
+	let large_icc_profile = fake_icc_profile(150_001);
+	let small_icc_profile = fake_icc_profile(3_144);
+
+	for (path, file_type) in samples
+	{
+		let mut file_buffer = read(path)?;
+		let     exif_before = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+
+		if file_type != FileExtension::TIFF
+		{
+			assert!(
+				matches!(icc::read_icc_profile(&file_buffer, file_type), Err(little_exif::Error::NoIccProfile)),
+				"{}", path
+			);
+		}
+
+		for icc_profile in [&large_icc_profile, &small_icc_profile]
+		{
+			icc::write_icc_profile(&mut file_buffer, icc_profile, file_type)?;
+			assert_eq!(&icc::read_icc_profile(&file_buffer, file_type)?, icc_profile, "{}", path);
+
+			// For TIFF images, the profile is part of the EXIF data
+			if file_type != FileExtension::TIFF
+			{
+				let exif_after = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+				assert_eq!(exif_before.as_ref().ok(), exif_after.as_ref().ok(), "{}", path);
+			}
+		}
+
+		if file_type == FileExtension::WEBP
+		{
+			// The ICCP chunk directly follows the VP8X chunk with the flag set
+			assert_eq!(&file_buffer[12..16], b"VP8X");
+			assert_eq!(file_buffer[20] & 0x20, 0x20);
+			assert_eq!(&file_buffer[30..34], b"ICCP");
+		}
+
+		icc::clear_icc_profile(&mut file_buffer, file_type)?;
+		assert!(
+			matches!(icc::read_icc_profile(&file_buffer, file_type), Err(little_exif::Error::NoIccProfile)),
+			"{}", path
+		);
+
+		if file_type != FileExtension::TIFF
+		{
+			let exif_after = Metadata::new_from_vec(&file_buffer, file_type).and_then(|metadata| metadata.encode());
+			assert_eq!(exif_before.ok(), exif_after.ok(), "{}", path);
+		}
+
+		if file_type == FileExtension::WEBP
+		{
+			assert_eq!(file_buffer[20] & 0x20, 0x00);
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+icc_profile_heif()
+-> Result<(), little_exif::Error>
+{
+	let original_buffer = read("tests/with_properties.heic")?;
+	let mut file_buffer = original_buffer.clone();
+
+	// The new colour information property gets associated with the primary
+	// item, which also moves the image and EXIF data
+	icc::write_icc_profile(&mut file_buffer, &fake_icc_profile(1_000), FileExtension::HEIF)?;
+	assert_eq!(file_buffer.len(), original_buffer.len() + 1_000 + 12 + 1);
+	assert!(file_buffer.ends_with(&original_buffer[original_buffer.len()-342..]));
+
+	// Removing the profile again restores the original file
+	icc::clear_icc_profile(&mut file_buffer, FileExtension::HEIF)?;
+	assert_eq!(file_buffer, original_buffer);
+
+	// Files without any item properties have no profile to remove
+	let mut file_buffer = read("tests/read_sample.heic")?;
+	assert!(matches!(icc::read_icc_profile(&file_buffer, FileExtension::HEIF), Err(little_exif::Error::NoIccProfile)));
+	icc::clear_icc_profile(&mut file_buffer, FileExtension::HEIF)?;
+	assert_eq!(file_buffer, read("tests/read_sample.heic")?);
+
+	Ok(())
+}
+
+const MALFORMED_INPUT_SAMPLES: [(&str, little_exif::filetype::FileExtension); 13] = [
+	("tests/read_sample.jpg",                little_exif::filetype::FileExtension::JPEG),
+	("tests/sample2.jpg",                    little_exif::filetype::FileExtension::JPEG),
+	("tests/no_exif.jpeg",                   little_exif::filetype::FileExtension::JPEG),
+	("tests/sample2.png",                    little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: true }),
+	("tests/png_parse_test_image.png",       little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: true }),
+	("tests/with_exif.jxl",                  little_exif::filetype::FileExtension::JXL),
+	("tests/iso_no_exif.jxl",                little_exif::filetype::FileExtension::JXL),
+	("tests/read_sample.tif",                little_exif::filetype::FileExtension::TIFF),
+	("tests/read_sample.webp",               little_exif::filetype::FileExtension::WEBP),
+	("tests/sample2_extended.webp",          little_exif::filetype::FileExtension::WEBP),
+	("tests/sample2_simple_lossless.webp",   little_exif::filetype::FileExtension::WEBP),
+	("tests/sample2_simple_lossy.webp",      little_exif::filetype::FileExtension::WEBP),
+	("tests/read_sample.heic",               little_exif::filetype::FileExtension::HEIF),
+];
+
+/// Decodes the given data in strict and lenient mode and reports whether this
+/// panicked
+fn
+decodes_without_panic
+(
+	file_buffer: &Vec<u8>,
+	file_type:   little_exif::filetype::FileExtension
+)
+-> bool
+{
+	return std::panic::catch_unwind(|| {
+		let _ = Metadata::new_from_vec(file_buffer, file_type);
+		let _ = Metadata::new_from_vec_with_options(
+			file_buffer, 
+			file_type, 
+			&DecodeOptions { strict: false }
+		);
+	}).is_ok();
+}
+
+#[test]
+fn
+new_from_vec_truncated_input()
+{
+	let mut failures = Vec::new();
+
+	for (path, file_type) in MALFORMED_INPUT_SAMPLES
+	{
+		let image_data = read(path).unwrap();
+
+		// Every length for the first few KB (where the EXIF data is usually
+		// located), coarser steps afterwards
+		let lengths = (0..image_data.len().min(4096))
+			.chain((4096..image_data.len()).step_by(509));
+
+		for length in lengths
+		{
+			if !decodes_without_panic(&image_data[..length].to_vec(), file_type)
+			{
+				failures.push(format!("{} truncated to {} bytes", path, length));
+			}
+		}
+	}
+
+	assert!(failures.is_empty(), "Panics while decoding:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn
+new_from_vec_bit_flipped_input()
+{
+	let mut failures = Vec::new();
+
+	// Simple deterministic pseudo random numbers so that failures are
+	// reproducible without any additional dependency
+	let mut state = 0x2545f4914f6cdd1du64;
+	let mut next_random = move || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+
+	for (path, file_type) in MALFORMED_INPUT_SAMPLES
+	{
+		let image_data = read(path).unwrap();
+
+		// Flip every bit in the first bytes, followed by random flips of up to
+		// 4 bits anywhere in the first few KB
+		let single_flips = (0..image_data.len().min(512) * 8)
+			.map(|bit| vec![bit]);
+		let random_flips = (0..2000)
+			.map(|_| {
+				let count = 1 + next_random() as usize % 4;
+				(0..count)
+					.map(|_| next_random() as usize % (image_data.len().min(4096) * 8))
+					.collect::<Vec<usize>>()
+			})
+			.collect::<Vec<Vec<usize>>>();
+
+		for bits in single_flips.chain(random_flips)
+		{
+			let mut mangled_data = image_data.clone();
+			for bit in bits.iter()
+			{
+				mangled_data[bit / 8] ^= 1 << (bit % 8);
+			}
+
+			if !decodes_without_panic(&mangled_data, file_type)
+			{
+				failures.push(format!("{} with flipped bits {:?}", path, bits));
+			}
+		}
+	}
+
+	assert!(failures.is_empty(), "Panics while decoding:\n{}", failures.join("\n"));
+}
+
+/// Replaces the value/offset field of the given tag in IFD0 of little endian
+/// encoded EXIF data with the given offset
+fn
+corrupt_ifd0_entry_offset
+(
+	encoded_data: &mut Vec<u8>,
+	hex_tag:      u16,
+	new_offset:   u32
+)
+{
+	let ifd0_offset       = u32::from_le_bytes(encoded_data[4..8].try_into().unwrap()) as usize;
+	let number_of_entries = u16::from_le_bytes(encoded_data[ifd0_offset..ifd0_offset+2].try_into().unwrap()) as usize;
+
+	for entry_nr in 0..number_of_entries
+	{
+		let entry_start = ifd0_offset + 2 + 12 * entry_nr;
+		if encoded_data[entry_start..entry_start+2] == hex_tag.to_le_bytes()
+		{
+			encoded_data[entry_start+8..entry_start+12].copy_from_slice(&new_offset.to_le_bytes());
+			return;
+		}
+	}
+
+	panic!("Tag 0x{:04x} not found in IFD0", hex_tag);
+}
+
+#[test]
+fn
+new_from_vec_lenient_skips_bad_entry()
+{
+	let mut encoded_data = get_test_metadata().unwrap().encode().unwrap();
+	corrupt_ifd0_entry_offset(&mut encoded_data, 0x010e, 0x7fff_fff0);
+
+	// Strict decoding (the default) fails entirely
+	let strict_result = Metadata::new_from_vec(&encoded_data, FileExtension::TIFF);
+	assert!(matches!(strict_result, Err(little_exif::Error::MalformedIfd { .. })));
+
+	// Lenient decoding only drops the ImageDescription
+	let (metadata, warnings) = Metadata::new_from_vec_with_options(
+		&encoded_data, 
+		FileExtension::TIFF, 
+		&DecodeOptions { strict: false }
+	).unwrap();
+
+	assert!(metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_none());
+	assert!(metadata.get_tag(&ExifTag::Model(String::new())).next().is_some());
+	assert!(metadata.get_tag(&ExifTag::ISO(vec![0])).next().is_some());
+
+	assert_eq!(warnings.len(), 1);
+	assert_eq!(warnings[0].tag,            Some(0x010e));
+	assert_eq!(warnings[0].group,          ExifTagGroup::GENERIC);
+	assert_eq!(warnings[0].generic_ifd_nr, 0);
+}
+
+#[test]
+fn
+new_from_vec_lenient_skips_bad_subifd()
+{
+	// Let the ExifIFD offset point to an IFD at the end of the data that 
+	// claims to have far more entries than there is data for
+	let mut encoded_data = get_test_metadata().unwrap().encode().unwrap();
+	let     bad_offset   = encoded_data.len() as u32;
+	encoded_data.extend([0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+	corrupt_ifd0_entry_offset(&mut encoded_data, 0x8769, bad_offset);
+
+	assert!(Metadata::new_from_vec(&encoded_data, FileExtension::TIFF).is_err());
+
+	// The ExifIFD with the ISO value is lost, everything in IFD0 is kept
+	let (metadata, warnings) = Metadata::new_from_vec_with_options(
+		&encoded_data, 
+		FileExtension::TIFF, 
+		&DecodeOptions { strict: false }
+	).unwrap();
+
+	assert!(metadata.get_tag(&ExifTag::ISO(vec![0])).next().is_none());
+	assert!(metadata.get_tag(&ExifTag::Model(String::new())).next().is_some());
+	assert!(metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	assert_eq!(warnings.len(), 1);
+	assert_eq!(warnings[0].tag,   None);
+	assert_eq!(warnings[0].group, ExifTagGroup::EXIF);
+}
+
+
+/// Mirrors the `round_trip` fuzz target: Writing back the metadata that has
+/// been read, writing new metadata and clearing the metadata must not panic
+/// and whatever got written successfully has to be readable again.
+fn
+round_trip
+(
+	file_buffer: &Vec<u8>,
+	file_type:   FileExtension
+)
+{
+	if let Ok(metadata) = Metadata::new_from_vec(file_buffer, file_type)
+	{
+		let mut written_buffer = file_buffer.clone();
+		if metadata.write_to_vec(&mut written_buffer, file_type).is_ok()
+		{
+			Metadata::new_from_vec(&written_buffer, file_type).unwrap();
+		}
+	}
+
+	let mut new_metadata = Metadata::new();
+	new_metadata.set_tag(ExifTag::ImageDescription("little_exif".to_string()));
+
+	let mut written_buffer = file_buffer.clone();
+	if new_metadata.write_to_vec(&mut written_buffer, file_type).is_ok()
+	{
+		let reread_metadata = Metadata::new_from_vec(&written_buffer, file_type).unwrap();
+		assert!(reread_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+	}
+
+	let mut streamed_buffer = Vec::new();
+	if new_metadata.write_to_stream(&mut std::io::Cursor::new(file_buffer), &mut streamed_buffer, file_type).is_ok()
+	{
+		let reread_metadata = Metadata::new_from_vec(&streamed_buffer, file_type).unwrap();
+		assert!(reread_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+	}
+
+	let mut cleared_buffer = file_buffer.clone();
+	if Metadata::clear_metadata(&mut cleared_buffer, file_type).is_ok()
+	{
+		let _ = Metadata::new_from_vec(&cleared_buffer, file_type);
+	}
+}
+
+/// Inputs that made the fuzz targets in `fuzz/` fail, stored in a folder that
+/// is named after the respective target
+#[test]
+fn
+fuzz_regressions()
+{
+	let targets = [
+		("heif", FileExtension::HEIF),
+		("jpg",  FileExtension::JPEG),
+		("jxl",  FileExtension::JXL),
+		("png",  FileExtension::PNG { as_zTXt_chunk: true }),
+		("tiff", FileExtension::TIFF),
+		("webp", FileExtension::WEBP),
+	];
+
+	let mut failures = Vec::new();
+
+	for (target_suffix, file_type) in targets
+	{
+		for target in ["read", "round_trip"]
+		{
+			let directory = format!("tests/fuzz_regressions/{}_{}", target, target_suffix);
+			let Ok(entries) = std::fs::read_dir(&directory) else { continue; };
+
+			for entry in entries
+			{
+				let path        = entry.unwrap().path();
+				let file_buffer = read(&path).unwrap();
+
+				let result = std::panic::catch_unwind(|| {
+					if target == "read"
+					{
+						let _ = Metadata::new_from_vec(&file_buffer, file_type);
+						let _ = Metadata::new_from_vec_with_options(
+							&file_buffer, 
+							file_type, 
+							&DecodeOptions { strict: false }
+						);
+						let _ = Metadata::new_from_reader(
+							&mut std::io::Cursor::new(&file_buffer), 
+							file_type
+						);
+					}
+					else
+					{
+						round_trip(&file_buffer, file_type);
+					}
+				});
+
+				if result.is_err()
+				{
+					failures.push(path.display().to_string());
+				}
+			}
+		}
+	}
+
+	assert!(failures.is_empty(), "Failing fuzz regressions:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn
+png_with_malformed_xmp_is_an_error()
+-> Result<(), little_exif::Error>
+{
+	// Writing removes EXIF data from the XMP, which can't be done here
+	for file_name in ["unclosed_xmp_element", "non_utf8_xmp_element"]
+	{
+		let mut file_buffer = read(format!("tests/fuzz_regressions/round_trip_png/{}", file_name))?;
+		let result          = Metadata::new().write_to_vec(&mut file_buffer, FileExtension::PNG { as_zTXt_chunk: true });
+		assert!(matches!(result, Err(little_exif::Error::MalformedXmp(_))));
+	}
+
+	Ok(())
+}
+
+
+
+/// Constructs a JPEG in the Multi-Picture Format by appending the secondary
+/// image to read_sample.jpg. Both get listed in an MPF segment that is 
+/// inserted after the EXIF segment, i.e. before the APP13 segment
+fn
+mpf_sample
+(
+	secondary: &[u8]
+)
+-> Vec<u8>
+{
+	return mpf_sample_at(secondary, 298);
+}
+
+/// Same as `mpf_sample`, with the MPF segment inserted at the given position
+fn
+mpf_sample_at
+(
+	secondary:       &[u8],
+	insert_position: usize
+)
+-> Vec<u8>
+{
+	let image           = read("tests/read_sample.jpg").unwrap();
+	let segment_length  = 2 + 4 + 50 + 32;
+	let header_position = insert_position + 4 + 4;
+	let primary_size    = (image.len() + 2 + segment_length) as u32;
+	let offset          = primary_size - header_position as u32;
+
+	let mut segment = vec![0xff, 0xe2];
+	segment.extend((segment_length as u16).to_be_bytes());
+	segment.extend(b"MPF\0MM\0\x2a\0\0\0\x08");
+
+	// MP Index IFD with the MPFVersion, NumberOfImages and MPEntry tags
+	segment.extend([0x00, 0x03]);
+	segment.extend([0xb0, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x04]);
+	segment.extend(b"0100");
+	segment.extend([0xb0, 0x01, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02]);
+	segment.extend([0xb0, 0x02, 0x00, 0x07, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 50]);
+	segment.extend([0x00, 0x00, 0x00, 0x00]);
+
+	// Entries for the primary image and the appended one
+	segment.extend(0x20030000u32.to_be_bytes());
+	segment.extend(primary_size.to_be_bytes());
+	segment.extend([0x00; 8]);
+	segment.extend(0x00010001u32.to_be_bytes());
+	segment.extend((secondary.len() as u32).to_be_bytes());
+	segment.extend(offset.to_be_bytes());
+	segment.extend([0x00; 4]);
+
+	let mut file_buffer = image[..insert_position].to_vec();
+	file_buffer.extend(segment);
+	file_buffer.extend(&image[insert_position..]);
+	file_buffer.extend(secondary);
+	return file_buffer;
+}
+
+#[test]
+fn
+mpf_read_images()
+-> Result<(), little_exif::Error>
+{
+	let image       = read("tests/read_sample.jpg")?;
+	let file_buffer = mpf_sample(&image);
+
+	assert!(read_mpf_images(&image)?.is_empty());
+
+	let images = read_mpf_images(&file_buffer)?;
+	assert_eq!(images.len(), 2);
+	assert_eq!(images[0].position, 0);
+	assert_eq!(images[0].get_type_code(), 0x030000);
+	assert_eq!(images[1].get_type_code(), 0x010001);
+	assert_eq!(images[1].get_data(&file_buffer)?, &image[..]);
+
+	// The appended image has its own EXIF data
+	assert_eq!(
+		images[1].get_metadata(&file_buffer)?.encode()?,
+		Metadata::new_from_vec(&image, FileExtension::JPEG)?.encode()?
+	);
+
+	Ok(())
+}
+
+#[test]
+fn
+mpf_offsets_after_editing()
+-> Result<(), little_exif::Error>
+{
+	let image           = read("tests/read_sample.jpg")?;
+	let mut file_buffer = mpf_sample(&image);
+
+	// Grows the EXIF segment in front of the MPF segment
+	let mut metadata = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("A description that makes the EXIF data grow".repeat(20)));
+	metadata.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(read_mpf_images(&file_buffer)?[1].get_data(&file_buffer)?, &image[..]);
+
+	// Grows and removes the APP13 segment, which is located between the MPF 
+	// segment and the appended image
+	let mut resources = PhotoshopResources::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	resources.set_resource(ImageResource::new(photoshop::THUMBNAIL_RESOURCE_ID, vec![0xab; 100_001]));
+	resources.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(read_mpf_images(&file_buffer)?[1].get_data(&file_buffer)?, &image[..]);
+
+	Metadata::clear_app13_segment(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(read_mpf_images(&file_buffer)?[1].get_data(&file_buffer)?, &image[..]);
+
+	// The primary image only got modified in front of its image data
+	let images = read_mpf_images(&file_buffer)?;
+	assert!(file_buffer[..images[1].position].ends_with(&image[image.len()-100..]));
+
+	Ok(())
+}
+
+#[test]
+fn
+mpf_offsets_after_writing_file()
+-> Result<(), little_exif::Error>
+{
+	let image = read("tests/read_sample.jpg")?;
+	let path  = Path::new("tests/mpf_sample_copy.jpg");
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello MPF!".to_string()));
+
+	// The EXIF segment is located between the MPF segment and the appended
+	// image, so replacing it moves that image
+	for atomic in [true, false]
+	{
+		std::fs::write(path, mpf_sample_at(&image, 2))?;
+
+		let options = WriteOptions { atomic, ..WriteOptions::default() };
+		metadata.write_to_file_with_options(path, &options)?;
+
+		let file_buffer = read(path)?;
+		assert_eq!(read_mpf_images(&file_buffer)?[1].get_data(&file_buffer)?, &image[..], "atomic: {}", atomic);
+	}
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+mpf_offsets_after_writing_stream()
+-> Result<(), little_exif::Error>
+{
+	let image       = read("tests/read_sample.jpg")?;
+	let file_buffer = mpf_sample_at(&image, 2);
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello MPF!".to_string()));
+
+	let mut output = Vec::new();
+	metadata.write_to_stream(&mut std::io::Cursor::new(&file_buffer), &mut output, FileExtension::JPEG)?;
+	assert_eq!(read_mpf_images(&output)?[1].get_data(&output)?, &image[..]);
+
+	// Same result as when the image gets loaded into memory
+	let mut expected = file_buffer.clone();
+	metadata.write_to_vec(&mut expected, FileExtension::JPEG)?;
+	assert_eq!(output, expected);
+
+	Ok(())
+}
+
+
+
+/// Constructs an entry of the `Container:Directory` of a Motion Photo
+fn
+container_item
+(
+	mime:     &str,
+	semantic: &str,
+	length:   usize,
+	padding:  usize
+)
+-> XmpValue
+{
+	let field = |name: &str, value: String| XmpProperty {
+		namespace: namespace::CONTAINER_ITEM.to_string(),
+		name:      name.to_string(),
+		value:     XmpValue::Simple(value),
+	};
+
+	return XmpValue::Struct(vec![XmpProperty {
+		namespace: namespace::CONTAINER.to_string(),
+		name:      "Item".to_string(),
+		value:     XmpValue::Struct(vec![
+			field("Mime",     mime.to_string()),
+			field("Semantic", semantic.to_string()),
+			field("Length",   length.to_string()),
+			field("Padding",  padding.to_string()),
+		]),
+	}]);
+}
+
+/// Constructs a Motion Photo out of read_sample.jpg with the given video
+/// appended after 8 bytes of padding
+fn
+motion_photo_sample
+(
+	video: &[u8]
+)
+-> Result<Vec<u8>, little_exif::Error>
+{
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+
+	let mut xmp = Xmp::new();
+	xmp.set_property(namespace::CONTAINER, "Directory", XmpValue::Array(XmpArrayType::Seq, vec![
+		container_item("image/jpeg", "Primary",     0,           8),
+		container_item("video/mp4",  "MotionPhoto", video.len(), 0),
+	]));
+	xmp.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+
+	file_buffer.extend([0x00; 8]);
+	file_buffer.extend(video);
+	return Ok(file_buffer);
+}
+
+#[test]
+fn
+jpg_trailer_motion_photo()
+-> Result<(), little_exif::Error>
+{
+	let video           = [b"\0\0\0\x18ftypmp42".to_vec(), vec![0xab; 5_000]].concat();
+	let mut file_buffer = motion_photo_sample(&video)?;
+	let trailer         = [vec![0x00; 8], video.clone()].concat();
+
+	assert!(jpeg::read_trailer(&read("tests/read_sample.jpg")?)?.is_empty());
+	assert_eq!(jpeg::read_trailer(&file_buffer)?, &trailer[..]);
+
+	let items = jpeg::read_container_directory(&file_buffer)?;
+	assert_eq!(items.len(), 2);
+	assert_eq!(items[0].position, 0);
+	assert_eq!(items[0].position + items[0].length + 8, items[1].position);
+	assert_eq!(items[1].mime, "video/mp4");
+	assert_eq!(jpeg::read_motion_photo_video(&file_buffer)?, Some(&video[..]));
+
+	// Both growing and removing the EXIF data keep the trailer intact
+	let mut metadata = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("Motion Photo".repeat(100)));
+	metadata.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(jpeg::read_trailer(&file_buffer)?, &trailer[..]);
+	assert_eq!(jpeg::read_motion_photo_video(&file_buffer)?, Some(&video[..]));
+
+	Metadata::clear_metadata(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(jpeg::read_trailer(&file_buffer)?, &trailer[..]);
+	assert_eq!(jpeg::read_motion_photo_video(&file_buffer)?, Some(&video[..]));
+
+	// Older Motion Photos only give the offset of the video from the end
+	let mut micro_video = read("tests/read_sample.jpg")?;
+	let mut xmp = Xmp::new();
+	xmp.set_property(namespace::GCAMERA, "MicroVideo",       XmpValue::Simple("1".to_string()));
+	xmp.set_property(namespace::GCAMERA, "MicroVideoOffset", XmpValue::Simple(video.len().to_string()));
+	xmp.write_to_vec(&mut micro_video, FileExtension::JPEG)?;
+	micro_video.extend(&video);
+	assert!(jpeg::read_container_directory(&micro_video)?.is_empty());
+	assert_eq!(jpeg::read_motion_photo_video(&micro_video)?, Some(&video[..]));
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_trailer_huge_item_length()
+-> Result<(), little_exif::Error>
+{
+	let video = vec![0xab; 100];
+
+	// Lengths and paddings whose sum with the end of the image overflows
+	let huge_values = [
+		(usize::MAX,         8),
+		(usize::MAX / 2 + 1, usize::MAX / 2 + 1),
+	];
+
+	for (length, padding) in huge_values
+	{
+		let mut file_buffer = read("tests/read_sample.jpg")?;
+
+		let mut xmp = Xmp::new();
+		xmp.set_property(namespace::CONTAINER, "Directory", XmpValue::Array(XmpArrayType::Seq, vec![
+			container_item("image/jpeg", "Primary",     0,      padding),
+			container_item("video/mp4",  "MotionPhoto", length, 0),
+		]));
+		xmp.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+		file_buffer.extend(&video);
+
+		assert!(matches!(
+			jpeg::read_container_directory(&file_buffer),
+			Err(little_exif::Error::ContainerError(_))
+		));
+		assert!(jpeg::read_motion_photo_video(&file_buffer).is_err());
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_trailer_survives_file_writes()
+-> Result<(), little_exif::Error>
+{
+	// Samsung images end with a trailer that is terminated by "SEFT"
+	let trailer = [vec![0x5a; 300], b"SEFH".to_vec(), vec![0x00; 8], b"SEFT".to_vec()].concat();
+	let path    = Path::new("tests/read_sample_trailer_copy.jpg");
+
+	let mut file_buffer = read("tests/read_sample.jpg")?;
+	file_buffer.extend(&trailer);
+	std::fs::write(path, &file_buffer)?;
+
+	for atomic in [true, false]
+	{
+		let options = WriteOptions { atomic, ..WriteOptions::default() };
+
+		let mut metadata = Metadata::new();
+		metadata.set_tag(ExifTag::ImageDescription("Trailer".repeat(100)));
+		metadata.write_to_file_with_options(path, &options)?;
+		assert_eq!(jpeg::file_read_trailer(path)?, trailer);
+
+		// The EXIF data shrinks when written again without the description
+		Metadata::new().write_to_file_with_options(path, &options)?;
+		assert_eq!(jpeg::file_read_trailer(path)?, trailer);
+
+		Metadata::file_clear_metadata_with_options(path, &options)?;
+		assert_eq!(jpeg::file_read_trailer(path)?, trailer);
+	}
+
+	remove_file(path)?;
+	Ok(())
+}
+
+
+
+/// Constructs an Ultra HDR image with no_exif.jpeg serving as gain map. The
+/// gain map gets listed in the MPF segment and, optionally, in the
+/// `Container:Directory` of the primary image
+fn
+ultra_hdr_sample
+(
+	with_container_directory: bool
+)
+-> Result<(Vec<u8>, Vec<u8>), little_exif::Error>
+{
+	let simple = |text: &str| XmpValue::Simple(text.to_string());
+
+	let mut gain_map     = read("tests/no_exif.jpeg")?;
+	let mut gain_map_xmp = Xmp::new();
+	gain_map_xmp.set_property(namespace::HDRGM, "Version",        simple("1.0"));
+	gain_map_xmp.set_property(namespace::HDRGM, "GainMapMax",     simple("2.5"));
+	gain_map_xmp.set_property(namespace::HDRGM, "HDRCapacityMax", simple("2.5"));
+	gain_map_xmp.set_property(namespace::HDRGM, "Gamma", XmpValue::Array(
+		XmpArrayType::Seq, 
+		vec![simple("1"), simple("1.1"), simple("1.2")]
+	));
+	gain_map_xmp.write_to_vec(&mut gain_map, FileExtension::JPEG)?;
+
+	let mut file_buffer = mpf_sample(&gain_map);
+
+	let mut xmp = Xmp::new();
+	xmp.set_property(namespace::HDRGM, "Version", simple("1.0"));
+	if with_container_directory
+	{
+		xmp.set_property(namespace::CONTAINER, "Directory", XmpValue::Array(XmpArrayType::Seq, vec![
+			container_item("image/jpeg", "Primary", 0,              0),
+			container_item("image/jpeg", "GainMap", gain_map.len(), 0),
+		]));
+	}
+	xmp.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+
+	return Ok((file_buffer, gain_map));
+}
+
+#[test]
+fn
+ultra_hdr_read_gain_map_metadata()
+-> Result<(), little_exif::Error>
+{
+	assert!(!jpeg::is_ultra_hdr(&read("tests/read_sample.jpg")?)?);
+	assert_eq!(jpeg::read_gain_map_metadata(&read("tests/read_sample.jpg")?)?, None);
+
+	for with_container_directory in [true, false]
+	{
+		let (file_buffer, gain_map) = ultra_hdr_sample(with_container_directory)?;
+		assert!(jpeg::is_ultra_hdr(&file_buffer)?);
+		assert_eq!(jpeg::read_gain_map(&file_buffer)?, Some(&gain_map[..]));
+
+		let gain_map_metadata = jpeg::read_gain_map_metadata(&file_buffer)?.unwrap();
+		assert_eq!(gain_map_metadata.version,          "1.0");
+		assert_eq!(gain_map_metadata.gain_map_min,     [0.0; 3]);
+		assert_eq!(gain_map_metadata.gain_map_max,     [2.5; 3]);
+		assert_eq!(gain_map_metadata.gamma,            [1.0, 1.1, 1.2]);
+		assert_eq!(gain_map_metadata.offset_sdr,       [1.0 / 64.0; 3]);
+		assert_eq!(gain_map_metadata.hdr_capacity_max, 2.5);
+		assert!(!gain_map_metadata.base_rendition_is_hdr);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+ultra_hdr_write_gain_map_metadata()
+-> Result<(), little_exif::Error>
+{
+	let (mut file_buffer, gain_map) = ultra_hdr_sample(true)?;
+
+	// Editing the EXIF data of the primary image keeps the gain map intact
+	let mut metadata = Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("Ultra HDR".repeat(100)));
+	metadata.write_to_vec(&mut file_buffer, FileExtension::JPEG)?;
+	assert_eq!(jpeg::read_gain_map(&file_buffer)?, Some(&gain_map[..]));
+
+	// The gain map grows as its XMP data gets rewritten
+	let mut gain_map_metadata = jpeg::read_gain_map_metadata(&file_buffer)?.unwrap();
+	gain_map_metadata.offset_hdr            = [0.01, 0.02, 0.03];
+	gain_map_metadata.hdr_capacity_max      = 3.0;
+	gain_map_metadata.base_rendition_is_hdr = true;
+	jpeg::write_gain_map_metadata(&mut file_buffer, &gain_map_metadata)?;
+
+	assert_eq!(jpeg::read_gain_map_metadata(&file_buffer)?, Some(gain_map_metadata));
+	assert_eq!(Metadata::new_from_vec(&file_buffer, FileExtension::JPEG)?.encode()?, metadata.encode()?);
+
+	// Both the container directory and the MPF segment refer to the new gain map
+	let new_gain_map = jpeg::read_gain_map(&file_buffer)?.unwrap();
+	let images       = read_mpf_images(&file_buffer)?;
+	let items        = jpeg::read_container_directory(&file_buffer)?;
+	assert_ne!(new_gain_map.len(), gain_map.len());
+	assert_eq!(images[1].get_data(&file_buffer)?, new_gain_map);
+	assert_eq!(items[1].get_data(&file_buffer)?, new_gain_map);
+	assert!(jpeg::read_trailer(new_gain_map)?.is_empty());
+
+	Ok(())
+}
+
+/// Encodes a JUMBF superbox with the given label and child boxes
+fn
+jumbf_superbox
+(
+	label:    &str,
+	children: &[u8]
+)
+-> Vec<u8>
+{
+	let mut description = vec![0u8; 16];
+	description.push(0x03);
+	description.extend(label.as_bytes());
+	description.push(0x00);
+
+	let mut jumbf = ((16 + description.len() + children.len()) as u32).to_be_bytes().to_vec();
+	jumbf.extend(b"jumb");
+	jumbf.extend(((8 + description.len()) as u32).to_be_bytes());
+	jumbf.extend(b"jumd");
+	jumbf.extend(description);
+	jumbf.extend(children);
+	return jumbf;
+}
+
+fn
+c2pa_manifest_store()
+-> Vec<u8>
+{
+	let signature = jumbf_superbox("c2pa.signature", &[0xc2; 100]);
+	let manifest  = jumbf_superbox("urn:uuid:8d6b3dba-4ac4-4c5a-a3cb-3b1bd4aa3e7e", &signature);
+	return jumbf_superbox("c2pa", &manifest);
+}
+
+/// Encodes APP11 segments holding the given JUMBF box, split into packets
+/// of the given size
+fn
+app11_segments
+(
+	jumbf:        &[u8],
+	box_instance: u16,
+	packet_size:  usize
+)
+-> Vec<u8>
+{
+	let mut segments = Vec::new();
+	for (index, data) in jumbf.chunks(packet_size).enumerate()
+	{
+		let mut payload = b"JP".to_vec();
+		payload.extend(box_instance.to_be_bytes());
+		payload.extend((index as u32 + 1).to_be_bytes());
+		if index > 0
+		{
+			payload.extend(&jumbf[0..8]);
+		}
+		payload.extend(data);
+
+		segments.extend([0xff, 0xeb]);
+		segments.extend((payload.len() as u16 + 2).to_be_bytes());
+		segments.extend(payload);
+	}
+	return segments;
+}
+
+/// Returns pairs of images without and with the given manifest store for the
+/// various formats
+fn
+c2pa_samples
+(
+	jumbf: &[u8]
+)
+-> Result<Vec<(Vec<u8>, Vec<u8>, FileExtension)>, little_exif::Error>
+{
+	let mut samples  = Vec::new();
+	let     metadata = Metadata::new_from_path(Path::new("tests/read_sample.jpg"))?;
+
+	// JPEG: The manifest store split into two packets, next to another 
+	// JUMBF box that has nothing to do with C2PA and is kept
+	let original       = read("tests/read_sample.jpg")?;
+	let other_segments = app11_segments(&jumbf_superbox("other", &[0x00; 10]), 1, 1000);
+	let c2pa_segments  = app11_segments(jumbf, 2, jumbf.len() / 2 + 1);
+	samples.push((
+		[&original[..2], &other_segments, &original[2..]].concat(),
+		[&original[..2], &other_segments, &c2pa_segments, &original[2..]].concat(),
+		FileExtension::JPEG
+	));
+
+	// PNG: caBX chunk after the IHDR chunk
+	let mut original = read("tests/sample2.png")?;
+	metadata.write_to_vec(&mut original, FileExtension::PNG { as_zTXt_chunk: false })?;
+	let mut cabx_chunk = (jumbf.len() as u32).to_be_bytes().to_vec();
+	cabx_chunk.extend(b"caBX");
+	cabx_chunk.extend(jumbf);
+	cabx_chunk.extend(crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&cabx_chunk[4..]).to_be_bytes());
+	let with_manifest = [&original[..33], &cabx_chunk, &original[33..]].concat();
+	samples.push((original, with_manifest, FileExtension::PNG { as_zTXt_chunk: false }));
+
+	// WebP: C2PA chunk at the end, which requires updating the RIFF size
+	let mut original = read("tests/sample2_extended.webp")?;
+	metadata.write_to_vec(&mut original, FileExtension::WEBP)?;
+	let mut with_manifest = original.clone();
+	with_manifest.extend(b"C2PA");
+	with_manifest.extend((jumbf.len() as u32).to_le_bytes());
+	with_manifest.extend(jumbf);
+	if jumbf.len() % 2 == 1
+	{
+		with_manifest.push(0x00);
+	}
+	let riff_size = (with_manifest.len() - 8) as u32;
+	with_manifest[4..8].copy_from_slice(&riff_size.to_le_bytes());
+	samples.push((original, with_manifest, FileExtension::WEBP));
+
+	// JXL: jumb box at the end
+	let original = read("tests/with_exif.jxl")?;
+	let with_manifest = [&original[..], jumbf].concat();
+	samples.push((original, with_manifest, FileExtension::JXL));
+
+	// HEIF: uuid box in front of the mdat box, so the extent offset of the 
+	// only item (stored in the iloc box at bytes 126..130) needs to be moved
+	let original = read("tests/read_sample.heic")?;
+	let mut uuid_box = ((24 + 4 + 9 + 8 + jumbf.len()) as u32).to_be_bytes().to_vec();
+	uuid_box.extend(b"uuid");
+	uuid_box.extend([
+		0xd8, 0xfe, 0xc3, 0xd6, 0x1b, 0x0e, 0x48, 0x3c,
+		0x92, 0x97, 0x58, 0x28, 0x87, 0x7e, 0xc4, 0x81
+	]);
+	uuid_box.extend([0x00; 4]);
+	uuid_box.extend(b"manifest\0");
+	uuid_box.extend([0x00; 8]);
+	uuid_box.extend(jumbf);
+	let mut with_manifest = [&original[..134], &uuid_box, &original[134..]].concat();
+	let extent_offset = u32::from_be_bytes([original[126], original[127], original[128], original[129]]);
+	with_manifest[126..130].copy_from_slice(&(extent_offset + uuid_box.len() as u32).to_be_bytes());
+	samples.push((original, with_manifest, FileExtension::HEIF));
+
+	return Ok(samples);
+}
+
+#[test]
+fn
+c2pa_read_and_clear_manifest()
+-> Result<(), little_exif::Error>
+{
+	let jumbf = c2pa_manifest_store();
+	assert!(c2pa::is_signed(&jumbf));
+
+	for (without_manifest, mut with_manifest, file_type) in c2pa_samples(&jumbf)?
+	{
+		assert!(!c2pa::has_manifest(&without_manifest, file_type)?);
+		assert!(matches!(
+			c2pa::read_jumbf(&without_manifest, file_type),
+			Err(little_exif::Error::NoC2paManifest)
+		));
+
+		assert!(c2pa::has_manifest(&with_manifest, file_type)?, "{:?}", file_type);
+		assert_eq!(c2pa::read_jumbf(&with_manifest, file_type)?, jumbf, "{:?}", file_type);
+
+		// The metadata of the image can still be read
+		assert_eq!(
+			Metadata::new_from_vec(&with_manifest, file_type)?.encode()?,
+			Metadata::new_from_vec(&without_manifest, file_type)?.encode()?
+		);
+
+		c2pa::clear_manifest(&mut with_manifest, file_type)?;
+		assert_eq!(with_manifest, without_manifest, "{:?}", file_type);
+	}
+
+	// TIFF: Tag 0xcd41 of IFD0
+	let mut metadata = Metadata::new_from_path(Path::new("tests/read_sample.tif"))?;
+	metadata.set_tag(ExifTag::UnknownUNDEF(jumbf.clone(), 0xcd41, ExifTagGroup::GENERIC));
+	let mut tiff_buffer = read("tests/read_sample.tif")?;
+	metadata.write_to_vec(&mut tiff_buffer, FileExtension::TIFF)?;
+	assert_eq!(c2pa::read_jumbf(&tiff_buffer, FileExtension::TIFF)?, jumbf);
+
+	c2pa::clear_manifest(&mut tiff_buffer, FileExtension::TIFF)?;
+	assert!(!c2pa::has_manifest(&tiff_buffer, FileExtension::TIFF)?);
+
+	Ok(())
+}
+
+#[test]
+fn
+c2pa_write_policy()
+-> Result<(), little_exif::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello C2PA!".to_string()));
+
+	for (_, with_manifest, file_type) in c2pa_samples(&c2pa_manifest_store())?
+	{
+		// Writing is refused and the image stays untouched
+		let mut file_buffer = with_manifest.clone();
+		let options         = WriteOptions { c2pa: C2paPolicy::Fail, ..WriteOptions::default() };
+		assert!(matches!(
+			metadata.write_to_vec_with_options(&mut file_buffer, file_type, &options),
+			Err(little_exif::Error::C2paManifestPresent)
+		));
+		assert_eq!(file_buffer, with_manifest);
+
+		// By default, the manifest is kept
+		metadata.write_to_vec(&mut file_buffer, file_type)?;
+		assert!(c2pa::has_manifest(&file_buffer, file_type)?, "{:?}", file_type);
+
+		let mut file_buffer = with_manifest.clone();
+		let options         = WriteOptions { c2pa: C2paPolicy::Strip, ..WriteOptions::default() };
+		metadata.write_to_vec_with_options(&mut file_buffer, file_type, &options)?;
+		assert!(!c2pa::has_manifest(&file_buffer, file_type)?, "{:?}", file_type);
+		assert_eq!(
+			Metadata::new_from_vec(&file_buffer, file_type)?.encode()?, 
+			metadata.encode()?
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+c2pa_write_policy_for_streams()
+-> Result<(), little_exif::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello C2PA!".to_string()));
+
+	for (_, with_manifest, file_type) in c2pa_samples(&c2pa_manifest_store())?
+	{
+		let mut output = Vec::new();
+		let options    = WriteOptions { c2pa: C2paPolicy::Fail, ..WriteOptions::default() };
+		let result     = metadata.write_to_stream_with_options(
+			&mut std::io::Cursor::new(&with_manifest), &mut output, file_type, &options
+		);
+		assert!(matches!(result, Err(little_exif::Error::C2paManifestPresent)), "{:?}", file_type);
+		assert!(output.is_empty());
+
+		let mut output = Vec::new();
+		metadata.write_to_stream(&mut std::io::Cursor::new(&with_manifest), &mut output, file_type)?;
+		assert!(c2pa::has_manifest(&output, file_type)?, "{:?}", file_type);
+
+		let mut output = Vec::new();
+		let options    = WriteOptions { c2pa: C2paPolicy::Strip, ..WriteOptions::default() };
+		metadata.write_to_stream_with_options(
+			&mut std::io::Cursor::new(&with_manifest), &mut output, file_type, &options
+		)?;
+		assert!(!c2pa::has_manifest(&output, file_type)?, "{:?}", file_type);
+		assert_eq!(
+			Metadata::new_from_vec(&output, file_type)?.encode()?, 
+			metadata.encode()?
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+c2pa_write_policy_for_files()
+-> Result<(), little_exif::Error>
+{
+	let (_, with_manifest, _) = c2pa_samples(&c2pa_manifest_store())?.remove(0);
+	let path                  = Path::new("tests/read_sample_c2pa_copy.jpg");
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello C2PA!".to_string()));
+
+	for atomic in [true, false]
+	{
+		std::fs::write(path, &with_manifest)?;
+
+		let options = WriteOptions { atomic, c2pa: C2paPolicy::Fail, ..WriteOptions::default() };
+		assert!(metadata.write_to_file_with_options(path, &options).is_err());
+		assert_eq!(read(path)?, with_manifest);
+
+		let options = WriteOptions { atomic, c2pa: C2paPolicy::Preserve, ..WriteOptions::default() };
+		metadata.write_to_file_with_options(path, &options)?;
+		assert!(c2pa::file_has_manifest(path)?);
+
+		let options = WriteOptions { atomic, c2pa: C2paPolicy::Strip, ..WriteOptions::default() };
+		metadata.write_to_file_with_options(path, &options)?;
+		assert!(!c2pa::file_has_manifest(path)?);
+		assert_eq!(Metadata::new_from_path(path)?.encode()?, metadata.encode()?);
+	}
+
+	remove_file(path)?;
+
+	Ok(())
+}
+
+/// Performs the given write on the images of `c2pa_samples` whose file type
+/// is supported by it, once with each C2PA policy, and checks the outcome.
+/// The result of the write is checked by the given function.
+fn
+check_c2pa_write_policy
+<W, S, C>
+(
+	is_supported: S,
+	write:        W,
+	check:        C
+)
+-> Result<(), little_exif::Error>
+where
+	W: Fn(&mut Vec<u8>, FileExtension, &WriteOptions) -> Result<(), little_exif::Error>,
+	S: Fn(FileExtension) -> bool,
+	C: Fn(&[u8], FileExtension) -> Result<(), little_exif::Error>,
+{
+	for (_, with_manifest, file_type) in c2pa_samples(&c2pa_manifest_store())?
+	{
+		if !is_supported(file_type)
+		{
+			continue;
+		}
+
+		// Writing is refused and the image stays untouched
+		let mut file_buffer = with_manifest.clone();
+		let options         = WriteOptions { c2pa: C2paPolicy::Fail, ..WriteOptions::default() };
+		assert!(
+			matches!(write(&mut file_buffer, file_type, &options), Err(little_exif::Error::C2paManifestPresent)),
+			"{:?}", file_type
+		);
+		assert_eq!(file_buffer, with_manifest);
+
+		// By default, the manifest is kept
+		write(&mut file_buffer, file_type, &WriteOptions::default())?;
+		assert!(c2pa::has_manifest(&file_buffer, file_type)?, "{:?}", file_type);
+		check(&file_buffer, file_type)?;
+
+		let mut file_buffer = with_manifest.clone();
+		let options         = WriteOptions { c2pa: C2paPolicy::Strip, ..WriteOptions::default() };
+		write(&mut file_buffer, file_type, &options)?;
+		assert!(!c2pa::has_manifest(&file_buffer, file_type)?, "{:?}", file_type);
+		check(&file_buffer, file_type)?;
+	}
+
+	Ok(())
+}
+
+/// Same as `check_c2pa_write_policy`, but for a write to a JPEG file, which
+/// is performed both atomically and in place
+fn
+check_c2pa_write_policy_for_file
+<W: Fn(&Path, &WriteOptions) -> Result<(), little_exif::Error>>
+(
+	file_name: &str,
+	write:     W
+)
+-> Result<(), little_exif::Error>
+{
+	let (_, with_manifest, _) = c2pa_samples(&c2pa_manifest_store())?.remove(0);
+	let path                  = Path::new("tests").join(file_name);
+
+	for atomic in [true, false]
+	{
+		std::fs::write(&path, &with_manifest)?;
+
+		let options = WriteOptions { atomic, c2pa: C2paPolicy::Fail, ..WriteOptions::default() };
+		assert!(matches!(write(&path, &options), Err(little_exif::Error::C2paManifestPresent)));
+		assert_eq!(read(&path)?, with_manifest);
+
+		let options = WriteOptions { atomic, ..WriteOptions::default() };
+		write(&path, &options)?;
+		assert!(c2pa::file_has_manifest(&path)?);
+
+		let options = WriteOptions { atomic, c2pa: C2paPolicy::Strip, ..WriteOptions::default() };
+		write(&path, &options)?;
+		assert!(!c2pa::file_has_manifest(&path)?);
+	}
+
+	remove_file(&path)?;
+
+	Ok(())
+}
+
+#[test]
+fn
+c2pa_write_policy_for_clearing_metadata()
+-> Result<(), little_exif::Error>
+{
+	check_c2pa_write_policy(
+		|_| true,
+		Metadata::clear_metadata_with_options,
+		|file_buffer, file_type| {
+			// HEIF images keep their orientation and resolution tags
+			let cleared = match Metadata::new_from_vec(file_buffer, file_type)
+			{
+				Ok(metadata) => metadata.into_iter().all(|tag| [0x0112, 0x011a, 0x011b, 0x0128].contains(&tag.as_u16())),
+				Err(little_exif::Error::NoExifData) => true,
+				Err(error) => return Err(error),
+			};
+			assert!(cleared, "{:?}", file_type);
+			Ok(())
+		}
+	)?;
+
+	check_c2pa_write_policy_for_file(
+		"read_sample_c2pa_clear_copy.jpg",
+		|path, options| {
+			Metadata::file_clear_metadata_with_options(path, options)?;
+			assert!(matches!(Metadata::new_from_path(path), Err(little_exif::Error::NoExifData)));
+			Ok(())
+		}
+	)
+}
+
+#[test]
+fn
+c2pa_write_policy_for_xmp()
+-> Result<(), little_exif::Error>
+{
+	let mut xmp = Xmp::new();
+	xmp.set_property(namespace::XMP, "Rating", XmpValue::Simple("3".to_string()));
+
+	// Writing XMP to a HEIF image requires an existing XMP item
+	check_c2pa_write_policy(
+		|file_type| file_type != FileExtension::HEIF,
+		|file_buffer, file_type, options| xmp.write_to_vec_with_options(file_buffer, file_type, options),
+		|file_buffer, file_type| {
+			assert_eq!(Xmp::new_from_vec(file_buffer, file_type)?.get_properties(), xmp.get_properties(), "{:?}", file_type);
+			Ok(())
+		}
+	)?;
+
+	check_c2pa_write_policy_for_file(
+		"read_sample_c2pa_xmp_copy.jpg",
+		|path, options| xmp.write_to_file_with_options(path, options)
+	)
+}
+
+#[test]
+fn
+c2pa_write_policy_for_iptc_and_photoshop()
+-> Result<(), little_exif::Error>
+{
+	let mut iptc = Iptc::new();
+	iptc.set_string(IptcTag::CaptionAbstract, "Hello C2PA!");
+
+	check_c2pa_write_policy(
+		|file_type| file_type == FileExtension::JPEG,
+		|file_buffer, file_type, options| iptc.write_to_vec_with_options(file_buffer, file_type, options),
+		|file_buffer, file_type| {
+			assert_eq!(
+				Iptc::new_from_vec(file_buffer, file_type)?.get_string(IptcTag::CaptionAbstract),
+				Some("Hello C2PA!".to_string())
+			);
+			Ok(())
+		}
+	)?;
+
+	check_c2pa_write_policy_for_file(
+		"read_sample_c2pa_iptc_copy.jpg",
+		|path, options| iptc.write_to_file_with_options(path, options)
+	)?;
+
+	let mut resources = PhotoshopResources::new();
+	resources.set_resource(ImageResource::new(photoshop::THUMBNAIL_RESOURCE_ID, vec![0xab; 100]));
+
+	check_c2pa_write_policy(
+		|file_type| file_type == FileExtension::JPEG,
+		|file_buffer, file_type, options| resources.write_to_vec_with_options(file_buffer, file_type, options),
+		|file_buffer, file_type| {
+			assert_eq!(PhotoshopResources::new_from_vec(file_buffer, file_type)?, resources);
+			Ok(())
+		}
+	)?;
+
+	check_c2pa_write_policy_for_file(
+		"read_sample_c2pa_photoshop_copy.jpg",
+		|path, options| resources.write_to_file_with_options(path, options)
+	)
+}
+
+#[test]
+fn
+c2pa_write_policy_for_icc_profile()
+-> Result<(), little_exif::Error>
+{
+	let icc_profile = fake_icc_profile(1_000);
+
+	// The HEIF sample lacks the primary item the profile would belong to
+	check_c2pa_write_policy(
+		|file_type| file_type != FileExtension::JXL && file_type != FileExtension::HEIF,
+		|file_buffer, file_type, options| icc::write_icc_profile_with_options(file_buffer, &icc_profile, file_type, options),
+		|file_buffer, file_type| {
+			assert_eq!(icc::read_icc_profile(file_buffer, file_type)?, icc_profile, "{:?}", file_type);
+			Ok(())
+		}
+	)?;
+
+	check_c2pa_write_policy_for_file(
+		"read_sample_c2pa_icc_copy.jpg",
+		|path, options| icc::file_write_icc_profile_with_options(path, &icc_profile, options)
+	)
+}
+
+/// Encodes metadata with a MakerNote whose data starts with the given bytes
+/// and returns the TIFF data together with the offset of the MakerNote. The
+/// offset is passed to `maker_note` so that it can refer to its own data.
+fn
+tiff_with_maker_note
+(
+	maker_note: impl Fn(u32) -> Vec<u8>
+)
+-> Result<(Vec<u8>, u32), little_exif::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::Make("Canon".to_string()));
+	metadata.set_tag(ExifTag::ISO(vec![100]));
+	metadata.set_tag(ExifTag::MakerNote(maker_note(0)));
+
+	// The layout does not depend on the content of the MakerNote
+	let offset = find_maker_note(&metadata.encode()?, &maker_note(0));
+	metadata.set_tag(ExifTag::MakerNote(maker_note(offset)));
+
+	return Ok((metadata.encode()?, offset));
+}
+
+fn
+find_maker_note
+(
+	tiff:       &[u8],
+	maker_note: &[u8]
+)
+-> u32
+{
+	return tiff.windows(maker_note.len())
+		.position(|window| window == maker_note)
+		.expect("MakerNote not found") as u32;
+}
+
+/// Canon style maker note: An IFD with one entry whose data is located right
+/// after the IFD, addressed relative to the start of the TIFF data
+fn
+canon_maker_note
+(
+	offset: u32
+)
+-> Vec<u8>
+{
+	let mut maker_note = vec![0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x08, 0x00, 0x00, 0x00];
+	maker_note.extend((offset + 18).to_le_bytes());
+	maker_note.extend([0x00; 4]);
+	maker_note.extend(b"LensData");
+	return maker_note;
+}
+
+#[test]
+fn
+maker_note_offsets_stay_valid()
+-> Result<(), little_exif::Error>
+{
+	let (tiff, original_offset) = tiff_with_maker_note(canon_maker_note)?;
+
+	// More data in front of the MakerNote: Its offsets have to be adjusted
+	let mut metadata = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?;
+	metadata.set_tag(ExifTag::ImageDescription("Moves the MakerNote backwards".to_string()));
+	let encoded = metadata.encode()?;
+
+	let new_offset = find_maker_note(&encoded, b"LensData") - 18;
+	assert!(new_offset > original_offset);
+	assert_eq!(&encoded[new_offset as usize..][..18], &canon_maker_note(new_offset)[..18]);
+	assert!(metadata.get_tag(&ExifTag::OffsetSchema(vec![])).next().is_none());
+
+	// Less data in front of the MakerNote: It is kept at its original offset
+	let mut metadata = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?;
+	metadata.remove_tag(ExifTag::Make(String::new()));
+	let encoded = metadata.encode()?;
+	assert_eq!(find_maker_note(&encoded, &canon_maker_note(original_offset)), original_offset);
+
+	Ok(())
+}
+
+#[test]
+fn
+maker_note_offset_schema()
+-> Result<(), little_exif::Error>
+{
+	let unknown_maker_note = |offset: u32| [b"Vendor\0\0".to_vec(), offset.to_le_bytes().to_vec()].concat();
+	let (tiff, original_offset) = tiff_with_maker_note(unknown_maker_note)?;
+
+	// Unknown layout, so the movement gets recorded in OffsetSchema
+	let mut metadata = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?;
+	metadata.set_tag(ExifTag::ImageDescription("Moves the MakerNote backwards".to_string()));
+	let encoded    = metadata.encode()?;
+	let new_offset = find_maker_note(&encoded, &unknown_maker_note(original_offset));
+
+	let moved = Metadata::new_from_vec(&encoded, FileExtension::TIFF)?;
+	assert_eq!(
+		moved.get_tag(&ExifTag::OffsetSchema(vec![])).next(),
+		Some(&ExifTag::OffsetSchema(vec![(new_offset - original_offset) as i32]))
+	);
+
+	// OffsetSchema keeps referring to the original offset on further edits
+	let mut metadata = moved.clone();
+	metadata.remove_tag(ExifTag::ImageDescription(String::new()));
+	let encoded    = metadata.encode()?;
+	let new_offset = find_maker_note(&encoded, &unknown_maker_note(original_offset));
+	assert_eq!(
+		Metadata::new_from_vec(&encoded, FileExtension::TIFF)?.get_tag(&ExifTag::OffsetSchema(vec![])).next(),
+		Some(&ExifTag::OffsetSchema(vec![(new_offset - original_offset) as i32]))
+	);
+
+	Ok(())
+}
+
+#[test]
+fn
+maker_note_reencode_without_offset_schema()
+-> Result<(), little_exif::Error>
+{
+	let unknown_maker_note = |offset: u32| [b"Vendor\0\0".to_vec(), offset.to_le_bytes().to_vec()].concat();
+	let (tiff, _) = tiff_with_maker_note(unknown_maker_note)?;
+
+	// Without any changes, the MakerNote stays where it is and there is no
+	// need for OffsetSchema
+	let encoded = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?.encode()?;
+	assert_eq!(encoded, tiff);
+	assert!(Metadata::new_from_vec(&encoded, FileExtension::TIFF)?.get_tag(&ExifTag::OffsetSchema(vec![])).next().is_none());
+
+	Ok(())
+}
+
+#[test]
+fn
+maker_note_far_offset()
+-> Result<(), little_exif::Error>
+{
+	let unknown_maker_note = |offset: u32| [b"Vendor\0\0".to_vec(), offset.to_le_bytes().to_vec()].concat();
+	let maker_notes: [&dyn Fn(u32) -> Vec<u8>; 2] = [&canon_maker_note, &unknown_maker_note];
+
+	for maker_note in maker_notes
+	{
+		// A large tag in front of the MakerNote that gets removed afterwards
+		let mut metadata = Metadata::new();
+		metadata.set_tag(ExifTag::ImageDescription("x".repeat(100_000)));
+		metadata.set_tag(ExifTag::MakerNote(maker_note(0)));
+		let original_offset = find_maker_note(&metadata.encode()?, &maker_note(0));
+		metadata.set_tag(ExifTag::MakerNote(maker_note(original_offset)));
+
+		let mut metadata = Metadata::new_from_vec(&metadata.encode()?, FileExtension::TIFF)?;
+		metadata.remove_tag(ExifTag::ImageDescription(String::new()));
+		let encoded = metadata.encode()?;
+
+		// The MakerNote does not get padded back to its original offset
+		assert!(encoded.len() < 1000);
+
+		// Instead, its offsets get adjusted or the difference is recorded
+		let moved = Metadata::new_from_vec(&encoded, FileExtension::TIFF)?;
+		if let Some(ExifTag::OffsetSchema(offset_schema)) = moved.get_tag(&ExifTag::OffsetSchema(vec![])).next()
+		{
+			let new_offset = find_maker_note(&encoded, &maker_note(original_offset));
+			assert_eq!(offset_schema, &vec![new_offset as i32 - original_offset as i32]);
+		}
+		else
+		{
+			let new_offset = find_maker_note(&encoded, b"LensData") - 18;
+			assert_eq!(&encoded[new_offset as usize..][..18], &maker_note(new_offset)[..18]);
+		}
+	}
+
+	Ok(())
+}
+
+/// Encodes an IFD with the given entries (hex value, format, number of
+/// components, encoded value). Values with more than 4 bytes are stored after
+/// the IFD, with offsets relative to a position `ifd_offset` bytes in front
+/// of the IFD.
+fn
+maker_note_ifd
+(
+	entries:    &[(u16, u16, u32, &[u8])],
+	big_endian: bool,
+	ifd_offset: u32
+)
+-> Vec<u8>
+{
+	let u16_bytes = |value: u16| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+	let u32_bytes = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+
+	let mut ifd        = u16_bytes(entries.len() as u16).to_vec();
+	let mut value_area = Vec::<u8>::new();
+	let value_offset   = ifd_offset + 2 + 12 * entries.len() as u32 + 4;
+
+	for (hex_value, format, count, value) in entries
+	{
+		ifd.extend(u16_bytes(*hex_value));
+		ifd.extend(u16_bytes(*format));
+		ifd.extend(u32_bytes(*count));
+		if value.len() > 4
+		{
+			ifd.extend(u32_bytes(value_offset + value_area.len() as u32));
+			value_area.extend(*value);
+		}
+		else
+		{
+			ifd.extend(*value);
+			ifd.extend(vec![0u8; 4 - value.len()]);
+		}
+	}
+
+	ifd.extend([0u8; 4]);
+	ifd.extend(value_area);
+	return ifd;
+}
+
+fn
+decoded_maker_note
+(
+	maker_note: impl Fn(u32) -> Vec<u8>
+)
+-> Result<Metadata, little_exif::Error>
+{
+	let (tiff, _) = tiff_with_maker_note(maker_note)?;
+	let metadata  = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?;
+
+	// The decoded tags are only for reading, the MakerNote stays as it is
+	assert_eq!(metadata.encode()?, tiff);
+
+	return Ok(metadata);
+}
+
+#[test]
+fn
+maker_note_decoding()
+-> Result<(), little_exif::Error>
+{
+	// Canon: Plain IFD with offsets relative to the TIFF data
+	let metadata = decoded_maker_note(|offset| maker_note_ifd(&[
+		(0x000c, 4, 1,  &1234u32.to_le_bytes()),
+		(0x0095, 2, 13, b"EF50mm f/1.8\0"),
+	], false, offset))?;
+	assert_eq!(
+		metadata.get_tag(&ExifTag::CanonLensModel(String::new())).next(),
+		Some(&ExifTag::CanonLensModel("EF50mm f/1.8".to_string()))
+	);
+	assert_eq!(
+		metadata.get_tag(&ExifTag::CanonSerialNumber(vec![])).next(),
+		Some(&ExifTag::CanonSerialNumber(vec![1234]))
+	);
+
+	// Nikon: Embedded TIFF header, which the offsets are relative to
+	let metadata = decoded_maker_note(|_| [
+		b"Nikon\0\x02\x10\0\0MM\0\x2a\0\0\0\x08".to_vec(),
+		maker_note_ifd(&[
+			(0x001d, 2, 8, b"3001234\0"),
+			(0x0095, 2, 5, b"HISO\0"),
+			(0x00a7, 4, 1, &31337u32.to_be_bytes()),
+		], true, 8)
+	].concat())?;
+	assert_eq!(
+		metadata.get_tag(&ExifTag::NikonShutterCount(vec![])).next(),
+		Some(&ExifTag::NikonShutterCount(vec![31337]))
+	);
+	assert_eq!(
+		metadata.get_tag(&ExifTag::NikonSerialNumber(String::new())).next(),
+		Some(&ExifTag::NikonSerialNumber("3001234".to_string()))
+	);
+
+	// The hex value of Canon's lens model means something else for Nikon
+	assert!(metadata.get_tag_by_hex(0x0095, Some(ExifTagGroup::MAKERNOTES)).next().unwrap().is_unknown());
+
+	// Fujifilm: Little endian, offsets relative to the maker note
+	let metadata = decoded_maker_note(|_| [
+		b"FUJIFILM\x0c\0\0\0".to_vec(),
+		maker_note_ifd(&[
+			(0x0010, 2, 9,  b"FC1234567"),
+			(0x1438, 3, 1,  &42u16.to_le_bytes()),
+		], false, 12)
+	].concat())?;
+	assert_eq!(
+		metadata.get_tag(&ExifTag::FujifilmImageCount(vec![])).next(),
+		Some(&ExifTag::FujifilmImageCount(vec![42]))
+	);
+
+	// Apple: Big endian, offsets relative to the maker note
+	let metadata = decoded_maker_note(|_| [
+		b"Apple iOS\0\0\x01MM".to_vec(),
+		maker_note_ifd(&[
+			(0x0011, 2, 37, b"6C2C8E2E-1A8B-4C3C-9D5F-2B1E0E6F7A11\0"),
+		], true, 14)
+	].concat())?;
+	assert_eq!(
+		metadata.get_tag(&ExifTag::AppleContentIdentifier(String::new())).next(),
+		Some(&ExifTag::AppleContentIdentifier("6C2C8E2E-1A8B-4C3C-9D5F-2B1E0E6F7A11".to_string()))
+	);
+
+	// Replacing the MakerNote discards the tags decoded from the old one
+	let mut metadata = metadata;
+	metadata.set_tag(ExifTag::MakerNote(vec![0u8; 8]));
+	assert!(metadata.get_ifd(ExifTagGroup::MAKERNOTES, 0).is_none());
+
+	Ok(())
+}
+
+#[test]
+fn
+maker_note_decoding_problems_are_warnings()
+-> Result<(), little_exif::Error>
+{
+	// Claims to have way more entries than there is data
+	let (tiff, _) = tiff_with_maker_note(|_| vec![0xff; 20])?;
+
+	let (metadata, warnings) = Metadata::new_from_vec_with_options(
+		&tiff, 
+		FileExtension::TIFF, 
+		&DecodeOptions::default()
+	)?;
+	assert!(warnings.iter().all(|warning| warning.group == ExifTagGroup::MAKERNOTES));
+	assert!(!warnings.is_empty());
+	assert!(metadata.get_tag(&ExifTag::ISO(vec![])).next().is_some());
+
+	Ok(())
+}
+
+#[test]
+fn
+thumbnail_round_trip()
+-> Result<(), little_exif::Error>
+{
+	let thumbnail = [vec![0xff, 0xd8, 0xff, 0xdb], vec![0x42; 100], vec![0xff, 0xd9]].concat();
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello World!".to_string()));
+	assert!(metadata.thumbnail().is_none());
+	assert!(!metadata.remove_thumbnail());
+
+	// Only JPEG data is accepted
+	assert!(metadata.set_thumbnail(vec![0x89, b'P', b'N', b'G']).is_err());
+	metadata.set_thumbnail(thumbnail.clone())?;
+
+	let mut image_data = read("tests/sample2.jpg")?;
+	metadata.write_to_vec(&mut image_data, FileExtension::JPEG)?;
+
+	let mut metadata = Metadata::new_from_vec(&image_data, FileExtension::JPEG)?;
+	assert_eq!(metadata.thumbnail(), Some(thumbnail.as_slice()));
+	assert_eq!(
+		metadata.get_ifd(ExifTagGroup::GENERIC, 1).unwrap().get_tags().iter().find(|tag| tag.as_u16() == 0x0103),
+		Some(&ExifTag::Compression(vec![6]))
+	);
+
+	// Without a thumbnail, IFD1 serves no purpose
+	assert!(metadata.remove_thumbnail());
+	let mut image_data = read("tests/sample2.jpg")?;
+	metadata.write_to_vec(&mut image_data, FileExtension::JPEG)?;
+
+	let metadata = Metadata::new_from_vec(&image_data, FileExtension::JPEG)?;
+	assert!(metadata.thumbnail().is_none());
+	assert!(metadata.get_ifd(ExifTagGroup::GENERIC, 1).is_none());
+	assert!(metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	Ok(())
+}
+
+#[test]
+fn
+thumbnail_gets_dropped_if_jpeg_segment_too_large()
+-> Result<(), little_exif::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello World!".to_string()));
+	metadata.set_thumbnail([vec![0xff, 0xd8], vec![0x42; 70_000]].concat())?;
+
+	let mut image_data = read("tests/sample2.jpg")?;
+	metadata.write_to_vec(&mut image_data, FileExtension::JPEG)?;
+
+	let decoded = Metadata::new_from_vec(&image_data, FileExtension::JPEG)?;
+	assert!(decoded.thumbnail().is_none());
+	assert!(decoded.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	// Formats without that limit keep the thumbnail
+	let mut image_data = read("tests/sample2.png")?;
+	metadata.write_to_vec(&mut image_data, FileExtension::PNG { as_zTXt_chunk: true })?;
+
+	let decoded = Metadata::new_from_vec(&image_data, FileExtension::PNG { as_zTXt_chunk: true })?;
+	assert_eq!(decoded.thumbnail().map(|data| data.len()), Some(70_002));
+
+	Ok(())
+}
+
+#[test]
+fn
+exif_size_reduction_for_jpeg()
+-> Result<(), little_exif::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Hello World!".to_string()));
+	metadata.set_tag(ExifTag::MakerNote(vec![0x42; 30_000]));
+	metadata.set_tag(ExifTag::UnknownUNDEF(vec![0x42; 40_000], 0xcd42, ExifTagGroup::GENERIC));
+	metadata.set_thumbnail([vec![0xff, 0xd8], vec![0x42; 10_000]].concat())?;
+
+	// Without reductions, writing fails and leaves the image untouched
+	let original   = read("tests/sample2.jpg")?;
+	let mut image  = original.clone();
+	let options    = WriteOptions { exif_size_reduction: ExifSizeReduction::none(), ..WriteOptions::default() };
+	let result     = metadata.write_to_vec_with_options(&mut image, FileExtension::JPEG, &options);
+	assert!(matches!(result, Err(little_exif::Error::ExifTooLarge { .. })));
+	assert_eq!(image, original);
+
+	// Dropping the thumbnail is not enough here, the default fails as well
+	assert!(metadata.write_to_vec(&mut image, FileExtension::JPEG).is_err());
+	assert!(metadata.as_u8_vec(FileExtension::JPEG).is_err());
+	assert!(metadata.clone().reduce_for_jpeg(&ExifSizeReduction::default()).is_err());
+	assert!(metadata.thumbnail().is_some());
+
+	// The reductions are applied in order until the data fits
+	let mut reduced = metadata.clone();
+	assert_eq!(
+		reduced.reduce_for_jpeg(&ExifSizeReduction::all())?,
+		vec![RemovedExifData::Thumbnail, RemovedExifData::MakerNote]
+	);
+	assert!(reduced.get_tag_by_hex(0xcd42, Some(ExifTagGroup::GENERIC)).next().is_some());
+
+	let mut reduced = metadata.clone();
+	let reduction   = ExifSizeReduction { drop_unknown_tags: true, ..ExifSizeReduction::none() };
+	assert_eq!(
+		reduced.reduce_for_jpeg(&reduction)?,
+		vec![RemovedExifData::UnknownTag { hex: 0xcd42, group: ExifTagGroup::GENERIC, generic_ifd_nr: 0 }]
+	);
+	assert!(reduced.thumbnail().is_some());
+
+	// Writing with the reductions produces a valid image
+	let options = WriteOptions { exif_size_reduction: ExifSizeReduction::all(), ..WriteOptions::default() };
+	metadata.write_to_vec_with_options(&mut image, FileExtension::JPEG, &options)?;
+
+	let decoded = Metadata::new_from_vec(&image, FileExtension::JPEG)?;
+	assert!(decoded.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+	assert!(decoded.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_none());
+	assert!(decoded.thumbnail().is_none());
+
+	// Other formats don't have that limit
+	let mut image = read("tests/sample2.png")?;
+	metadata.write_to_vec_with_options(&mut image, FileExtension::PNG { as_zTXt_chunk: true }, &options)?;
+	let decoded = Metadata::new_from_vec(&image, FileExtension::PNG { as_zTXt_chunk: true })?;
+	assert!(decoded.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_some());
+
+	Ok(())
+}
+
+/// Creates a little endian TIFF with a 32x32 grayscale image made up of four
+/// 16x16 tiles, each filled with its index, followed by some free space
+fn
+tiled_tiff()
+-> Vec<u8>
+{
+	let tiles            = (0..4u8).map(|index| vec![index; 256]).collect::<Vec<Vec<u8>>>();
+	let tiles_start      = 8 + 2 + 12 * 11 + 4 + 16 + 8;
+	let free_start       = tiles_start + 4 * 256;
+	let tile_offsets     = (0..4u32).flat_map(|index| (tiles_start + index * 256).to_le_bytes()).collect::<Vec<u8>>();
+	let tile_byte_counts = [256u16; 4].iter().flat_map(|count| count.to_le_bytes()).collect::<Vec<u8>>();
+
+	let ifd = maker_note_ifd(&[
+		(0x0100, 4, 1, &32u32.to_le_bytes()),                                   // ImageWidth
+		(0x0101, 4, 1, &32u32.to_le_bytes()),                                   // ImageHeight
+		(0x0103, 3, 1, &1u16.to_le_bytes()),                                    // Compression
+		(0x0106, 3, 1, &1u16.to_le_bytes()),                                    // PhotometricInterpretation
+		(0x0115, 3, 1, &1u16.to_le_bytes()),                                    // SamplesPerPixel
+		(0x0120, 4, 1, &(free_start as u32).to_le_bytes()),                     // FreeOffsets
+		(0x0121, 4, 1, &16u32.to_le_bytes()),                                   // FreeByteCounts
+		(0x0142, 3, 1, &16u16.to_le_bytes()),                                   // TileWidth
+		(0x0143, 3, 1, &16u16.to_le_bytes()),                                   // TileLength
+		(0x0144, 4, 4, &tile_offsets),                                          // TileOffsets
+		(0x0145, 3, 4, &tile_byte_counts),                                      // TileByteCounts
+	], false, 8);
+
+	return [b"II*\0".to_vec(), 8u32.to_le_bytes().to_vec(), ifd, tiles.concat(), vec![0xff; 16]].concat();
+}
+
+#[test]
+fn
+tiled_tiff_keeps_image_data()
+-> Result<(), little_exif::Error>
+{
+	let tiff = tiled_tiff();
+
+	let mut metadata = Metadata::new_from_vec(&tiff, FileExtension::TIFF)?;
+	metadata.set_tag(ExifTag::ImageDescription("Hello World!".to_string()));
+
+	let mut written = tiff.clone();
+	metadata.write_to_vec(&mut written, FileExtension::TIFF)?;
+
+	let decoded = Metadata::new_from_vec(&written, FileExtension::TIFF)?;
+	assert!(decoded.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+	assert_eq!(
+		decoded.get_tag(&ExifTag::TileOffsets(vec![], vec![])).next(),
+		Some(&ExifTag::TileOffsets(vec![], (0..4u8).map(|index| vec![index; 256]).collect()))
+	);
+	assert_eq!(
+		decoded.get_tag(&ExifTag::TileByteCounts(vec![])).next(),
+		Some(&ExifTag::TileByteCounts(vec![256; 4]))
+	);
+	assert_eq!(
+		decoded.get_tag(&ExifTag::TileWidth(vec![])).next(),
+		Some(&ExifTag::TileWidth(vec![16]))
+	);
+	assert_eq!(
+		decoded.get_tag(&ExifTag::FreeOffsets(vec![], vec![])).next(),
+		Some(&ExifTag::FreeOffsets(vec![], vec![vec![0xff; 16]]))
+	);
+
+	// Writing again doesn't change anything
+	let mut rewritten = written.clone();
+	decoded.write_to_vec(&mut rewritten, FileExtension::TIFF)?;
+	assert_eq!(rewritten, written);
+
+	Ok(())
+}